@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use game::bitboard::BitboardImpl;
+use game::board::{Board, Move};
+use game::movegen::anchors_and_limits;
+use game::TileMap;
+
+pub fn clone(c: &mut Criterion) {
+    let tiles = TileMap::english();
+    let mut board = Board::<BitboardImpl>::standard(&tiles);
+    let m = Move::parse("8D QUIXOTIC", &board, &tiles).unwrap();
+    board.apply(&m).unwrap();
+    c.bench_function("board_clone", |b| b.iter(|| black_box(&board).clone()));
+}
+
+pub fn anchors(c: &mut Criterion) {
+    let tiles = TileMap::english();
+    let mut board = Board::<BitboardImpl>::standard(&tiles);
+    for m in ["8D QUIXOTIC", "E7 JUMP", "12C BOX"] {
+        let m = Move::parse(m, &board, &tiles).unwrap();
+        board.apply(&m).unwrap();
+    }
+    c.bench_function("anchors_and_limits", |b| {
+        b.iter(|| anchors_and_limits(black_box(&board)))
+    });
+}
+
+criterion_group!(board, clone, anchors);
+criterion_main!(board);
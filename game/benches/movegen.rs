@@ -0,0 +1,94 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use game::bitboard::BitboardImpl;
+use game::board::Board;
+use game::lexicon::Lexicon;
+use game::movegen::{
+    generate_into, iter_moves, packed_moves, top_moves, CrossChecks, MoveBuffer, RawScore,
+};
+use game::{Rack, TileBag, TileMap};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// A lexicon of random words, weighted like the tile distribution, and a board filled by playing
+/// the best scoring move a number of times.
+fn position() -> (TileMap, Lexicon, Board<BitboardImpl>, Rack) {
+    let tiles = TileMap::english();
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let letters: Vec<_> = tiles
+        .letters()
+        .flat_map(|(idx, letter)| std::iter::repeat_n(letter, tiles.count(idx).into()))
+        .collect();
+    let words: Vec<String> = (0..60_000)
+        .map(|_| {
+            let len = rng.gen_range(2..=8);
+            (0..len)
+                .map(|_| letters[rng.gen_range(0..letters.len())])
+                .collect()
+        })
+        .collect();
+    let lexicon = Lexicon::from_words(words.iter().map(String::as_str), &tiles);
+
+    let mut board = Board::standard(&tiles);
+    let mut bag = TileBag::seeded(&tiles, 7);
+    let mut rack = Rack::default();
+    for _ in 0..12 {
+        bag.draw_to_fill(&mut rack);
+        let Some(best) = top_moves(&board, &rack, &lexicon, &tiles, 1, &RawScore).pop() else {
+            continue;
+        };
+        for (_, p) in best.m.placed() {
+            let t = if p.is_blank {
+                tiles.blank_idx().unwrap()
+            } else {
+                p.tile
+            };
+            rack.remove(t).unwrap();
+        }
+        board.apply(&best.m).unwrap();
+    }
+    bag.draw_to_fill(&mut rack);
+    (tiles, lexicon, board, rack)
+}
+
+pub fn generation(c: &mut Criterion) {
+    let (tiles, lexicon, board, rack) = position();
+    c.bench_function("iter_moves", |b| {
+        b.iter(|| iter_moves(black_box(&board), &rack, &lexicon).count())
+    });
+    let packed = lexicon.dawg().packed().unwrap();
+    c.bench_function("packed_moves", |b| {
+        b.iter(|| packed_moves(black_box(&board), &rack, &lexicon, &packed).count())
+    });
+    let mut buffer = MoveBuffer::new();
+    c.bench_function("generate_into", |b| {
+        b.iter(|| {
+            generate_into(&mut buffer, black_box(&board), &rack, &lexicon);
+            buffer.len()
+        })
+    });
+    // Keeping cross checks current after a move, against computing them from scratch.
+    let m = top_moves(&board, &rack, &lexicon, &tiles, 1, &RawScore)
+        .pop()
+        .expect("The rack has a move")
+        .m;
+    let checks = CrossChecks::new(&board, &lexicon);
+    let mut after = board.clone();
+    after.apply(&m).unwrap();
+    c.bench_function("cross_checks", |b| {
+        b.iter(|| CrossChecks::new(black_box(&after), &lexicon))
+    });
+    c.bench_function("cross_checks_update", |b| {
+        b.iter(|| {
+            let mut checks = checks.clone();
+            checks.update(black_box(&after), &lexicon, &m);
+            checks
+        })
+    });
+    #[cfg(feature = "rayon")]
+    c.bench_function("par_moves", |b| {
+        b.iter(|| game::movegen::par_moves(black_box(&board), &rack, &lexicon).len())
+    });
+}
+
+criterion_group!(movegen, generation);
+criterion_main!(movegen);
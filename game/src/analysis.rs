@@ -0,0 +1,218 @@
+//! Analysis of positions for frontends and teaching tools.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Play, Player, Row},
+    bot::Equity,
+    error::Result,
+    leaves::LeaveTable,
+    lexicon::Lexicon,
+    movegen::{filtered_moves, leave, MoveFilter},
+    score::{score_move, score_move_with},
+    Game, Rack, TileMap,
+};
+
+/// One value per square, indexed by [`Row::as_idx`] and then
+/// [`Column::as_idx`](crate::board::Column::as_idx).
+pub type Heatmap = [[u16; Row::MAX as usize]; Row::MAX as usize];
+
+/// For every square, the highest score of a play with `rack` that places a tile on it, zero where
+/// no play does. Shows at a glance where on the board the rack can do damage.
+pub fn heatmap<BB: Bitboard>(
+    board: &Board<BB>,
+    rack: &Rack,
+    lexicon: &Lexicon,
+    tiles: &TileMap,
+) -> Heatmap {
+    let mut heatmap = [[0; Row::MAX as usize]; Row::MAX as usize];
+    for m in filtered_moves(board, rack, lexicon, MoveFilter::new().literal()) {
+        let score = score_move(board, &m, tiles).total();
+        let score = u16::try_from(score).unwrap_or(u16::MAX);
+        for (l, _) in m.placed() {
+            let square = &mut heatmap[l.row().as_idx()][l.column().as_idx()];
+            *square = (*square).max(score);
+        }
+    }
+    heatmap
+}
+
+/// How [`annotate`] judges plays: by equity, the score plus the value of the leave from `leaves`,
+/// like the [`Equity`] bot.
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    pub leaves: Arc<LeaveTable>,
+    /// How many of the best plays to list for every turn.
+    pub top: usize,
+}
+
+impl EngineConfig {
+    pub fn new(leaves: impl Into<Arc<LeaveTable>>) -> Self {
+        Self {
+            leaves: leaves.into(),
+            top: 5,
+        }
+    }
+}
+
+/// A play with its equity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatedPlay {
+    pub play: Play,
+    pub equity: f32,
+}
+
+/// What [`annotate`] found for one turn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnAnnotation {
+    /// The turns played before this one.
+    pub ply: usize,
+    pub player: Player,
+    pub played: RatedPlay,
+    /// Whether the play formed a phony, which the engine would never play.
+    pub phony: bool,
+    /// The best plays, the best first.
+    pub best: Vec<RatedPlay>,
+    /// The equity given up against the best play, zero if the play was as good.
+    pub equity_loss: f32,
+}
+
+/// For every turn of `game` so far, the best plays and what the play made cost, e.g. to find the
+/// blunders of a finished game.
+///
+/// Once the bag is empty, plays are judged by score alone, without solving the endgame.
+pub fn annotate<BB: Bitboard>(
+    game: &Game<BB>,
+    config: &EngineConfig,
+) -> Result<Vec<TurnAnnotation>> {
+    let engine = Equity::new(config.leaves.clone());
+    let mut replay = game.replay();
+    let mut annotations = Vec::with_capacity(game.history().len());
+    for (ply, record) in game.history().iter().enumerate() {
+        replay.seek(ply)?;
+        let position = replay.game();
+        let view = position.view(record.player());
+        let mut best: Vec<RatedPlay> = engine
+            .rated(&view)
+            .into_iter()
+            .map(|(play, equity)| RatedPlay { play, equity })
+            .collect();
+        // Stable, so equal plays stay in the order the engine rated them.
+        best.sort_by(|a, b| b.equity.total_cmp(&a.equity));
+        best.truncate(config.top.max(1));
+
+        let play = &record.summary.play;
+        let rack = &record.rack_before;
+        let keeps_value = view.bag_len() > 0;
+        let equity = match play {
+            Play::Place(m) => {
+                let score =
+                    score_move_with(position.board(), m, position.tiles(), position.rules());
+                let score = score.total() as f32;
+                let kept = leave(rack, m, position.tiles());
+                score
+                    + if keeps_value {
+                        config.leaves.value_of(&kept)
+                    } else {
+                        0.0
+                    }
+            }
+            Play::Exchange(tiles) => {
+                let mut kept = rack.clone();
+                kept.remove_all(tiles)?;
+                config.leaves.value_of(kept.tiles())
+            }
+            Play::Pass if keeps_value => config.leaves.value_of(rack.tiles()),
+            Play::Pass => 0.0,
+        };
+        // Passing isn't rated, so with nothing else to do it's the best there is.
+        let top = best.first().map_or(equity, |b| b.equity);
+        annotations.push(TurnAnnotation {
+            ply,
+            player: record.player(),
+            played: RatedPlay {
+                play: play.clone(),
+                equity,
+            },
+            phony: !record.phonies.is_empty(),
+            best,
+            equity_loss: (top - equity).max(0.0),
+        });
+    }
+    Ok(annotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::{Location, Move},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn best_score_per_square() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "ACT", "AT", "TA", "CATS"], &tiles);
+        let rack = Rack::parse("CAT", &tiles).unwrap();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let map = heatmap(&board, &rack, &lexicon, &tiles);
+        let at = |map: &Heatmap, l: &str| {
+            let l: Location = l.parse().unwrap();
+            map[l.row().as_idx()][l.column().as_idx()]
+        };
+        // Three letter words through the center double word, both ways.
+        assert_eq!(at(&map, "H8"), 10);
+        assert_eq!(at(&map, "F8"), 10);
+        assert_eq!(at(&map, "H10"), 10);
+        assert_eq!(at(&map, "E8"), 0);
+        assert_eq!(map.iter().flatten().filter(|s| **s > 0).count(), 9);
+
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        let rack = Rack::parse("S", &tiles).unwrap();
+        let map = heatmap(&board, &rack, &lexicon, &tiles);
+        // Only CATS, and the tiles already on the board stay cold.
+        assert_eq!(at(&map, "J8"), 6);
+        assert_eq!(at(&map, "H8"), 0);
+        assert_eq!(map.iter().flatten().filter(|s| **s > 0).count(), 1);
+    }
+
+    #[test]
+    fn annotate_turns() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "AT"], &tiles);
+        let mut game: Game<BitboardImpl> = crate::GameBuilder::new()
+            .tiles(tiles.clone())
+            .lexicons(lexicon)
+            .rack(Player::First, Rack::parse("CATEEEI", &tiles).unwrap())
+            .rack(Player::Second, Rack::parse("OOUUIIE", &tiles).unwrap())
+            .seed(1)
+            .build()
+            .unwrap();
+        let m = Move::parse("8H AT", game.board(), game.tiles()).unwrap();
+        game.play(Player::First, Play::Place(m)).unwrap();
+        game.play(Player::Second, Play::Pass).unwrap();
+
+        let annotations = annotate(&game, &EngineConfig::new(LeaveTable::new())).unwrap();
+        assert_eq!(annotations.len(), 2);
+        let first = &annotations[0];
+        assert_eq!(first.played.equity, 4.0);
+        let Play::Place(best) = &first.best[0].play else {
+            panic!("Expected a placement");
+        };
+        assert_eq!(best.notation(&tiles), "8H CAT");
+        // CAT scores 10.
+        assert_eq!(first.equity_loss, 6.0);
+        assert!(first.best.len() <= 5 && !first.phony);
+
+        // Nothing to play, and with no leave values an exchange is no better.
+        let second = &annotations[1];
+        assert_eq!((second.player, second.equity_loss), (Player::Second, 0.0));
+    }
+}
@@ -88,9 +88,12 @@ impl Bitboard for BitboardImpl {
         Self::new_raw(rows)
     }
 
-    fn new_raw(mut rows: [u16; 16]) -> Self {
-        rows[15] = 0;
-        Self { rows: rows.into() }
+    fn new_raw(rows: [u16; 16]) -> Self {
+        Self { rows: rows.into() } & Self::full()
+    }
+
+    fn to_raw(self) -> [u16; 16] {
+        self.rows.into()
     }
 
     fn count_ones(self) -> u32 {
@@ -111,7 +114,6 @@ impl Bitboard for BitboardImpl {
     }
 
     fn up(self, by: usize) -> Self {
-        let by = by as usize;
         let mut rows: [u16; 16] = self.rows.into();
         rows.rotate_right(by);
         for i in 0..by {
@@ -123,7 +125,6 @@ impl Bitboard for BitboardImpl {
     }
 
     fn down(self, by: usize) -> Self {
-        let by = by as usize;
         let mut rows: [u16; 16] = self.rows.into();
         rows.rotate_left(by);
         for i in (0..by).rev() {
@@ -133,6 +134,17 @@ impl Bitboard for BitboardImpl {
         let rows = rows.into();
         Self { rows }
     }
+
+    fn transpose(self) -> Self {
+        let rows: [u16; 16] = self.rows.into();
+        let mut out = Self::EMPTY;
+        for (r, row) in rows.iter().take(15).enumerate() {
+            for (c, col) in out.iter_mut().take(15).enumerate() {
+                *col |= ((row >> c) & 1) << r;
+            }
+        }
+        Self { rows: out.into() }
+    }
 }
 
 impl PartialEq for BitboardImpl {
@@ -1,27 +1,58 @@
 use std::{
     fmt::Debug,
-    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not},
 };
 
-mod scalar;
 #[cfg(target_feature = "avx2")]
 mod avx2;
+mod scalar;
 
-#[cfg(not(target_feature = "avx2"))]
-pub use scalar::BitboardImpl;
 #[cfg(target_feature = "avx2")]
 pub use avx2::BitboardImpl;
+#[cfg(not(target_feature = "avx2"))]
+pub use scalar::BitboardImpl;
 
-use crate::board::Location;
+use crate::board::{Column, Location, Row};
 
 pub trait Bitboard:
-    Copy + Clone + Debug + Not + BitAnd + BitOr + BitAndAssign + BitOrAssign
+    Copy
+    + Clone
+    + Debug
+    + PartialEq
+    + Not<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + BitAndAssign
+    + BitOrAssign
+    + BitXorAssign
 {
     fn empty() -> Self;
     fn full() -> Self;
 
+    fn is_empty(self) -> bool {
+        self == Self::empty()
+    }
+
+    fn is_set(self, l: Location) -> bool {
+        !(self & Self::for_location(l)).is_empty()
+    }
+
     fn for_location(l: Location) -> Self;
     fn new_raw(rows: [u16; 16]) -> Self;
+    /// The rows as stored, see [`Bitboard::new_raw`]. Unused bits should always be zero.
+    fn to_raw(self) -> [u16; 16];
+
+    /// All set squares, row by row.
+    fn locations(self) -> impl Iterator<Item = Location> {
+        let rows = self.to_raw();
+        Row::all().flat_map(move |row| {
+            let bits = rows[row.as_idx()];
+            Column::all()
+                .filter(move |column| (bits >> column.as_idx()) & 1 == 1)
+                .map(move |column| Location::new(row, column))
+        })
+    }
 
     fn count_ones(self) -> u32;
 
@@ -29,6 +60,9 @@ pub trait Bitboard:
     fn left(self, by: usize) -> Self;
     fn up(self, by: usize) -> Self;
     fn down(self, by: usize) -> Self;
+
+    /// Mirrors the board along its main diagonal: row `r`, column `c` ends up at row `c`, column `r`.
+    fn transpose(self) -> Self;
 }
 
 #[cfg(test)]
@@ -40,9 +74,22 @@ mod tests {
     prop_compose! {
         fn arb_bitboard()(id in any::<[[bool; 15]; 15]>()) -> BitboardImpl {
             let mut raw: [u16; 16] = [0; 16];
-            for i in 0..15 {
-                for b in 0..15 {
-                    raw[i] |= id[i][b] as u16 >> b
+            for (row, bits) in raw.iter_mut().zip(id.iter()) {
+                for (b, bit) in bits.iter().enumerate() {
+                    *row |= (*bit as u16) >> b
+                }
+            }
+            BitboardImpl::new_raw(raw)
+        }
+    }
+
+    prop_compose! {
+        /// Any of the board's squares may be set, not just the first column.
+        fn arb_board_squares()(id in any::<[[bool; 15]; 15]>()) -> BitboardImpl {
+            let mut raw: [u16; 16] = [0; 16];
+            for (row, bits) in raw.iter_mut().zip(id.iter()) {
+                for (b, bit) in bits.iter().enumerate() {
+                    *row |= (*bit as u16) << b
                 }
             }
             BitboardImpl::new_raw(raw)
@@ -130,6 +177,28 @@ mod tests {
         assert_eq!(b.down(1), BitboardImpl::empty());
     }
 
+    #[test]
+    fn transpose_row_becomes_column() {
+        let b = BitboardImpl::new_raw([0x7FFF, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let expected = BitboardImpl::new_raw([0x0001; 16]);
+        assert_eq!(b.transpose(), expected);
+    }
+
+    #[test]
+    fn new_raw_clears_unused_bits() {
+        let b = BitboardImpl::new_raw([0xFFFF; 16]);
+        assert_eq!(b.to_raw(), BitboardImpl::full().to_raw());
+        assert_eq!(b.to_raw()[15], 0);
+        assert_eq!(b.to_raw()[0], 0x7FFF);
+    }
+
+    #[test]
+    fn locations() {
+        let b = BitboardImpl::new_raw([0, 0b101, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x4000, 0]);
+        let locations: Vec<_> = b.locations().map(|l| l.to_string()).collect();
+        assert_eq!(locations, ["A2", "C2", "O15"]);
+    }
+
     #[test]
     fn not_empty_full() {
         assert_eq!(!BitboardImpl::empty(), BitboardImpl::full());
@@ -194,5 +263,25 @@ mod tests {
       fn invert_involution(bb in arb_bitboard()) {
         assert_eq!(!!bb, bb);
       }
+
+      #[test]
+      fn locations_roundtrip(bb in arb_board_squares()) {
+        let rebuilt = bb
+          .locations()
+          .fold(BitboardImpl::empty(), |acc, l| acc | BitboardImpl::for_location(l));
+        assert_eq!(rebuilt, bb);
+        assert_eq!(bb.locations().count() as u32, bb.count_ones());
+      }
+
+      #[test]
+      fn transpose_involution(bb in arb_board_squares()) {
+        assert_eq!(bb.transpose().transpose(), bb);
+      }
+
+      #[test]
+      fn transpose_swaps_shifts(by in 0..15usize, bb in arb_board_squares()) {
+        assert_eq!(bb.right(by).transpose(), bb.transpose().up(by));
+        assert_eq!(bb.left(by).transpose(), bb.transpose().down(by));
+      }
     }
 }
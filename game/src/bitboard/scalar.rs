@@ -93,6 +93,10 @@ impl Bitboard for BitboardImpl {
         s
     }
 
+    fn to_raw(self) -> [u16; 16] {
+        self.rows
+    }
+
     fn count_ones(self) -> u32 {
         self.row_iter().fold(0, |acc, r| acc + r.count_ones())
     }
@@ -111,7 +115,6 @@ impl Bitboard for BitboardImpl {
     }
 
     fn up(self, by: usize) -> Self {
-        let by = by as usize;
         let mut out = self;
         out.rows.rotate_right(by);
         for i in 0..by {
@@ -122,7 +125,6 @@ impl Bitboard for BitboardImpl {
     }
 
     fn down(self, by: usize) -> Self {
-        let by = by as usize;
         let mut out = self;
         out.rows.rotate_left(by);
         for i in (0..by).rev() {
@@ -131,6 +133,16 @@ impl Bitboard for BitboardImpl {
         out.rows[15] = 0;
         out
     }
+
+    fn transpose(self) -> Self {
+        let mut out = Self::EMPTY;
+        for (r, row) in self.row_iter().take(15).enumerate() {
+            for (c, col) in out.row_iter_mut().take(15).enumerate() {
+                *col |= ((row >> c) & 1) << r;
+            }
+        }
+        out
+    }
 }
 
 impl PartialEq for BitboardImpl {
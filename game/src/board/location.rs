@@ -0,0 +1,362 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{BoardError, Result, ScrubbleError};
+
+macro_rules! coordinate {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(u8);
+
+        impl $name {
+            pub const MAX: u8 = 15;
+
+            /// `coord` is 1-based, like on a physical board.
+            pub const fn new(coord: u8) -> Option<Self> {
+                if coord >= 1 && coord <= Self::MAX {
+                    Some(Self(coord))
+                } else {
+                    None
+                }
+            }
+
+            /// The 1-based value.
+            pub fn get(self) -> u8 {
+                self.0
+            }
+
+            /// The 0-based index, as used for bitboards.
+            pub fn as_idx(self) -> usize {
+                self.0 as usize - 1
+            }
+
+            pub fn from_idx(idx: usize) -> Option<Self> {
+                (idx < Self::MAX as usize).then(|| Self(idx as u8 + 1))
+            }
+
+            pub fn all() -> impl DoubleEndedIterator<Item = Self> + ExactSizeIterator {
+                (1..=Self::MAX).map(Self)
+            }
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = ScrubbleError;
+
+            fn try_from(coord: u8) -> Result<Self> {
+                Self::new(coord).ok_or_else(|| {
+                    BoardError::OutOfRange {
+                        kind: stringify!($name),
+                        value: coord,
+                        max: Self::MAX,
+                    }
+                    .into()
+                })
+            }
+        }
+    };
+}
+
+coordinate!(
+    /// A row of the board, 1 (top) to 15 (bottom).
+    Row
+);
+coordinate!(
+    /// A column of the board, 1 (A, left) to 15 (O, right).
+    Column
+);
+
+impl Row {
+    fn to_column(self) -> Column {
+        Column(self.0)
+    }
+}
+
+impl Column {
+    pub fn letter(self) -> char {
+        (b'A' + self.0 - 1) as char
+    }
+
+    /// Case-insensitive.
+    pub fn from_letter(c: char) -> Option<Self> {
+        let c = c.to_ascii_uppercase();
+        c.is_ascii_uppercase()
+            .then(|| Self::new(c as u8 - b'A' + 1))
+            .flatten()
+    }
+
+    fn to_row(self) -> Row {
+        Row(self.0)
+    }
+}
+
+impl Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+/// Serialized in its display form, e.g. "H8".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Location {
+    row: Row,
+    column: Column,
+}
+
+impl Location {
+    /// H8.
+    pub const CENTER: Self = Self {
+        row: Row(8),
+        column: Column(8),
+    };
+
+    pub fn new(row: Row, column: Column) -> Self {
+        Self { row, column }
+    }
+
+    /// Parses standard coordinate notation, where the order of row and column gives the direction
+    /// of a play: "8H" (row first) is horizontal, "H8" (column first) is vertical.
+    ///
+    /// Columns are the letters A to O, rows the numbers 1 to 15. Column letters are case-insensitive.
+    pub fn parse_with_direction(s: &str) -> Result<(Self, Direction)> {
+        let s = s.trim();
+        let Some(first) = s.chars().next() else {
+            return Err(BoardError::EmptyCoordinate.into());
+        };
+        let (column, row, direction) = if first.is_ascii_digit() {
+            let split = s.char_indices().last().map_or(0, |(i, _)| i);
+            (&s[split..], &s[..split], Direction::Horizontal)
+        } else {
+            let split = first.len_utf8();
+            (&s[..split], &s[split..], Direction::Vertical)
+        };
+        let Some(column) = column.chars().next().and_then(Column::from_letter) else {
+            return Err(BoardError::InvalidColumn(s.to_owned()).into());
+        };
+        let Some(row) = row.parse().ok().and_then(Row::new) else {
+            return Err(BoardError::InvalidRow(s.to_owned()).into());
+        };
+        Ok((Self { row, column }, direction))
+    }
+
+    /// Formats the location in standard coordinate notation for a play in `direction`, see
+    /// [`Location::parse_with_direction`].
+    pub fn notation(self, direction: Direction) -> String {
+        match direction {
+            Direction::Horizontal => format!("{}{}", self.row, self.column),
+            Direction::Vertical => format!("{}{}", self.column, self.row),
+        }
+    }
+
+    /// The location `n` squares further along `direction`, or `None` if that is off the board.
+    ///
+    /// Negative `n` walks backwards: up for [`Direction::Vertical`], left for [`Direction::Horizontal`].
+    pub fn offset(self, direction: Direction, n: isize) -> Option<Self> {
+        match direction {
+            Direction::Horizontal => Some(Self {
+                row: self.row,
+                column: Column::from_idx(self.column.as_idx().checked_add_signed(n)?)?,
+            }),
+            Direction::Vertical => Some(Self {
+                row: Row::from_idx(self.row.as_idx().checked_add_signed(n)?)?,
+                column: self.column,
+            }),
+        }
+    }
+
+    /// All locations from this one (inclusive) to the edge of the board along `direction`.
+    pub fn ray(self, direction: Direction) -> impl Iterator<Item = Self> {
+        std::iter::successors(Some(self), move |l| l.offset(direction, 1))
+    }
+
+    pub fn row(&self) -> Row {
+        self.row
+    }
+
+    pub fn column(&self) -> Column {
+        self.column
+    }
+
+    /// The location with row and column swapped, see [`Board::transposed`](super::Board::transposed).
+    pub fn transposed(self) -> Self {
+        Self {
+            row: self.column.to_row(),
+            column: self.row.to_column(),
+        }
+    }
+}
+
+/// Accepts both "H8" and "8H", see [`Location::parse_with_direction`] if the order matters.
+impl FromStr for Location {
+    type Err = ScrubbleError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse_with_direction(s).map(|(l, _)| l)
+    }
+}
+
+impl TryFrom<String> for Location {
+    type Error = ScrubbleError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Location> for String {
+    fn from(l: Location) -> Self {
+        l.to_string()
+    }
+}
+
+/// Column letter followed by row number, e.g. "H8".
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.column, self.row)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+impl Direction {
+    /// The direction cross words run in.
+    pub fn perpendicular(self) -> Self {
+        match self {
+            Direction::Horizontal => Direction::Vertical,
+            Direction::Vertical => Direction::Horizontal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    #[test]
+    fn coordinate_bounds() {
+        assert_eq!(Row::new(0), None);
+        assert_eq!(Row::new(16), None);
+        assert_eq!(Column::new(1).map(Column::as_idx), Some(0));
+        assert_eq!(Row::from_idx(14).map(Row::get), Some(15));
+        assert_eq!(Row::from_idx(15), None);
+        assert!(Column::try_from(0).is_err());
+        assert_eq!(Row::all().count(), 15);
+    }
+
+    #[test]
+    fn column_letters() {
+        assert_eq!(Column::from_letter('a'), Column::new(1));
+        assert_eq!(Column::from_letter('O').map(Column::letter), Some('O'));
+        assert_eq!(Column::from_letter('P'), None);
+        assert_eq!(Column::from_letter('é'), None);
+    }
+
+    #[test]
+    fn location_notation() {
+        let (l, d) = Location::parse_with_direction("8H").unwrap();
+        assert_eq!(
+            (l.row().as_idx(), l.column().as_idx(), d),
+            (7, 7, Direction::Horizontal)
+        );
+        let (l, d) = Location::parse_with_direction("a15").unwrap();
+        assert_eq!(
+            (l.row().as_idx(), l.column().as_idx(), d),
+            (14, 0, Direction::Vertical)
+        );
+        assert_eq!(l.to_string(), "A15");
+        assert_eq!(l.notation(Direction::Horizontal), "15A");
+        assert_eq!("15A".parse::<Location>().unwrap(), l);
+    }
+
+    #[test]
+    fn location_notation_out_of_range() {
+        for s in [
+            "", "8", "H", "P8", "H0", "H16", "16H", "8HH", "-1A", "é8", "8é",
+        ] {
+            assert!(s.parse::<Location>().is_err(), "{s:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn location_offset() {
+        let l: Location = "H8".parse().unwrap();
+        assert_eq!(
+            l.offset(Direction::Horizontal, 2),
+            Some("J8".parse().unwrap())
+        );
+        assert_eq!(
+            l.offset(Direction::Vertical, -7),
+            Some("H1".parse().unwrap())
+        );
+        assert_eq!(l.offset(Direction::Vertical, -8), None);
+        assert_eq!(l.offset(Direction::Horizontal, 8), None);
+    }
+
+    #[test]
+    fn location_ray() {
+        let l: Location = "M3".parse().unwrap();
+        let ray: Vec<_> = l
+            .ray(Direction::Horizontal)
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(ray, ["M3", "N3", "O3"]);
+        assert_eq!(l.ray(Direction::Vertical).count(), 13);
+    }
+
+    prop_compose! {
+        fn arb_location()(row in 1..=15u8, column in 1..=15u8) -> Location {
+            Location::new(Row::new(row).unwrap(), Column::new(column).unwrap())
+        }
+    }
+
+    proptest! {
+      #[test]
+      fn row_validation(coord in any::<u8>()) {
+        assert_eq!(Row::try_from(coord).is_ok(), (1..=15).contains(&coord));
+      }
+
+      #[test]
+      fn column_validation(coord in any::<u8>()) {
+        assert_eq!(Column::new(coord).is_some(), (1..=15).contains(&coord));
+      }
+
+      #[test]
+      fn idx_roundtrip(idx in 0..15usize) {
+        assert_eq!(Row::from_idx(idx).unwrap().as_idx(), idx);
+        assert_eq!(Column::from_idx(idx).unwrap().as_idx(), idx);
+      }
+
+      #[test]
+      fn notation_roundtrip(l in arb_location()) {
+        for d in [Direction::Horizontal, Direction::Vertical] {
+          assert_eq!(Location::parse_with_direction(&l.notation(d)).unwrap(), (l, d));
+        }
+      }
+
+      #[test]
+      fn transpose_involution(l in arb_location()) {
+        assert_eq!(l.transposed().transposed(), l);
+        assert_eq!(l.transposed().row().as_idx(), l.column().as_idx());
+      }
+
+      #[test]
+      fn ray_stays_on_board(l in arb_location()) {
+        assert_eq!(l.ray(Direction::Horizontal).count(), 15 - l.column().as_idx());
+        assert_eq!(l.ray(Direction::Vertical).count(), 15 - l.row().as_idx());
+      }
+    }
+}
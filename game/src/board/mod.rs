@@ -1,20 +1,74 @@
-use std::ops::Index;
+use std::ops::{Index, IndexMut};
+
+use serde::{Deserialize, Serialize};
 
 use crate::bitboard::Bitboard;
 
+use crate::{
+    error::{BoardError, Result, TileError},
+    TileMap, TileMapIdx,
+};
+
+mod location;
+mod moves;
+mod play;
+mod premiums;
+mod validate;
+
+pub use location::{Column, Direction, Location, Row};
+pub use moves::{Move, MoveTile, TilePlacement};
+pub use play::Play;
+pub use premiums::{Premium, Premiums};
+pub use validate::PlayError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Player {
     First,
     Second,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+impl Player {
+    /// The opponent.
+    pub fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Scores {
-    first: u16,
-    second: u16,
+    first: i32,
+    second: i32,
+}
+
+impl Scores {
+    /// Adds `points` to `player`'s score, saturating at the bounds of `i32`.
+    ///
+    /// `points` may be negative, e.g. for end-of-game rack deductions.
+    pub fn add(&mut self, player: Player, points: i32) {
+        self[player] = self[player].saturating_add(points);
+    }
+
+    /// Adds `points` to `player`'s score, returning `None` and leaving the score untouched on overflow.
+    pub fn checked_add(&mut self, player: Player, points: i32) -> Option<i32> {
+        let new = self[player].checked_add(points)?;
+        self[player] = new;
+        Some(new)
+    }
+
+    /// `player`'s score minus the other player's score.
+    pub fn spread(&self, player: Player) -> i32 {
+        match player {
+            Player::First => self.first.saturating_sub(self.second),
+            Player::Second => self.second.saturating_sub(self.first),
+        }
+    }
 }
 
 impl Index<Player> for Scores {
-    type Output = u16;
+    type Output = i32;
 
     fn index(&self, index: Player) -> &Self::Output {
         match index {
@@ -24,63 +78,398 @@ impl Index<Player> for Scores {
     }
 }
 
+impl IndexMut<Player> for Scores {
+    fn index_mut(&mut self, index: Player) -> &mut Self::Output {
+        match index {
+            Player::First => &mut self.first,
+            Player::Second => &mut self.second,
+        }
+    }
+}
+
+/// The tiles on the board.
+///
+/// Tiles are stored as bit planes: an occupied square has bit `i` of its [`TileMapIdx`] set in
+/// `tile_bits[i]`. This keeps the board a fixed-size value, so cloning it to branch off a search is
+/// just a copy. A blank is stored as the letter it was designated as, and is additionally set in
+/// `blanks`: word validation only needs the letters, scoring checks `blanks` to count those squares
+/// as zero.
+#[derive(Debug, Clone)]
 pub struct Board<BB: Bitboard> {
+    occupied: BB,
+    tile_bits: [BB; 8],
     blanks: BB,
-    letters: Vec<BB>,
+    num_letters: u8,
+    /// The blank of the tile map, which is only ever placed designated as a letter.
+    blank: Option<TileMapIdx>,
+    premiums: Premiums<BB>,
     current_turn: Player,
     scores: Scores,
 }
 
 impl<BB: Bitboard> Board<BB> {
-    pub fn new(num_letters: u8) -> Self {
+    /// An empty board for tiles of `tiles`.
+    pub fn new(tiles: &TileMap, premiums: Premiums<BB>) -> Self {
         Self {
-            blanks: Bitboard::empty(),
-            letters: vec![Bitboard::empty(); num_letters as usize],
+            occupied: BB::empty(),
+            tile_bits: [BB::empty(); 8],
+            blanks: BB::empty(),
+            num_letters: tiles.len(),
+            blank: tiles.blank_idx(),
+            premiums,
             current_turn: Player::First,
             scores: Scores::default(),
         }
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-pub struct Coordinate(u8);
+    /// An empty board with the standard Scrabble layout.
+    pub fn standard(tiles: &TileMap) -> Self {
+        Self::new(tiles, Premiums::standard())
+    }
 
-impl Coordinate {
-    pub fn new(coord: u8) -> Option<Self> {
-        (coord != 0 || coord <= 15).then_some(Self(coord))
+    /// An empty board with the Words With Friends layout.
+    pub fn words_with_friends(tiles: &TileMap) -> Self {
+        Self::new(tiles, Premiums::words_with_friends())
     }
 
-    pub fn as_idx(self) -> usize {
-        self.0 as usize - 1
+    /// An empty Super Scrabble board. Always fails with [`BoardError::UnsupportedSize`] for now:
+    /// its 21×21 squares don't fit the 15×15 bitboards.
+    pub fn super_scrabble(_tiles: &TileMap) -> Result<Self> {
+        Err(BoardError::UnsupportedSize { size: 21 }.into())
     }
 
-    pub fn from_idx(idx: usize) -> Option<Self> {
-        (idx <= 15).then_some(Self(idx as u8))
+    pub fn premiums(&self) -> &Premiums<BB> {
+        &self.premiums
     }
-}
 
-pub struct Location {
-    row: Coordinate,
-    column: Coordinate,
-}
+    /// All squares with a tile on them.
+    pub fn occupied(&self) -> BB {
+        self.occupied
+    }
 
-impl Location {
-    pub fn row(&self) -> Coordinate {
-        self.row
+    /// All squares with a blank on them.
+    pub fn blanks(&self) -> BB {
+        self.blanks
     }
 
-    pub fn column(&self) -> Coordinate {
-        self.column
+    /// All squares holding `tile`, including blanks designated as `tile`.
+    pub fn letter(&self, tile: TileMapIdx) -> BB {
+        self.tile_bits
+            .iter()
+            .enumerate()
+            .fold(self.occupied, |acc, (bit, plane)| {
+                if (tile.as_u8() >> bit) & 1 == 1 {
+                    acc & *plane
+                } else {
+                    acc & !*plane
+                }
+            })
+    }
+
+    /// The letter on `l`. For blanks, this is the letter the blank was designated as.
+    pub fn tile_at(&self, l: Location) -> Option<TileMapIdx> {
+        if !self.occupied.is_set(l) {
+            return None;
+        }
+        let idx = self
+            .tile_bits
+            .iter()
+            .enumerate()
+            .filter(|(_, plane)| plane.is_set(l))
+            .fold(0, |acc, (bit, _)| acc | (1 << bit));
+        Some(TileMapIdx(idx))
+    }
+
+    pub fn is_blank(&self, l: Location) -> bool {
+        self.blanks.is_set(l)
+    }
+
+    /// The letter the blank on `l` stands for, or `None` if `l` does not hold a blank.
+    pub fn blank_designation(&self, l: Location) -> Option<TileMapIdx> {
+        self.is_blank(l).then(|| self.tile_at(l)).flatten()
+    }
+
+    /// Puts `tile` on the empty square `l`. If `is_blank` is set, a blank designated as `tile` is
+    /// placed instead. `tile` can't be the blank itself.
+    pub fn place(&mut self, l: Location, tile: TileMapIdx, is_blank: bool) -> Result<()> {
+        if let Some(existing) = self.tile_at(l) {
+            return Err(BoardError::Occupied {
+                location: l,
+                tile: existing,
+            }
+            .into());
+        }
+        self.check_placeable(tile)?;
+        let bb = BB::for_location(l);
+        self.occupied |= bb;
+        for (bit, plane) in self.tile_bits.iter_mut().enumerate() {
+            if (tile.as_u8() >> bit) & 1 == 1 {
+                *plane |= bb;
+            }
+        }
+        if is_blank {
+            self.blanks |= bb;
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /// Checks the internal consistency of the board representation: tile bits and blanks only on
+    /// occupied squares, only tiles from the tile map, and no stray bits outside the 15×15 area.
+    ///
+    /// Runs automatically after every mutation in debug builds.
+    pub fn check_invariants(&self) -> Result<()> {
+        let named = [("occupied", &self.occupied), ("blanks", &self.blanks)];
+        let planes = self.tile_bits.iter().map(|plane| ("tile bits", plane));
+        for (name, bb) in named.into_iter().chain(planes) {
+            let raw = bb.to_raw();
+            if raw[15] != 0 || raw.iter().any(|r| r & 0x8000 != 0) {
+                return Err(
+                    BoardError::Invariant(format!("Unused bits set in {name}: {bb:?}")).into(),
+                );
+            }
+            if !(*bb & !self.occupied).is_empty() {
+                return Err(BoardError::Invariant(format!(
+                    "{name} has squares that aren't occupied: {bb:?}"
+                ))
+                .into());
+            }
+        }
+        if let Some(l) = self.occupied.locations().find(|l| {
+            self.tile_at(*l)
+                .is_none_or(|t| t.as_u8() >= self.num_letters)
+        }) {
+            return Err(
+                BoardError::Invariant(format!("{l} holds a tile outside of the tile map")).into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn debug_check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(e) = self.check_invariants() {
+                panic!("Board invariant violated: {e}");
+            }
+        }
+    }
+
+    /// Puts the newly placed tiles of `m` on the board.
+    ///
+    /// Nothing is changed if the move doesn't fit the board, see [`Move::validate_against`].
+    pub fn apply(&mut self, m: &Move) -> Result<()> {
+        m.validate_against(self)?;
+        for (_, p) in m.placed() {
+            self.check_placeable(p.tile)?;
+        }
+        for (l, placement) in m.placed() {
+            self.place(l, placement.tile, placement.is_blank)?;
+        }
+        Ok(())
+    }
+
+    /// Fails unless `tile` is a letter of the tile map.
+    fn check_placeable(&self, tile: TileMapIdx) -> Result<()> {
+        if tile.as_u8() >= self.num_letters {
+            return Err(TileError::NotInTileMap(tile.as_u8()).into());
+        }
+        if Some(tile) == self.blank {
+            return Err(BoardError::UndesignatedBlank.into());
+        }
+        Ok(())
+    }
+
+    /// Takes the newly placed tiles of `m` off the board again, e.g. when it's challenged off.
+    ///
+    /// Nothing is changed unless every one of them is on the board as placed.
+    pub fn retract(&mut self, m: &Move) -> Result<()> {
+        if let Some((l, _)) = m
+            .placed()
+            .find(|(l, p)| self.tile_at(*l) != Some(p.tile) || self.is_blank(*l) != p.is_blank)
+        {
+            return Err(BoardError::NotPlacedThere(l).into());
+        }
+        let cleared = m
+            .placed()
+            .fold(BB::empty(), |cleared, (l, _)| cleared | BB::for_location(l));
+        self.occupied &= !cleared;
+        self.blanks &= !cleared;
+        for plane in &mut self.tile_bits {
+            *plane &= !cleared;
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    pub fn current_turn(&self) -> Player {
+        self.current_turn
+    }
+
+    /// Hands the turn to the other player.
+    pub fn end_turn(&mut self) {
+        self.current_turn = self.current_turn.other();
+    }
+
+    pub fn scores(&self) -> Scores {
+        self.scores
+    }
+
+    pub fn scores_mut(&mut self) -> &mut Scores {
+        &mut self.scores
     }
-}
 
-pub enum Direction {
-    Horizontal,
-    Vertical,
+    /// Returns a copy of this board mirrored along the main diagonal.
+    ///
+    /// Vertical words on the original board are horizontal words on the transposed board, so
+    /// anything that works along rows can handle columns by running on the transposed board and
+    /// mapping its locations back with [`Location::transposed`].
+    pub fn transposed(&self) -> Self {
+        Self {
+            occupied: self.occupied.transpose(),
+            tile_bits: self.tile_bits.map(|plane| plane.transpose()),
+            blanks: self.blanks.transpose(),
+            num_letters: self.num_letters,
+            blank: self.blank,
+            premiums: self.premiums.transposed(),
+            current_turn: self.current_turn,
+            scores: self.scores,
+        }
+    }
 }
 
-pub struct Move {
-    location: Location,
-    direction: Direction,
-    // word: Vec<TileMapIdx>,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn scores_add_negative() {
+        let mut scores = Scores::default();
+        scores.add(Player::First, 10);
+        scores.add(Player::First, -14);
+        scores[Player::Second] += 3;
+        assert_eq!(scores[Player::First], -4);
+        assert_eq!(scores.spread(Player::Second), 7);
+    }
+
+    #[test]
+    fn scores_overflow() {
+        let mut scores = Scores::default();
+        scores.add(Player::First, i32::MAX);
+        scores.add(Player::First, 1);
+        assert_eq!(scores[Player::First], i32::MAX);
+        assert_eq!(scores.checked_add(Player::First, 1), None);
+        assert_eq!(scores.checked_add(Player::First, -1), Some(i32::MAX - 1));
+    }
+
+    #[test]
+    fn blanks_keep_their_letter() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let e = tiles.find("E").unwrap();
+        let h8 = "H8".parse().unwrap();
+        let i8 = "I8".parse().unwrap();
+        board.place(h8, e, true).unwrap();
+        board.place(i8, e, false).unwrap();
+        assert_eq!(board.tile_at(h8), Some(e));
+        assert_eq!(board.tile_at(i8), Some(e));
+        assert_eq!(board.blank_designation(h8), Some(e));
+        assert_eq!(board.blank_designation(i8), None);
+        assert_eq!(board.letter(e).count_ones(), 2);
+        assert_eq!(board.occupied().count_ones(), 2);
+        assert!(board.place(h8, e, false).is_err());
+    }
+
+    #[test]
+    fn super_scrabble_is_unsupported() {
+        let tiles = TileMap::english_super();
+        let Err(err) = Board::<BitboardImpl>::super_scrabble(&tiles) else {
+            panic!("Built a 21×21 board");
+        };
+        assert_eq!(err.to_string(), "21×21 boards aren't supported, only 15×15");
+    }
+
+    #[test]
+    fn blank_only_as_a_letter() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let blank = tiles.blank_idx().unwrap();
+        let h8 = "H8".parse().unwrap();
+        for is_blank in [false, true] {
+            let Err(err) = board.place(h8, blank, is_blank) else {
+                panic!("Placed the blank itself");
+            };
+            assert_eq!(err.to_string(), BoardError::UndesignatedBlank.to_string());
+        }
+        let m = Move::new(
+            h8,
+            Direction::Horizontal,
+            vec![MoveTile::Placed(TilePlacement {
+                tile: blank,
+                is_blank: false,
+            })],
+        )
+        .unwrap();
+        assert!(board.apply(&m).is_err());
+        assert_eq!(board.occupied().count_ones(), 0);
+    }
+
+    #[test]
+    fn invariants() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        board
+            .place("H8".parse().unwrap(), TileMapIdx(3), true)
+            .unwrap();
+        assert!(board.check_invariants().is_ok());
+        assert!(board.transposed().check_invariants().is_ok());
+
+        let mut corrupt = board.clone();
+        corrupt.blanks |= BitboardImpl::for_location("A1".parse().unwrap());
+        assert!(corrupt.check_invariants().is_err());
+
+        let mut corrupt = board.clone();
+        corrupt.tile_bits[7] = corrupt.occupied;
+        assert!(corrupt.check_invariants().is_err());
+    }
+
+    #[test]
+    fn clones_are_independent() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let z = tiles.find("Z").unwrap();
+        board.place("H8".parse().unwrap(), z, false).unwrap();
+        let snapshot = board.clone();
+        board.place("H9".parse().unwrap(), z, false).unwrap();
+        assert_eq!(snapshot.letter(z).count_ones(), 1);
+        assert_eq!(board.letter(z).count_ones(), 2);
+        assert_eq!(snapshot.tile_at("H8".parse().unwrap()), Some(z));
+        assert_eq!(snapshot.tile_at("H9".parse().unwrap()), None);
+        assert_eq!(
+            board.letter(tiles.find("A").unwrap()),
+            BitboardImpl::empty()
+        );
+        board = snapshot;
+        assert_eq!(board.occupied().count_ones(), 1);
+    }
+
+    #[test]
+    fn retract_undoes_apply() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        let m = Move::parse("G7 a.E", &board, &tiles).unwrap();
+        let before = board.clone();
+        board.apply(&m).unwrap();
+        board.retract(&m).unwrap();
+        assert_eq!(board.occupied(), before.occupied());
+        assert_eq!(board.blanks(), BitboardImpl::empty());
+        assert_eq!(board.tile_bits, before.tile_bits);
+        // Only the tiles the move placed, and only once.
+        assert!(board.retract(&m).is_err());
+        assert_eq!(board.occupied().count_ones(), 3);
+    }
 }
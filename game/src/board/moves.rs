@@ -0,0 +1,403 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    error::{BoardError, Result, TileError},
+    TileMap, TileMapIdx,
+};
+
+use super::{Board, Direction, Location, PlayError};
+
+/// A tile taken from the rack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TilePlacement {
+    /// For blanks, the letter the blank is designated as.
+    pub tile: TileMapIdx,
+    pub is_blank: bool,
+}
+
+/// One square of the main word of a [`Move`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MoveTile {
+    /// A tile newly placed by this move.
+    Placed(TilePlacement),
+    /// A tile that was already on the board and is played through.
+    OnBoard(TileMapIdx),
+}
+
+impl MoveTile {
+    /// The letter on this square, designated letter for blanks.
+    pub fn tile(self) -> TileMapIdx {
+        match self {
+            MoveTile::Placed(p) => p.tile,
+            MoveTile::OnBoard(t) => t,
+        }
+    }
+}
+
+/// A play of tiles forming one main word.
+///
+/// `location` is the first square of the main word, and `tiles` covers every square of the main
+/// word in order, including the tiles played through.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Move {
+    location: Location,
+    direction: Direction,
+    tiles: Vec<MoveTile>,
+}
+
+impl Move {
+    /// Checks that the move places at least one tile and stays on the board.
+    pub fn new(location: Location, direction: Direction, tiles: Vec<MoveTile>) -> Result<Self> {
+        if !tiles.iter().any(|t| matches!(t, MoveTile::Placed(_))) {
+            return Err(BoardError::NoTilesPlaced.into());
+        }
+        if location
+            .offset(direction, tiles.len() as isize - 1)
+            .is_none()
+        {
+            return Err(BoardError::OffBoard {
+                len: tiles.len(),
+                at: location.notation(direction),
+            }
+            .into());
+        }
+        Ok(Self {
+            location,
+            direction,
+            tiles,
+        })
+    }
+
+    /// Builds the move that places `placements` on the empty squares starting at `location`.
+    ///
+    /// Occupied squares are skipped over and become part of the main word, as do the tiles directly
+    /// before `location` and after the last placement.
+    pub fn from_placements<BB: Bitboard>(
+        board: &Board<BB>,
+        location: Location,
+        direction: Direction,
+        placements: &[TilePlacement],
+    ) -> Result<Self> {
+        let mut start = location;
+        while let Some(prev) = start.offset(direction, -1) {
+            if board.tile_at(prev).is_none() {
+                break;
+            }
+            start = prev;
+        }
+        let mut tiles = Vec::with_capacity(placements.len());
+        let mut placements = placements.iter();
+        let mut pending = placements.next();
+        for l in start.ray(direction) {
+            match (board.tile_at(l), pending) {
+                (Some(existing), _) => tiles.push(MoveTile::OnBoard(existing)),
+                (None, Some(p)) => {
+                    tiles.push(MoveTile::Placed(*p));
+                    pending = placements.next();
+                }
+                (None, None) => break,
+            }
+        }
+        if pending.is_some() {
+            return Err(BoardError::OffBoard {
+                len: tiles.len() + 1 + placements.len(),
+                at: location.notation(direction),
+            }
+            .into());
+        }
+        Self::new(start, direction, tiles)
+    }
+
+    /// Parses standard notation: "8D WORD" is horizontal, "D8 WORD" vertical, see
+    /// [`Location::parse_with_direction`].
+    ///
+    /// Lowercase letters are blanks. Tiles already on `board` can be written either as their letter
+    /// or as `.`.
+    pub fn parse<BB: Bitboard>(s: &str, board: &Board<BB>, tiles: &TileMap) -> Result<Self> {
+        let Some((coordinate, word)) = s.trim().split_once(char::is_whitespace) else {
+            return Err(BoardError::InvalidMove(s.to_owned()).into());
+        };
+        let (location, direction) = Location::parse_with_direction(coordinate)?;
+        let mut squares = location.ray(direction);
+        let mut move_tiles = Vec::new();
+        let mut rest = word.trim();
+        while !rest.is_empty() {
+            let Some(l) = squares.next() else {
+                return Err(BoardError::OffBoard {
+                    len: move_tiles.len() + 1,
+                    at: location.notation(direction),
+                }
+                .into());
+            };
+            let existing = board.tile_at(l);
+            if let Some(r) = rest.strip_prefix('.') {
+                let Some(existing) = existing else {
+                    return Err(PlayError::ExpectedTile(l).into());
+                };
+                move_tiles.push(MoveTile::OnBoard(existing));
+                rest = r;
+                continue;
+            }
+            let Some((tile, is_blank, len)) = parse_tile(tiles, rest) else {
+                return Err(TileError::UnknownTile {
+                    at: rest.to_owned(),
+                    input: s.to_owned(),
+                }
+                .into());
+            };
+            rest = &rest[len..];
+            move_tiles.push(match existing {
+                Some(existing) if existing == tile => MoveTile::OnBoard(existing),
+                Some(_) => return Err(PlayError::DifferentTile(l).into()),
+                None => MoveTile::Placed(TilePlacement { tile, is_blank }),
+            });
+        }
+        let m = Self::new(location, direction, move_tiles)?;
+        m.validate_against(board)?;
+        Ok(m)
+    }
+
+    /// Formats the move in the notation accepted by [`Move::parse`].
+    pub fn notation(&self, tiles: &TileMap) -> String {
+        let mut out = self.location.notation(self.direction);
+        out.push(' ');
+        for t in &self.tiles {
+            let letter = tiles.get(t.tile()).map_or("?", String::as_str);
+            match t {
+                MoveTile::Placed(p) if p.is_blank => out.push_str(&letter.to_lowercase()),
+                _ => out.push_str(letter),
+            }
+        }
+        out
+    }
+
+    /// Checks that the move matches `board`: tiles played through are there, placed tiles go on
+    /// empty squares, and the main word isn't directly extended by other tiles.
+    pub fn validate_against<BB: Bitboard>(&self, board: &Board<BB>) -> Result<()> {
+        Ok(self.check_fit(board)?)
+    }
+
+    /// [`Move::validate_against`], with the reason it doesn't fit as a [`PlayError`].
+    pub(super) fn check_fit<BB: Bitboard>(&self, board: &Board<BB>) -> Result<(), PlayError> {
+        for (l, t) in self.squares() {
+            match (t, board.tile_at(l)) {
+                (MoveTile::Placed(_), Some(_)) => return Err(PlayError::Occupied(l)),
+                (MoveTile::OnBoard(_), None) => return Err(PlayError::ExpectedTile(l)),
+                (MoveTile::OnBoard(expected), Some(actual)) if expected != actual => {
+                    return Err(PlayError::DifferentTile(l));
+                }
+                _ => {}
+            }
+        }
+        let before = self.location.offset(self.direction, -1);
+        let after = self
+            .location
+            .offset(self.direction, self.tiles.len() as isize);
+        for l in [before, after].into_iter().flatten() {
+            if board.tile_at(l).is_some() {
+                return Err(PlayError::WordContinues(l));
+            }
+        }
+        Ok(())
+    }
+
+    /// The words formed on `board`, which the move has to fit: the main word, unless it's a single
+    /// tile, then the word across each placed tile that touches other tiles. Each word is its
+    /// direction and its squares in order.
+    pub fn words<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+    ) -> Vec<(Direction, Vec<(Location, MoveTile)>)> {
+        let mut words = Vec::new();
+        if self.tiles.len() > 1 {
+            words.push((self.direction, self.squares().collect()));
+        }
+        let across = self.direction.perpendicular();
+        for (l, placement) in self.placed() {
+            // The tiles already on the board directly before and after `l`, going away from it.
+            let run = |step: isize| -> Vec<_> {
+                std::iter::successors(l.offset(across, step), |s| s.offset(across, step))
+                    .map_while(|s| Some((s, MoveTile::OnBoard(board.tile_at(s)?))))
+                    .collect()
+            };
+            let (before, after) = (run(-1), run(1));
+            if before.is_empty() && after.is_empty() {
+                continue;
+            }
+            let squares = before
+                .into_iter()
+                .rev()
+                .chain(std::iter::once((l, MoveTile::Placed(placement))))
+                .chain(after)
+                .collect();
+            words.push((across, squares));
+        }
+        words
+    }
+
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn tiles(&self) -> &[MoveTile] {
+        &self.tiles
+    }
+
+    /// Every square of the main word with its tile.
+    pub fn squares(&self) -> impl Iterator<Item = (Location, MoveTile)> + '_ {
+        self.location
+            .ray(self.direction)
+            .zip(self.tiles.iter().copied())
+    }
+
+    /// The squares that receive a tile from the rack.
+    pub fn placed(&self) -> impl Iterator<Item = (Location, TilePlacement)> + '_ {
+        self.squares().filter_map(|(l, t)| match t {
+            MoveTile::Placed(p) => Some((l, p)),
+            MoveTile::OnBoard(_) => None,
+        })
+    }
+
+    pub fn num_placed(&self) -> usize {
+        self.placed().count()
+    }
+}
+
+/// The longest tile at the start of `s`: uppercase for regular tiles, lowercase for blanks.
+///
+/// Returns the tile, whether it is a blank, and its length in bytes.
+fn parse_tile(tiles: &TileMap, s: &str) -> Option<(TileMapIdx, bool, usize)> {
+    tiles
+        .letters()
+        .filter_map(|(idx, letter)| {
+            if s.starts_with(letter) {
+                Some((idx, false, letter.len()))
+            } else {
+                let lower = letter.to_lowercase();
+                s.starts_with(&lower).then_some((idx, true, lower.len()))
+            }
+        })
+        .max_by_key(|(_, _, len)| *len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, TileMap};
+    use pretty_assertions::assert_eq;
+
+    fn placements(tiles: &TileMap, word: &str) -> Vec<TilePlacement> {
+        word.chars()
+            .map(|c| TilePlacement {
+                tile: tiles.find(&c.to_ascii_uppercase().to_string()).unwrap(),
+                is_blank: c.is_ascii_lowercase(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn play_through_existing_tiles() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let first = Move::from_placements(
+            &board,
+            "8G".parse().unwrap(),
+            Direction::Horizontal,
+            &placements(&tiles, "CAt"),
+        )
+        .unwrap();
+        board.apply(&first).unwrap();
+        assert!(board.is_blank("I8".parse().unwrap()));
+
+        // Placing S and E around the A of CAT, starting from above.
+        let second = Move::from_placements(
+            &board,
+            "H7".parse().unwrap(),
+            Direction::Vertical,
+            &placements(&tiles, "SE"),
+        )
+        .unwrap();
+        assert_eq!(second.location(), "H7".parse().unwrap());
+        assert_eq!(second.tiles().len(), 3);
+        assert_eq!(second.num_placed(), 2);
+        assert_eq!(
+            second.tiles()[1],
+            MoveTile::OnBoard(tiles.find("A").unwrap())
+        );
+        board.apply(&second).unwrap();
+        assert_eq!(board.occupied().count_ones(), 5);
+        assert!(board.apply(&second).is_err());
+    }
+
+    #[test]
+    fn hooks_extend_main_word() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let first = Move::from_placements(
+            &board,
+            "8H".parse().unwrap(),
+            Direction::Horizontal,
+            &placements(&tiles, "CAT"),
+        )
+        .unwrap();
+        board.apply(&first).unwrap();
+        let hook = Move::from_placements(
+            &board,
+            "8K".parse().unwrap(),
+            Direction::Horizontal,
+            &placements(&tiles, "S"),
+        )
+        .unwrap();
+        assert_eq!(hook.location(), "H8".parse().unwrap());
+        assert_eq!(hook.tiles().len(), 4);
+
+        let truncated = Move::new(
+            "H8".parse().unwrap(),
+            Direction::Horizontal,
+            vec![MoveTile::Placed(placements(&tiles, "S")[0])],
+        )
+        .unwrap();
+        assert!(truncated.validate_against(&board).is_err());
+    }
+
+    #[test]
+    fn notation_roundtrip() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let first = Move::parse("8G CAt", &board, &tiles).unwrap();
+        assert_eq!(first.notation(&tiles), "8G CAt");
+        assert_eq!(first.num_placed(), 3);
+        board.apply(&first).unwrap();
+
+        let second = Move::parse("H7 S.E", &board, &tiles).unwrap();
+        assert_eq!(second.notation(&tiles), "H7 SAE");
+        assert_eq!(Move::parse("H7 SAE", &board, &tiles).unwrap(), second);
+        assert_eq!(second.num_placed(), 2);
+
+        assert!(Move::parse("H7 SOE", &board, &tiles).is_err());
+        assert!(Move::parse("H6 S.E", &board, &tiles).is_err());
+        assert!(Move::parse("H7", &board, &tiles).is_err());
+        assert!(Move::parse("H7 S.?", &board, &tiles).is_err());
+        assert!(Move::parse("8M CATS", &board, &tiles).is_err());
+        assert!(Move::parse("8J S", &board, &tiles).is_err());
+    }
+
+    #[test]
+    fn off_board() {
+        let tiles = TileMap::english();
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        assert!(Move::from_placements(
+            &board,
+            "8M".parse().unwrap(),
+            Direction::Horizontal,
+            &placements(&tiles, "CATS"),
+        )
+        .is_err());
+        assert!(Move::new("H8".parse().unwrap(), Direction::Vertical, vec![]).is_err());
+    }
+}
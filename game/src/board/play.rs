@@ -0,0 +1,122 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{bitboard::Bitboard, lexicon::Lexicon, Rack, TileBag, TileMap, TileMapIdx};
+
+use super::{Board, Move, PlayError};
+
+/// Everything a player can do on their turn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Play {
+    Place(Move),
+    /// Swaps these tiles from the rack for new ones from the bag.
+    Exchange(Vec<TileMapIdx>),
+    Pass,
+}
+
+impl Play {
+    /// Checks that the play is allowed: see [`Move::validate`] for placements. An exchange needs
+    /// at least one tile, all on `rack`, and [enough tiles](TileBag::min_for_exchange) in `bag`.
+    pub fn validate<BB: Bitboard, R: Rng>(
+        &self,
+        board: &Board<BB>,
+        rack: &Rack,
+        bag: &TileBag<R>,
+        lexicon: &Lexicon,
+    ) -> Result<(), PlayError> {
+        match self {
+            Play::Place(m) => m.validate(board, rack, lexicon),
+            Play::Exchange(tiles) => {
+                if tiles.is_empty() {
+                    return Err(PlayError::EmptyExchange);
+                }
+                if !bag.can_exchange() {
+                    return Err(PlayError::BagTooSmall {
+                        remaining: bag.remaining(),
+                        needed: bag.min_for_exchange(),
+                    });
+                }
+                let mut rest = rack.clone();
+                for t in tiles {
+                    if rest.remove(*t).is_err() {
+                        return Err(PlayError::NotOnRack(lexicon.spell(&[*t])));
+                    }
+                }
+                Ok(())
+            }
+            Play::Pass => Ok(()),
+        }
+    }
+
+    /// The move, if the play places tiles.
+    pub fn as_move(&self) -> Option<&Move> {
+        match self {
+            Play::Place(m) => Some(m),
+            Play::Exchange(_) | Play::Pass => None,
+        }
+    }
+
+    /// Formats the play like GCG does: placements as in [`Move::notation`], exchanges as `-`
+    /// followed by the tiles, and passes as a lone `-`.
+    pub fn notation(&self, tiles: &TileMap) -> String {
+        match self {
+            Play::Place(m) => m.notation(tiles),
+            Play::Exchange(exchanged) => {
+                let rack = Rack::from_tiles(exchanged.clone(), exchanged.len())
+                    .expect("The rack is as large as the exchange");
+                format!("-{}", rack.display(tiles))
+            }
+            Play::Pass => "-".to_string(),
+        }
+    }
+}
+
+impl From<Move> for Play {
+    fn from(m: Move) -> Self {
+        Play::Place(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn exchange_and_pass() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["QI"], &tiles);
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let rack = Rack::parse("QQVW?", &tiles).unwrap();
+        let mut bag = TileBag::seeded(&tiles, 1);
+        let exchange =
+            |letters: &str| Play::Exchange(Rack::parse(letters, &tiles).unwrap().tiles().to_vec());
+
+        assert_eq!(Play::Pass.validate(&board, &rack, &bag, &lexicon), Ok(()));
+        assert_eq!(
+            exchange("QQ?").validate(&board, &rack, &bag, &lexicon),
+            Ok(())
+        );
+        assert_eq!(exchange("VQ?").notation(&tiles), "-QV?");
+        assert_eq!(Play::Pass.notation(&tiles), "-");
+        assert_eq!(
+            exchange("QQQ").validate(&board, &rack, &bag, &lexicon),
+            Err(PlayError::NotOnRack("Q".to_string()))
+        );
+        assert_eq!(
+            exchange("").validate(&board, &rack, &bag, &lexicon),
+            Err(PlayError::EmptyExchange)
+        );
+
+        bag.draw(94);
+        assert_eq!(
+            exchange("V").validate(&board, &rack, &bag, &lexicon),
+            Err(PlayError::BagTooSmall {
+                remaining: 6,
+                needed: 7
+            })
+        );
+        assert_eq!(Play::Pass.validate(&board, &rack, &bag, &lexicon), Ok(()));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::{
+    bitboard::Bitboard,
+    error::{BoardError, Result},
+};
+
+use super::{Column, Location, Row};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Premium {
+    DoubleLetter,
+    TripleLetter,
+    DoubleWord,
+    TripleWord,
+}
+
+impl Premium {
+    pub fn letter_multiplier(self) -> u16 {
+        match self {
+            Premium::DoubleLetter => 2,
+            Premium::TripleLetter => 3,
+            Premium::DoubleWord | Premium::TripleWord => 1,
+        }
+    }
+
+    pub fn word_multiplier(self) -> u16 {
+        match self {
+            Premium::DoubleWord => 2,
+            Premium::TripleWord => 3,
+            Premium::DoubleLetter | Premium::TripleLetter => 1,
+        }
+    }
+}
+
+/// The premium squares of a board layout, plus the square the first play has to cover.
+///
+/// Layouts are always 15×15: larger variants such as Super Scrabble (21×21) don't fit the bitboard
+/// representation, see [`Board::super_scrabble`](super::Board::super_scrabble).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Premiums<BB: Bitboard> {
+    double_letter: BB,
+    triple_letter: BB,
+    double_word: BB,
+    triple_word: BB,
+    start: Location,
+}
+
+impl<BB: Bitboard> Premiums<BB> {
+    /// Standard Scrabble.
+    const STANDARD: [&'static str; 15] = [
+        "T..d...T...d..T",
+        ".D...t...t...D.",
+        "..D...d.d...D..",
+        "d..D...d...D..d",
+        "....D.....D....",
+        ".t...t...t...t.",
+        "..d...d.d...d..",
+        "T..d...D...d..T",
+        "..d...d.d...d..",
+        ".t...t...t...t.",
+        "....D.....D....",
+        "d..D...d...D..d",
+        "..D...d.d...D..",
+        ".D...t...t...D.",
+        "T..d...T...d..T",
+    ];
+
+    /// The classic Words With Friends layout.
+    const WORDS_WITH_FRIENDS: [&'static str; 15] = [
+        "...T..t.t..T...",
+        "..d..D...D..d..",
+        ".d..d.....d..d.",
+        "T..t...D...t..T",
+        "..d...d.d...d..",
+        ".D...t...t...D.",
+        "t...d.....d...t",
+        "...D.......D...",
+        "t...d.....d...t",
+        ".D...t...t...D.",
+        "..d...d.d...d..",
+        "T..t...D...t..T",
+        ".d..d.....d..d.",
+        "..d..D...D..d..",
+        "...T..t.t..T...",
+    ];
+
+    /// The center square, H8.
+    const CENTER: Location = Location::CENTER;
+
+    /// No premium squares at all.
+    pub fn none() -> Self {
+        Self {
+            double_letter: BB::empty(),
+            triple_letter: BB::empty(),
+            double_word: BB::empty(),
+            triple_word: BB::empty(),
+            start: Self::CENTER,
+        }
+    }
+
+    pub fn standard() -> Self {
+        Self::from_rows(&Self::STANDARD, Self::CENTER).expect("The standard layout is valid")
+    }
+
+    pub fn words_with_friends() -> Self {
+        Self::from_rows(&Self::WORDS_WITH_FRIENDS, Self::CENTER)
+            .expect("The Words With Friends layout is valid")
+    }
+
+    /// Parses a layout from one string per row, top to bottom.
+    ///
+    /// Each row has one character per column: `d` double letter, `t` triple letter,
+    /// `D` double word, `T` triple word, `.` no premium.
+    pub fn from_rows(rows: &[&str], start: Location) -> Result<Self> {
+        if rows.len() != Row::MAX as usize {
+            return Err(BoardError::RowCount(rows.len()).into());
+        }
+        let mut premiums = Self::none();
+        premiums.start = start;
+        for (row, line) in Row::all().zip(rows) {
+            if line.chars().count() != Column::MAX as usize {
+                return Err(BoardError::RowLength {
+                    row,
+                    line: line.to_string(),
+                }
+                .into());
+            }
+            for (column, c) in Column::all().zip(line.chars()) {
+                let bb = match c {
+                    'd' => &mut premiums.double_letter,
+                    't' => &mut premiums.triple_letter,
+                    'D' => &mut premiums.double_word,
+                    'T' => &mut premiums.triple_word,
+                    '.' => continue,
+                    premium => {
+                        return Err(BoardError::UnknownPremium {
+                            premium,
+                            location: Location::new(row, column),
+                        }
+                        .into())
+                    }
+                };
+                *bb |= BB::for_location(Location::new(row, column));
+            }
+        }
+        Ok(premiums)
+    }
+
+    /// The layout in the form [`Premiums::from_rows`] reads.
+    pub fn to_rows(&self) -> Vec<String> {
+        Row::all()
+            .map(|row| {
+                Column::all()
+                    .map(|column| match self.get(Location::new(row, column)) {
+                        Some(Premium::DoubleLetter) => 'd',
+                        Some(Premium::TripleLetter) => 't',
+                        Some(Premium::DoubleWord) => 'D',
+                        Some(Premium::TripleWord) => 'T',
+                        None => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn get(&self, l: Location) -> Option<Premium> {
+        if self.double_letter.is_set(l) {
+            Some(Premium::DoubleLetter)
+        } else if self.triple_letter.is_set(l) {
+            Some(Premium::TripleLetter)
+        } else if self.double_word.is_set(l) {
+            Some(Premium::DoubleWord)
+        } else if self.triple_word.is_set(l) {
+            Some(Premium::TripleWord)
+        } else {
+            None
+        }
+    }
+
+    pub fn bitboard(&self, premium: Premium) -> BB {
+        match premium {
+            Premium::DoubleLetter => self.double_letter,
+            Premium::TripleLetter => self.triple_letter,
+            Premium::DoubleWord => self.double_word,
+            Premium::TripleWord => self.triple_word,
+        }
+    }
+
+    /// The square the first play has to cover.
+    pub fn start(&self) -> Location {
+        self.start
+    }
+
+    pub fn transposed(&self) -> Self {
+        Self {
+            double_letter: self.double_letter.transpose(),
+            triple_letter: self.triple_letter.transpose(),
+            double_word: self.double_word.transpose(),
+            triple_word: self.triple_word.transpose(),
+            start: self.start.transposed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    fn counts(p: &Premiums<BitboardImpl>) -> [u32; 4] {
+        [
+            Premium::DoubleLetter,
+            Premium::TripleLetter,
+            Premium::DoubleWord,
+            Premium::TripleWord,
+        ]
+        .map(|premium| p.bitboard(premium).count_ones())
+    }
+
+    #[test]
+    fn standard_layout() {
+        let p = Premiums::<BitboardImpl>::standard();
+        assert_eq!(counts(&p), [24, 12, 17, 8]);
+        assert_eq!(p.get("H8".parse().unwrap()), Some(Premium::DoubleWord));
+        assert_eq!(p.get("A1".parse().unwrap()), Some(Premium::TripleWord));
+        assert_eq!(p.get("D1".parse().unwrap()), Some(Premium::DoubleLetter));
+        assert_eq!(p.get("F2".parse().unwrap()), Some(Premium::TripleLetter));
+        assert_eq!(p.get("B1".parse().unwrap()), None);
+        assert_eq!(p.transposed(), p);
+        assert_eq!(p.to_rows(), Premiums::<BitboardImpl>::STANDARD);
+    }
+
+    #[test]
+    fn words_with_friends_layout() {
+        let p = Premiums::<BitboardImpl>::words_with_friends();
+        assert_eq!(counts(&p), [24, 16, 12, 8]);
+        assert_eq!(p.get("H8".parse().unwrap()), None);
+        assert_eq!(p.get("D1".parse().unwrap()), Some(Premium::TripleWord));
+        assert_eq!(p.transposed(), p);
+    }
+
+    #[test]
+    fn from_rows_rejects_bad_layouts() {
+        let start = Location::CENTER;
+        assert!(Premiums::<BitboardImpl>::from_rows(&["..............."; 14], start).is_err());
+        let mut rows = Premiums::<BitboardImpl>::STANDARD;
+        rows[3] = "d..D...d...D..";
+        assert!(Premiums::<BitboardImpl>::from_rows(&rows, start).is_err());
+        rows[3] = "d..D...x...D..d";
+        assert!(Premiums::<BitboardImpl>::from_rows(&rows, start).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use thiserror::Error;
+
+use crate::{
+    bitboard::Bitboard,
+    lexicon::{AlphagramIndex, Lexicon},
+    tiles::TileCounts,
+    Rack, TileMap, TileMapIdx,
+};
+
+use super::{Board, Location, Move, MoveTile};
+
+/// Why a [`Move`] or other [`Play`](super::Play) isn't allowed, see [`Move::validate`] and
+/// [`Play::validate`](super::Play::validate).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PlayError {
+    /// A tile is placed on a square that's already occupied.
+    #[error("{0} is already occupied")]
+    Occupied(Location),
+    /// The move plays through a square that's empty.
+    #[error("{0} is expected to be occupied but is empty")]
+    ExpectedTile(Location),
+    /// The move plays through a square holding a different tile.
+    #[error("{0} holds a different tile than expected")]
+    DifferentTile(Location),
+    /// The main word is directly extended by a tile on this square.
+    #[error("The main word continues onto {0}")]
+    WordContinues(Location),
+    /// The first move doesn't cover the start square.
+    #[error("The first move has to cover {0}")]
+    MissesStart(Location),
+    /// The move doesn't touch any tile already on the board.
+    #[error("The move doesn't connect to any tile")]
+    NotConnected,
+    /// The move forms no word of two letters or more, e.g. a single tile as the first move.
+    #[error("The move forms no word")]
+    NoWord,
+    /// The rack lacks a tile the move places, spelled as in the lexicon, `?` for a blank.
+    #[error("The rack has no {0}")]
+    NotOnRack(String),
+    /// The words formed that aren't in the lexicon, in the order of [`Move::words`].
+    #[error("{} not in the lexicon", match .0.as_slice() {
+        [word] => format!("{word} is"),
+        words => format!("{} are", words.join(", ")),
+    })]
+    InvalidWords(Vec<String>),
+    /// An exchange of no tiles.
+    #[error("An exchange needs at least one tile")]
+    EmptyExchange,
+    /// An exchange with only `remaining` tiles left in the bag, fewer than `needed`.
+    #[error("Exchanging needs at least {needed} tiles in the bag, there are {remaining}")]
+    BagTooSmall { remaining: usize, needed: usize },
+}
+
+impl Move {
+    /// Checks that the move can be played from `rack` on `board`: it fits the board, the first
+    /// move covers the start square and later ones touch existing tiles, `rack` holds the tiles it
+    /// places, and every word it forms is in `lexicon`.
+    pub fn validate<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        rack: &Rack,
+        lexicon: &Lexicon,
+    ) -> Result<(), PlayError> {
+        self.validate_by(board, rack, lexicon, |word| lexicon.contains(word))
+    }
+
+    /// Like [`Move::validate`], but for [Clabbers](crate::rules::Variant::Clabbers): the words
+    /// only have to be anagrams of words in `index`, which has to be built from `lexicon`.
+    pub fn validate_clabbers<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        rack: &Rack,
+        lexicon: &Lexicon,
+        index: &AlphagramIndex,
+    ) -> Result<(), PlayError> {
+        self.validate_by(board, rack, lexicon, |word| index.contains_anagram(word))
+    }
+
+    fn validate_by<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        rack: &Rack,
+        lexicon: &Lexicon,
+        is_word: impl Fn(&[TileMapIdx]) -> bool,
+    ) -> Result<(), PlayError> {
+        self.validate_placement(board, rack, lexicon)?;
+        let invalid: Vec<_> = self
+            .words(board)
+            .iter()
+            .map(|(_, squares)| squares.iter().map(|(_, t)| t.tile()).collect::<Vec<_>>())
+            .filter(|word| !is_word(word))
+            .map(|word| lexicon.spell(&word))
+            .collect();
+        if !invalid.is_empty() {
+            return Err(PlayError::InvalidWords(invalid));
+        }
+        Ok(())
+    }
+
+    /// Like [`Move::validate`], but without checking the words, which are left to a challenge.
+    /// `lexicon` only provides the blank and the spelling of tiles.
+    pub fn validate_placement<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        rack: &Rack,
+        lexicon: &Lexicon,
+    ) -> Result<(), PlayError> {
+        self.check_fit(board)?;
+        let words = self.words(board);
+        if board.occupied().is_empty() {
+            let start = board.premiums().start();
+            if !self.squares().any(|(l, _)| l == start) {
+                return Err(PlayError::MissesStart(start));
+            }
+        } else {
+            let plays_through = self
+                .tiles()
+                .iter()
+                .any(|t| matches!(t, MoveTile::OnBoard(_)));
+            // Any word besides the main one crosses an existing tile.
+            let crosses = words.len() > usize::from(self.tiles().len() > 1);
+            if !plays_through && !crosses {
+                return Err(PlayError::NotConnected);
+            }
+        }
+        if words.is_empty() {
+            return Err(PlayError::NoWord);
+        }
+
+        let mut available = TileCounts::from(rack);
+        for (_, p) in self.placed() {
+            let tile = if p.is_blank {
+                lexicon.blank()
+            } else {
+                Some(p.tile)
+            };
+            if tile.map_or(0, |t| available.remove(t, 1)) == 0 {
+                let letter = match p.is_blank {
+                    true => TileMap::BLANK.to_string(),
+                    false => lexicon.spell(&[p.tile]),
+                };
+                return Err(PlayError::NotOnRack(letter));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::{Direction, TilePlacement},
+        TileMap,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn check(
+        board: &Board<BitboardImpl>,
+        notation: &str,
+        rack: &str,
+        lexicon: &Lexicon,
+    ) -> Result<(), PlayError> {
+        let tiles = TileMap::english();
+        let m = Move::parse(notation, board, &tiles).unwrap();
+        m.validate(board, &Rack::parse(rack, &tiles).unwrap(), lexicon)
+    }
+
+    #[test]
+    fn first_move() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "AT"], &tiles);
+        let board = Board::standard(&tiles);
+        assert_eq!(check(&board, "8G CAT", "CAT", &lexicon), Ok(()));
+        assert_eq!(check(&board, "8G CAt", "CA?", &lexicon), Ok(()));
+        assert_eq!(
+            check(&board, "8A CAT", "CAT", &lexicon),
+            Err(PlayError::MissesStart("H8".parse().unwrap()))
+        );
+        assert_eq!(
+            check(&board, "8G CAT", "CA?", &lexicon),
+            Err(PlayError::NotOnRack("T".to_string()))
+        );
+        assert_eq!(
+            check(&board, "8G CAt", "CAT", &lexicon),
+            Err(PlayError::NotOnRack("?".to_string()))
+        );
+        assert_eq!(
+            check(&board, "8G TAC", "CAT", &lexicon),
+            Err(PlayError::InvalidWords(vec!["TAC".to_string()]))
+        );
+        // A single tile forms no word, so no lexicon can allow it.
+        assert_eq!(check(&board, "8H Q", "Q", &lexicon), Err(PlayError::NoWord));
+        assert_eq!(
+            Move::parse("8H Q", &board, &tiles)
+                .unwrap()
+                .validate_placement(&board, &Rack::parse("Q", &tiles).unwrap(), &lexicon),
+            Err(PlayError::NoWord)
+        );
+    }
+
+    #[test]
+    fn later_moves() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "AT"], &tiles);
+        let mut board = Board::standard(&tiles);
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        assert_eq!(check(&board, "8G CATS", "S", &lexicon), Ok(()));
+        assert_eq!(
+            check(&board, "10A AT", "AT", &lexicon),
+            Err(PlayError::NotConnected)
+        );
+        // Forms AT, CA and AT.
+        let err = check(&board, "9G AT", "AT", &lexicon).unwrap_err();
+        assert_eq!(err, PlayError::InvalidWords(vec!["CA".to_string()]));
+        assert_eq!(err.to_string(), "CA is not in the lexicon");
+
+        let x = TilePlacement {
+            tile: tiles.find("X").unwrap(),
+            is_blank: false,
+        };
+        let onto_c = Move::new(
+            "G8".parse().unwrap(),
+            Direction::Vertical,
+            vec![MoveTile::Placed(x)],
+        )
+        .unwrap();
+        assert_eq!(
+            onto_c.validate(&board, &Rack::parse("X", &tiles).unwrap(), &lexicon),
+            Err(PlayError::Occupied("G8".parse().unwrap()))
+        );
+    }
+}
@@ -0,0 +1,541 @@
+//! Computer players.
+
+use std::{collections::HashSet, sync::Arc};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Move, Play},
+    leaves::LeaveTable,
+    movegen::{clabbers_moves, iter_moves, leave},
+    rules::Variant,
+    score::score_move_with,
+    view::GameView,
+    Game, Rack, TileMap, TileMapIdx,
+};
+
+/// Chooses the plays of one side of a game, e.g. in [self-play](crate::selfplay::run). Implement
+/// it to plug in an engine of your own.
+pub trait Strategy<BB: Bitboard> {
+    /// The play for the player whose view of the game `view` is, on their turn. Random choices
+    /// should be made with the view's [turn RNG](GameView::turn_rng), so games with the same seed
+    /// play out the same.
+    fn choose(&mut self, view: &GameView<BB>) -> Play;
+}
+
+/// Plays the highest scoring move, the baseline stronger strategies are measured against.
+///
+/// When no move scores, it exchanges the tiles [`exchange_junk`] picks, or passes if the bag is
+/// too small to exchange.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Greedy;
+
+impl<BB: Bitboard> Strategy<BB> for Greedy {
+    fn choose(&mut self, view: &GameView<BB>) -> Play {
+        let mut best: Option<(u32, Move)> = None;
+        for m in legal_moves(view) {
+            let score = score_move_with(view.board(), &m, view.tiles(), view.rules()).total();
+            // Ties go to the move generated first, so the choice is deterministic.
+            if best.as_ref().is_none_or(|(s, _)| score > *s) {
+                best = Some((score, m));
+            }
+        }
+        match best {
+            Some((score, m)) if score > 0 => Play::Place(m),
+            best => exchange_or(view, best.map_or(Play::Pass, |(_, m)| Play::Place(m))),
+        }
+    }
+}
+
+/// Plays the move with the highest equity: its score plus the value of the tiles it keeps, from
+/// a [`LeaveTable`]. Exchanges are weighed the same way, as scoring nothing and keeping the best
+/// leave, see [`best_exchange`].
+///
+/// Once the bag is empty, leaves no longer matter and moves are ranked by score.
+#[derive(Debug, Clone)]
+pub struct Equity {
+    leaves: Arc<LeaveTable>,
+}
+
+impl Equity {
+    pub fn new(leaves: impl Into<Arc<LeaveTable>>) -> Self {
+        Self {
+            leaves: leaves.into(),
+        }
+    }
+
+    pub fn leaves(&self) -> &LeaveTable {
+        &self.leaves
+    }
+}
+
+impl Equity {
+    /// Every placement with its equity, and every exchange if the bag allows them.
+    pub(crate) fn rated<BB: Bitboard>(&self, view: &GameView<BB>) -> Vec<(Play, f32)> {
+        let (rack, tiles) = (view.rack(), view.tiles());
+        let endgame = view.bag_len() == 0;
+        let mut plays: Vec<(Play, f32)> = legal_moves(view)
+            .into_iter()
+            .map(|m| {
+                let score = score_move_with(view.board(), &m, tiles, view.rules()).total();
+                let mut equity = score as f32;
+                if !endgame {
+                    equity += self.leaves.value_of(&leave(rack, &m, tiles));
+                }
+                (Play::Place(m), equity)
+            })
+            .collect();
+        if view.can_exchange() {
+            plays.extend(
+                exchanges(rack, &self.leaves)
+                    .into_iter()
+                    .map(|(exchanged, value)| (Play::Exchange(exchanged), value)),
+            );
+        }
+        plays
+    }
+}
+
+impl<BB: Bitboard> Strategy<BB> for Equity {
+    fn choose(&mut self, view: &GameView<BB>) -> Play {
+        // Ties go to the play rated first, placements before the exchange.
+        best_rated(self.rated(view))
+    }
+}
+
+/// How well a [`Leveled`] bot plays, for offering opponents of different strengths. Always playing
+/// the top equity play makes for a discouraging opponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BotLevel {
+    Beginner,
+    Intermediate,
+    /// Plays like [`Equity`].
+    Expert,
+}
+
+impl BotLevel {
+    pub fn settings(self) -> LevelSettings {
+        match self {
+            BotLevel::Beginner => LevelSettings {
+                min_word_probability: 0.07,
+                noise: 12.0,
+                max_bingo_rate: 0.05,
+            },
+            BotLevel::Intermediate => LevelSettings {
+                min_word_probability: 0.045,
+                noise: 5.0,
+                max_bingo_rate: 0.15,
+            },
+            BotLevel::Expert => LevelSettings {
+                min_word_probability: 0.0,
+                noise: 0.0,
+                max_bingo_rate: 1.0,
+            },
+        }
+    }
+}
+
+/// The knobs behind a [`BotLevel`], for tuning levels of your own.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelSettings {
+    /// Words with a lower [`word_probability`] aren't played, as if the bot didn't know them. This
+    /// mostly rules out the short words with the power tiles.
+    pub min_word_probability: f64,
+    /// Up to this many points of noise, either way, are added to the equity of every play.
+    pub noise: f32,
+    /// The most bingos per turn: above this rate, the bot passes bingos up.
+    pub max_bingo_rate: f32,
+}
+
+impl From<BotLevel> for LevelSettings {
+    fn from(level: BotLevel) -> Self {
+        level.settings()
+    }
+}
+
+/// An [`Equity`] bot playing at a [`BotLevel`]. Noise is drawn from the view's
+/// [turn RNG](GameView::turn_rng), and the bingo rate counts the turns this bot has played, so
+/// use a new one for every game.
+#[derive(Debug, Clone)]
+pub struct Leveled {
+    equity: Equity,
+    settings: LevelSettings,
+    turns: usize,
+    bingos: usize,
+}
+
+impl Leveled {
+    pub fn new(equity: Equity, level: impl Into<LevelSettings>) -> Self {
+        Self {
+            equity,
+            settings: level.into(),
+            turns: 0,
+            bingos: 0,
+        }
+    }
+
+    pub fn settings(&self) -> &LevelSettings {
+        &self.settings
+    }
+}
+
+impl<BB: Bitboard> Strategy<BB> for Leveled {
+    fn choose(&mut self, view: &GameView<BB>) -> Play {
+        let LevelSettings {
+            min_word_probability,
+            noise,
+            max_bingo_rate,
+        } = self.settings;
+        let is_bingo = |m: &Move| m.num_placed() >= view.rules().rack_size;
+        let bingo_allowed = (self.bingos + 1) as f32 <= max_bingo_rate * (self.turns + 1) as f32;
+        let mut rng = view.turn_rng();
+        let plays = self
+            .equity
+            .rated(view)
+            .into_iter()
+            .filter(|(play, _)| match play {
+                Play::Place(m) => {
+                    let word: Vec<TileMapIdx> = m.tiles().iter().map(|t| t.tile()).collect();
+                    (bingo_allowed || !is_bingo(m))
+                        && word_probability(&word, view.tiles()) >= min_word_probability
+                }
+                _ => true,
+            })
+            .map(|(play, equity)| {
+                let noise = if noise > 0.0 {
+                    rng.gen_range(-noise..=noise)
+                } else {
+                    0.0
+                };
+                (play, equity + noise)
+            })
+            .collect();
+        let play = best_rated(plays);
+        self.turns += 1;
+        if matches!(&play, Play::Place(m) if is_bingo(m)) {
+            self.bingos += 1;
+        }
+        play
+    }
+}
+
+/// How likely `word` is to be drawn from a full bag, per tile: the geometric mean, so words of
+/// different lengths compare. Words with common letters score high, ones with the power tiles low.
+pub fn word_probability(word: &[TileMapIdx], tiles: &TileMap) -> f64 {
+    let mut letters: Vec<TileMapIdx> = word.to_vec();
+    letters.sort();
+    letters.dedup();
+    let ways: f64 = letters
+        .iter()
+        .map(|&t| {
+            let needed = word.iter().filter(|&&w| w == t).count();
+            binomial(usize::from(tiles.count(t)), needed)
+        })
+        .product();
+    let p = ways / binomial(tiles.total_count(), word.len());
+    p.powf(1.0 / word.len().max(1) as f64)
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// The play with the highest rating, the first of equals, or a pass if there are none.
+fn best_rated(plays: Vec<(Play, f32)>) -> Play {
+    let mut best: Option<(Play, f32)> = None;
+    for (play, rating) in plays {
+        if best.as_ref().is_none_or(|(_, r)| rating > *r) {
+            best = Some((play, rating));
+        }
+    }
+    best.map_or(Play::Pass, |(play, _)| play)
+}
+
+/// The exchange keeping the most valuable leave according to `leaves`, as the tiles to exchange
+/// and the value of what's kept. Exchanges at least one tile; `None` for an empty rack.
+pub fn best_exchange(rack: &Rack, leaves: &LeaveTable) -> Option<(Vec<TileMapIdx>, f32)> {
+    let mut best: Option<(Vec<TileMapIdx>, f32)> = None;
+    for (exchanged, value) in exchanges(rack, leaves) {
+        if best.as_ref().is_none_or(|(_, v)| value > *v) {
+            best = Some((exchanged, value));
+        }
+    }
+    best
+}
+
+/// Every distinct exchange of at least one tile, as the tiles to exchange and the value of the
+/// leave kept according to `leaves`. Racks with repeated tiles have fewer than `2^n - 1`: which
+/// of two Es goes back doesn't matter.
+pub fn exchanges(rack: &Rack, leaves: &LeaveTable) -> Vec<(Vec<TileMapIdx>, f32)> {
+    let tiles = rack.tiles();
+    let mut exchanges: Vec<(Vec<TileMapIdx>, f32)> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut kept = Vec::with_capacity(tiles.len());
+    // Bit `i` of `mask` set: `tiles[i]` is exchanged.
+    for mask in 1..1u32 << tiles.len() {
+        let exchanged: Vec<TileMapIdx> = (0..tiles.len())
+            .filter(|i| mask & 1 << i != 0)
+            .map(|i| tiles[i])
+            .collect();
+        // The rack is sorted, so equal exchanges list their tiles in the same order.
+        if !seen.insert(exchanged.clone()) {
+            continue;
+        }
+        kept.clear();
+        kept.extend(
+            (0..tiles.len())
+                .filter(|i| mask & 1 << i == 0)
+                .map(|i| tiles[i]),
+        );
+        exchanges.push((exchanged, leaves.value_of(&kept)));
+    }
+    exchanges
+}
+
+/// Every legal placement for the viewing player: generated with the generation lexicon, or with
+/// the judging lexicon and its alphagram index in [Clabbers](Variant::Clabbers). None without a
+/// lexicon.
+pub fn legal_moves<BB: Bitboard>(view: &GameView<BB>) -> Vec<Move> {
+    placements(view.game, view.board(), view.rack())
+}
+
+/// Like [`legal_moves`], but for `rack` on `board` instead of the game's own, e.g. while searching
+/// ahead.
+pub(crate) fn placements<BB: Bitboard>(
+    game: &Game<BB>,
+    board: &Board<BB>,
+    rack: &Rack,
+) -> Vec<Move> {
+    let Some(lexicons) = game.lexicons() else {
+        return Vec::new();
+    };
+    match (game.rules().variant, game.alphagrams()) {
+        (Variant::Clabbers, Some(index)) => {
+            clabbers_moves(board, rack, lexicons.judge(), index).collect()
+        }
+        (Variant::Clabbers, None) => Vec::new(),
+        (Variant::Standard, _) => iter_moves(board, rack, lexicons.generation()).collect(),
+    }
+}
+
+/// A rule of thumb for what to throw back: everything but the blanks and one of each letter worth
+/// a single point, the tiles most words are made of. If that keeps every tile, all but the blanks.
+pub fn exchange_junk(rack: &Rack, tiles: &TileMap) -> Vec<TileMapIdx> {
+    let mut kept: Vec<TileMapIdx> = Vec::new();
+    let mut junk = Vec::new();
+    for &t in rack.tiles() {
+        if tiles.is_blank(t) || (tiles.score(t) == 1 && !kept.contains(&t)) {
+            kept.push(t);
+        } else {
+            junk.push(t);
+        }
+    }
+    if junk.is_empty() {
+        junk = rack
+            .tiles()
+            .iter()
+            .copied()
+            .filter(|t| !tiles.is_blank(*t))
+            .collect();
+    }
+    junk
+}
+
+/// An [`exchange_junk`] exchange if the bag allows it and there's something to exchange,
+/// `otherwise` if not.
+fn exchange_or<BB: Bitboard>(view: &GameView<BB>, otherwise: Play) -> Play {
+    let junk = exchange_junk(view.rack(), view.tiles());
+    if view.can_exchange() && !junk.is_empty() {
+        Play::Exchange(junk)
+    } else {
+        otherwise
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl, board::Player, lexicon::Lexicon, rules::Rules, score::score_move,
+        GameBuilder,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn setup(racks: [&str; 2], words: &[&str], rules: Rules) -> Game<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(words.iter().copied(), &tiles);
+        let [first, second] = racks.map(|r| Rack::parse(r, &tiles).unwrap());
+        GameBuilder::new()
+            .tiles(tiles)
+            .lexicons(lexicon)
+            .rules(rules)
+            .rack(Player::First, first)
+            .rack(Player::Second, second)
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    /// RETAINSZ on a rack of 8, with RETAINS and ZA to play.
+    fn long_rack() -> Game<BitboardImpl> {
+        let tiles = TileMap::english();
+        let rules = Rules {
+            rack_size: 8,
+            ..Rules::default()
+        };
+        let rack = Rack::from_tiles(tiles.tokenize("RETAINSZ").unwrap(), 8).unwrap();
+        GameBuilder::new()
+            .lexicons(Lexicon::from_words(["RETAINS", "ZA"], &tiles))
+            .tiles(tiles)
+            .rules(rules)
+            .rack(Player::First, rack)
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn greedy() {
+        let game = setup(["CATZ", "QX"], &["CAT", "AT", "ZA"], Rules::default());
+        let Play::Place(m) = Greedy.choose(&game.view(Player::First)) else {
+            panic!("Expected a placement");
+        };
+        assert_eq!(m.notation(game.tiles()), "8H ZA");
+        assert_eq!(score_move(game.board(), &m, game.tiles()).total(), 22);
+
+        // With a rack of 8, placing 7 tiles is no bingo, and RETAINS only scores 16.
+        let game = long_rack();
+        let Play::Place(m) = Greedy.choose(&game.view(Player::First)) else {
+            panic!("Expected a placement");
+        };
+        assert_eq!(m.num_placed(), 2);
+    }
+
+    #[test]
+    fn exchanges_or_passes() {
+        let game = setup(["QEEU?", "A"], &["CAT"], Rules::default());
+        let tiles = game.tiles();
+        assert_eq!(
+            Greedy.choose(&game.view(Player::First)),
+            Play::Exchange(tiles.tokenize("EQ").unwrap())
+        );
+        // Only blanks and distinct one-pointers left: all but the blank.
+        let rack = Rack::parse("AE?", tiles).unwrap();
+        assert_eq!(exchange_junk(&rack, tiles), tiles.tokenize("AE").unwrap());
+
+        let rules = Rules {
+            min_for_exchange: 200,
+            ..Rules::default()
+        };
+        let game = setup(["QEEU?", "A"], &["CAT"], rules);
+        assert_eq!(Greedy.choose(&game.view(Player::First)), Play::Pass);
+    }
+
+    #[test]
+    fn equity() {
+        let game = setup(["CATZ", "QX"], &["CAT", "AT", "ZA"], Rules::default());
+        let tiles = game.tiles();
+        let idx = |letters: &str| tiles.tokenize(letters).unwrap();
+        let mut leaves = LeaveTable::new();
+        // Keeping the Z is worth more than the points for ZA.
+        leaves.insert(&idx("Z"), 30.0).unwrap();
+        let mut bot = Equity::new(leaves.clone());
+        let Play::Place(m) = bot.choose(&game.view(Player::First)) else {
+            panic!("Expected a placement");
+        };
+        assert_eq!(m.notation(tiles), "8H CAT");
+
+        // Nothing keeps up with keeping ZA.
+        leaves.insert(&idx("AZ"), 100.0).unwrap();
+        let mut bot = Equity::new(leaves);
+        assert_eq!(
+            bot.choose(&game.view(Player::First)),
+            Play::Exchange(idx("CT"))
+        );
+        assert_eq!(
+            best_exchange(game.rack(Player::First), bot.leaves()),
+            Some((idx("CT"), 100.0))
+        );
+        // Ties go to the first exchange tried.
+        assert_eq!(
+            bot.choose(&game.view(Player::Second)),
+            Play::Exchange(idx("Q"))
+        );
+
+        // Scored with the rules' bingo bonus, which 7 tiles don't earn on a rack of 8.
+        let game = long_rack();
+        let rated = Equity::new(LeaveTable::new()).rated(&game.view(Player::First));
+        let retains = rated
+            .iter()
+            .find(|(play, _)| play.as_move().is_some_and(|m| m.num_placed() == 7))
+            .unwrap();
+        assert_eq!(retains.1, 16.0);
+    }
+
+    #[test]
+    fn levels() {
+        let game = setup(["CATZ", "QX"], &["CAT", "AT", "ZA"], Rules::default());
+        let view = game.view(Player::First);
+        let tiles = game.tiles();
+        let notation = |play: Play| match play {
+            Play::Place(m) => m.notation(tiles),
+            play => format!("{play:?}"),
+        };
+        let equity = Equity::new(LeaveTable::new());
+        let mut expert = Leveled::new(equity.clone(), BotLevel::Expert);
+        assert_eq!(notation(expert.choose(&view)), "8H ZA");
+
+        // ZA is too unlikely a word to know.
+        let at = word_probability(&tiles.tokenize("AT").unwrap(), tiles);
+        assert!((at - (9.0 * 6.0 / 4950.0f64).sqrt()).abs() < 1e-9);
+        let settings = LevelSettings {
+            noise: 0.0,
+            ..BotLevel::Beginner.settings()
+        };
+        let mut beginner = Leveled::new(equity.clone(), settings);
+        assert_eq!(notation(beginner.choose(&view)), "8H CAT");
+
+        // The noise comes from the turn RNG, so it's the same every time.
+        let mut noisy = Leveled::new(equity.clone(), BotLevel::Beginner);
+        assert_eq!(noisy.choose(&view), noisy.clone().choose(&view));
+
+        let game = setup(["RETAINS", "QX"], &["RETAINS", "AT"], Rules::default());
+        let view = game.view(Player::First);
+        let mut expert = Leveled::new(equity.clone(), BotLevel::Expert);
+        assert!(matches!(expert.choose(&view), Play::Place(m) if m.num_placed() == 7));
+        let no_bingos = LevelSettings {
+            max_bingo_rate: 0.0,
+            ..BotLevel::Expert.settings()
+        };
+        let mut capped = Leveled::new(equity, no_bingos);
+        assert!(matches!(capped.choose(&view), Play::Place(m) if m.num_placed() == 2));
+    }
+
+    #[test]
+    fn every_exchange() {
+        let tiles = TileMap::english();
+        let idx = |letters: &str| tiles.tokenize(letters).unwrap();
+        let mut leaves = LeaveTable::new();
+        leaves.insert(&idx("A"), 5.0).unwrap();
+        leaves.insert(&idx("AB"), -3.0).unwrap();
+        let rack = Rack::parse("AAB", &tiles).unwrap();
+        let mut all = exchanges(&rack, &leaves);
+        all.sort_by(|a, b| b.1.total_cmp(&a.1));
+        // A, B, AA, AB and AAB: the two As are interchangeable.
+        assert_eq!(
+            all,
+            vec![
+                (idx("AB"), 5.0),
+                (idx("AA"), 0.0),
+                (idx("B"), 0.0),
+                (idx("AAB"), 0.0),
+                (idx("A"), -3.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Setting up a [`Game`] piece by piece, see [`Game::builder`].
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Player, Premiums},
+    bot_seed_for,
+    clock::Clocks,
+    error::{GameError, Result, TileError},
+    lexicon::LexiconSet,
+    rack_idx,
+    rules::Rules,
+    Game, Rack, TileBag, TileMap, TileMapIdx,
+};
+
+/// Builds a [`Game`], optionally from a position in progress: tiles on a preset board or rack are
+/// taken out of the bag, and racks that aren't preset are drawn from what's left.
+#[derive(Debug, Clone)]
+pub struct GameBuilder<BB: Bitboard> {
+    tiles: Option<TileMap>,
+    lexicons: Option<LexiconSet>,
+    rules: Rules,
+    premiums: Option<Premiums<BB>>,
+    board: Option<Board<BB>>,
+    players: usize,
+    seed: Option<u64>,
+    bot_seed: Option<u64>,
+    racks: [Option<Rack>; 2],
+}
+
+impl<BB: Bitboard> Default for GameBuilder<BB> {
+    fn default() -> Self {
+        Self {
+            tiles: None,
+            lexicons: None,
+            rules: Rules::default(),
+            premiums: None,
+            board: None,
+            players: 2,
+            seed: None,
+            bot_seed: None,
+            racks: [None, None],
+        }
+    }
+}
+
+impl<BB: Bitboard> GameBuilder<BB> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tile set, English by default.
+    pub fn tiles(mut self, tiles: TileMap) -> Self {
+        self.tiles = Some(tiles);
+        self
+    }
+
+    /// See [`Game::with_lexicons`].
+    pub fn lexicons(mut self, lexicons: impl Into<LexiconSet>) -> Self {
+        self.lexicons = Some(lexicons.into());
+        self
+    }
+
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// The board layout, standard by default. Can't be combined with [`GameBuilder::board`].
+    pub fn premiums(mut self, premiums: Premiums<BB>) -> Self {
+        self.premiums = Some(premiums);
+        self
+    }
+
+    /// A board in progress, with its layout, tiles, scores and player to move.
+    pub fn board(mut self, board: Board<BB>) -> Self {
+        self.board = Some(board);
+        self
+    }
+
+    /// Only two-player games are supported so far.
+    pub fn players(mut self, players: usize) -> Self {
+        self.players = players;
+        self
+    }
+
+    /// Draws reproducibly based on `seed` instead of a random one, see [`Game::seed`].
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Seeds the [turn RNG](Game::turn_rng) bots decide with. By default it's derived from the
+    /// [seed](GameBuilder::seed), in a way that doesn't give the bag's draws away.
+    pub fn bot_seed(mut self, seed: u64) -> Self {
+        self.bot_seed = Some(seed);
+        self
+    }
+
+    /// Gives `player` exactly these tiles instead of drawing them.
+    pub fn rack(mut self, player: Player, rack: Rack) -> Self {
+        self.racks[rack_idx(player)] = Some(rack);
+        self
+    }
+
+    pub fn build(self) -> Result<Game<BB>> {
+        if self.players != 2 {
+            return Err(GameError::Players(self.players).into());
+        }
+        if self.board.is_some() && self.premiums.is_some() {
+            return Err(GameError::PresetLayout.into());
+        }
+        let tiles = self.tiles.unwrap_or_else(TileMap::english);
+        if let Some(lexicons) = &self.lexicons {
+            lexicons.validate_against(&tiles)?;
+        }
+        let board = match self.board {
+            Some(board) => board,
+            None => Board::new(&tiles, self.premiums.unwrap_or_else(Premiums::standard)),
+        };
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let mut bag = TileBag::new(&tiles, ChaCha8Rng::seed_from_u64(seed))
+            .with_min_for_exchange(self.rules.min_for_exchange);
+
+        for l in board.occupied().locations() {
+            let tile = if board.is_blank(l) {
+                tiles.blank_idx().ok_or(TileError::NoBlank)?
+            } else {
+                board.tile_at(l).expect("Occupied squares hold a tile")
+            };
+            if !bag.take(tile) {
+                return Err(GameError::NotEnough(letter(&tiles, tile)).into());
+            }
+        }
+        let mut racks = [(); 2].map(|()| Rack::new(self.rules.rack_size));
+        for (rack, preset) in racks.iter_mut().zip(&self.racks) {
+            let Some(preset) = preset else { continue };
+            for &tile in preset.tiles() {
+                if !bag.take(tile) {
+                    return Err(GameError::NotEnough(letter(&tiles, tile)).into());
+                }
+                rack.add(tile)?;
+            }
+        }
+        for (rack, preset) in racks.iter_mut().zip(&self.racks) {
+            if preset.is_none() {
+                bag.draw_to_fill(rack);
+            }
+        }
+
+        let mut game = Game {
+            board,
+            tiles,
+            lexicons: self.lexicons,
+            alphagrams: None,
+            bag,
+            racks,
+            rules: self.rules,
+            seed,
+            bot_seed: self.bot_seed.unwrap_or_else(|| bot_seed_for(seed)),
+            clocks: Clocks::new(self.rules.base_time),
+            last_play: None,
+            scoreless_turns: 0,
+            scoreless_before_last: 0,
+            resigned: None,
+            history: Vec::new(),
+            start: None,
+            events: Vec::new(),
+        };
+        game.index_alphagrams();
+        Ok(game)
+    }
+}
+
+fn letter(tiles: &TileMap, tile: TileMapIdx) -> String {
+    tiles.get(tile).map_or("?", String::as_str).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, board::Move};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn defaults() {
+        let game = GameBuilder::<BitboardImpl>::new().seed(1).build().unwrap();
+        assert_eq!(game.bag().remaining(), 86);
+        assert_eq!(game.board().premiums(), &Premiums::standard());
+        assert!(game.lexicons().is_none());
+        let again = Game::<BitboardImpl>::builder().seed(1).build().unwrap();
+        assert_eq!(again.rack(Player::First), game.rack(Player::First));
+    }
+
+    #[test]
+    fn presets() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let m = Move::parse("8G ZaX", &board, &tiles).unwrap();
+        board.apply(&m).unwrap();
+        board.end_turn();
+        let rack = Rack::parse("VV", &tiles).unwrap();
+        let game = Game::builder()
+            .board(board.clone())
+            .rack(Player::Second, rack.clone())
+            .rules(Rules {
+                rack_size: 5,
+                ..Rules::default()
+            })
+            .build()
+            .unwrap();
+        assert_eq!(game.board().current_turn(), Player::Second);
+        assert_eq!(game.rack(Player::Second).tiles(), rack.tiles());
+        assert_eq!(game.rack(Player::First).len(), 5);
+        assert_eq!(game.bag().remaining(), 100 - 3 - 2 - 5);
+
+        let builder = || Game::builder().board(board.clone());
+        assert!(builder()
+            .rack(Player::First, Rack::parse("ZE", &tiles).unwrap())
+            .build()
+            .is_err());
+        assert!(builder()
+            .rack(Player::First, rack.clone())
+            .rack(Player::Second, rack)
+            .build()
+            .is_err());
+        assert!(builder().premiums(Premiums::none()).build().is_err());
+        assert!(builder().players(3).build().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! Challenging the last play, see [`Game::challenge`](crate::Game::challenge).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Move, Player},
+    rules::ChallengeRule,
+    TileMapIdx,
+};
+
+/// The last play made in a game, as remembered for challenging it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastPlay {
+    pub player: Player,
+    pub m: Move,
+    /// The points it scored.
+    pub score: u32,
+    /// Every word it formed, main word first.
+    pub words: Vec<String>,
+    /// The words not in the lexicon when it was played. Whether any are is up to the opponent to
+    /// find out with a challenge, so this isn't meant to be shown before one.
+    pub phonies: Vec<String>,
+    /// The tiles the player drew after it.
+    pub drawn: Vec<TileMapIdx>,
+}
+
+impl LastPlay {
+    pub fn is_phony(&self) -> bool {
+        !self.phonies.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeOutcome {
+    /// A word was phony: the play was taken off the board and its score taken back.
+    Phony {
+        /// The words not in the lexicon.
+        invalid: Vec<String>,
+        play: LastPlay,
+    },
+    /// Every word was valid, and the challenger was penalized according to `rule`.
+    Valid { rule: ChallengeRule },
+}
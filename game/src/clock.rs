@@ -0,0 +1,90 @@
+//! Chess clocks, see [`Game::play_timed`](crate::Game::play_timed).
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::Player;
+
+/// How much time each player has used, against the [base time](crate::rules::Rules::base_time).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clocks {
+    base: Option<Duration>,
+    first: Duration,
+    second: Duration,
+}
+
+impl Clocks {
+    /// Clocks that haven't run yet. `None` for untimed games.
+    pub fn new(base: Option<Duration>) -> Self {
+        Self {
+            base,
+            ..Self::default()
+        }
+    }
+
+    pub fn base(&self) -> Option<Duration> {
+        self.base
+    }
+
+    pub fn used(&self, player: Player) -> Duration {
+        match player {
+            Player::First => self.first,
+            Player::Second => self.second,
+        }
+    }
+
+    /// The time `player` has left before going into overtime, `None` for untimed games.
+    pub fn remaining(&self, player: Player) -> Option<Duration> {
+        Some(self.base?.saturating_sub(self.used(player)))
+    }
+
+    /// How long `player` has gone over their time.
+    pub fn overtime(&self, player: Player) -> Duration {
+        self.base.map_or(Duration::ZERO, |base| {
+            self.used(player).saturating_sub(base)
+        })
+    }
+
+    /// Every started minute of overtime counts.
+    pub fn overtime_minutes(&self, player: Player) -> u32 {
+        let minutes = self.overtime(player).as_nanos().div_ceil(60_000_000_000);
+        u32::try_from(minutes).unwrap_or(u32::MAX)
+    }
+
+    pub(crate) fn spend(&mut self, player: Player, elapsed: Duration) {
+        match player {
+            Player::First => self.first += elapsed,
+            Player::Second => self.second += elapsed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn overtime() {
+        let mut clocks = Clocks::new(Some(Duration::from_secs(600)));
+        clocks.spend(Player::First, Duration::from_secs(590));
+        assert_eq!(
+            clocks.remaining(Player::First),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(clocks.overtime_minutes(Player::First), 0);
+
+        clocks.spend(Player::First, Duration::from_millis(10_001));
+        assert_eq!(clocks.remaining(Player::First), Some(Duration::ZERO));
+        assert_eq!(clocks.overtime_minutes(Player::First), 1);
+        clocks.spend(Player::First, Duration::from_secs(60));
+        assert_eq!(clocks.overtime_minutes(Player::First), 2);
+        assert_eq!(clocks.used(Player::Second), Duration::ZERO);
+
+        let mut untimed = Clocks::new(None);
+        untimed.spend(Player::First, Duration::from_secs(3600));
+        assert_eq!(untimed.remaining(Player::First), None);
+        assert_eq!(untimed.overtime_minutes(Player::First), 0);
+    }
+}
@@ -0,0 +1,579 @@
+//! Solving endgames: once the bag is empty, both racks are known and the rest of the game can be
+//! searched to the end.
+
+use std::{collections::HashMap, iter::repeat_n};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Column, Location, Move, Play, Player, Row, TilePlacement},
+    bot::placements,
+    error::{GameError, Result},
+    rack_idx, rack_tile,
+    score::score_move_with,
+    view::GameView,
+    Game, Rack, TileMap, TileMapIdx,
+};
+
+/// Finds the play maximizing the final spread once the bag is empty, with an alpha-beta search
+/// over both players' moves and passes until someone goes out or the scoreless turns run out.
+///
+/// Positions reached in different orders are looked up in a transposition table keyed on their
+/// Zobrist hash, and moves are searched best first by score, going out first of all. This finds
+/// out-in-two plays and milks a stuck opponent, at a cost growing quickly with the rack sizes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EndgameSolver {
+    max_depth: Option<usize>,
+}
+
+/// The result of [`EndgameSolver::solve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndgameSolution {
+    /// The play to make.
+    pub best: Play,
+    /// The expected rest of the game, starting with `best`, both players playing their best.
+    pub line: Vec<Play>,
+    /// The spread the player to move ends the game with, rack adjustments included.
+    pub spread: i32,
+    /// How many positions were searched.
+    pub nodes: u64,
+}
+
+impl EndgameSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops searching `plies` turns ahead. Positions where the game isn't over by then are
+    /// valued as if both players were stuck with their racks, so the result is only exact if the
+    /// game always ends in time.
+    pub fn max_depth(mut self, plies: usize) -> Self {
+        self.max_depth = Some(plies);
+        self
+    }
+
+    /// Solves the endgame for the viewing player, on their turn. With the bag empty, the
+    /// opponent's rack is whatever they haven't seen.
+    pub fn solve<BB: Bitboard>(&self, view: &GameView<BB>) -> Result<EndgameSolution> {
+        if view.game.outcome().is_some() {
+            return Err(GameError::GameOver.into());
+        }
+        if !view.is_my_turn() {
+            return Err(GameError::NotYourTurn(view.player()).into());
+        }
+        if view.bag_len() > 0 {
+            return Err(GameError::BagNotEmpty.into());
+        }
+        if view.lexicons().is_none() {
+            return Err(GameError::NoLexicon.into());
+        }
+        let unseen = view
+            .unseen()
+            .iter()
+            .flat_map(|(t, n)| repeat_n(t, n.into()))
+            .collect();
+        let opponent = Rack::from_tiles(unseen, view.rack().capacity())?;
+        let mut solution = self.search(view.game, Position::new(view, opponent))?;
+        solution.spread += view.scores().spread(view.player());
+        Ok(solution)
+    }
+
+    /// Solves `position`, which has to have an empty bag, with the tiles, rules and lexicons of
+    /// `game`. The spread is what the player to move gains from here on.
+    pub(crate) fn search<BB: Bitboard>(
+        &self,
+        game: &Game<BB>,
+        position: Position<BB>,
+    ) -> Result<EndgameSolution> {
+        let depth = self.max_depth.unwrap_or(usize::MAX).max(1);
+        let mut search = Search::new(game, position);
+        let spread = search.negamax(depth, -INFINITY, INFINITY)?;
+        let line = search.principal_variation(depth)?;
+        Ok(EndgameSolution {
+            best: line.first().cloned().unwrap_or(Play::Pass),
+            line,
+            spread,
+            nodes: search.nodes,
+        })
+    }
+}
+
+/// A position to search from: the board, both racks, whose turn it is and how many scoreless
+/// turns in a row there have been.
+#[derive(Debug, Clone)]
+pub(crate) struct Position<BB: Bitboard> {
+    pub board: Board<BB>,
+    pub racks: [Rack; 2],
+    pub to_move: Player,
+    pub scoreless: usize,
+}
+
+impl<BB: Bitboard> Position<BB> {
+    /// The position the viewing player is in, on their turn, if the opponent holds `opponent`.
+    pub fn new(view: &GameView<BB>, opponent: Rack) -> Self {
+        let mut racks = [view.rack().clone(), opponent];
+        if view.player() == Player::Second {
+            racks.reverse();
+        }
+        Self {
+            board: view.board().clone(),
+            racks,
+            to_move: view.player(),
+            scoreless: view.scoreless_turns(),
+        }
+    }
+
+    pub fn rack(&self, player: Player) -> &Rack {
+        &self.racks[rack_idx(player)]
+    }
+}
+
+/// The score of `m` on `board`, with the bingo bonus of the rules of `game`.
+pub(crate) fn score<BB: Bitboard>(game: &Game<BB>, board: &Board<BB>, m: &Move) -> i32 {
+    let score = score_move_with(board, m, game.tiles(), game.rules()).total();
+    i32::try_from(score).unwrap_or(i32::MAX)
+}
+
+/// What the tiles on `rack` are worth.
+pub(crate) fn rack_value(tiles: &TileMap, rack: &Rack) -> i32 {
+    rack.tiles()
+        .iter()
+        .map(|t| i32::from(tiles.score(*t)))
+        .sum()
+}
+
+/// Larger than any spread, small enough to negate.
+const INFINITY: i32 = i32::MAX / 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The value is at least this, the search was cut off.
+    Lower,
+    /// The value is at most this, no move reached the window.
+    Upper,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    depth: usize,
+    value: i32,
+    bound: Bound,
+    best: Play,
+}
+
+/// What undoing a play needs besides the play itself.
+struct Undo {
+    scoreless: usize,
+    placed: Vec<TileMapIdx>,
+}
+
+/// Random keys for every square contents, rack tile, scoreless turn count and the side to move,
+/// XORed together into a position's hash.
+struct Zobrist {
+    letters: usize,
+    copies: usize,
+    squares: Vec<u64>,
+    racks: Vec<u64>,
+    scoreless: Vec<u64>,
+    second_to_move: u64,
+}
+
+impl Zobrist {
+    fn new<BB: Bitboard>(letters: usize, copies: usize) -> Self {
+        // Fixed keys keep searches reproducible.
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let squares = usize::from(Row::MAX) * usize::from(Column::MAX);
+        Self {
+            letters,
+            copies,
+            squares: (0..squares * letters * 2).map(|_| rng.gen()).collect(),
+            racks: (0..2 * letters * copies).map(|_| rng.gen()).collect(),
+            scoreless: (0..=Game::<BB>::SCORELESS_TURNS)
+                .map(|_| rng.gen())
+                .collect(),
+            second_to_move: rng.gen(),
+        }
+    }
+
+    fn square(&self, l: Location, p: TilePlacement) -> u64 {
+        let square = l.row().as_idx() * usize::from(Column::MAX) + l.column().as_idx();
+        self.squares[(square * self.letters + p.tile.as_usize()) * 2 + usize::from(p.is_blank)]
+    }
+
+    /// The key of the `copy`th (from zero) `tile` on `player`'s rack.
+    fn rack(&self, player: Player, tile: TileMapIdx, copy: usize) -> u64 {
+        self.racks[(rack_idx(player) * self.letters + tile.as_usize()) * self.copies + copy]
+    }
+
+    fn position<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        racks: &[Rack; 2],
+        to_move: Player,
+        scoreless: usize,
+    ) -> u64 {
+        let mut hash = self.scoreless[scoreless];
+        if to_move == Player::Second {
+            hash ^= self.second_to_move;
+        }
+        for l in board.occupied().locations() {
+            if let Some(tile) = board.tile_at(l) {
+                let is_blank = board.is_blank(l);
+                hash ^= self.square(l, TilePlacement { tile, is_blank });
+            }
+        }
+        for player in [Player::First, Player::Second] {
+            let tiles = racks[rack_idx(player)].tiles();
+            for (i, &tile) in tiles.iter().enumerate() {
+                let copy = tiles[..i].iter().filter(|&&t| t == tile).count();
+                hash ^= self.rack(player, tile, copy);
+            }
+        }
+        hash
+    }
+}
+
+/// The position being searched, changed by making and unmaking plays.
+struct Search<'a, BB: Bitboard> {
+    game: &'a Game<BB>,
+    board: Board<BB>,
+    racks: [Rack; 2],
+    to_move: Player,
+    scoreless: usize,
+    zobrist: Zobrist,
+    hash: u64,
+    table: HashMap<u64, Entry>,
+    nodes: u64,
+}
+
+impl<'a, BB: Bitboard> Search<'a, BB> {
+    fn new(game: &'a Game<BB>, position: Position<BB>) -> Self {
+        let Position {
+            board,
+            racks,
+            to_move,
+            scoreless,
+        } = position;
+        let copies = racks.iter().map(Rack::capacity).max().unwrap_or(0);
+        let zobrist = Zobrist::new::<BB>(usize::from(game.tiles().len()), copies);
+        let hash = zobrist.position(&board, &racks, to_move, scoreless);
+        Self {
+            game,
+            board,
+            racks,
+            to_move,
+            scoreless,
+            zobrist,
+            hash,
+            table: HashMap::new(),
+            nodes: 0,
+        }
+    }
+
+    /// The value of the rest of the game for the player to move: the points they'll score minus
+    /// the points the opponent will, rack adjustments included.
+    fn negamax(&mut self, depth: usize, mut alpha: i32, mut beta: i32) -> Result<i32> {
+        self.nodes += 1;
+        if depth == 0 {
+            // Guess that neither player gets rid of any more tiles.
+            return Ok(self.rack_value(self.to_move.other()) - self.rack_value(self.to_move));
+        }
+        let original_alpha = alpha;
+        let mut hint = None;
+        if let Some(entry) = self.table.get(&self.hash) {
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return Ok(entry.value),
+                    Bound::Lower => alpha = alpha.max(entry.value),
+                    Bound::Upper => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return Ok(entry.value);
+                }
+            }
+            hint = Some(entry.best.clone());
+        }
+
+        let mut best: Option<(i32, Play)> = None;
+        for (play, score) in self.ordered_plays(hint) {
+            let value = self.value_of(&play, score, depth, alpha, beta)?;
+            if best.as_ref().is_none_or(|(v, _)| value > *v) {
+                best = Some((value, play));
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+        // Passing is always possible, so there is a best play.
+        let (value, best) = best.expect("no plays to search");
+        let bound = if value <= original_alpha {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(
+            self.hash,
+            Entry {
+                depth,
+                value,
+                bound,
+                best,
+            },
+        );
+        Ok(value)
+    }
+
+    /// The value of making `play`, which scores `score`, for the player to move.
+    fn value_of(
+        &mut self,
+        play: &Play,
+        score: i32,
+        depth: usize,
+        alpha: i32,
+        beta: i32,
+    ) -> Result<i32> {
+        let player = self.to_move;
+        let undo = self.make(play)?;
+        let value = if self.racks[rack_idx(player)].is_empty() {
+            score + 2 * self.rack_value(player.other())
+        } else if self.scoreless >= Game::<BB>::SCORELESS_TURNS {
+            score + self.rack_value(player.other()) - self.rack_value(player)
+        } else {
+            score - self.negamax(depth - 1, score - beta, score - alpha)?
+        };
+        self.unmake(play, undo)?;
+        Ok(value)
+    }
+
+    /// Every play for the player to move with its score, in the order to search them: `hint`
+    /// first, then placements by how much they gain right away, then passing.
+    fn ordered_plays(&self, hint: Option<Play>) -> Vec<(Play, i32)> {
+        let rack = &self.racks[rack_idx(self.to_move)];
+        let out_bonus = 2 * self.rack_value(self.to_move.other());
+        let mut plays: Vec<(Play, i32, i32)> = placements(self.game, &self.board, rack)
+            .into_iter()
+            .map(|m| {
+                let score = self.score(&m);
+                let gain = if m.num_placed() == rack.len() {
+                    score + out_bonus
+                } else {
+                    score
+                };
+                (Play::Place(m), score, gain)
+            })
+            .collect();
+        plays.sort_by_key(|(_, _, gain)| -gain);
+        plays.push((Play::Pass, 0, 0));
+        if let Some(i) = hint.and_then(|hint| plays.iter().position(|(p, _, _)| *p == hint)) {
+            let hinted = plays.remove(i);
+            plays.insert(0, hinted);
+        }
+        plays
+            .into_iter()
+            .map(|(play, score, _)| (play, score))
+            .collect()
+    }
+
+    fn score(&self, m: &Move) -> i32 {
+        score(self.game, &self.board, m)
+    }
+
+    fn rack_value(&self, player: Player) -> i32 {
+        rack_value(self.game.tiles(), &self.racks[rack_idx(player)])
+    }
+
+    /// Makes `play` for the player to move, a placement or a pass, and hands the turn over.
+    fn make(&mut self, play: &Play) -> Result<Undo> {
+        let player = self.to_move;
+        let mut placed = Vec::new();
+        let scored = match play {
+            Play::Place(m) => {
+                let scored = self.score(m) > 0;
+                self.board.apply(m)?;
+                let rack = &mut self.racks[rack_idx(player)];
+                for (l, p) in m.placed() {
+                    let tile = rack_tile(self.game.tiles(), p)?;
+                    let copies = rack.tiles().iter().filter(|&&t| t == tile).count();
+                    rack.remove(tile)?;
+                    self.hash ^= self.zobrist.rack(player, tile, copies.saturating_sub(1));
+                    self.hash ^= self.zobrist.square(l, p);
+                    placed.push(tile);
+                }
+                scored
+            }
+            Play::Pass => false,
+            Play::Exchange(_) => unreachable!("exchanges need tiles in the bag"),
+        };
+        let undo = Undo {
+            scoreless: self.scoreless,
+            placed,
+        };
+        self.set_scoreless(if scored { 0 } else { self.scoreless + 1 });
+        self.to_move = player.other();
+        self.hash ^= self.zobrist.second_to_move;
+        Ok(undo)
+    }
+
+    fn unmake(&mut self, play: &Play, undo: Undo) -> Result<()> {
+        self.to_move = self.to_move.other();
+        self.hash ^= self.zobrist.second_to_move;
+        self.set_scoreless(undo.scoreless);
+        if let Play::Place(m) = play {
+            self.board.retract(m)?;
+            let player = self.to_move;
+            let rack = &mut self.racks[rack_idx(player)];
+            for ((l, p), tile) in m.placed().zip(undo.placed) {
+                let copies = rack.tiles().iter().filter(|&&t| t == tile).count();
+                rack.add(tile)?;
+                self.hash ^= self.zobrist.rack(player, tile, copies);
+                self.hash ^= self.zobrist.square(l, p);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_scoreless(&mut self, scoreless: usize) {
+        let max = Game::<BB>::SCORELESS_TURNS;
+        self.hash ^= self.zobrist.scoreless[self.scoreless.min(max)];
+        self.scoreless = scoreless;
+        self.hash ^= self.zobrist.scoreless[scoreless.min(max)];
+    }
+
+    /// The best plays from the current position on, as found in the transposition table.
+    fn principal_variation(&mut self, mut depth: usize) -> Result<Vec<Play>> {
+        let mut line: Vec<(Play, Undo)> = Vec::new();
+        while depth > 0 {
+            let Some(entry) = self.table.get(&self.hash) else {
+                break;
+            };
+            let play = entry.best.clone();
+            let player = self.to_move;
+            let undo = self.make(&play)?;
+            line.push((play, undo));
+            depth -= 1;
+            if self.racks[rack_idx(player)].is_empty()
+                || self.scoreless >= Game::<BB>::SCORELESS_TURNS
+            {
+                break;
+            }
+        }
+        let mut plays = Vec::with_capacity(line.len());
+        while let Some((play, undo)) = line.pop() {
+            self.unmake(&play, undo)?;
+            plays.push(play);
+        }
+        plays.reverse();
+        Ok(plays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, lexicon::Lexicon, tiles::TileMapBuilder, GameBuilder};
+    use pretty_assertions::assert_eq;
+
+    /// A game between `racks` on an empty board, with a tile set of just their tiles so the bag
+    /// is empty.
+    fn setup(racks: [&str; 2], words: &[&str]) -> Game<BitboardImpl> {
+        let scores = [("A", 1), ("C", 3), ("S", 1), ("T", 1), ("Q", 10), ("X", 8)];
+        let tiles = scores
+            .iter()
+            .fold(TileMapBuilder::new(), |builder, &(letter, score)| {
+                let count = racks.concat().matches(letter).count();
+                builder.add_tile(letter, u8::try_from(count).unwrap(), score)
+            })
+            .build()
+            .unwrap();
+        let lexicon = Lexicon::from_words(words.iter().copied(), &tiles);
+        let [first, second] = racks.map(|r| Rack::parse(r, &tiles).unwrap());
+        GameBuilder::new()
+            .tiles(tiles)
+            .lexicons(lexicon)
+            .rack(Player::First, first)
+            .rack(Player::Second, second)
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn goes_out() {
+        let game = setup(["CAT", "Q"], &["CAT", "AT"]);
+        let solution = EndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap();
+        // 10 for CAT on the center star, and twice the Q.
+        assert_eq!(solution.spread, 30);
+        assert_eq!(solution.line.len(), 1);
+        assert!(matches!(&solution.best, Play::Place(m) if m.num_placed() == 3));
+    }
+
+    #[test]
+    fn stuck() {
+        // The Q can't be played: pass and watch the opponent go out.
+        let game = setup(["Q", "CAT"], &["CAT", "AT"]);
+        let solution = EndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap();
+        assert_eq!(solution.spread, -30);
+        assert_eq!(solution.best, Play::Pass);
+        assert_eq!(solution.line.len(), 2);
+
+        // Nobody can play, so the scoreless turns end the game, and both lose their racks.
+        let game = setup(["Q", "X"], &["CAT"]);
+        let solution = EndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap();
+        assert_eq!(solution.spread, -2);
+        assert_eq!(
+            solution.line,
+            vec![Play::Pass; Game::<BitboardImpl>::SCORELESS_TURNS]
+        );
+    }
+
+    #[test]
+    fn out_in_two() {
+        let game = setup(["CATS", "Q"], &["AT", "CAT", "CATS"]);
+        // Going out with CATS scores 12, plus twice the Q.
+        let greedy = EndgameSolver::new()
+            .max_depth(1)
+            .solve(&game.view(Player::First))
+            .unwrap();
+        assert_eq!(greedy.spread, 32);
+        // But the Q is stuck, so there's time for CAT and then the S, for 10 + 6.
+        let solution = EndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap();
+        assert_eq!(solution.spread, 36);
+        assert_eq!(solution.line.len(), 3);
+        assert!(matches!(&solution.best, Play::Place(m) if m.num_placed() == 3));
+        assert_eq!(solution.line[1], Play::Pass);
+        assert!(solution.nodes > greedy.nodes);
+    }
+
+    #[test]
+    fn needs_an_empty_bag() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT"], &tiles);
+        let game = GameBuilder::<BitboardImpl>::new()
+            .tiles(tiles)
+            .lexicons(lexicon)
+            .seed(1)
+            .build()
+            .unwrap();
+        let err = EndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap_err();
+        assert!(matches!(
+            err.cause(),
+            crate::ScrubbleError::Game(GameError::BagNotEmpty)
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+//! The errors the library returns, all wrapped in a [`ScrubbleError`].
+
+use std::{
+    fmt, io,
+    num::{ParseFloatError, ParseIntError},
+    path::PathBuf,
+};
+
+use thiserror::Error;
+
+use crate::{
+    board::{Column, Location, PlayError, Player, Row},
+    TileMap, TileMapIdx,
+};
+
+pub type Result<T, E = ScrubbleError> = std::result::Result<T, E>;
+
+/// Any error of the library. Match on [`ScrubbleError::cause`] to look past the context added to
+/// errors, such as which file was being read. Formatting with `{:#}` shows the context and the
+/// errors under it, separated by colons.
+#[derive(Debug, Error)]
+pub enum ScrubbleError {
+    #[error(transparent)]
+    Tile(#[from] TileError),
+    #[error(transparent)]
+    Board(#[from] BoardError),
+    #[error(transparent)]
+    Play(#[from] PlayError),
+    #[error(transparent)]
+    Lexicon(#[from] LexiconError),
+    #[error(transparent)]
+    Game(#[from] GameError),
+    #[error(transparent)]
+    Format(#[from] FormatError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// `source`, with what was being done when it happened.
+    #[error(fmt = fmt_context)]
+    Context {
+        context: String,
+        source: Box<ScrubbleError>,
+    },
+}
+
+impl ScrubbleError {
+    /// The error under any [context](ScrubbleError::Context).
+    pub fn cause(&self) -> &ScrubbleError {
+        match self {
+            ScrubbleError::Context { source, .. } => source.cause(),
+            e => e,
+        }
+    }
+}
+
+fn fmt_context(context: &str, source: &ScrubbleError, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(context)?;
+    if f.alternate() {
+        write!(f, ": {source:#}")?;
+    }
+    Ok(())
+}
+
+/// Errors about tiles, tile maps and racks.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TileError {
+    #[error("Max number of distinct tiles: {}", u8::MAX)]
+    TooManyTiles,
+    #[error("{:?} is reserved for the blank", TileMap::BLANK)]
+    ReservedBlank,
+    #[error("The tile map has no blank")]
+    NoBlank,
+    #[error("Invalid tile map:\n  {}", .0.join("\n  "))]
+    InvalidTileMap(Vec<String>),
+    /// Data made for a tile map with the `expected` fingerprint, read with another one.
+    #[error(
+        "Tile map mismatch: the data was made for tile map {expected:016x}, but {} is {actual:016x}",
+        .name.as_deref().unwrap_or("the current tile map")
+    )]
+    Mismatch {
+        expected: u64,
+        actual: u64,
+        name: Option<String>,
+    },
+    /// An index past the end of the tile map.
+    #[error("Tile {0} is not in the tile map")]
+    NotInTileMap(u8),
+    #[error("Can't split {word:?} into tiles at {at:?}")]
+    Unsplittable { word: String, at: String },
+    #[error("Unknown tile at {at:?} in {input:?}")]
+    UnknownTile { at: String, input: String },
+    #[error("Unclosed bracket in {0:?}")]
+    UnclosedBracket(String),
+    #[error("{len} tiles don't fit on a rack of {capacity}")]
+    RackOverflow { len: usize, capacity: usize },
+    #[error("The rack is full")]
+    RackFull,
+    #[error("Tile {} is not on the rack", .0.as_u8())]
+    NotOnRack(TileMapIdx),
+    #[error("The rack doesn't hold all of the tiles")]
+    NotAllOnRack,
+    #[error("Leaves can have at most {max} tiles, got {len}")]
+    LeaveTooLong { len: usize, max: usize },
+}
+
+/// Errors about coordinates, board layouts and moves that don't fit on the board.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BoardError {
+    /// A row or column number outside of `1..=max`.
+    #[error("{kind} must be between 1 and {max}, got {value}")]
+    OutOfRange {
+        kind: &'static str,
+        value: u8,
+        max: u8,
+    },
+    #[error("Empty coordinate")]
+    EmptyCoordinate,
+    #[error("Invalid column in coordinate {0:?}, expected A to O")]
+    InvalidColumn(String),
+    #[error("Invalid row in coordinate {0:?}, expected 1 to 15")]
+    InvalidRow(String),
+    #[error("Expected a coordinate and a word, got {0:?}")]
+    InvalidMove(String),
+    #[error("A move has to place at least one tile")]
+    NoTilesPlaced,
+    #[error("{len} tiles at {at} run off the board")]
+    OffBoard { len: usize, at: String },
+    #[error("{location} is already occupied by tile {}", .tile.as_u8())]
+    Occupied {
+        location: Location,
+        tile: TileMapIdx,
+    },
+    #[error("{0} doesn't hold the tile the move placed there")]
+    NotPlacedThere(Location),
+    #[error("The blank can only be placed designated as a letter")]
+    UndesignatedBlank,
+    /// The board's internal state is inconsistent.
+    #[error("{0}")]
+    Invariant(String),
+    #[error("Expected {max} rows, got {0}", max = Row::MAX)]
+    RowCount(usize),
+    /// A variant board larger than the 15×15 the bitboards hold.
+    #[error("{size}×{size} boards aren't supported, only {max}×{max}", max = Row::MAX)]
+    UnsupportedSize { size: u8 },
+    #[error("Expected {} columns in row {row}, got {line:?}", Column::MAX)]
+    RowLength { row: Row, line: String },
+    #[error("Unknown premium {premium:?} at {location}")]
+    UnknownPremium { premium: char, location: Location },
+}
+
+/// Errors reading, building or checking lexicons.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LexiconError {
+    #[error("Not a lexicon file")]
+    NotALexicon,
+    #[error("Unsupported lexicon format version {found}, expected {expected}")]
+    Version { found: u32, expected: u32 },
+    #[error("Unsupported Quackle lexicon version {0}")]
+    QuackleVersion(u8),
+    /// The data ends early; says what was being read.
+    #[error("Truncated {0}")]
+    Truncated(&'static str),
+    /// The data doesn't describe a valid lexicon.
+    #[error("Corrupt lexicon: {0}")]
+    Corrupt(String),
+    #[error("{len} edges don't fit the packed encoding, at most {max} do")]
+    TooManyEdges { len: usize, max: usize },
+    #[error("Too large for the lexicon format")]
+    TooLarge,
+    #[error("The tile map has no {} tile, used by words of the lexicon", .0.join(", "))]
+    MissingLetters(Vec<String>),
+    #[error("Can't spell {0:?} with the lexicon's tiles")]
+    Unspellable(String),
+    /// Words that mean something else with the tile map, see
+    /// [`Lexicon::validate_against`](crate::Lexicon::validate_against).
+    #[error("{count} of {total} words can't be spelled with the tile map: {listed}")]
+    Unspelled {
+        count: usize,
+        total: usize,
+        listed: String,
+    },
+    #[error("The lexicons were built with different tile maps")]
+    TileMapMismatch,
+}
+
+/// Errors about the state of a game: whose turn it is, whether it's over, and its setup.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GameError {
+    #[error("The game is over")]
+    GameOver,
+    #[error("It's not {0:?}'s turn")]
+    NotYourTurn(Player),
+    #[error("There are still tiles in the bag")]
+    BagNotEmpty,
+    /// Pre-endgames are for bags of 1 to `max` tiles.
+    #[error("Expected 1 to {max} tiles in the bag, there are {len}")]
+    NotPreEndgame { len: usize, max: usize },
+    #[error("The game has no lexicon")]
+    NoLexicon,
+    #[error("Clabbers needs an alphagram index")]
+    NoAlphagramIndex,
+    #[error("Challenges are void under these rules")]
+    ChallengesVoid,
+    #[error("There is no play to challenge")]
+    NothingToChallenge,
+    #[error("Only the player to move can challenge the last play")]
+    NotChallenger,
+    #[error("There is no turn {0}")]
+    NoSuchTurn(usize),
+    /// A tile the record says was drawn isn't left, spelled as in the tile map.
+    #[error("There are no {0} tiles left")]
+    NoneLeft(String),
+    #[error("Only two-player games are supported, not {0}")]
+    Players(usize),
+    #[error("A preset board comes with its own layout")]
+    PresetLayout,
+    /// The preset board and racks use more of a tile than the tile set has.
+    #[error("The tile set has too few {0} tiles for the preset board and racks")]
+    NotEnough(String),
+}
+
+/// Errors in the files the library reads: tile maps, saves, GCG records, leave tables and
+/// definitions.
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("Can't tell the format of {0:?} from its extension")]
+    UnknownExtension(PathBuf),
+    #[error("Not a saved game")]
+    NotASave,
+    #[error("The save has no format version")]
+    NoSaveVersion,
+    #[error("Unsupported save format version {found}, expected {expected}")]
+    SaveVersion { found: u64, expected: u32 },
+    #[error("Not a leave table")]
+    NotALeaveTable,
+    /// Content that doesn't follow the format, saying what's wrong.
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Unknown player {0:?}")]
+    UnknownPlayer(String),
+    #[error(transparent)]
+    Int(#[from] ParseIntError),
+    #[error(transparent)]
+    Float(#[from] ParseFloatError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[error(transparent)]
+    TomlWrite(#[from] toml::ser::Error),
+    #[error(transparent)]
+    Bincode(#[from] bincode::Error),
+}
+
+macro_rules! from_format_error {
+    ($($from:ty),*) => {
+        $(impl From<$from> for ScrubbleError {
+            fn from(e: $from) -> Self {
+                FormatError::from(e).into()
+            }
+        })*
+    };
+}
+
+from_format_error!(
+    ParseIntError,
+    ParseFloatError,
+    serde_json::Error,
+    toml::de::Error,
+    toml::ser::Error,
+    bincode::Error
+);
+
+/// Adds context to errors, like `anyhow::Context`.
+pub(crate) trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T>;
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T>;
+}
+
+impl<T, E: Into<ScrubbleError>> Context<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.with_context(|| context.into())
+    }
+
+    fn with_context(self, context: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|e| ScrubbleError::Context {
+            context: context(),
+            source: Box::new(e.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn cause() {
+        let e: Result<()> = Err(GameError::GameOver.into());
+        let e = e.context("Playing").context("Replaying").unwrap_err();
+        assert_eq!(e.to_string(), "Replaying");
+        assert_eq!(format!("{e:#}"), "Replaying: Playing: The game is over");
+        assert!(matches!(
+            e.cause(),
+            ScrubbleError::Game(GameError::GameOver)
+        ));
+        let source = std::error::Error::source(&e).unwrap();
+        assert_eq!(source.to_string(), "Playing");
+    }
+}
@@ -0,0 +1,259 @@
+//! Static evaluation of positions beyond raw score: how much a board gives the opponent to work
+//! with, and how likely a lead is to hold.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Column, Direction, Location, Player, Premium, Row, Scores},
+    lexicon::Lexicon,
+    movegen::{anchors_and_limits, CrossChecks},
+    score::BINGO_TILES,
+    view::GameView,
+};
+
+/// How open a board is, see [`board_metrics`]. Higher is more dangerous for whoever moves last.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoardMetrics {
+    /// Empty triple word squares a single play can cover, counted once per direction it can be
+    /// covered in.
+    pub open_triple_lanes: u32,
+    /// Empty squares where some tile forms valid cross words on its own.
+    pub hooks: u32,
+    /// Hooks on premium squares.
+    pub hotspots: u32,
+    /// The hotspots weighted by their letter and word multipliers, e.g. 3 for a triple letter.
+    pub hotspot_exposure: u32,
+    /// Rows and columns with room for a bingo: a stretch of at least [`BINGO_TILES`] playable
+    /// squares, unbroken by squares no tile can go on, that contains an anchor.
+    pub open_bingo_lines: u32,
+}
+
+/// Measures how open `board` is: the triple word lanes, hooks on premium squares and bingo lines
+/// the next player can use.
+///
+/// Everything is judged one square at a time with the cross checks of `lexicon`, so a lane or
+/// line counts as open even if no word actually fits it.
+pub fn board_metrics<BB: Bitboard>(board: &Board<BB>, lexicon: &Lexicon) -> BoardMetrics {
+    let anchors = anchors_and_limits(board).anchors();
+    let cross_checks = CrossChecks::new(board, lexicon);
+    let premiums = board.premiums();
+
+    let hooks = cross_checks
+        .hook_map()
+        .into_iter()
+        .fold(BB::empty(), |hooks, h| hooks | h);
+    let hotspots = hooks
+        & (premiums.bitboard(Premium::DoubleLetter)
+            | premiums.bitboard(Premium::TripleLetter)
+            | premiums.bitboard(Premium::DoubleWord)
+            | premiums.bitboard(Premium::TripleWord));
+    let hotspot_exposure = hotspots
+        .locations()
+        .filter_map(|l| premiums.get(l))
+        .map(|p| u32::from(p.letter_multiplier() * p.word_multiplier()))
+        .sum();
+
+    let mut metrics = BoardMetrics {
+        hooks: hooks.count_ones(),
+        hotspots: hotspots.count_ones(),
+        hotspot_exposure,
+        ..Default::default()
+    };
+    for direction in [Direction::Horizontal, Direction::Vertical] {
+        let playable = cross_checks.playable(direction);
+        let triples = premiums.bitboard(Premium::TripleWord) & playable;
+        metrics.open_triple_lanes += triples
+            .locations()
+            .filter(|l| reaches_anchor(*l, direction, playable, anchors))
+            .count() as u32;
+        metrics.open_bingo_lines += lines(direction)
+            .filter(|start| bingo_fits(start.ray(direction), board.occupied(), playable, anchors))
+            .count() as u32;
+    }
+    metrics
+}
+
+/// A position as [`win_probability`] sees it, from one player's side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standing {
+    /// Whose chances are evaluated.
+    pub player: Player,
+    pub scores: Scores,
+    /// The tiles `player` hasn't seen, in the bag and on the opponent's rack. The fewer, the less
+    /// time to catch up.
+    pub unseen: usize,
+}
+
+impl Standing {
+    /// The standing of the viewing player.
+    pub fn of<BB: Bitboard>(view: &GameView<BB>) -> Self {
+        Self {
+            player: view.player(),
+            scores: view.scores(),
+            unseen: view.bag_len() + view.opponent_rack_len(),
+        }
+    }
+}
+
+/// A logistic model of the chance of winning. A lead counts for more the fewer tiles are left: it
+/// is divided by `(unseen + 1) ^ tiles_exponent` and scaled by `spread`, after adding `on_turn`
+/// points for the player to move.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WinModel {
+    pub intercept: f64,
+    pub spread: f64,
+    pub tiles_exponent: f64,
+    /// What moving next is worth, in points.
+    pub on_turn: f64,
+}
+
+impl Default for WinModel {
+    /// Rough coefficients: at the start of the game, moving first wins about 55%, and a 20 point
+    /// lead with a rack's worth of tiles left holds about 75% of the time, with the opponent to
+    /// move.
+    fn default() -> Self {
+        Self {
+            intercept: 0.0,
+            spread: 0.5,
+            tiles_exponent: 0.7,
+            on_turn: 10.0,
+        }
+    }
+}
+
+impl WinModel {
+    /// The chance `position.player` wins, ties counting half, with `to_move` to play next.
+    pub fn win_probability(&self, position: &Standing, to_move: Player) -> f64 {
+        let mut spread = f64::from(position.scores.spread(position.player));
+        if to_move == position.player {
+            spread += self.on_turn;
+        } else {
+            spread -= self.on_turn;
+        }
+        let time = (position.unseen as f64 + 1.0).powf(self.tiles_exponent);
+        let z = self.intercept + self.spread * spread / time;
+        1.0 / (1.0 + (-z).exp())
+    }
+}
+
+/// The chance `position.player` wins, with `to_move` to play next, by the default [`WinModel`].
+pub fn win_probability(position: &Standing, to_move: Player) -> f64 {
+    WinModel::default().win_probability(position, to_move)
+}
+
+/// Whether a play in `direction` placing at most [`BINGO_TILES`] tiles can cover both `l` and an
+/// anchor.
+fn reaches_anchor<BB: Bitboard>(
+    l: Location,
+    direction: Direction,
+    playable: BB,
+    anchors: BB,
+) -> bool {
+    [-1, 1].into_iter().any(|step| {
+        std::iter::successors(Some(l), |s| s.offset(direction, step))
+            .take(BINGO_TILES)
+            .take_while(|s| playable.is_set(*s))
+            .any(|s| anchors.is_set(s))
+    })
+}
+
+/// The first square of every row, or every column.
+fn lines(direction: Direction) -> impl Iterator<Item = Location> {
+    (0..Row::MAX as usize).filter_map(move |i| {
+        let (row, column) = match direction {
+            Direction::Horizontal => (i, 0),
+            Direction::Vertical => (0, i),
+        };
+        Some(Location::new(
+            Row::from_idx(row)?,
+            Column::from_idx(column)?,
+        ))
+    })
+}
+
+/// Whether some stretch of `line` without unplayable empty squares has room for a bingo through an
+/// anchor.
+fn bingo_fits<BB: Bitboard>(
+    line: impl Iterator<Item = Location>,
+    occupied: BB,
+    playable: BB,
+    anchors: BB,
+) -> bool {
+    let mut room = 0;
+    let mut anchored = false;
+    for l in line {
+        if playable.is_set(l) {
+            room += 1;
+            anchored |= anchors.is_set(l);
+        } else if !occupied.is_set(l) {
+            room = 0;
+            anchored = false;
+        }
+        if room >= BINGO_TILES && anchored {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, board::Move, TileMap};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn openness() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "SCAT", "AT", "TA", "AS"], &tiles);
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        // Only the center row and column can take the first play.
+        assert_eq!(
+            board_metrics(&board, &lexicon),
+            BoardMetrics {
+                open_bingo_lines: 2,
+                ..Default::default()
+            }
+        );
+
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        assert_eq!(
+            board_metrics(&board, &lexicon),
+            BoardMetrics {
+                // A8 and O8 across, H1 and H15 down.
+                open_triple_lanes: 4,
+                // S, T and A hooks, with the A hooks on the double letters at I7 and I9.
+                hooks: 6,
+                hotspots: 2,
+                hotspot_exposure: 4,
+                // Nothing goes on G7 or G9, so rows 7 and 9 only open from H, and columns F to J.
+                open_bingo_lines: 3 + 5,
+            }
+        );
+    }
+
+    #[test]
+    fn win_chances() {
+        let standing = |spread: i32, unseen: usize| {
+            let mut scores = Scores::default();
+            scores.add(Player::First, spread);
+            Standing {
+                player: Player::First,
+                scores,
+                unseen,
+            }
+        };
+        let start = win_probability(&standing(0, 93), Player::First);
+        assert!((0.5..0.6).contains(&start));
+        let second = win_probability(&standing(0, 93), Player::Second);
+        assert!((second - (1.0 - start)).abs() < 1e-12);
+        // The same lead is safer late in the game.
+        let early = win_probability(&standing(20, 80), Player::Second);
+        let late = win_probability(&standing(20, 7), Player::Second);
+        assert!(0.5 < early && early < late && late > 0.7);
+        assert!(win_probability(&standing(-20, 7), Player::First) < 0.5);
+    }
+}
@@ -0,0 +1,41 @@
+//! Changes to a game as they happen, see [`Game::drain_events`](crate::Game::drain_events).
+
+use crate::{
+    board::{Location, Player, TilePlacement},
+    challenge::ChallengeOutcome,
+    outcome::GameOutcome,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    TilePlaced {
+        player: Player,
+        location: Location,
+        tile: TilePlacement,
+    },
+    /// A tile came off the board again, because its play was challenged off.
+    TileRemoved {
+        player: Player,
+        location: Location,
+        tile: TilePlacement,
+    },
+    ScoreChanged {
+        player: Player,
+        change: i32,
+        total: i32,
+    },
+    /// It's now `player`'s turn. Also sent when a game that had ended goes on after a challenge.
+    TurnStarted {
+        player: Player,
+    },
+    ChallengeResolved {
+        challenger: Player,
+        outcome: ChallengeOutcome,
+    },
+    GameEnded(GameOutcome),
+    /// Every turn from `ply` on was taken back. The events before this one no longer describe the
+    /// game, which is best read afresh.
+    Undone {
+        ply: usize,
+    },
+}
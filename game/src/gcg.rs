@@ -0,0 +1,717 @@
+//! The GCG game record format, as written by Quackle and cross-tables.
+//!
+//! [`Gcg::parse`] reads a record and [`Gcg::replay`] plays it on a [`Game`]. Racks are taken from
+//! the record rather than drawn, and challenges are settled as recorded rather than judged.
+//! [`Gcg::from_game`] records a game, and the `Display` impl writes a record back out.
+//!
+//! Besides the usual pragmas, `#seed` gives the [seed](Game::seed) the game was played with, and
+//! `#time` after a turn gives the seconds it took, as fed to [`Game::play_timed`].
+
+use std::{fmt, time::Duration};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Move, Play, Player},
+    challenge::ChallengeOutcome,
+    error::{Context, FormatError, Result},
+    outcome::EndReason,
+    rules::ChallengeRule,
+    Game, Rack, ScrubbleError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcgPlayer {
+    pub nick: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GcgAction {
+    /// A placement in [`Move::parse`] notation. Tiles played through may also be in parentheses.
+    Place(String),
+    Pass,
+    /// The tiles exchanged, in [`Rack::parse`] notation.
+    Exchange(String),
+    /// An exchange of this many unknown tiles.
+    ExchangeCount(usize),
+    /// The player's last play was challenged off.
+    Withdrawn,
+    /// The player's last play survived a challenge, earning the points of the event.
+    ChallengeBonus,
+    /// The player went out, and gets the value of the opponent's tiles left, given here.
+    OpponentRack(String),
+    /// The player loses the value of their tiles left, given here.
+    OwnRack(String),
+    /// The player went over time.
+    TimePenalty,
+}
+
+impl GcgAction {
+    /// Whether the action takes a turn, rather than adjusting the one before or the final scores.
+    pub fn is_turn(&self) -> bool {
+        matches!(
+            self,
+            Self::Place(_) | Self::Pass | Self::Exchange(_) | Self::ExchangeCount(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcgEvent {
+    pub nick: String,
+    /// The player's rack before the event, empty if not recorded.
+    pub rack: String,
+    pub action: GcgAction,
+    /// The change in score, as recorded.
+    pub score: i32,
+    /// The player's total after the event, if recorded.
+    pub total: Option<i32>,
+    pub note: Option<String>,
+    /// How long the turn took, if recorded.
+    pub elapsed: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Gcg {
+    pub players: [Option<GcgPlayer>; 2],
+    pub title: Option<String>,
+    pub lexicon: Option<String>,
+    pub seed: Option<u64>,
+    /// Every other pragma, by name without the `#`.
+    pub pragmas: Vec<(String, String)>,
+    pub events: Vec<GcgEvent>,
+}
+
+impl Gcg {
+    /// Parses a GCG record. Unknown pragmas are kept, notes go with the event before them, and
+    /// lines continuing a note are appended to it.
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut gcg = Self::default();
+        let mut in_note = false;
+        for (i, line) in s.trim_start_matches('\u{feff}').lines().enumerate() {
+            let line = line.trim();
+            if let Some(event) = line.strip_prefix('>') {
+                let event = parse_event(event).with_context(|| format!("Line {}", i + 1))?;
+                gcg.events.push(event);
+                in_note = false;
+            } else if let Some(pragma) = line.strip_prefix('#') {
+                let (name, value) = pragma
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((pragma, ""));
+                let value = value.trim().to_string();
+                in_note = name == "note";
+                match name {
+                    "player1" | "player2" => {
+                        let idx = usize::from(name == "player2");
+                        let (nick, full_name) = value
+                            .split_once(char::is_whitespace)
+                            .unwrap_or((&value, ""));
+                        gcg.players[idx] = Some(GcgPlayer {
+                            nick: nick.to_string(),
+                            name: full_name.trim().to_string(),
+                        });
+                    }
+                    "title" => gcg.title = Some(value),
+                    "lexicon" => gcg.lexicon = Some(value),
+                    "seed" => {
+                        let seed = value
+                            .parse()
+                            .with_context(|| format!("Line {}: invalid seed {value:?}", i + 1))?;
+                        gcg.seed = Some(seed);
+                    }
+                    "time" => {
+                        let elapsed = value
+                            .parse()
+                            .ok()
+                            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                            .ok_or_else(|| {
+                                invalid(format!("Line {}: invalid time {value:?}", i + 1))
+                            })?;
+                        let Some(event) = gcg.events.last_mut() else {
+                            return Err(invalid(format!("Line {}: time before any event", i + 1)));
+                        };
+                        event.elapsed = Some(elapsed);
+                    }
+                    "note" => match gcg.events.last_mut() {
+                        Some(event) => append_note(event, &value),
+                        None => gcg.pragmas.push(("note".to_string(), value)),
+                    },
+                    _ => gcg.pragmas.push((name.to_string(), value)),
+                }
+            } else if !line.is_empty() {
+                match gcg.events.last_mut() {
+                    Some(event) if in_note => append_note(event, line),
+                    _ => {
+                        return Err(FormatError::Invalid(format!(
+                            "Line {}: expected a pragma or an event, got {line:?}",
+                            i + 1
+                        ))
+                        .into())
+                    }
+                }
+            }
+        }
+        Ok(gcg)
+    }
+
+    /// The player going by `nick`: the one named in the `#player1` or `#player2` pragma, or else
+    /// whoever has the first event.
+    pub fn player(&self, nick: &str) -> Option<Player> {
+        let named = |n: usize| self.players[n].as_ref().map(|p| p.nick.as_str());
+        if named(0) == Some(nick) {
+            return Some(Player::First);
+        }
+        if named(1) == Some(nick) {
+            return Some(Player::Second);
+        }
+        let first = self.events.first()?;
+        Some(if first.nick == nick {
+            Player::First
+        } else {
+            Player::Second
+        })
+    }
+
+    /// Plays the record on `game`, which should be fresh and set up with the record's lexicon.
+    ///
+    /// Recorded scores are not checked, and time penalties are left to the clocks, which run by the
+    /// recorded times. Turns that are missing, like one lost to a double challenge, are made up
+    /// with passes. If the record has a seed, the bag is reseeded with it first, so tiles the record
+    /// doesn't list are drawn as they were.
+    pub fn replay<BB: Bitboard>(&self, mut game: Game<BB>) -> Result<Game<BB>> {
+        if let Some(seed) = self.seed.filter(|_| game.history().is_empty()) {
+            game = game.with_seed(seed);
+        }
+        for (i, event) in self.events.iter().enumerate() {
+            replay_event(self, &mut game, event)
+                .with_context(|| format!("Event {} by {}", i + 1, event.nick))?;
+        }
+        Ok(game)
+    }
+
+    /// Records `game` so far, with the players called `player1` and `player2` and the game's seed.
+    ///
+    /// Turns keep the time they took. A finished game ends with the rack and overtime adjustments of
+    /// its [outcome](Game::outcome), so the last totals are the final scores.
+    pub fn from_game<BB: Bitboard>(game: &Game<BB>) -> Self {
+        let tiles = game.tiles();
+        let nick = |player: Player| match player {
+            Player::First => "player1",
+            Player::Second => "player2",
+        };
+        let mut gcg = Self {
+            players: [Player::First, Player::Second].map(|p| {
+                Some(GcgPlayer {
+                    nick: nick(p).to_string(),
+                    name: String::new(),
+                })
+            }),
+            seed: Some(game.seed()),
+            ..Self::default()
+        };
+        let mut totals = game
+            .start
+            .as_ref()
+            .map_or_else(|| game.board().scores(), |start| start.board.scores());
+        let mut push = |player: Player, rack: String, action: GcgAction, score: i32| {
+            totals.add(player, score);
+            gcg.events.push(GcgEvent {
+                nick: nick(player).to_string(),
+                rack,
+                action,
+                score,
+                total: Some(totals[player]),
+                note: None,
+                elapsed: None,
+            });
+            gcg.events.len() - 1
+        };
+
+        let mut notes = Vec::new();
+        let mut times = Vec::new();
+        for record in game.history() {
+            let player = record.player();
+            let action = match &record.summary.play {
+                Play::Place(m) => GcgAction::Place(m.notation(tiles)),
+                Play::Exchange(exchanged) => {
+                    let rack = Rack::from_tiles(exchanged.clone(), exchanged.len())
+                        .expect("The tiles fit a rack of their own size");
+                    GcgAction::Exchange(rack.to_letters(tiles))
+                }
+                Play::Pass => GcgAction::Pass,
+            };
+            let score = i32::try_from(record.summary.score.total()).unwrap_or(i32::MAX);
+            let turn = push(player, record.rack_before.to_letters(tiles), action, score);
+            if let Some(comment) = &record.comment {
+                notes.push((turn, comment.clone()));
+            }
+            if !record.summary.elapsed.is_zero() {
+                times.push((turn, record.summary.elapsed));
+            }
+            match &record.challenge {
+                Some(ChallengeOutcome::Phony { play, .. }) => {
+                    let score = i32::try_from(play.score).unwrap_or(i32::MAX);
+                    push(play.player, String::new(), GcgAction::Withdrawn, -score);
+                }
+                Some(ChallengeOutcome::Valid {
+                    rule: ChallengeRule::Points(points),
+                }) => {
+                    let points = i32::try_from(*points).unwrap_or(i32::MAX);
+                    push(player, String::new(), GcgAction::ChallengeBonus, points);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(outcome) = game.outcome() {
+            for player in [Player::First, Player::Second] {
+                let rack = game.rack(player);
+                let letters = rack.to_letters(tiles);
+                let left: i32 = rack
+                    .tiles()
+                    .iter()
+                    .map(|t| i32::from(tiles.score(*t)))
+                    .sum();
+                match outcome.reason {
+                    EndReason::WentOut(out) if out != player => {
+                        push(
+                            out,
+                            String::new(),
+                            GcgAction::OpponentRack(letters.clone()),
+                            left,
+                        );
+                    }
+                    EndReason::Resigned(_) => continue,
+                    _ => {}
+                }
+                // A zero deduction would read back as the opponent's rack, and changes nothing.
+                if left > 0 {
+                    push(player, letters.clone(), GcgAction::OwnRack(letters), -left);
+                }
+                let overtime = game
+                    .clocks()
+                    .overtime_minutes(player)
+                    .saturating_mul(game.rules().overtime_penalty);
+                if overtime > 0 {
+                    let penalty = i32::try_from(overtime).unwrap_or(i32::MAX);
+                    push(player, String::new(), GcgAction::TimePenalty, -penalty);
+                }
+            }
+        }
+        for (event, note) in notes {
+            gcg.events[event].note = Some(note);
+        }
+        for (event, elapsed) in times {
+            gcg.events[event].elapsed = Some(elapsed);
+        }
+        gcg
+    }
+}
+
+impl fmt::Display for Gcg {
+    /// Writes the record in the form [`Gcg::parse`] reads, one pragma or event per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, player) in self.players.iter().enumerate() {
+            if let Some(GcgPlayer { nick, name }) = player {
+                writeln!(
+                    f,
+                    "{}",
+                    format!("#player{} {nick} {name}", i + 1).trim_end()
+                )?;
+            }
+        }
+        if let Some(title) = &self.title {
+            writeln!(f, "#title {title}")?;
+        }
+        if let Some(lexicon) = &self.lexicon {
+            writeln!(f, "#lexicon {lexicon}")?;
+        }
+        if let Some(seed) = self.seed {
+            writeln!(f, "#seed {seed}")?;
+        }
+        for (name, value) in &self.pragmas {
+            writeln!(f, "#{name} {value}")?;
+        }
+        for event in &self.events {
+            write!(f, ">{}: ", event.nick)?;
+            if !event.rack.is_empty() {
+                write!(f, "{} ", event.rack)?;
+            }
+            match &event.action {
+                GcgAction::Place(notation) => write!(f, "{notation}")?,
+                GcgAction::Pass => write!(f, "-")?,
+                GcgAction::Exchange(tiles) => write!(f, "-{tiles}")?,
+                GcgAction::ExchangeCount(n) => write!(f, "-{n}")?,
+                GcgAction::Withdrawn => write!(f, "--")?,
+                GcgAction::ChallengeBonus => write!(f, "(challenge)")?,
+                GcgAction::OpponentRack(tiles) | GcgAction::OwnRack(tiles) => {
+                    write!(f, "({tiles})")?;
+                }
+                GcgAction::TimePenalty => write!(f, "(time)")?,
+            }
+            write!(f, " {:+}", event.score)?;
+            if let Some(total) = event.total {
+                write!(f, " {total}")?;
+            }
+            writeln!(f)?;
+            if let Some(note) = &event.note {
+                writeln!(f, "#note {note}")?;
+            }
+            if let Some(elapsed) = event.elapsed {
+                writeln!(f, "#time {}", elapsed.as_secs_f64())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn replay_event<BB: Bitboard>(gcg: &Gcg, game: &mut Game<BB>, event: &GcgEvent) -> Result<()> {
+    let player = gcg
+        .player(&event.nick)
+        .ok_or_else(|| FormatError::UnknownPlayer(event.nick.clone()))?;
+    if event.action.is_turn() {
+        if game.board().current_turn() != player {
+            game.play(player.other(), Play::Pass)?;
+        }
+        if !event.rack.is_empty() {
+            let rack = Rack::parse(&event.rack, game.tiles())?;
+            game.set_rack(player, rack.tiles())?;
+        }
+    }
+    let elapsed = event.elapsed.unwrap_or_default();
+    match &event.action {
+        GcgAction::Place(notation) => {
+            let notation: String = notation.chars().filter(|c| !"()".contains(*c)).collect();
+            let m = Move::parse(&notation, game.board(), game.tiles())?;
+            game.play_timed(player, Play::Place(m), elapsed)?;
+        }
+        GcgAction::Pass => {
+            game.play_timed(player, Play::Pass, elapsed)?;
+        }
+        GcgAction::Exchange(tiles) => {
+            let tiles = Rack::parse(tiles, game.tiles())?.tiles().to_vec();
+            game.play_timed(player, Play::Exchange(tiles), elapsed)?;
+        }
+        GcgAction::ExchangeCount(n) => {
+            let tiles = game.rack(player).tiles();
+            if *n > tiles.len() {
+                return Err(invalid(format!(
+                    "Can't exchange {n} tiles from a rack of {}",
+                    tiles.len()
+                )));
+            }
+            let exchanged = tiles[..*n].to_vec();
+            game.play_timed(player, Play::Exchange(exchanged), elapsed)?;
+        }
+        GcgAction::Withdrawn => {
+            let Some(last) = game.last_play().filter(|l| l.player == player) else {
+                return Err(invalid("There is no play of theirs to withdraw".into()));
+            };
+            // The record doesn't say which words were phony.
+            let mut invalid = last.phonies.clone();
+            if invalid.is_empty() {
+                invalid.clone_from(&last.words);
+            }
+            game.settle_challenge(invalid, game.rules().challenge)?;
+        }
+        GcgAction::ChallengeBonus => {
+            if !game.last_play().is_some_and(|l| l.player == player) {
+                return Err(invalid(
+                    "There is no play of theirs that was challenged".into(),
+                ));
+            }
+            let Ok(points) = u32::try_from(event.score) else {
+                return Err(invalid("Negative challenge bonus".into()));
+            };
+            game.settle_challenge(Vec::new(), ChallengeRule::Points(points))?;
+        }
+        GcgAction::OpponentRack(tiles) => {
+            let rack = Rack::parse(tiles, game.tiles())?;
+            game.set_rack(player.other(), rack.tiles())?;
+        }
+        GcgAction::OwnRack(tiles) => {
+            let rack = Rack::parse(tiles, game.tiles())?;
+            game.set_rack(player, rack.tiles())?;
+        }
+        GcgAction::TimePenalty => {}
+    }
+    let is_challenge = matches!(
+        event.action,
+        GcgAction::Withdrawn | GcgAction::ChallengeBonus
+    );
+    if let Some(note) = &event.note {
+        if event.action.is_turn() || is_challenge {
+            game.annotate(game.history().len() - 1, note.clone())?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> ScrubbleError {
+    FormatError::Invalid(message).into()
+}
+
+fn append_note(event: &mut GcgEvent, text: &str) {
+    match &mut event.note {
+        Some(note) => {
+            note.push('\n');
+            note.push_str(text);
+        }
+        None => event.note = Some(text.to_string()),
+    }
+}
+
+/// Parses an event line after the `>`: `nick: RACK ACTION SCORE TOTAL`.
+fn parse_event(s: &str) -> Result<GcgEvent> {
+    let Some((nick, rest)) = s.split_once(':') else {
+        return Err(invalid(format!("Expected \"nick:\" at the start of {s:?}")));
+    };
+    let mut tokens: Vec<_> = rest.split_whitespace().collect();
+    let signed = |t: &str| t.starts_with(['+', '-']) && t.parse::<i32>().is_ok();
+    let total = match tokens.as_slice() {
+        [.., score, total] if signed(score) && !total.starts_with('+') => {
+            Some(total.parse().context("Invalid total")?)
+        }
+        [.., score] if signed(score) => None,
+        _ => return Err(invalid(format!("No score in {s:?}"))),
+    };
+    let score_idx = tokens.len() - 1 - usize::from(total.is_some());
+    let score = tokens[score_idx].parse()?;
+    tokens.truncate(score_idx);
+
+    // The rack is left out by some writers, but it never holds digits, parentheses or dashes.
+    let is_rack = |t: &str| !t.starts_with(['(', '-']) && !t.contains(|c: char| c.is_ascii_digit());
+    let (rack, action) = match tokens.as_slice() {
+        [rack, action @ ..] if is_rack(rack) && !action.is_empty() => (*rack, action),
+        action => ("", action),
+    };
+    let action = match action {
+        [coordinate, word] => GcgAction::Place(format!("{coordinate} {word}")),
+        ["-"] => GcgAction::Pass,
+        ["--"] => GcgAction::Withdrawn,
+        ["(challenge)"] => GcgAction::ChallengeBonus,
+        ["(time)"] => GcgAction::TimePenalty,
+        [exchanged] if exchanged.starts_with('-') => match exchanged[1..].parse() {
+            Ok(n) => GcgAction::ExchangeCount(n),
+            Err(_) => GcgAction::Exchange(exchanged[1..].to_string()),
+        },
+        [tiles] if tiles.starts_with('(') && tiles.ends_with(')') => {
+            let tiles = tiles[1..tiles.len() - 1].to_string();
+            if score < 0 {
+                GcgAction::OwnRack(tiles)
+            } else {
+                GcgAction::OpponentRack(tiles)
+            }
+        }
+        _ => return Err(invalid(format!("Unknown action in {s:?}"))),
+    };
+    Ok(GcgEvent {
+        nick: nick.trim().to_string(),
+        rack: rack.to_string(),
+        action,
+        score,
+        total,
+        note: None,
+        elapsed: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, movegen::iter_moves, Lexicon, TileMap};
+    use pretty_assertions::assert_eq;
+
+    const RECORD: &str = "#character-encoding UTF-8
+#player1 alice Alice Smith
+#player2 bob Bob Jones
+#title Test game
+#lexicon TEST
+#seed 42
+>alice: ACQRSTZ 8G CAT +10 10
+#note Keeps the S.
+#time 95
+>bob: AEIIOUU -IIUU +0 0
+>alice: QRSZ 8G (CAT)S +6 16
+>bob: AETX I8 .AX +11 11
+>bob: AETX -- -11 0
+>alice: QRZ - +0 16
+>bob: ?AETX I8 .a +1 1
+>bob: ?ETX (challenge) +5 6
+>alice: QRZ (QRZ) -42 -26
+";
+
+    #[test]
+    fn parse() {
+        let gcg = Gcg::parse(RECORD).unwrap();
+        assert_eq!(
+            gcg.players[1],
+            Some(GcgPlayer {
+                nick: "bob".to_string(),
+                name: "Bob Jones".to_string(),
+            })
+        );
+        assert_eq!(gcg.title.as_deref(), Some("Test game"));
+        assert_eq!(gcg.lexicon.as_deref(), Some("TEST"));
+        assert_eq!(gcg.seed, Some(42));
+        assert_eq!(
+            gcg.pragmas,
+            [("character-encoding".to_string(), "UTF-8".to_string())]
+        );
+        assert_eq!(
+            gcg.events[0],
+            GcgEvent {
+                nick: "alice".to_string(),
+                rack: "ACQRSTZ".to_string(),
+                action: GcgAction::Place("8G CAT".to_string()),
+                score: 10,
+                total: Some(10),
+                note: Some("Keeps the S.".to_string()),
+                elapsed: Some(Duration::from_secs(95)),
+            }
+        );
+        let actions: Vec<_> = gcg.events[1..].iter().map(|e| e.action.clone()).collect();
+        assert_eq!(
+            actions,
+            [
+                GcgAction::Exchange("IIUU".to_string()),
+                GcgAction::Place("8G (CAT)S".to_string()),
+                GcgAction::Place("I8 .AX".to_string()),
+                GcgAction::Withdrawn,
+                GcgAction::Pass,
+                GcgAction::Place("I8 .a".to_string()),
+                GcgAction::ChallengeBonus,
+                GcgAction::OwnRack("QRZ".to_string()),
+            ]
+        );
+        assert_eq!(gcg.events[8].total, Some(-26));
+        assert_eq!(gcg.player("bob"), Some(Player::Second));
+    }
+
+    #[test]
+    fn dialects() {
+        let gcg = Gcg::parse("\u{feff}>Ann: 8H QI +22\r\n>Ben: EFG -3 +0 0\n>Ann: (EFG) +14 36\n")
+            .unwrap();
+        let event = &gcg.events[0];
+        assert_eq!((event.rack.as_str(), event.total), ("", None));
+        assert_eq!(gcg.events[1].action, GcgAction::ExchangeCount(3));
+        assert_eq!(
+            gcg.events[2].action,
+            GcgAction::OpponentRack("EFG".to_string())
+        );
+        assert_eq!(gcg.player("Ann"), Some(Player::First));
+        assert_eq!(gcg.player("Ben"), Some(Player::Second));
+        assert!(Gcg::parse(">Ann: 8H QI").is_err());
+        assert!(Gcg::parse("stray text").is_err());
+        assert!(Gcg::parse("#seed -1").is_err());
+        assert!(Gcg::parse("#time 5").is_err());
+        assert!(Gcg::parse(">Ann: 8H QI +22\n#time soon").is_err());
+    }
+
+    #[test]
+    fn replay() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "AT", "TA", "QI"], &tiles);
+        let game = Game::<BitboardImpl>::new(tiles)
+            .with_lexicons(lexicon)
+            .unwrap();
+        let mut game = Gcg::parse(RECORD).unwrap().replay(game).unwrap();
+
+        assert_eq!(game.seed(), 42);
+        assert_eq!(game.clocks().used(Player::First), Duration::from_secs(95));
+        let history = game.history();
+        assert_eq!(history.len(), 6);
+        assert_eq!(history[0].comment.as_deref(), Some("Keeps the S."));
+        assert!(history[3].is_retracted());
+        assert_eq!(
+            history[5].challenge,
+            Some(ChallengeOutcome::Valid {
+                rule: ChallengeRule::Points(5)
+            })
+        );
+        assert_eq!(game.board().scores()[Player::First], 16);
+        assert_eq!(game.board().scores()[Player::Second], 6);
+        assert_eq!(game.board().occupied().count_ones(), 5);
+        assert_eq!(
+            game.rack(Player::First).tiles(),
+            game.tiles().tokenize("QRZ").unwrap()
+        );
+
+        game.undo_to(4).unwrap();
+        assert!(game.history()[3].is_retracted());
+        assert_eq!(game.board().scores()[Player::First], 16);
+        assert_eq!(game.board().occupied().count_ones(), 4);
+    }
+
+    /// Writes `game` out, reads it back and replays it on `fresh`, checking the turns match.
+    fn round_trip(game: &Game<BitboardImpl>, fresh: Game<BitboardImpl>) -> Game<BitboardImpl> {
+        let gcg = Gcg::from_game(game);
+        assert_eq!(Gcg::parse(&gcg.to_string()).unwrap(), gcg);
+        let replayed = gcg.replay(fresh).unwrap();
+        let turns = |g: &Game<BitboardImpl>| -> Vec<_> {
+            g.history()
+                .iter()
+                .map(|r| {
+                    let s = &r.summary;
+                    let total = s.score.total();
+                    (
+                        s.player,
+                        s.play.clone(),
+                        total,
+                        s.elapsed,
+                        r.challenge.clone(),
+                        r.comment.clone(),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(turns(&replayed), turns(game));
+        assert_eq!(replayed.seed(), game.seed());
+        assert_eq!(replayed.clocks(), game.clocks());
+        replayed
+    }
+
+    #[test]
+    fn write() {
+        let tiles = TileMap::english();
+        let words = [
+            "CAT", "CATS", "AT", "TA", "QI", "DE", "ED", "EH", "HE", "RE", "ER", "XI",
+        ];
+        let lexicon = Lexicon::from_words(words, &tiles);
+        let fresh = || {
+            Game::<BitboardImpl>::new(tiles.clone())
+                .with_lexicons(lexicon.clone())
+                .unwrap()
+        };
+        let game = Gcg::parse(RECORD).unwrap().replay(fresh()).unwrap();
+        let text = Gcg::from_game(&game).to_string();
+        assert!(text.starts_with("#player1 player1\n#player2 player2\n#seed 42\n"));
+        assert!(text.contains(">player2: AETX? I8 Ta +1 1\n"));
+        let replayed = round_trip(&game, fresh());
+        assert_eq!(replayed.board().scores(), game.board().scores());
+
+        let mut game = fresh().with_seed(7);
+        while game.outcome().is_none() {
+            let player = game.board().current_turn();
+            let lexicon = game.lexicons().unwrap().generation();
+            let play = iter_moves(game.board(), game.rack(player), lexicon)
+                .next()
+                .map_or(Play::Pass, Play::Place);
+            // Slow enough to go over time.
+            game.play_timed(player, play, Duration::from_secs(300))
+                .unwrap();
+        }
+        let gcg = Gcg::from_game(&game);
+        let outcome = game.outcome().unwrap();
+        for (player, nick) in [(Player::First, "player1"), (Player::Second, "player2")] {
+            let last = gcg.events.iter().rev().find(|e| e.nick == nick).unwrap();
+            assert_eq!(last.total, Some(outcome.scores[player]));
+        }
+        assert!(gcg
+            .events
+            .iter()
+            .any(|e| e.action == GcgAction::TimePenalty));
+        assert!(gcg.to_string().contains("\n#time 300\n"));
+        assert_eq!(round_trip(&game, fresh()).outcome(), Some(outcome));
+    }
+}
@@ -0,0 +1,288 @@
+//! Guessing the opponent's rack from their last play. The tiles they kept are likelier to be ones
+//! that made that play a good choice: a player who skipped an obvious better spot probably didn't
+//! have the tiles for it, and one who exchanged kept what they valued.
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Move},
+    bot::placements,
+    error::Result,
+    leaves::LeaveTable,
+    movegen::leave,
+    rack_tile,
+    score::score_move_with,
+    tiles::TileCounts,
+    view::{GameView, SeenPlay},
+    Rack, TileMapIdx,
+};
+
+/// Infers the opponent's rack from their last turn, by sampling the tiles they could have kept
+/// and weighing each sample by how well the play fits it.
+///
+/// For a placement, a kept leave is weighed by how close the play comes to the best equity
+/// possible with it, the leave values from a [`LeaveTable`]. For an exchange, by the value of the
+/// leave. Nothing is inferred after a pass, a play challenged off, or once the bag is empty and the
+/// rack is known anyway.
+#[derive(Debug, Clone)]
+pub struct RackInferrer {
+    leaves: Arc<LeaveTable>,
+    samples: usize,
+    temperature: f32,
+}
+
+/// A distribution over the opponent's rack: the tiles they kept from their last turn, and tiles
+/// drawn at random from what's unseen for the rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RackInference {
+    /// Distinct leaves with their probabilities, the likeliest first.
+    leaves: Vec<(Vec<TileMapIdx>, f64)>,
+    unseen: TileCounts,
+    rack_len: usize,
+}
+
+impl RackInferrer {
+    pub fn new(leaves: impl Into<Arc<LeaveTable>>) -> Self {
+        Self {
+            leaves: leaves.into(),
+            samples: 500,
+            temperature: 5.0,
+        }
+    }
+
+    /// How many leaves to sample, 500 by default.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// How many points of equity a play may give up before the leave is `e` times less likely, 5
+    /// by default. The higher, the closer to uniform.
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// The opponent's rack as the viewing player can infer it. Samples with the view's
+    /// [turn RNG](GameView::turn_rng).
+    pub fn infer<BB: Bitboard>(&self, view: &GameView<BB>) -> Result<RackInference> {
+        let unseen = view.unseen().into_counts();
+        let rack_len = view.opponent_rack_len();
+        let uniform = RackInference {
+            leaves: Vec::new(),
+            unseen: unseen.clone(),
+            rack_len,
+        };
+        let opponent = view.player().other();
+        let Some(last) = view.turns().next_back() else {
+            return Ok(uniform);
+        };
+        if last.player != opponent || last.retracted || view.bag_len() == 0 {
+            return Ok(uniform);
+        }
+
+        let (evidence, kept_len) = match last.play {
+            SeenPlay::Place(m) => {
+                let mut board = view.board().clone();
+                board.retract(m)?;
+                let played = m
+                    .placed()
+                    .map(|(_, p)| rack_tile(view.tiles(), p))
+                    .collect::<Result<_>>()?;
+                let evidence = Evidence::Placed { m, board, played };
+                (evidence, rack_len.saturating_sub(m.num_placed()))
+            }
+            SeenPlay::Exchange(n) => (Evidence::Exchanged, rack_len.saturating_sub(n)),
+            SeenPlay::Pass => return Ok(uniform),
+        };
+
+        let mut rng = view.turn_rng();
+        let mut sample = Rack::new(rack_len);
+        let mut weighed: Vec<(Vec<TileMapIdx>, f64)> = Vec::with_capacity(self.samples);
+        for _ in 0..self.samples {
+            unseen.sample_into(kept_len, &mut rng, &mut sample);
+            let kept = sample.tiles().to_vec();
+            let rating = self.rate(view, &evidence, &kept)?;
+            weighed.push((kept, f64::from(rating)));
+        }
+
+        // Ratings become weights relative to the best, so they don't overflow.
+        let max = weighed
+            .iter()
+            .map(|(_, r)| *r)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let mut leaves: Vec<(Vec<TileMapIdx>, f64)> = Vec::new();
+        for (kept, rating) in weighed {
+            let weight = ((rating - max) / f64::from(self.temperature)).exp();
+            match leaves.iter_mut().find(|(l, _)| *l == kept) {
+                Some((_, w)) => *w += weight,
+                None => leaves.push((kept, weight)),
+            }
+        }
+        let total: f64 = leaves.iter().map(|(_, w)| w).sum();
+        for (_, w) in &mut leaves {
+            *w /= total;
+        }
+        leaves.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(RackInference {
+            leaves,
+            unseen,
+            rack_len,
+        })
+    }
+
+    /// How well keeping `kept` fits the last turn: the equity given up by the play, as a
+    /// negative number, or the value of the leave kept through an exchange.
+    fn rate<BB: Bitboard>(
+        &self,
+        view: &GameView<BB>,
+        evidence: &Evidence<BB>,
+        kept: &[TileMapIdx],
+    ) -> Result<f32> {
+        let Evidence::Placed { m, board, played } = evidence else {
+            return Ok(self.leaves.value_of(kept));
+        };
+        let tiles = view.tiles();
+        let rack = Rack::from_tiles([kept, played].concat(), kept.len() + played.len())?;
+        let equity = |m: &Move| {
+            score_move_with(board, m, tiles, view.rules()).total() as f32
+                + self.leaves.value_of(&leave(&rack, m, tiles))
+        };
+        let best = placements(view.game, board, &rack)
+            .iter()
+            .map(equity)
+            .fold(f32::NEG_INFINITY, f32::max);
+        Ok((equity(m) - best).min(0.0))
+    }
+}
+
+/// What the opponent's last turn showed.
+enum Evidence<'a, BB: Bitboard> {
+    /// They played `m` on `board`, placing the tiles `played` from their rack.
+    Placed {
+        m: &'a Move,
+        board: Board<BB>,
+        played: Vec<TileMapIdx>,
+    },
+    Exchanged,
+}
+
+impl RackInference {
+    /// The tiles the opponent likely kept from their last turn, with their probabilities, the
+    /// likeliest first. Empty if nothing could be inferred.
+    pub fn leaves(&self) -> &[(Vec<TileMapIdx>, f64)] {
+        &self.leaves
+    }
+
+    /// A rack for the opponent: a leave drawn by its probability, filled up at random from the
+    /// other unseen tiles. Uniform over the unseen tiles if nothing could be inferred.
+    pub fn sample(&self, rng: &mut impl Rng) -> Rack {
+        let mut pick = rng.gen::<f64>();
+        let kept = self
+            .leaves
+            .iter()
+            .find(|(_, p)| {
+                pick -= p;
+                pick < 0.0
+            })
+            .or(self.leaves.last())
+            .map_or(&[][..], |(kept, _)| kept.as_slice());
+        let rest: TileCounts = self.unseen.saturating_sub(&kept.iter().copied().collect());
+        let drawn = rest.sample_rack(self.rack_len - kept.len(), rng);
+        let tiles = [kept, drawn.tiles()].concat();
+        Rack::from_tiles(tiles, self.rack_len).expect("The leave and draw fill the rack")
+    }
+
+    /// How many copies of `tile` the opponent holds on average.
+    pub fn expected_count(&self, tile: TileMapIdx) -> f64 {
+        let in_pool = f64::from(self.unseen.get(tile));
+        let total = self.unseen.total() as f64;
+        let drawn = |kept_len: usize, kept_count: f64| {
+            let rest = total - kept_len as f64;
+            if rest <= 0.0 {
+                0.0
+            } else {
+                (self.rack_len - kept_len) as f64 * (in_pool - kept_count) / rest
+            }
+        };
+        if self.leaves.is_empty() {
+            return drawn(0, 0.0);
+        }
+        self.leaves
+            .iter()
+            .map(|(kept, p)| {
+                let count = kept.iter().filter(|&&t| t == tile).count() as f64;
+                p * (count + drawn(kept.len(), count))
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::{Play, Player},
+        lexicon::Lexicon,
+        Game, GameBuilder, TileMap,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn setup() -> Game<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["AT", "CAT"], &tiles);
+        GameBuilder::new()
+            .tiles(tiles.clone())
+            .lexicons(lexicon)
+            .rack(Player::First, Rack::parse("EEEIOUU", &tiles).unwrap())
+            .rack(Player::Second, Rack::parse("ATEEIOU", &tiles).unwrap())
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn skipped_spots() {
+        let mut game = setup();
+        game.play(Player::First, Play::Pass).unwrap();
+        let uniform = RackInferrer::new(LeaveTable::new())
+            .temperature(f32::INFINITY)
+            .infer(&game.view(Player::First))
+            .unwrap();
+        let m = Move::parse("8H AT", game.board(), game.tiles()).unwrap();
+        // Nothing to infer yet from a pass.
+        assert!(RackInferrer::new(LeaveTable::new())
+            .infer(&game.view(Player::First))
+            .unwrap()
+            .leaves()
+            .is_empty());
+
+        game.play(Player::Second, Play::Place(m)).unwrap();
+        let view = game.view(Player::First);
+        let c = game.tiles().find("C").unwrap();
+        let uniform_c = RackInferrer::new(LeaveTable::new())
+            .temperature(f32::INFINITY)
+            .infer(&view)
+            .unwrap()
+            .expected_count(c);
+        let inferred = RackInferrer::new(LeaveTable::new())
+            .temperature(1.0)
+            .infer(&view)
+            .unwrap();
+        // With a C, they would have played CAT for 6 more points. They may still have drawn one.
+        assert!(inferred.expected_count(c) < uniform_c / 2.0);
+        assert!((uniform.expected_count(c) - 2.0 * 7.0 / 93.0).abs() < 1e-9);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..20 {
+            let rack = inferred.sample(&mut rng);
+            assert_eq!(rack.len(), 7);
+            assert!(view.unseen().is_superset(&TileCounts::from(&rack)));
+        }
+    }
+}
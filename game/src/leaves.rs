@@ -0,0 +1,330 @@
+//! Leave values: how much the tiles kept on the rack after a play are worth.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    error::{Context, FormatError, Result, TileError},
+    Rack, TileMap, TileMapIdx,
+};
+
+/// File formats for leave tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaveFormat {
+    /// One `leave,value` pair per line, e.g. `AEIN?,21.5`, with `?` for blanks. Lines starting
+    /// with `#` are comments, except for an optional `# tiles: <fingerprint>` header.
+    Csv,
+    /// A compact binary form. Starts with [`LeaveTable::MAGIC`], the
+    /// [tile map fingerprint](TileMap::fingerprint) and the entry count as little-endian `u64` and
+    /// `u32`, followed by one entry each: a `u8` length, that many tile indices, and the value as a
+    /// little-endian `f32`.
+    Binary,
+}
+
+impl LeaveFormat {
+    /// Guesses the format from the file extension: `.csv`, anything else is binary.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::Binary,
+        }
+    }
+}
+
+/// Equity of rack leaves, indexed by the sorted multiset of tiles left.
+///
+/// Leaves are packed into a `u64` key, one byte per tile, so at most [`LeaveTable::MAX_LEN`] tiles
+/// long. Leaves not in the table, including the empty leave, are worth zero.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LeaveTable {
+    values: HashMap<u64, f32>,
+}
+
+impl LeaveTable {
+    pub const MAX_LEN: usize = 8;
+    pub const MAGIC: &'static [u8; 4] = b"SCLV";
+    const CSV_FINGERPRINT: &'static str = "# tiles:";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The key of a leave, or `None` if it's too long. Tiles are stored off by one so leaves of
+    /// different lengths never collide.
+    fn key(tiles: &[TileMapIdx]) -> Option<u64> {
+        if tiles.len() > Self::MAX_LEN {
+            return None;
+        }
+        let mut sorted = [0u8; Self::MAX_LEN];
+        for (s, t) in sorted.iter_mut().zip(tiles) {
+            *s = t.as_u8();
+        }
+        let sorted = &mut sorted[..tiles.len()];
+        sorted.sort_unstable();
+        Some(
+            sorted
+                .iter()
+                .fold(0, |key, t| (key << 8) | (u64::from(*t) + 1)),
+        )
+    }
+
+    fn tiles_of(key: u64) -> Vec<TileMapIdx> {
+        let mut tiles = Vec::with_capacity(Self::MAX_LEN);
+        let mut key = key;
+        while key != 0 {
+            tiles.push(TileMapIdx((key & 0xff) as u8 - 1));
+            key >>= 8;
+        }
+        tiles.reverse();
+        tiles
+    }
+
+    /// Sets the value of the leave `tiles`, in any order.
+    pub fn insert(&mut self, tiles: &[TileMapIdx], value: f32) -> Result<()> {
+        let Some(key) = Self::key(tiles) else {
+            return Err(TileError::LeaveTooLong {
+                len: tiles.len(),
+                max: Self::MAX_LEN,
+            }
+            .into());
+        };
+        self.values.insert(key, value);
+        Ok(())
+    }
+
+    pub fn value(&self, rack: &Rack) -> f32 {
+        self.value_of(rack.tiles())
+    }
+
+    /// The value of the leave `tiles`, in any order.
+    pub fn value_of(&self, tiles: &[TileMapIdx]) -> f32 {
+        Self::key(tiles)
+            .and_then(|key| self.values.get(&key))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Every leave with its value, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<TileMapIdx>, f32)> + '_ {
+        self.values
+            .iter()
+            .map(|(key, value)| (Self::tiles_of(*key), *value))
+    }
+
+    pub fn from_reader(reader: impl Read, format: LeaveFormat, tiles: &TileMap) -> Result<Self> {
+        match format {
+            LeaveFormat::Csv => Self::from_csv(BufReader::new(reader), tiles),
+            LeaveFormat::Binary => Self::from_binary(reader, tiles),
+        }
+    }
+
+    /// Reads a leave table, picking the format from the file extension.
+    pub fn from_path(path: impl AsRef<Path>, tiles: &TileMap) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        Self::from_reader(file, LeaveFormat::from_path(path), tiles)
+            .with_context(|| format!("Reading {path:?}"))
+    }
+
+    fn from_csv(reader: impl BufRead, tiles: &TileMap) -> Result<Self> {
+        let mut table = Self::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if let Some(fingerprint) = line.strip_prefix(Self::CSV_FINGERPRINT) {
+                let fingerprint = u64::from_str_radix(fingerprint.trim(), 16)
+                    .with_context(|| format!("Line {}: {line:?}", i + 1))?;
+                tiles.check_fingerprint(fingerprint)?;
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = || -> Result<_> {
+                let Some((leave, value)) = line.split_once(',') else {
+                    return Err(FormatError::Invalid("Expected leave,value".into()).into());
+                };
+                let leave = Rack::parse_tiles(leave.trim(), tiles)?;
+                let value: f32 = value.trim().parse()?;
+                Ok((leave, value))
+            };
+            let (leave, value) = parsed().with_context(|| format!("Line {}: {line:?}", i + 1))?;
+            table.insert(&leave, value)?;
+        }
+        Ok(table)
+    }
+
+    fn from_binary(mut reader: impl Read, tiles: &TileMap) -> Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != Self::MAGIC {
+            return Err(FormatError::NotALeaveTable.into());
+        }
+        let mut fingerprint = [0; 8];
+        reader.read_exact(&mut fingerprint)?;
+        tiles.check_fingerprint(u64::from_le_bytes(fingerprint))?;
+        let mut count = [0; 4];
+        reader.read_exact(&mut count)?;
+        let count = u32::from_le_bytes(count);
+
+        let mut table = Self::new();
+        let mut leave = Vec::with_capacity(Self::MAX_LEN);
+        for _ in 0..count {
+            let mut len = [0];
+            reader.read_exact(&mut len)?;
+            let mut raw = vec![0; len[0] as usize];
+            reader.read_exact(&mut raw)?;
+            leave.clear();
+            for idx in raw {
+                let Some(idx) = TileMapIdx::new(idx, tiles) else {
+                    return Err(TileError::NotInTileMap(idx).into());
+                };
+                leave.push(idx);
+            }
+            let mut value = [0; 4];
+            reader.read_exact(&mut value)?;
+            table.insert(&leave, f32::from_le_bytes(value))?;
+        }
+        Ok(table)
+    }
+
+    /// Writes the table sorted by leave length, then tiles, so output is deterministic.
+    pub fn to_writer(
+        &self,
+        writer: impl Write,
+        format: LeaveFormat,
+        tiles: &TileMap,
+    ) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        match format {
+            LeaveFormat::Csv => {
+                writeln!(
+                    writer,
+                    "{} {:016x}",
+                    Self::CSV_FINGERPRINT,
+                    tiles.fingerprint()
+                )?;
+                for (leave, value) in entries {
+                    let letters = Rack::from_tiles(leave, Self::MAX_LEN)?.to_letters(tiles);
+                    writeln!(writer, "{letters},{value}")?;
+                }
+            }
+            LeaveFormat::Binary => {
+                writer.write_all(Self::MAGIC)?;
+                writer.write_all(&tiles.fingerprint().to_le_bytes())?;
+                writer.write_all(&(entries.len() as u32).to_le_bytes())?;
+                for (leave, value) in entries {
+                    writer.write_all(&[leave.len() as u8])?;
+                    for t in leave {
+                        writer.write_all(&[t.as_u8()])?;
+                    }
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const CSV: &str = "# leave,value\nQ,-7.5\n?,25.1\nES,8\nSE?,30.25\n\nAEINST,12\n";
+
+    #[test]
+    fn lookup_ignores_order() {
+        let tiles = TileMap::english();
+        let table = LeaveTable::from_reader(CSV.as_bytes(), LeaveFormat::Csv, &tiles).unwrap();
+        assert_eq!(table.len(), 5);
+        let value = |leave| table.value(&Rack::parse(leave, &tiles).unwrap());
+        assert_eq!(value("Q"), -7.5);
+        assert_eq!(value("?ES"), 30.25);
+        assert_eq!(value("SE"), 8.0);
+        assert_eq!(value("TSNIEA"), 12.0);
+        assert_eq!(value("EE"), 0.0);
+        assert_eq!(value(""), 0.0);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let tiles = TileMap::english();
+        let table = LeaveTable::from_reader(CSV.as_bytes(), LeaveFormat::Csv, &tiles).unwrap();
+        for format in [LeaveFormat::Csv, LeaveFormat::Binary] {
+            let mut out = Vec::new();
+            table.to_writer(&mut out, format, &tiles).unwrap();
+            let read = LeaveTable::from_reader(out.as_slice(), format, &tiles).unwrap();
+            assert_eq!(read, table);
+        }
+        let mut csv = Vec::new();
+        table.to_writer(&mut csv, LeaveFormat::Csv, &tiles).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!(
+                "# tiles: {:016x}\nQ,-7.5\n?,25.1\nES,8\nES?,30.25\nAEINST,12\n",
+                tiles.fingerprint()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let tiles = TileMap::english();
+        let csv = |s: &str| LeaveTable::from_reader(s.as_bytes(), LeaveFormat::Csv, &tiles);
+        assert!(csv("AE").is_err());
+        assert!(csv("A1,3").is_err());
+        assert!(csv("AE,x").is_err());
+        let binary = |b: &[u8]| LeaveTable::from_reader(b, LeaveFormat::Binary, &tiles);
+        let header = |count: u32| -> Vec<u8> {
+            [
+                LeaveTable::MAGIC.as_slice(),
+                &tiles.fingerprint().to_le_bytes(),
+                &count.to_le_bytes(),
+            ]
+            .concat()
+        };
+        assert!(binary(b"nope\0\0\0\0").is_err());
+        assert!(binary(&[header(1), b"\x01\xff\0\0\0\0".to_vec()].concat()).is_err());
+        assert!(binary(&[header(1), b"\x01".to_vec()].concat()).is_err());
+        assert!(binary(&header(0)).is_ok());
+        let mut table = LeaveTable::new();
+        table.insert(&[tiles.find("Q").unwrap()], -7.0).unwrap();
+        let mut out = Vec::new();
+        for format in [LeaveFormat::Csv, LeaveFormat::Binary] {
+            out.clear();
+            table.to_writer(&mut out, format, &tiles).unwrap();
+            let french = TileMap::french();
+            assert!(LeaveTable::from_reader(out.as_slice(), format, &french).is_err());
+        }
+        assert!(table.insert(&[tiles.find("A").unwrap(); 9], 1.0).is_err());
+    }
+
+    #[test]
+    fn csv_leaves_up_to_max_len() {
+        let tiles = TileMap::english();
+        let csv = |s: &str| LeaveTable::from_reader(s.as_bytes(), LeaveFormat::Csv, &tiles);
+        let table = csv("AEINRST?,40\n").unwrap();
+        let leave = Rack::parse_tiles("?AEINRST", &tiles).unwrap();
+        assert_eq!(table.value_of(&leave), 40.0);
+        let Err(err) = csv("AEINRST??,40\n") else {
+            panic!("Read a leave longer than the maximum");
+        };
+        assert!(err.to_string().contains("at most 8"), "{err}");
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use crate::{board::Row, TileMap, TileMapIdx};
+
+use super::{combinations, Dawg, Lexicon, NodeId, Word};
+
+/// Words grouped by alphagram, their tiles in sorted order, so all the words using exactly a given
+/// set of tiles are one lookup away. See [`Lexicon::build_alphagram_index`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlphagramIndex {
+    /// Words in tile order.
+    words: HashMap<Word, Vec<Word>>,
+    /// The alphagrams themselves, for finding those containing some tiles, see
+    /// [`AlphagramCursor`].
+    alphagrams: Dawg,
+    num_tiles: usize,
+}
+
+/// Some tiles, in no particular order, that at least one alphagram of an [`AlphagramIndex`]
+/// contains. Generates moves for [Clabbers](crate::rules::Variant::Clabbers), where any order of
+/// the tiles of a word is a word, the way a [`LexiconCursor`](super::LexiconCursor) does for
+/// words in order.
+#[derive(Clone, Copy)]
+pub struct AlphagramCursor<'a> {
+    index: &'a AlphagramIndex,
+    /// Sorted, the first `len` of them.
+    tiles: [TileMapIdx; Row::MAX as usize],
+    len: usize,
+}
+
+/// `tiles` in sorted order.
+pub fn alphagram(tiles: &[TileMapIdx]) -> Word {
+    let mut sorted = tiles.to_vec();
+    sorted.sort_unstable();
+    sorted
+}
+
+impl AlphagramIndex {
+    /// The words using exactly `tiles`, in any order, e.g. a full rack. Blanks aren't expanded;
+    /// use [`Lexicon::anagrams`] for those.
+    pub fn get(&self, tiles: &[TileMapIdx]) -> &[Word] {
+        self.words.get(&alphagram(tiles)).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `tiles` in some order are a word.
+    pub fn contains_anagram(&self, tiles: &[TileMapIdx]) -> bool {
+        self.alphagrams.contains(&alphagram(tiles))
+    }
+
+    /// A cursor holding no tiles yet.
+    pub fn cursor(&self) -> AlphagramCursor<'_> {
+        AlphagramCursor {
+            index: self,
+            tiles: [TileMapIdx(0); Row::MAX as usize],
+            len: 0,
+        }
+    }
+
+    /// Whether some alphagram below `node` contains all of `sorted`. Alphagrams are sorted too,
+    /// so a tile of `sorted` can only be matched before the path passes it.
+    fn completes(&self, node: NodeId, sorted: &[TileMapIdx]) -> bool {
+        let Some((&first, rest)) = sorted.split_first() else {
+            return true;
+        };
+        for (tile, child) in self.alphagrams.children(node) {
+            if tile > first {
+                break;
+            }
+            let needed = if tile == first { rest } else { sorted };
+            if self.completes(child, needed) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Every alphagram with its words, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Word, &[Word])> {
+        self.words.iter().map(|(a, w)| (a, w.as_slice()))
+    }
+
+    /// The number of alphagrams.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The number of tiles of the tile map the lexicon was built with, including the blank.
+    pub(crate) fn num_tiles(&self) -> usize {
+        self.num_tiles
+    }
+
+    /// The alphagrams of `len` tiles, most likely to be drawn from a full bag of `tiles` first,
+    /// as in study lists. Alphagrams equally likely come in tile order.
+    pub fn ranked(&self, len: usize, tiles: &TileMap) -> Vec<&Word> {
+        let mut ranked: Vec<_> = self
+            .words
+            .keys()
+            .filter(|a| a.len() == len)
+            .map(|a| (combinations(a, tiles), a))
+            .collect();
+        ranked.sort_by(|(ca, a), (cb, b)| cb.cmp(ca).then_with(|| a.cmp(b)));
+        ranked.into_iter().map(|(_, a)| a).collect()
+    }
+}
+
+impl<'a> AlphagramCursor<'a> {
+    /// The cursor after adding `tile`, if some alphagram still contains all the tiles.
+    pub fn child(self, tile: TileMapIdx) -> Option<Self> {
+        if self.len == self.tiles.len() {
+            return None;
+        }
+        let mut child = self;
+        let pos = self.tiles[..self.len].partition_point(|t| *t <= tile);
+        child.tiles.copy_within(pos..self.len, pos + 1);
+        child.tiles[pos] = tile;
+        child.len += 1;
+        let index = self.index;
+        index
+            .completes(index.alphagrams.root(), &child.tiles[..child.len])
+            .then_some(child)
+    }
+
+    /// The cursor after adding every tile of `tiles`.
+    pub fn walk(self, tiles: &[TileMapIdx]) -> Option<Self> {
+        tiles
+            .iter()
+            .try_fold(self, |cursor, tile| cursor.child(*tile))
+    }
+
+    /// The tiles that can be added, in tile order, with the cursor after each.
+    pub fn children(self) -> impl Iterator<Item = (TileMapIdx, Self)> + 'a {
+        (0..self.index.num_tiles).filter_map(move |t| {
+            let tile = TileMapIdx(t as u8);
+            self.child(tile).map(|child| (tile, child))
+        })
+    }
+
+    /// Whether the tiles added so far are an alphagram.
+    pub fn is_terminal(self) -> bool {
+        self.index.alphagrams.contains(&self.tiles[..self.len])
+    }
+}
+
+impl Lexicon {
+    /// Groups every word by alphagram. Takes a pass over the whole lexicon, so build it once and
+    /// keep it around.
+    pub fn build_alphagram_index(&self) -> AlphagramIndex {
+        let mut words: HashMap<Word, Vec<Word>> = HashMap::new();
+        for word in self.words() {
+            words.entry(alphagram(&word)).or_default().push(word);
+        }
+        AlphagramIndex {
+            alphagrams: words.keys().collect(),
+            words,
+            num_tiles: self.num_tiles(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn lookup() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let lexicon = Lexicon::from_words(
+            ["STOP", "POTS", "TOPS", "SPOT", "OPTS", "POST", "ZAX", "AX"],
+            &tiles,
+        );
+        let index = lexicon.build_alphagram_index();
+        assert_eq!(index.len(), 3);
+        let found: Vec<_> = index
+            .get(&word("TSOP"))
+            .iter()
+            .map(|w| lexicon.spell(w))
+            .collect();
+        assert_eq!(found, ["OPTS", "POST", "POTS", "SPOT", "STOP", "TOPS"]);
+        assert!(index.get(&word("TSO")).is_empty());
+
+        let ranked: Vec<_> = index
+            .ranked(2, &tiles)
+            .iter()
+            .map(|a| lexicon.spell(a))
+            .collect();
+        assert_eq!(ranked, ["AX"]);
+        let full_racks = lexicon.anagrams(&crate::Rack::parse("PTOS", &tiles).unwrap());
+        let four: Vec<_> = full_racks.into_iter().filter(|w| w.len() == 4).collect();
+        assert_eq!(four, index.get(&word("PTOS")));
+    }
+
+    #[test]
+    fn cursor() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let index = Lexicon::from_words(["STOP", "ZAX"], &tiles).build_alphagram_index();
+        assert!(index.contains_anagram(&word("TOPS")));
+        assert!(index.contains_anagram(&word("XZA")));
+        assert!(!index.contains_anagram(&word("ZA")));
+
+        let cursor = index.cursor().walk(&word("TS")).unwrap();
+        assert!(!cursor.is_terminal());
+        let next: String = cursor
+            .children()
+            .map(|(t, _)| tiles.get(t).unwrap().as_str())
+            .collect();
+        assert_eq!(next, "OP");
+        assert!(cursor.walk(&word("PO")).unwrap().is_terminal());
+        assert!(cursor.child(word("T")[0]).is_none());
+        assert!(index.cursor().walk(&word("SA")).is_none());
+    }
+}
@@ -0,0 +1,321 @@
+//! The binary lexicon format. Everything is little-endian:
+//!
+//! - [`Lexicon::MAGIC`], then the format version and the tile map fingerprint as `u32` and `u64`
+//! - Whether the tile map is [case-insensitive](TileMap::case_insensitive), as a `u32` of 0 or 1
+//! - The tile map letters: a `u32` count, then each as a `u32` byte length and UTF-8, padded with
+//!   zeros to a multiple of 4 bytes
+//! - The DAWG as `u32`s: word count, root node, length of the node array, length of the edge
+//!   array, the node array, the edge array (see [`Dawg`])
+//! - The GADDAG: its word count as a `u32`, then its paths in the same form as the DAWG
+//!
+//! Every field is 4-byte aligned, so the arrays can be used in place, see [`Lexicon::load_mmap`].
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+#[cfg(feature = "mmap")]
+use std::{
+    io::{Cursor, Seek, SeekFrom},
+    sync::Arc,
+};
+
+use crate::{
+    error::{Context, LexiconError, Result},
+    TileMap,
+};
+
+use super::{storage::U32s, Dawg, Gaddag, Lexicon};
+
+impl Lexicon {
+    pub const MAGIC: &'static [u8; 4] = b"SCLX";
+    pub const FORMAT_VERSION: u32 = 2;
+
+    /// Writes the built lexicon, so it can be [loaded](Lexicon::load) without rebuilding it.
+    /// [Skipped](Lexicon::skipped) words and [definitions](Lexicon::definition) aren't saved.
+    pub fn save(&self, writer: impl Write) -> Result<()> {
+        let mut w = BufWriter::new(writer);
+        w.write_all(Self::MAGIC)?;
+        w.write_all(&Self::FORMAT_VERSION.to_le_bytes())?;
+        w.write_all(&self.fingerprint.to_le_bytes())?;
+        write_u32(&mut w, usize::from(self.tokenizer.is_case_insensitive()))?;
+        write_u32(&mut w, self.letters.len())?;
+        for letter in &self.letters {
+            write_u32(&mut w, letter.len())?;
+            w.write_all(letter.as_bytes())?;
+            w.write_all(&[0; 3][..letter.len().next_multiple_of(4) - letter.len()])?;
+        }
+        write_dawg(&mut w, &self.dawg)?;
+        write_u32(&mut w, self.gaddag.len())?;
+        write_dawg(&mut w, self.gaddag.paths())?;
+        w.flush()?;
+        Ok(())
+    }
+
+    pub fn save_to_path(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("Creating {path:?}"))?;
+        self.save(file).with_context(|| format!("Writing {path:?}"))
+    }
+
+    /// Reads a lexicon written by [`Lexicon::save`]. Fails if it was built for a different tile
+    /// map than `tiles`.
+    pub fn load(reader: impl Read, tiles: &TileMap) -> Result<Self> {
+        Self::read(&mut BufReader::new(reader), tiles, |r, len| {
+            read_u32s(r, len).map(U32s::Owned)
+        })
+    }
+
+    /// Reads everything but the node arrays, which `read_array` gets from the reader as it's
+    /// positioned at the start of each array.
+    fn read<R: Read>(
+        r: &mut R,
+        tiles: &TileMap,
+        mut read_array: impl FnMut(&mut R, usize) -> Result<U32s>,
+    ) -> Result<Self> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != Self::MAGIC {
+            return Err(LexiconError::NotALexicon.into());
+        }
+        let version = read_u32(r)?;
+        if version != Self::FORMAT_VERSION {
+            return Err(LexiconError::Version {
+                found: version,
+                expected: Self::FORMAT_VERSION,
+            }
+            .into());
+        }
+        let mut fingerprint = [0; 8];
+        r.read_exact(&mut fingerprint)?;
+        let fingerprint = u64::from_le_bytes(fingerprint);
+        tiles.check_fingerprint(fingerprint)?;
+        let case_insensitive = match read_u32(r)? {
+            0 => false,
+            1 => true,
+            flag => {
+                return Err(LexiconError::Corrupt(format!(
+                    "Invalid case-insensitivity flag {flag}"
+                ))
+                .into())
+            }
+        };
+
+        let num_letters = read_u32(r)?;
+        let mut letters = Vec::with_capacity(num_letters.min(256) as usize);
+        for _ in 0..num_letters {
+            let len = read_u32(r)? as usize;
+            let mut bytes = read_bytes(r, len.next_multiple_of(4))?;
+            bytes.truncate(len);
+            let Ok(letter) = String::from_utf8(bytes) else {
+                return Err(LexiconError::Corrupt("Lexicon letter isn't UTF-8".into()).into());
+            };
+            letters.push(letter);
+        }
+        let dawg = read_dawg(r, &mut read_array).context("Reading the DAWG")?;
+        let gaddag_words = read_u32(r)? as usize;
+        let paths = read_dawg(r, &mut read_array).context("Reading the GADDAG")?;
+        Ok(Self {
+            tokenizer: Self::tokenizer(&letters, case_insensitive)
+                .context("Reading the letters")?,
+            letters,
+            fingerprint,
+            dawg,
+            gaddag: Gaddag::from_parts(paths, gaddag_words),
+            skipped: Vec::new(),
+            definitions: HashMap::new(),
+        })
+    }
+
+    /// Like [`Lexicon::load_from_path`], but maps the file into memory and uses the node arrays
+    /// in place instead of copying them. Processes loading the same file share its pages.
+    ///
+    /// The file must not be modified while the lexicon is alive. The arrays are still checked
+    /// once on load, so a corrupt file is an error rather than a panic later.
+    #[cfg(feature = "mmap")]
+    pub fn load_mmap(path: impl AsRef<Path>, tiles: &TileMap) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        // SAFETY: Lexicon files are only ever written whole by `save`, and modifying one while
+        // it's mapped is documented as not allowed.
+        let map = Arc::new(
+            unsafe { memmap2::Mmap::map(&file) }.with_context(|| format!("Mapping {path:?}"))?,
+        );
+        if cfg!(target_endian = "big") {
+            return Self::load(&map[..], tiles).with_context(|| format!("Reading {path:?}"));
+        }
+        let mut cursor = Cursor::new(&map[..]);
+        Self::read(&mut cursor, tiles, |r, len| {
+            let start = r.position() as usize;
+            if start + len * 4 > map.len() {
+                return Err(LexiconError::Truncated("lexicon file").into());
+            }
+            r.seek(SeekFrom::Current(len as i64 * 4))?;
+            Ok(U32s::Mapped {
+                map: Arc::clone(&map),
+                start,
+                len,
+            })
+        })
+        .with_context(|| format!("Reading {path:?}"))
+    }
+
+    pub fn load_from_path(path: impl AsRef<Path>, tiles: &TileMap) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        Self::load(file, tiles).with_context(|| format!("Reading {path:?}"))
+    }
+}
+
+fn write_u32(w: &mut impl Write, n: usize) -> Result<()> {
+    let n = u32::try_from(n).map_err(|_| LexiconError::TooLarge)?;
+    w.write_all(&n.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Reads `len` bytes, allocating as they arrive rather than all at once, so a corrupt length
+/// can't ask for more memory than the data holds.
+fn read_bytes(r: &mut impl Read, len: usize) -> Result<Vec<u8>> {
+    const CHUNK: usize = 1 << 16;
+    let mut bytes = Vec::with_capacity(len.min(CHUNK));
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(LexiconError::Truncated("lexicon file").into());
+    }
+    Ok(bytes)
+}
+
+fn read_u32s(r: &mut impl Read, len: usize) -> Result<Vec<u32>> {
+    let len = len.checked_mul(4).ok_or(LexiconError::TooLarge)?;
+    Ok(read_bytes(r, len)?
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes(b.try_into().expect("Chunks of 4")))
+        .collect())
+}
+
+fn write_dawg(w: &mut impl Write, dawg: &Dawg) -> Result<()> {
+    write_u32(w, dawg.len())?;
+    write_u32(w, dawg.root().0 as usize)?;
+    write_u32(w, dawg.nodes_raw().len())?;
+    write_u32(w, dawg.edges_raw().len())?;
+    for n in dawg.nodes_raw().iter().chain(dawg.edges_raw()) {
+        w.write_all(&n.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_dawg<R: Read>(
+    r: &mut R,
+    read_array: &mut impl FnMut(&mut R, usize) -> Result<U32s>,
+) -> Result<Dawg> {
+    let words = read_u32(r)? as usize;
+    let root = read_u32(r)?;
+    let num_nodes = read_u32(r)? as usize;
+    let num_edges = read_u32(r)? as usize;
+    let nodes = read_array(r, num_nodes)?;
+    let edges = read_array(r, num_edges)?;
+    Dawg::from_parts(nodes, edges, root, words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn lexicon(tiles: &TileMap) -> Lexicon {
+        Lexicon::from_words(["CHURRO", "AÑO", "LLAMA", "AMAR", "AMARRA"], tiles)
+    }
+
+    #[test]
+    fn roundtrip() {
+        let tiles = TileMap::spanish();
+        let lexicon = lexicon(&tiles);
+        let mut buf = Vec::new();
+        lexicon.save(&mut buf).unwrap();
+        assert_eq!(buf.len() % 4, 0);
+        let loaded = Lexicon::load(buf.as_slice(), &tiles).unwrap();
+        assert_eq!(loaded, lexicon);
+        assert!(loaded.gaddag().contains(&tiles.tokenize("AMARRA").unwrap()));
+    }
+
+    #[test]
+    fn keeps_case_insensitivity() {
+        let tiles = TileMap::new(vec!["ch".into(), "a".into()])
+            .unwrap()
+            .case_insensitive(true);
+        let lexicon = Lexicon::from_words(["cha"], &tiles);
+        let mut buf = Vec::new();
+        lexicon.save(&mut buf).unwrap();
+        let loaded = Lexicon::load(buf.as_slice(), &tiles).unwrap();
+        assert_eq!(loaded, lexicon);
+        // Uppercasing alone doesn't match the lowercase tiles.
+        assert_eq!(loaded.tokenize("CHA"), Some(tiles.tokenize("cha").unwrap()));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap() {
+        let tiles = TileMap::spanish();
+        let lexicon = lexicon(&tiles);
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("scrubble-mmap-{}.lex", std::process::id()));
+        let truncated = dir.join(format!(
+            "scrubble-mmap-{}-truncated.lex",
+            std::process::id()
+        ));
+        lexicon.save_to_path(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&truncated, &bytes[..bytes.len() - 8]).unwrap();
+
+        let mapped = Lexicon::load_mmap(&path, &tiles);
+        let truncated_result = Lexicon::load_mmap(&truncated, &tiles);
+        std::fs::remove_file(&truncated).unwrap();
+        let mapped = mapped.unwrap();
+        assert_eq!(mapped, lexicon);
+        assert!(mapped.contains(&tiles.tokenize("CHURRO").unwrap()));
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+        assert!(truncated_result.is_err());
+    }
+
+    #[test]
+    fn rejects_mismatches() {
+        let tiles = TileMap::spanish();
+        let mut buf = Vec::new();
+        lexicon(&tiles).save(&mut buf).unwrap();
+        let err = Lexicon::load(buf.as_slice(), &TileMap::english()).unwrap_err();
+        assert!(err.to_string().contains("Tile map mismatch"), "{err}");
+
+        let mut bad_version = buf.clone();
+        bad_version[4] = 99;
+        assert!(Lexicon::load(bad_version.as_slice(), &tiles).is_err());
+        assert!(Lexicon::load(&buf[..buf.len() - 4], &tiles).is_err());
+        assert!(Lexicon::load(&b"SCLV"[..], &tiles).is_err());
+
+        // An edge pointing past the last node.
+        let mut corrupt = buf.clone();
+        let len = corrupt.len();
+        corrupt[len - 1] = 0xff;
+        assert!(Lexicon::load(corrupt.as_slice(), &tiles).is_err());
+
+        // A DAWG claiming 16 GiB of nodes fails as truncated instead of allocating them.
+        let letters: usize = lexicon(&tiles)
+            .letters
+            .iter()
+            .map(|l| 4 + l.len().next_multiple_of(4))
+            .sum();
+        let num_nodes = 16 + 4 + 4 + letters + 8;
+        let mut huge = buf.clone();
+        huge[num_nodes..num_nodes + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = Lexicon::load(huge.as_slice(), &tiles).unwrap_err();
+        assert!(format!("{err:#}").contains("Truncated"), "{err:#}");
+    }
+}
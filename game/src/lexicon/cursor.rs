@@ -0,0 +1,199 @@
+use std::fmt;
+
+use crate::TileMapIdx;
+
+use super::{AlphagramCursor, Dawg, Gaddag, Lexicon, NodeId, PackedDawg};
+
+/// A position in a [`Dawg`], [`Gaddag`] or [`PackedDawg`], i.e. the tiles followed so far from
+/// the root.
+///
+/// Cheap to copy, so a move generator can keep one per square it's extending from instead of
+/// walking from the root again for every candidate.
+#[derive(Clone, Copy)]
+pub struct LexiconCursor<'a> {
+    graph: Graph<'a>,
+    node: NodeId,
+}
+
+#[derive(Clone, Copy)]
+enum Graph<'a> {
+    Dawg(&'a Dawg),
+    Packed(&'a PackedDawg),
+}
+
+impl<'a> LexiconCursor<'a> {
+    fn new(graph: &'a Dawg) -> Self {
+        Self {
+            graph: Graph::Dawg(graph),
+            node: graph.root(),
+        }
+    }
+
+    pub(super) fn packed(graph: &'a PackedDawg) -> Self {
+        Self {
+            graph: Graph::Packed(graph),
+            node: graph.root(),
+        }
+    }
+
+    /// The cursor after following `tile`, if any word continues with it.
+    pub fn child(self, tile: TileMapIdx) -> Option<Self> {
+        let child = match self.graph {
+            Graph::Dawg(g) => g.child(self.node, tile),
+            Graph::Packed(g) => g.child(self.node, tile),
+        };
+        child.map(|node| Self { node, ..self })
+    }
+
+    /// The cursor after following every tile of `tiles`.
+    pub fn walk(self, tiles: &[TileMapIdx]) -> Option<Self> {
+        tiles
+            .iter()
+            .try_fold(self, |cursor, tile| cursor.child(*tile))
+    }
+
+    /// Crosses the [GADDAG separator](Gaddag::SEPARATOR). Always `None` for DAWG cursors.
+    pub fn separator(self) -> Option<Self> {
+        self.child(Gaddag::SEPARATOR)
+    }
+
+    /// The tiles that can follow, in tile order, with the cursor after each.
+    pub fn children(self) -> impl Iterator<Item = (TileMapIdx, Self)> + 'a {
+        let (dawg, packed) = match self.graph {
+            Graph::Dawg(g) => (Some(g.children(self.node)), None),
+            Graph::Packed(g) => (None, Some(g.children(self.node))),
+        };
+        dawg.into_iter()
+            .flatten()
+            .chain(packed.into_iter().flatten())
+            .map(move |(tile, node)| (tile, Self { node, ..self }))
+    }
+
+    /// Whether the tiles followed so far complete a word.
+    pub fn is_terminal(self) -> bool {
+        match self.graph {
+            Graph::Dawg(g) => g.is_word(self.node),
+            Graph::Packed(g) => g.is_word(self.node),
+        }
+    }
+}
+
+impl PartialEq for LexiconCursor<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let same_graph = match (self.graph, other.graph) {
+            (Graph::Dawg(a), Graph::Dawg(b)) => std::ptr::eq(a, b),
+            (Graph::Packed(a), Graph::Packed(b)) => std::ptr::eq(a, b),
+            _ => false,
+        };
+        same_graph && self.node == other.node
+    }
+}
+
+impl Eq for LexiconCursor<'_> {}
+
+impl fmt::Debug for LexiconCursor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LexiconCursor").field(&self.node).finish()
+    }
+}
+
+/// What the move generator needs of a cursor, so it can follow words in order with a
+/// [`LexiconCursor`], or in any order with an [`AlphagramCursor`].
+pub(crate) trait WordCursor<'a>: Copy {
+    fn child(self, tile: TileMapIdx) -> Option<Self>;
+
+    fn children(self) -> impl Iterator<Item = (TileMapIdx, Self)> + 'a;
+
+    fn is_terminal(self) -> bool;
+
+    fn walk(self, tiles: &[TileMapIdx]) -> Option<Self> {
+        tiles
+            .iter()
+            .try_fold(self, |cursor, tile| cursor.child(*tile))
+    }
+}
+
+impl<'a> WordCursor<'a> for LexiconCursor<'a> {
+    fn child(self, tile: TileMapIdx) -> Option<Self> {
+        LexiconCursor::child(self, tile)
+    }
+
+    fn children(self) -> impl Iterator<Item = (TileMapIdx, Self)> + 'a {
+        LexiconCursor::children(self)
+    }
+
+    fn is_terminal(self) -> bool {
+        LexiconCursor::is_terminal(self)
+    }
+}
+
+impl<'a> WordCursor<'a> for AlphagramCursor<'a> {
+    fn child(self, tile: TileMapIdx) -> Option<Self> {
+        AlphagramCursor::child(self, tile)
+    }
+
+    fn children(self) -> impl Iterator<Item = (TileMapIdx, Self)> + 'a {
+        AlphagramCursor::children(self)
+    }
+
+    fn is_terminal(self) -> bool {
+        AlphagramCursor::is_terminal(self)
+    }
+}
+
+impl Dawg {
+    /// A cursor at the root.
+    pub fn cursor(&self) -> LexiconCursor<'_> {
+        LexiconCursor::new(self)
+    }
+}
+
+impl Gaddag {
+    /// A cursor at the root. Tiles to the left of the anchor are followed first, rightmost first,
+    /// then the [separator](LexiconCursor::separator) and the tiles to the right.
+    pub fn cursor(&self) -> LexiconCursor<'_> {
+        LexiconCursor::new(self.paths())
+    }
+}
+
+impl Lexicon {
+    /// A cursor at the root of the [`Dawg`], for following words left to right.
+    pub fn cursor(&self) -> LexiconCursor<'_> {
+        self.dawg.cursor()
+    }
+
+    /// A cursor at the root of the [`Gaddag`], for extending from an anchor.
+    pub fn gaddag_cursor(&self) -> LexiconCursor<'_> {
+        self.gaddag.cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileMap;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn follow_words() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let idx = |l| tiles.find(l).unwrap();
+        let lexicon = Lexicon::from_words(["CAR", "CARE", "CARS", "SCAR"], &tiles);
+
+        let car = lexicon.cursor().walk(&word("CAR")).unwrap();
+        assert!(car.is_terminal());
+        assert_eq!(car.child(idx("E")).unwrap().child(idx("S")), None);
+        let next: Vec<_> = car.children().map(|(t, c)| (t, c.is_terminal())).collect();
+        assert_eq!(next, [(idx("E"), true), (idx("S"), true)]);
+        assert_eq!(car.separator(), None);
+        assert_eq!(lexicon.cursor().walk(&word("CAR")), Some(car));
+        assert_ne!(lexicon.cursor(), lexicon.gaddag_cursor());
+
+        // Anchored on the A of "SCAR": left to C and S, then right to R.
+        let left = lexicon.gaddag_cursor().walk(&word("ACS")).unwrap();
+        assert!(!left.is_terminal());
+        let scar = left.separator().unwrap().child(idx("R")).unwrap();
+        assert!(scar.is_terminal());
+    }
+}
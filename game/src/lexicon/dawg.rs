@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::{LexiconError, Result},
+    TileMapIdx,
+};
+
+use super::{storage::U32s, NodeId, Trie};
+
+/// A minimized [`Trie`]: nodes whose suffixes are the same are merged, so "CATS" and "BATS" share
+/// the nodes for "ATS". Much smaller than a trie for large word lists, with the same queries.
+///
+/// Building one is slow-ish and meant to be done once per word list, then
+/// [saved](super::Lexicon::save).
+///
+/// Stored as two flat arrays of `u32`s, which is also the on-disk layout:
+/// - `nodes`: two entries per node, the index of its first edge, and its number of edges with
+///   [`Dawg::WORD_FLAG`] set if the path to the node spells a word.
+/// - `edges`: the tile in the low 8 bits, the child node above. A node's edges are contiguous and
+///   sorted by tile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dawg {
+    nodes: U32s,
+    edges: U32s,
+    root: NodeId,
+    words: usize,
+}
+
+/// Size statistics of a [`Dawg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DawgStats {
+    pub words: usize,
+    pub nodes: usize,
+    pub edges: usize,
+    /// Nodes of the trie the DAWG was built from.
+    pub trie_nodes: usize,
+}
+
+/// Node signature used to find equivalent nodes while minimizing.
+type Signature = (bool, Vec<u32>);
+
+impl Dawg {
+    pub(super) const WORD_FLAG: u32 = 1 << 31;
+    /// Node ids have to fit into the upper 24 bits of an edge.
+    pub const MAX_NODES: usize = 1 << 24;
+
+    pub fn from_trie(trie: &Trie) -> Self {
+        let mut builder = Builder::default();
+        let root = builder.minimize(trie, trie.root());
+        Self {
+            nodes: U32s::Owned(builder.nodes),
+            edges: U32s::Owned(builder.edges),
+            root,
+            words: trie.len(),
+        }
+    }
+
+    fn edge(tile: TileMapIdx, child: NodeId) -> u32 {
+        u32::from(tile.as_u8()) | (child.0 << 8)
+    }
+
+    fn unpack_edge(edge: u32) -> (TileMapIdx, NodeId) {
+        (TileMapIdx(edge as u8), NodeId(edge >> 8))
+    }
+
+    /// Checks that the arrays describe a valid DAWG, so lookups can't go out of bounds.
+    pub(super) fn from_parts(nodes: U32s, edges: U32s, root: u32, words: usize) -> Result<Self> {
+        let num_nodes = nodes.len() / 2;
+        let corrupt = |message: &str| Err(LexiconError::Corrupt(message.into()).into());
+        if !nodes.len().is_multiple_of(2) {
+            return Err(LexiconError::Truncated("DAWG node").into());
+        }
+        if root as usize >= num_nodes {
+            return corrupt("DAWG root out of range");
+        }
+        for node in nodes.chunks_exact(2) {
+            let end = node[0] as usize + (node[1] & !Self::WORD_FLAG) as usize;
+            if end > edges.len() {
+                return corrupt("DAWG edges out of range");
+            }
+        }
+        if edges.iter().any(|edge| (edge >> 8) as usize >= num_nodes) {
+            return corrupt("DAWG edge to a missing node");
+        }
+        Ok(Self {
+            nodes,
+            edges,
+            root: NodeId(root),
+            words,
+        })
+    }
+
+    pub(super) fn nodes_raw(&self) -> &[u32] {
+        self.nodes.as_slice()
+    }
+
+    pub(super) fn edges_raw(&self) -> &[u32] {
+        self.edges.as_slice()
+    }
+
+    fn edges(&self, node: NodeId) -> &[u32] {
+        let i = node.0 as usize * 2;
+        let nodes = self.nodes.as_slice();
+        let start = nodes[i] as usize;
+        let len = (nodes[i + 1] & !Self::WORD_FLAG) as usize;
+        &self.edges.as_slice()[start..start + len]
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn child(&self, node: NodeId, tile: TileMapIdx) -> Option<NodeId> {
+        let edges = self.edges(node);
+        edges
+            .binary_search_by_key(&tile.as_u8(), |e| *e as u8)
+            .ok()
+            .map(|pos| Self::unpack_edge(edges[pos]).1)
+    }
+
+    /// The tiles that can follow `node`, in tile order.
+    pub fn children(&self, node: NodeId) -> impl Iterator<Item = (TileMapIdx, NodeId)> + '_ {
+        self.edges(node).iter().map(|e| Self::unpack_edge(*e))
+    }
+
+    /// Whether the path to `node` spells a word.
+    pub fn is_word(&self, node: NodeId) -> bool {
+        self.nodes.as_slice()[node.0 as usize * 2 + 1] & Self::WORD_FLAG != 0
+    }
+
+    /// The node reached by following `tiles` from the root.
+    pub fn walk(&self, tiles: &[TileMapIdx]) -> Option<NodeId> {
+        tiles
+            .iter()
+            .try_fold(self.root(), |node, tile| self.child(node, *tile))
+    }
+
+    pub fn contains(&self, word: &[TileMapIdx]) -> bool {
+        self.walk(word).is_some_and(|node| self.is_word(node))
+    }
+
+    /// Whether some word starts with `prefix`, including `prefix` itself.
+    pub fn is_prefix(&self, prefix: &[TileMapIdx]) -> bool {
+        self.walk(prefix).is_some()
+    }
+
+    /// All words, in tile order.
+    pub fn words(&self) -> impl Iterator<Item = Vec<TileMapIdx>> + '_ {
+        // Depth-first, with the next edge to take at each node of the current path.
+        let mut stack = vec![(self.root, 0)];
+        let mut path = Vec::new();
+        let mut root_is_word = self.is_word(self.root);
+        std::iter::from_fn(move || {
+            if std::mem::take(&mut root_is_word) {
+                return Some(Vec::new());
+            }
+            while let Some(&(node, next)) = stack.last() {
+                let Some((tile, child)) = self.edges(node).get(next).map(|e| Self::unpack_edge(*e))
+                else {
+                    stack.pop();
+                    path.pop();
+                    continue;
+                };
+                stack.last_mut().expect("Just looked at it").1 += 1;
+                path.push(tile);
+                stack.push((child, 0));
+                if self.is_word(child) {
+                    return Some(path.clone());
+                }
+            }
+            None
+        })
+    }
+
+    /// The number of words.
+    pub fn len(&self) -> usize {
+        self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() / 2
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Sizes, compared to `trie` which this was built from.
+    pub fn stats(&self, trie: &Trie) -> DawgStats {
+        DawgStats {
+            words: self.words,
+            nodes: self.node_count(),
+            edges: self.edge_count(),
+            trie_nodes: trie.node_count(),
+        }
+    }
+}
+
+/// Accumulates the arrays of a [`Dawg`] while minimizing a [`Trie`].
+#[derive(Default)]
+struct Builder {
+    nodes: Vec<u32>,
+    edges: Vec<u32>,
+    canonical: HashMap<Signature, NodeId>,
+}
+
+impl Builder {
+    /// Adds the minimized form of the subtree at `node`, returning its id.
+    fn minimize(&mut self, trie: &Trie, node: NodeId) -> NodeId {
+        let children: Vec<_> = trie
+            .children(node)
+            .map(|(tile, child)| Dawg::edge(tile, self.minimize(trie, child)))
+            .collect();
+        let signature = (trie.is_word(node), children);
+        if let Some(existing) = self.canonical.get(&signature) {
+            return *existing;
+        }
+        let id = self.nodes.len() / 2;
+        assert!(
+            id < Dawg::MAX_NODES,
+            "More than {} DAWG nodes",
+            Dawg::MAX_NODES
+        );
+        let flag = if signature.0 { Dawg::WORD_FLAG } else { 0 };
+        self.nodes.push(self.edges.len() as u32);
+        self.nodes.push(signature.1.len() as u32 | flag);
+        self.edges.extend_from_slice(&signature.1);
+        let id = NodeId(id as u32);
+        self.canonical.insert(signature, id);
+        id
+    }
+}
+
+impl<W: AsRef<[TileMapIdx]>> FromIterator<W> for Dawg {
+    fn from_iter<T: IntoIterator<Item = W>>(iter: T) -> Self {
+        Self::from_trie(&iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexicon::Lexicon, TileMap};
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    #[test]
+    fn shares_suffixes() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CATS", "BATS", "RATS", "CAT"], &tiles);
+        let trie: Trie = lexicon.words().collect();
+        let dawg = lexicon.dawg().clone();
+        assert_eq!(
+            dawg.stats(&trie),
+            DawgStats {
+                words: 4,
+                nodes: 8,
+                edges: 9,
+                trie_nodes: 13,
+            }
+        );
+        let word = |w| tiles.tokenize(w).unwrap();
+        assert!(dawg.contains(&word("CAT")));
+        assert!(!dawg.contains(&word("BAT")));
+        assert!(dawg.contains(&word("RATS")));
+        assert!(dawg.is_prefix(&word("BA")));
+        assert!(!dawg.is_prefix(&word("CO")));
+        let first: Vec<_> = dawg.children(dawg.root()).map(|(t, _)| t).collect();
+        assert_eq!(first, word("BCR"));
+    }
+
+    #[test]
+    fn empty() {
+        let dawg: Dawg = Vec::<Vec<TileMapIdx>>::new().into_iter().collect();
+        assert!(dawg.is_empty());
+        assert_eq!(dawg.node_count(), 1);
+        assert!(!dawg.contains(&[]));
+    }
+
+    fn arb_words() -> impl Strategy<Value = Vec<Vec<TileMapIdx>>> {
+        prop::collection::vec(
+            prop::collection::vec((0u8..4).prop_map(TileMapIdx), 0..6),
+            0..40,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn same_words_as_trie(words in arb_words(), probes in arb_words()) {
+            let trie: Trie = words.iter().collect();
+            let dawg = Dawg::from_trie(&trie);
+            prop_assert!(dawg.node_count() <= trie.node_count());
+            prop_assert_eq!(dawg.len(), trie.len());
+            let mut unique = words.clone();
+            unique.sort();
+            unique.dedup();
+            prop_assert_eq!(dawg.words().collect::<Vec<_>>(), unique);
+            for w in words.iter().chain(&probes) {
+                prop_assert_eq!(dawg.contains(w), trie.contains(w));
+                prop_assert_eq!(dawg.is_prefix(w), trie.is_prefix(w));
+            }
+        }
+    }
+}
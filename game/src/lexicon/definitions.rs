@@ -0,0 +1,88 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::error::{Context, FormatError, LexiconError, Result};
+
+use super::Lexicon;
+
+impl Lexicon {
+    /// Reads definitions from a two-column TSV: a word, a tab, and its definition or an ID to look
+    /// it up by. Later lines win, and words don't have to be in the lexicon, so phonies can be
+    /// explained too. Lines starting with `#` and blank lines are skipped. Returns how many
+    /// definitions were read.
+    pub fn load_definitions(&mut self, reader: impl BufRead) -> Result<usize> {
+        let mut read = 0;
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = || -> Result<_> {
+                let Some((word, definition)) = line.split_once('\t') else {
+                    return Err(FormatError::Invalid("Expected word<TAB>definition".into()).into());
+                };
+                let Some(tiles) = self.tokenize(word.trim()) else {
+                    return Err(LexiconError::Unspellable(word.to_owned()).into());
+                };
+                Ok((tiles, definition.trim().to_owned()))
+            };
+            let (word, definition) =
+                parsed().with_context(|| format!("Line {}: {line:?}", i + 1))?;
+            self.definitions.insert(word, definition);
+            read += 1;
+        }
+        Ok(read)
+    }
+
+    pub fn load_definitions_from_path(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        self.load_definitions(BufReader::new(file))
+            .with_context(|| format!("Reading {path:?}"))
+    }
+
+    /// The definition of `word`, if one was [loaded](Lexicon::load_definitions).
+    pub fn definition(&self, word: &str) -> Option<&str> {
+        self.definitions
+            .get(&self.tokenize(word)?)
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileMap;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn definitions() {
+        let tiles = TileMap::english();
+        let mut lexicon = Lexicon::from_words(["QI", "ZA", "XU"], &tiles);
+        let tsv = "# word\tdefinition\nQI\tvital force\r\nza\tpizza\n\nZA\tpizza, informally\nQIS\tnot a word\n";
+        assert_eq!(lexicon.load_definitions(tsv.as_bytes()).unwrap(), 4);
+        assert_eq!(lexicon.definition("qi"), Some("vital force"));
+        assert_eq!(lexicon.definition("ZA"), Some("pizza, informally"));
+        assert_eq!(lexicon.definition("QIS"), Some("not a word"));
+        assert_eq!(lexicon.definition("XU"), None);
+        assert_eq!(lexicon.definition("1"), None);
+
+        let err = lexicon
+            .load_definitions("QI vital force".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Line 1: \"QI vital force\": Expected word<TAB>definition"
+        );
+        assert!(lexicon.load_definitions("Q1\tx".as_bytes()).is_err());
+
+        let merged = Lexicon::from_words(["QAT"], &tiles)
+            .merge(&lexicon)
+            .unwrap();
+        assert_eq!(merged.definition("QI"), Some("vital force"));
+    }
+}
@@ -0,0 +1,149 @@
+use std::{cmp::Ordering, iter::Peekable};
+
+use crate::error::{LexiconError, Result};
+
+use super::{Dawg, Gaddag, Lexicon, Trie, Word};
+
+/// The words that differ between two lexicons, see [`Lexicon::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LexiconDiff {
+    /// Words only in the newer lexicon, in tile order.
+    pub added: Vec<Word>,
+    /// Words only in the older lexicon, in tile order.
+    pub removed: Vec<Word>,
+}
+
+impl LexiconDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Lexicon {
+    /// The words added and removed going from `self` to `newer`, e.g. from one dictionary edition
+    /// to the next. Both have to be built with the same tile map.
+    pub fn diff(&self, newer: &Lexicon) -> Result<LexiconDiff> {
+        self.check_compatible(newer)?;
+        let mut diff = LexiconDiff::default();
+        merge_words(
+            self.words(),
+            newer.words(),
+            |word, ordering| match ordering {
+                Ordering::Less => diff.removed.push(word),
+                Ordering::Greater => diff.added.push(word),
+                Ordering::Equal => {}
+            },
+        );
+        Ok(diff)
+    }
+
+    /// A lexicon with the words of both, e.g. a dictionary plus a house list. Both have to be
+    /// built with the same tile map. Definitions in `other` win.
+    pub fn merge(&self, other: &Lexicon) -> Result<Lexicon> {
+        self.check_compatible(other)?;
+        let mut words = Vec::with_capacity(self.len().max(other.len()));
+        merge_words(self.words(), other.words(), |word, _| words.push(word));
+        let mut merged = self.with_words(words);
+        merged.definitions.extend(other.definitions.clone());
+        Ok(merged)
+    }
+
+    /// `self` with `diff` applied, e.g. to bring a house list up to a new edition.
+    pub fn apply(&self, diff: &LexiconDiff) -> Lexicon {
+        let mut words = Vec::with_capacity(self.len() + diff.added.len());
+        let removed = diff.removed.iter().cloned();
+        merge_words(self.words(), removed, |word, ordering| {
+            if ordering == Ordering::Less {
+                words.push(word);
+            }
+        });
+        words.extend(diff.added.iter().cloned());
+        words.sort_unstable();
+        words.dedup();
+        self.with_words(words)
+    }
+
+    fn check_compatible(&self, other: &Lexicon) -> Result<()> {
+        if self.fingerprint != other.fingerprint || self.letters != other.letters {
+            return Err(LexiconError::TileMapMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// A lexicon of `words` with the same tiles as `self`.
+    fn with_words(&self, words: Vec<Word>) -> Lexicon {
+        let trie: Trie = words.iter().collect();
+        Lexicon {
+            letters: self.letters.clone(),
+            tokenizer: self.tokenizer.clone(),
+            fingerprint: self.fingerprint,
+            dawg: Dawg::from_trie(&trie),
+            gaddag: Gaddag::from_words(&words),
+            skipped: Vec::new(),
+            definitions: self.definitions.clone(),
+        }
+    }
+}
+
+/// Walks two sorted word lists in step, calling `f` once per distinct word with whether it's
+/// only in `a` (`Less`), only in `b` (`Greater`), or in both.
+fn merge_words(
+    a: impl Iterator<Item = Word>,
+    b: impl Iterator<Item = Word>,
+    mut f: impl FnMut(Word, Ordering),
+) {
+    let (mut a, mut b): (Peekable<_>, Peekable<_>) = (a.peekable(), b.peekable());
+    loop {
+        let ordering = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => x.cmp(y),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return,
+        };
+        let word = match ordering {
+            Ordering::Less => a.next(),
+            Ordering::Greater => b.next(),
+            Ordering::Equal => {
+                b.next();
+                a.next()
+            }
+        };
+        f(word.expect("peeked"), ordering);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileMap;
+    use pretty_assertions::assert_eq;
+
+    fn spell(lexicon: &Lexicon, words: &[Word]) -> Vec<String> {
+        words.iter().map(|w| lexicon.spell(w)).collect()
+    }
+
+    #[test]
+    fn diff_and_merge() {
+        let tiles = TileMap::english();
+        let old = Lexicon::from_words(["CAT", "COW", "OK", "ZA"], &tiles);
+        let new = Lexicon::from_words(["CAT", "OK", "OKE", "QI", "ZA"], &tiles);
+
+        let diff = old.diff(&new).unwrap();
+        assert_eq!(spell(&old, &diff.added), ["OKE", "QI"]);
+        assert_eq!(spell(&old, &diff.removed), ["COW"]);
+        assert!(old.diff(&old).unwrap().is_empty());
+        assert_eq!(old.apply(&diff), new);
+
+        let merged = old.merge(&new).unwrap();
+        let words: Vec<_> = merged.words().collect();
+        assert_eq!(
+            spell(&merged, &words),
+            ["CAT", "COW", "OK", "OKE", "QI", "ZA"]
+        );
+        assert!(merged.gaddag().contains(&tiles.tokenize("COW").unwrap()));
+
+        let spanish = Lexicon::from_words(["CAT"], &TileMap::spanish());
+        assert!(old.diff(&spanish).is_err());
+        assert!(old.merge(&spanish).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::TileMapIdx;
+
+use super::{Dawg, DawgStats, NodeId, Trie};
+
+/// Gordon's GADDAG: every word is stored once per split point as the reversed prefix, a
+/// [separator](Gaddag::SEPARATOR), and the rest of the word. "CARE" is stored as "C◇ARE", "AC◇RE",
+/// "RAC◇E" and "ERAC".
+///
+/// A move generator can start at any tile of a word, extend to the left by walking the reversed
+/// prefix, then cross the separator and extend to the right, all in one pass. The paths are
+/// minimized like a [`Dawg`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gaddag {
+    dawg: Dawg,
+    words: usize,
+}
+
+impl Gaddag {
+    /// Marks the switch from the reversed prefix to the suffix. Never a valid tile, as tile maps
+    /// have at most 255 tiles.
+    pub const SEPARATOR: TileMapIdx = TileMapIdx(u8::MAX);
+
+    pub fn from_words<W: AsRef<[TileMapIdx]>>(words: impl IntoIterator<Item = W>) -> Self {
+        let mut trie = Trie::new();
+        let mut words_added = 0;
+        let mut path = Vec::new();
+        for word in words {
+            let word = word.as_ref();
+            if word.is_empty() {
+                continue;
+            }
+            for split in 1..=word.len() {
+                path.clear();
+                path.extend(word[..split].iter().rev());
+                if split < word.len() {
+                    path.push(Self::SEPARATOR);
+                    path.extend_from_slice(&word[split..]);
+                }
+                let new = trie.insert(&path);
+                // Only count each word once, for its fully reversed path.
+                if split == word.len() && new {
+                    words_added += 1;
+                }
+            }
+        }
+        Self {
+            dawg: Dawg::from_trie(&trie),
+            words: words_added,
+        }
+    }
+
+    pub(super) fn from_parts(dawg: Dawg, words: usize) -> Self {
+        Self { dawg, words }
+    }
+
+    /// The underlying minimized paths.
+    pub(super) fn paths(&self) -> &Dawg {
+        &self.dawg
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.dawg.root()
+    }
+
+    /// Follows `tile`, which can also be [`Gaddag::SEPARATOR`].
+    pub fn child(&self, node: NodeId, tile: TileMapIdx) -> Option<NodeId> {
+        self.dawg.child(node, tile)
+    }
+
+    /// The node after the separator, where extending to the right starts.
+    pub fn separator(&self, node: NodeId) -> Option<NodeId> {
+        self.dawg.child(node, Self::SEPARATOR)
+    }
+
+    /// The tiles that can follow `node`, in tile order. The separator comes last.
+    pub fn children(&self, node: NodeId) -> impl Iterator<Item = (TileMapIdx, NodeId)> + '_ {
+        self.dawg.children(node)
+    }
+
+    /// Whether the path to `node` completes a word. Paths end either after the suffix, or, for the
+    /// fully reversed word, without a separator.
+    pub fn is_word(&self, node: NodeId) -> bool {
+        self.dawg.is_word(node)
+    }
+
+    /// The node reached after placing `tiles` to the left of the anchor, rightmost first.
+    pub fn walk(&self, tiles: &[TileMapIdx]) -> Option<NodeId> {
+        self.dawg.walk(tiles)
+    }
+
+    pub fn contains(&self, word: &[TileMapIdx]) -> bool {
+        !word.is_empty()
+            && word
+                .iter()
+                .rev()
+                .try_fold(self.root(), |node, tile| self.child(node, *tile))
+                .is_some_and(|node| self.is_word(node))
+    }
+
+    /// Whether `infix` occurs anywhere in some word.
+    pub fn contains_infix(&self, infix: &[TileMapIdx]) -> bool {
+        infix
+            .iter()
+            .rev()
+            .try_fold(self.root(), |node, tile| self.child(node, *tile))
+            .is_some()
+    }
+
+    /// The number of words.
+    pub fn len(&self) -> usize {
+        self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.dawg.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.dawg.edge_count()
+    }
+
+    /// Sizes, compared to `trie`, which holds the plain words.
+    pub fn stats(&self, trie: &Trie) -> DawgStats {
+        DawgStats {
+            words: self.words,
+            ..self.dawg.stats(trie)
+        }
+    }
+}
+
+impl<W: AsRef<[TileMapIdx]>> FromIterator<W> for Gaddag {
+    fn from_iter<T: IntoIterator<Item = W>>(iter: T) -> Self {
+        Self::from_words(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexicon::Lexicon, TileMap};
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    #[test]
+    fn extend_from_anchor() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let lexicon = Lexicon::from_words(["CARE", "CARES", "SCARE", "AR"], &tiles);
+        let gaddag = lexicon.gaddag();
+        assert_eq!(gaddag.len(), 4);
+        assert!(gaddag.contains(&word("CARE")));
+        assert!(!gaddag.contains(&word("CAR")));
+        assert!(gaddag.contains_infix(&word("CAR")));
+        assert!(!gaddag.contains_infix(&word("RC")));
+
+        // Anchored on the R: left to A, C, then right to E.
+        let left = gaddag.walk(&word("RAC")).unwrap();
+        assert!(!gaddag.is_word(left));
+        let right = gaddag.separator(left).unwrap();
+        let e = gaddag.child(right, tiles.find("E").unwrap()).unwrap();
+        assert!(gaddag.is_word(e));
+        let s = gaddag.child(e, tiles.find("S").unwrap()).unwrap();
+        assert!(gaddag.is_word(s));
+
+        // "SCARE" continues to the left of "CARE".
+        let scar = gaddag.walk(&word("RACS")).unwrap();
+        assert!(gaddag.separator(scar).is_some());
+        // Nothing but S extends "CARE" on the left.
+        let erac = gaddag.walk(&word("ERAC")).unwrap();
+        let left_hooks: Vec<_> = gaddag
+            .children(erac)
+            .filter(|(t, n)| *t != Gaddag::SEPARATOR && gaddag.is_word(*n))
+            .map(|(t, _)| t)
+            .collect();
+        assert_eq!(left_hooks, word("S"));
+        assert_eq!(gaddag.children(erac).last().unwrap().0, Gaddag::SEPARATOR);
+    }
+
+    fn arb_words() -> impl Strategy<Value = Vec<Vec<TileMapIdx>>> {
+        prop::collection::vec(
+            prop::collection::vec((0u8..4).prop_map(TileMapIdx), 1..6),
+            0..30,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn every_split_is_reachable(words in arb_words(), probes in arb_words()) {
+            let gaddag: Gaddag = words.iter().collect();
+            let trie: Trie = words.iter().collect();
+            prop_assert_eq!(gaddag.len(), trie.len());
+            for w in &words {
+                for split in 1..w.len() {
+                    let mut path: Vec<_> = w[..split].iter().rev().copied().collect();
+                    path.push(Gaddag::SEPARATOR);
+                    path.extend_from_slice(&w[split..]);
+                    let node = gaddag.walk(&path);
+                    prop_assert!(node.is_some_and(|n| gaddag.is_word(n)));
+                }
+            }
+            for w in &probes {
+                prop_assert_eq!(gaddag.contains(w), trie.contains(w));
+            }
+        }
+    }
+}
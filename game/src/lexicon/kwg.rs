@@ -0,0 +1,168 @@
+//! KWG (Kurnia word graph) files, as built by wolges and used by macondo.
+//!
+//! A KWG is an array of little-endian `u32` nodes, one per edge: the index of the node starting
+//! the edge's children in the low 22 bits (0 if there are none), a flag for the last of its
+//! siblings in bit 22, a flag for the path so far spelling a word in bit 23, and the tile in the
+//! top 8 bits. Tiles count from 1, 0 being the GADDAG separator. Node 0 points to the DAWG, node 1
+//! to the GADDAG, which is rebuilt here instead of being read.
+
+use std::{fs::File, io::Read, path::Path};
+
+use crate::{
+    error::{Context, LexiconError, Result},
+    TileMap, TileMapIdx,
+};
+
+use super::{Lexicon, Word};
+
+const ARC_MASK: u32 = (1 << 22) - 1;
+const IS_END: u32 = 1 << 22;
+const ACCEPTS: u32 = 1 << 23;
+/// Longer words mean the nodes loop back on themselves.
+const MAX_WORD_LEN: usize = u8::MAX as usize;
+
+impl Lexicon {
+    /// Reads a KWG lexicon. KWGs don't store their alphabet, so tile `n` is taken to be the `n`th
+    /// letter of `tiles`, which holds for wolges' built-in alphabets and the matching tile maps.
+    pub fn from_kwg(mut reader: impl Read, tiles: &TileMap) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if data.len() < 8 || !data.len().is_multiple_of(4) {
+            return Err(LexiconError::Truncated("KWG lexicon").into());
+        }
+        let nodes: Vec<u32> = data
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let mut walk = Walk {
+            nodes: &nodes,
+            letters: tiles.letters().map(|(idx, _)| idx).collect(),
+            words: Vec::new(),
+        };
+        let root = (nodes[0] & ARC_MASK) as usize;
+        if root != 0 {
+            walk.siblings(root, &mut Vec::new())?;
+        }
+        Ok(Self::from_tokenized(walk.words, Vec::new(), tiles))
+    }
+
+    pub fn from_kwg_path(path: impl AsRef<Path>, tiles: &TileMap) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        Self::from_kwg(file, tiles).with_context(|| format!("Reading {path:?}"))
+    }
+}
+
+struct Walk<'a> {
+    nodes: &'a [u32],
+    /// The tile map's letters, KWG tile `n` being `letters[n - 1]`.
+    letters: Vec<TileMapIdx>,
+    words: Vec<Word>,
+}
+
+impl Walk<'_> {
+    fn siblings(&mut self, first: usize, path: &mut Word) -> Result<()> {
+        let corrupt = |message: String| Err(LexiconError::Corrupt(message).into());
+        if path.len() >= MAX_WORD_LEN {
+            return corrupt(format!(
+                "KWG lexicon has words longer than {MAX_WORD_LEN} letters"
+            ));
+        }
+        for i in first.. {
+            let Some(&node) = self.nodes.get(i) else {
+                return corrupt(format!("KWG node {i} is past the end of the file"));
+            };
+            let tile = (node >> 24) as usize;
+            let Some(&letter) = tile.checked_sub(1).and_then(|t| self.letters.get(t)) else {
+                return corrupt(format!(
+                    "KWG uses tile {tile}, but the tile map has {} letters",
+                    self.letters.len()
+                ));
+            };
+            path.push(letter);
+            if node & ACCEPTS != 0 {
+                self.words.push(path.clone());
+            }
+            let children = (node & ARC_MASK) as usize;
+            if children != 0 {
+                self.siblings(children, path)?;
+            }
+            path.pop();
+            if node & IS_END != 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexicon::{NodeId, Trie};
+    use pretty_assertions::assert_eq;
+
+    /// Lays out `trie` as a KWG without a GADDAG.
+    fn kwg(trie: &Trie) -> Vec<u8> {
+        fn siblings(trie: &Trie, node: NodeId, out: &mut Vec<u32>) -> u32 {
+            let children: Vec<_> = trie.children(node).collect();
+            if children.is_empty() {
+                return 0;
+            }
+            let first = out.len();
+            out.resize(first + children.len(), 0);
+            for (i, (tile, child)) in children.iter().enumerate() {
+                let mut node = siblings(trie, *child, out) | (u32::from(tile.as_u8()) + 1) << 24;
+                if trie.is_word(*child) {
+                    node |= ACCEPTS;
+                }
+                if i == children.len() - 1 {
+                    node |= IS_END;
+                }
+                out[first + i] = node;
+            }
+            first as u32
+        }
+        let mut out = vec![0, IS_END];
+        out[0] = siblings(trie, trie.root(), &mut out) | IS_END;
+        out.iter().flat_map(|n| n.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn read() {
+        let tiles = TileMap::spanish();
+        let trie: Trie = ["OCHO", "AÑOS", "AÑO", "LLAMA"]
+            .into_iter()
+            .map(|w| tiles.tokenize(w).unwrap())
+            .collect();
+        let lexicon = Lexicon::from_kwg(kwg(&trie).as_slice(), &tiles).unwrap();
+        let words: Vec<_> = lexicon.words().map(|w| lexicon.spell(&w)).collect();
+        assert_eq!(words, ["AÑO", "AÑOS", "LLAMA", "OCHO"]);
+        assert!(lexicon.gaddag().contains(&tiles.tokenize("LLAMA").unwrap()));
+    }
+
+    #[test]
+    fn rejects_bad_nodes() {
+        let tiles = TileMap::english();
+        let read = |nodes: &[u32]| {
+            let bytes: Vec<_> = nodes.iter().flat_map(|n| n.to_le_bytes()).collect();
+            Lexicon::from_kwg(bytes.as_slice(), &tiles)
+        };
+        assert_eq!(read(&[IS_END, IS_END]).unwrap().len(), 0);
+        assert!(read(&[IS_END]).is_err());
+        assert!(Lexicon::from_kwg([0u8; 9].as_slice(), &tiles).is_err());
+        // Points past the end.
+        assert!(read(&[2, IS_END, 3]).is_err());
+        // Loops back on itself.
+        assert!(read(&[2, IS_END, 2 | IS_END | 1 << 24]).is_err());
+        // Tile 0 is the separator, and English has 26 letters.
+        assert!(read(&[2, IS_END, IS_END | ACCEPTS]).is_err());
+        assert!(read(&[2, IS_END, IS_END | ACCEPTS | 27 << 24]).is_err());
+        assert_eq!(
+            read(&[2, IS_END, IS_END | ACCEPTS | 26 << 24])
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,460 @@
+//! Word lists and the structures to query them.
+
+use std::{collections::HashMap, io::BufRead};
+
+use static_assertions::assert_impl_all;
+
+use crate::{
+    error::{LexiconError, Result},
+    Rack, TileMap, TileMapIdx,
+};
+
+mod alphagram;
+mod binary;
+mod cursor;
+mod dawg;
+mod definitions;
+mod diff;
+mod gaddag;
+mod kwg;
+mod packed;
+mod pattern;
+mod quackle;
+mod set;
+mod stats;
+mod storage;
+mod trie;
+
+pub use alphagram::{alphagram, AlphagramCursor, AlphagramIndex};
+pub use cursor::LexiconCursor;
+pub(crate) use cursor::WordCursor;
+pub use dawg::{Dawg, DawgStats};
+pub use diff::LexiconDiff;
+pub use gaddag::Gaddag;
+pub use packed::PackedDawg;
+pub use pattern::{Pattern, PatternPart};
+pub use set::LexiconSet;
+pub use stats::{combinations, LexiconStats};
+pub use trie::Trie;
+
+/// A node of a [`Trie`], [`Dawg`] or [`Gaddag`], valid only for the structure it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+/// A word as tiles of the [`TileMap`] its [`Lexicon`] was built with.
+pub type Word = Vec<TileMapIdx>;
+
+/// The tiles that extend a word into another word, see [`Lexicon::hooks`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hooks {
+    /// Tiles that can go before the word, in tile order.
+    pub front: Vec<TileMapIdx>,
+    /// Tiles that can go after the word, in tile order.
+    pub back: Vec<TileMapIdx>,
+}
+
+/// The outcome of [judging](Lexicon::judge) the words formed by a play.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JudgeResult {
+    /// The words not in the lexicon, in the order given.
+    pub invalid: Vec<String>,
+}
+
+impl JudgeResult {
+    /// Whether every word is valid, i.e. a challenge against the play fails.
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+/// A word list split into tiles, stored as a [`Dawg`] for lookups and a [`Gaddag`] for move
+/// generation.
+///
+/// Immutable once built, with no interior mutability, so one lexicon can be shared between
+/// threads behind an [`Arc`](std::sync::Arc), e.g. by simulation workers, without copying it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexicon {
+    /// The letter of each tile of the tile map the words were split with.
+    letters: Vec<String>,
+    /// A tile map of just those letters, ignoring case if the original did, which
+    /// [`Lexicon::tokenize`] splits words with.
+    tokenizer: TileMap,
+    fingerprint: u64,
+    dawg: Dawg,
+    gaddag: Gaddag,
+    /// Words that couldn't be split into tiles.
+    skipped: Vec<String>,
+    /// See [`Lexicon::definition`].
+    definitions: HashMap<Word, String>,
+}
+
+// Sharing lexicons between threads is part of the API, so losing this should fail to compile.
+assert_impl_all!(Lexicon: Send, Sync);
+assert_impl_all!(Dawg: Send, Sync);
+assert_impl_all!(Gaddag: Send, Sync);
+assert_impl_all!(PackedDawg: Send, Sync);
+assert_impl_all!(LexiconSet: Send, Sync);
+assert_impl_all!(LexiconCursor<'static>: Send, Sync, Copy);
+
+impl Lexicon {
+    /// How many offending words [`Lexicon::validate_against`] lists before summarizing.
+    const MAX_REPORTED: usize = 10;
+
+    /// Splits every word into tiles. Words that can't be split are left out and listed in
+    /// [`Lexicon::skipped`].
+    ///
+    /// Words are tried as written, then uppercased, so lowercase word lists work with the built-in
+    /// tile maps.
+    pub fn from_words<S: AsRef<str>>(words: impl IntoIterator<Item = S>, tiles: &TileMap) -> Self {
+        let mut tokenized = Vec::new();
+        let mut skipped = Vec::new();
+        for word in words {
+            let word = word.as_ref();
+            match tiles
+                .tokenize(word)
+                .or_else(|_| tiles.tokenize(&word.to_uppercase()))
+            {
+                Ok(t) if !t.is_empty() => tokenized.push(t),
+                _ => skipped.push(word.to_owned()),
+            }
+        }
+        Self::from_tokenized(tokenized, skipped, tiles)
+    }
+
+    fn from_tokenized(tokenized: Vec<Word>, skipped: Vec<String>, tiles: &TileMap) -> Self {
+        let trie: Trie = tokenized.iter().collect();
+        let letters: Vec<_> = tiles.iter().map(|(_, l)| l.to_owned()).collect();
+        Self {
+            tokenizer: Self::tokenizer(&letters, tiles.is_case_insensitive())
+                .expect("The letters of a tile map make a tile map"),
+            letters,
+            fingerprint: tiles.fingerprint(),
+            dawg: Dawg::from_trie(&trie),
+            gaddag: Gaddag::from_words(&tokenized),
+            skipped,
+            definitions: HashMap::new(),
+        }
+    }
+
+    fn tokenizer(letters: &[String], case_insensitive: bool) -> Result<TileMap> {
+        Ok(TileMap::new(letters.to_vec())?.case_insensitive(case_insensitive))
+    }
+
+    /// Reads a plain-text word list such as TWL or SOWPODS: one word per line, blank lines ignored.
+    /// Anything after the first whitespace on a line, e.g. a definition, is ignored too.
+    pub fn from_word_list(reader: impl BufRead, tiles: &TileMap) -> Result<Self> {
+        let mut words = Vec::new();
+        for line in reader.lines() {
+            // `lines` strips "\n" and "\r\n", the rest is whitespace.
+            if let Some(word) = line?.split_whitespace().next() {
+                words.push(word.to_owned());
+            }
+        }
+        Ok(Self::from_words(words, tiles))
+    }
+
+    /// Words that were left out because they couldn't be split into tiles.
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    pub fn dawg(&self) -> &Dawg {
+        &self.dawg
+    }
+
+    pub fn gaddag(&self) -> &Gaddag {
+        &self.gaddag
+    }
+
+    /// The [fingerprint](TileMap::fingerprint) of the tile map the words were split with.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn contains(&self, word: &[TileMapIdx]) -> bool {
+        self.dawg.contains(word)
+    }
+
+    /// All words, in tile order.
+    pub fn words(&self) -> impl Iterator<Item = Word> + '_ {
+        self.dawg.words()
+    }
+
+    /// Writes out `word` with the letters of the tile map the lexicon was built with.
+    pub fn spell(&self, word: &[TileMapIdx]) -> String {
+        word.iter()
+            .map(|t| self.letters.get(t.as_usize()).map_or("?", String::as_str))
+            .collect()
+    }
+
+    /// Splits `word` into the tiles the lexicon was built with, like [`TileMap::tokenize`]. Like
+    /// [`Lexicon::from_words`], tries the word as written, then uppercased.
+    pub fn tokenize(&self, word: &str) -> Option<Word> {
+        let blank = self.blank();
+        self.tokenizer
+            .tokenize(word)
+            .or_else(|_| self.tokenizer.tokenize(&word.to_uppercase()))
+            .ok()
+            .filter(|t| !blank.is_some_and(|b| t.contains(&b)))
+    }
+
+    /// Checks all the words formed by a play at once, as a challenge does. Words that can't be
+    /// spelled with the lexicon's tiles are invalid.
+    pub fn judge(&self, words: &[&str]) -> JudgeResult {
+        self.judge_by(words, |t| self.contains(t))
+    }
+
+    /// Like [`Lexicon::judge`], but for [Clabbers](crate::rules::Variant::Clabbers): any order of
+    /// the tiles of a word is valid. `index` has to be built from this lexicon.
+    pub fn judge_clabbers(&self, words: &[&str], index: &AlphagramIndex) -> JudgeResult {
+        self.judge_by(words, |t| index.contains_anagram(t))
+    }
+
+    fn judge_by(&self, words: &[&str], is_word: impl Fn(&[TileMapIdx]) -> bool) -> JudgeResult {
+        JudgeResult {
+            invalid: words
+                .iter()
+                .filter(|w| !self.tokenize(w).is_some_and(|t| is_word(&t)))
+                .map(|w| w.to_string())
+                .collect(),
+        }
+    }
+
+    /// The number of tiles of the tile map the lexicon was built with, including the blank.
+    pub(crate) fn num_tiles(&self) -> usize {
+        self.letters.len()
+    }
+
+    /// The blank of the tile map the lexicon was built with.
+    pub(crate) fn blank(&self) -> Option<TileMapIdx> {
+        self.letters
+            .iter()
+            .position(|l| l == TileMap::BLANK)
+            .map(|idx| TileMapIdx(idx as u8))
+    }
+
+    /// Every word that can be made from some or all of the tiles on `rack`, with blanks standing
+    /// in for any letter. Words come in tile order.
+    pub fn anagrams(&self, rack: &Rack) -> Vec<Word> {
+        let mut counts = [0u8; 256];
+        let mut blanks = 0;
+        for t in rack.tiles() {
+            if Some(*t) == self.blank() {
+                blanks += 1;
+            } else {
+                counts[t.as_usize()] += 1;
+            }
+        }
+        let mut words = Vec::new();
+        let mut path = Vec::new();
+        self.collect_anagrams(self.dawg.root(), &mut counts, blanks, &mut path, &mut words);
+        words
+    }
+
+    fn collect_anagrams(
+        &self,
+        node: NodeId,
+        counts: &mut [u8; 256],
+        blanks: u8,
+        path: &mut Word,
+        words: &mut Vec<Word>,
+    ) {
+        for (tile, child) in self.dawg.children(node) {
+            // A real tile leaves the blank free for later, so it's never worse to use one.
+            let real = counts[tile.as_usize()] > 0;
+            let blanks_left = match (real, blanks) {
+                (true, _) => blanks,
+                (false, 0) => continue,
+                (false, _) => blanks - 1,
+            };
+            if real {
+                counts[tile.as_usize()] -= 1;
+            }
+            path.push(tile);
+            if self.dawg.is_word(child) {
+                words.push(path.clone());
+            }
+            self.collect_anagrams(child, counts, blanks_left, path, words);
+            path.pop();
+            if real {
+                counts[tile.as_usize()] += 1;
+            }
+        }
+    }
+
+    /// The tiles that make another word when put directly before or after `word`. `word` itself
+    /// doesn't have to be a word.
+    pub fn hooks(&self, word: &[TileMapIdx]) -> Hooks {
+        // The GADDAG path of a whole word is the word reversed, without a separator.
+        let reversed = word
+            .iter()
+            .rev()
+            .try_fold(self.gaddag.root(), |node, tile| {
+                self.gaddag.child(node, *tile)
+            });
+        let front = reversed.map_or_else(Vec::new, |node| {
+            self.gaddag
+                .children(node)
+                .filter(|(t, child)| *t != Gaddag::SEPARATOR && self.gaddag.is_word(*child))
+                .map(|(t, _)| t)
+                .collect()
+        });
+        let back = self.dawg.walk(word).map_or_else(Vec::new, |node| {
+            self.dawg
+                .children(node)
+                .filter(|(_, child)| self.dawg.is_word(*child))
+                .map(|(t, _)| t)
+                .collect()
+        });
+        Hooks { front, back }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dawg.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dawg.is_empty()
+    }
+
+    /// The words that `tiles` would split into different tiles than the lexicon did, or can't
+    /// split at all.
+    pub fn invalid_words(&self, tiles: &TileMap) -> Vec<String> {
+        if tiles.fingerprint() == self.fingerprint {
+            return Vec::new();
+        }
+        self.words()
+            .filter(|w| tiles.tokenize(&self.spell(w)).ok().as_ref() != Some(w))
+            .map(|w| self.spell(&w))
+            .collect()
+    }
+
+    /// Checks that every word means the same with `tiles`, e.g. to catch a Spanish word list being
+    /// used with English tiles.
+    pub fn validate_against(&self, tiles: &TileMap) -> Result<()> {
+        let invalid = self.invalid_words(tiles);
+        if invalid.is_empty() {
+            return Ok(());
+        }
+        let mut listed = invalid[..invalid.len().min(Self::MAX_REPORTED)].join(", ");
+        if invalid.len() > Self::MAX_REPORTED {
+            listed += &format!(" and {} more", invalid.len() - Self::MAX_REPORTED);
+        }
+        Err(LexiconError::Unspelled {
+            count: invalid.len(),
+            total: self.len(),
+            listed,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn validate() {
+        let english = TileMap::english();
+        let spanish = TileMap::spanish();
+        let lexicon = Lexicon::from_words(["CHURRO", "AÑO", "LLAMA"], &spanish);
+        assert!(lexicon.validate_against(&spanish).is_ok());
+        assert!(lexicon.contains(&spanish.tokenize("CHURRO").unwrap()));
+        // English has no CH, LL or Ñ tiles.
+        assert_eq!(lexicon.invalid_words(&english), ["AÑO", "CHURRO", "LLAMA"]);
+        assert_eq!(
+            lexicon.validate_against(&english).unwrap_err().to_string(),
+            "3 of 3 words can't be spelled with the tile map: AÑO, CHURRO, LLAMA"
+        );
+
+        let digits = TileMap::new((0..12).map(|i| i.to_string()).collect()).unwrap();
+        let many = Lexicon::from_words((0..12).map(|i| i.to_string()), &digits);
+        assert_eq!(
+            many.validate_against(&english).unwrap_err().to_string(),
+            "12 of 12 words can't be spelled with the tile map: \
+             0, 1, 2, 3, 4, 5, 6, 7, 8, 9 and 2 more"
+        );
+    }
+
+    #[test]
+    fn word_list() {
+        let tiles = TileMap::english();
+        let list = "aa\r\nAAH  to exclaim\n\nAB1\r\n  zyzzyva\nAA\n";
+        let lexicon = Lexicon::from_word_list(list.as_bytes(), &tiles).unwrap();
+        assert_eq!(lexicon.len(), 3);
+        assert_eq!(lexicon.skipped(), ["AB1"]);
+        let words: Vec<_> = lexicon.words().map(|w| lexicon.spell(&w)).collect();
+        assert_eq!(words, ["AA", "AAH", "ZYZZYVA"]);
+        assert!(lexicon.gaddag().contains(&tiles.tokenize("AAH").unwrap()));
+    }
+
+    #[test]
+    fn judge() {
+        let spanish = TileMap::spanish();
+        let lexicon = Lexicon::from_words(["CHURRO", "CHURROS", "AÑO"], &spanish);
+        assert!(lexicon.judge(&["CHURRO", "churros", "AÑO"]).is_valid());
+        assert_eq!(
+            lexicon.judge(&["CHURRO", "CHURR", "ANO", "W", ""]).invalid,
+            ["CHURR", "ANO", "W", ""]
+        );
+        assert_eq!(
+            lexicon.tokenize("chURRo"),
+            Some(spanish.tokenize("CHURRO").unwrap())
+        );
+        assert_eq!(lexicon.tokenize("?"), None);
+    }
+
+    #[test]
+    fn tokenize_backtracks() {
+        // The longest tile first, AB, leaves a C no tile matches.
+        let tiles = TileMap::new(["A", "AB", "BC"].map(String::from).to_vec()).unwrap();
+        let lexicon = Lexicon::from_words(["ABC"], &tiles);
+        assert_eq!(
+            lexicon.tokenize("ABC"),
+            Some(tiles.tokenize("ABC").unwrap())
+        );
+        assert!(lexicon.judge(&["ABC"]).is_valid());
+    }
+
+    #[test]
+    fn hooks() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            ["CARE", "SCARE", "CARES", "CARED", "CARET", "ACARE", "ARE"],
+            &tiles,
+        );
+        let word = |w| tiles.tokenize(w).unwrap();
+        let hooks = lexicon.hooks(&word("CARE"));
+        assert_eq!(hooks.front, word("AS"));
+        assert_eq!(hooks.back, word("DST"));
+        assert_eq!(lexicon.hooks(&word("AR")).back, word("E"));
+        assert_eq!(lexicon.hooks(&word("RE")).front, word("A"));
+        assert_eq!(lexicon.hooks(&word("XYZ")), Hooks::default());
+    }
+
+    #[test]
+    fn anagrams() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "AT", "TA", "ACT", "CAT", "TACT", "SCAT", "CATS", "CASTS", "ZA",
+            ],
+            &tiles,
+        );
+        let anagrams = |rack| -> Vec<String> {
+            lexicon
+                .anagrams(&Rack::parse(rack, &tiles).unwrap())
+                .iter()
+                .map(|w| lexicon.spell(w))
+                .collect()
+        };
+        assert_eq!(anagrams("TCA"), ["ACT", "AT", "CAT", "TA"]);
+        assert_eq!(
+            anagrams("TCA?"),
+            ["ACT", "AT", "CAT", "CATS", "SCAT", "TA", "TACT", "ZA"]
+        );
+        assert_eq!(anagrams("??"), ["AT", "TA", "ZA"]);
+        assert_eq!(anagrams("Q"), Vec::<String>::new());
+    }
+}
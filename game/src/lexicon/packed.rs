@@ -0,0 +1,213 @@
+use crate::{
+    error::{LexiconError, Result},
+    TileMapIdx,
+};
+
+use super::{Dawg, Gaddag, LexiconCursor, NodeId};
+
+/// A [`Dawg`] or [`Gaddag`] packed into one `u32` per edge, about half the size, so more of a
+/// large lexicon stays in cache during move generation.
+///
+/// Each edge holds the tile in the low 8 bits, [`PackedDawg::TERMINAL`] if following it completes
+/// a word, [`PackedDawg::END`] on the last edge of a node, and the index of the child's first edge
+/// in the top 22 bits, 0 if the child has none. Edge 0 stands in for an edge into the root.
+///
+/// A node is identified by the edge leading into it, so finding a child scans the node's edges
+/// instead of binary searching them; nodes rarely have more than a handful.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedDawg {
+    edges: Vec<u32>,
+    words: usize,
+}
+
+impl PackedDawg {
+    pub const TERMINAL: u32 = 1 << 8;
+    pub const END: u32 = 1 << 9;
+    const INDEX_SHIFT: u32 = 10;
+    /// Edge indices have to fit into 22 bits.
+    pub const MAX_EDGES: usize = 1 << (32 - Self::INDEX_SHIFT);
+
+    /// Packs `dawg`, failing if it has too many edges for 22-bit indices.
+    pub fn from_dawg(dawg: &Dawg) -> Result<Self> {
+        // The first edge of each node with any, after the root's stand-in.
+        let mut starts = vec![0u32; dawg.node_count()];
+        let mut len = 1;
+        for (node, start) in starts.iter_mut().enumerate() {
+            let edges = dawg.children(NodeId(node as u32)).count();
+            if edges > 0 {
+                *start = len as u32;
+                len += edges;
+            }
+        }
+        if len > Self::MAX_EDGES {
+            return Err(LexiconError::TooManyEdges {
+                len,
+                max: Self::MAX_EDGES,
+            }
+            .into());
+        }
+
+        let pack = |tile: TileMapIdx, child: NodeId| {
+            let mut edge = u32::from(tile.as_u8()) | starts[child.0 as usize] << Self::INDEX_SHIFT;
+            if dawg.is_word(child) {
+                edge |= Self::TERMINAL;
+            }
+            edge
+        };
+        let mut edges = vec![0; len];
+        edges[0] = pack(TileMapIdx(0), dawg.root()) | Self::END;
+        for node in 0..dawg.node_count() {
+            let node = NodeId(node as u32);
+            let start = starts[node.0 as usize] as usize;
+            for (i, (tile, child)) in dawg.children(node).enumerate() {
+                edges[start + i] = pack(tile, child);
+            }
+            let count = dawg.children(node).count();
+            if count > 0 {
+                edges[start + count - 1] |= Self::END;
+            }
+        }
+        Ok(Self {
+            edges,
+            words: dawg.len(),
+        })
+    }
+
+    fn first_child(&self, node: NodeId) -> usize {
+        (self.edges[node.0 as usize] >> Self::INDEX_SHIFT) as usize
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn child(&self, node: NodeId, tile: TileMapIdx) -> Option<NodeId> {
+        self.children(node)
+            .take_while(|(t, _)| *t <= tile)
+            .find(|(t, _)| *t == tile)
+            .map(|(_, child)| child)
+    }
+
+    /// The tiles that can follow `node`, in tile order.
+    pub fn children(&self, node: NodeId) -> impl Iterator<Item = (TileMapIdx, NodeId)> + '_ {
+        let first = self.first_child(node);
+        let mut next = (first != 0).then_some(first);
+        std::iter::from_fn(move || {
+            let i = next?;
+            let edge = self.edges[i];
+            next = (edge & Self::END == 0).then_some(i + 1);
+            Some((TileMapIdx(edge as u8), NodeId(i as u32)))
+        })
+    }
+
+    /// Whether the path to `node` spells a word.
+    pub fn is_word(&self, node: NodeId) -> bool {
+        self.edges[node.0 as usize] & Self::TERMINAL != 0
+    }
+
+    /// The node reached by following `tiles` from the root.
+    pub fn walk(&self, tiles: &[TileMapIdx]) -> Option<NodeId> {
+        tiles
+            .iter()
+            .try_fold(self.root(), |node, tile| self.child(node, *tile))
+    }
+
+    /// Whether the path `word` spells a word. For a packed [`Gaddag`], see
+    /// [`Gaddag::contains`] for how words are laid out.
+    pub fn contains(&self, word: &[TileMapIdx]) -> bool {
+        self.walk(word).is_some_and(|node| self.is_word(node))
+    }
+
+    /// The number of words.
+    pub fn len(&self) -> usize {
+        self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words == 0
+    }
+
+    /// Edges, including the root's stand-in.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// The size of the encoding in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.edges.len() * std::mem::size_of::<u32>()
+    }
+
+    /// A cursor at the root.
+    pub fn cursor(&self) -> LexiconCursor<'_> {
+        LexiconCursor::packed(self)
+    }
+}
+
+impl Dawg {
+    /// See [`PackedDawg`].
+    pub fn packed(&self) -> Result<PackedDawg> {
+        PackedDawg::from_dawg(self)
+    }
+}
+
+impl Gaddag {
+    /// See [`PackedDawg`]. Paths are the same as in the GADDAG, including the
+    /// [separator](Gaddag::SEPARATOR).
+    pub fn packed(&self) -> Result<PackedDawg> {
+        let mut packed = PackedDawg::from_dawg(self.paths())?;
+        packed.words = self.len();
+        Ok(packed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexicon::Lexicon, TileMap};
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    #[test]
+    fn same_words_in_less_space() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "CAR", "BATS", "BAT"], &tiles);
+        let dawg = lexicon.dawg().packed().unwrap();
+        assert_eq!(dawg.len(), 5);
+        assert_eq!(dawg.edge_count(), lexicon.dawg().edge_count() + 1);
+        assert!(dawg.contains(&word("CATS")));
+        assert!(!dawg.contains(&word("CA")));
+        assert!(!dawg.contains(&word("BATSS")));
+        let ca = dawg.walk(&word("CA")).unwrap();
+        let next: Vec<_> = dawg
+            .children(ca)
+            .map(|(t, n)| (t, dawg.is_word(n)))
+            .collect();
+        assert_eq!(next, [(word("R")[0], true), (word("T")[0], true)]);
+
+        let gaddag = lexicon.gaddag().packed().unwrap();
+        let cursor = gaddag.cursor().walk(&word("AC")).unwrap();
+        let cats = cursor.separator().unwrap().walk(&word("TS")).unwrap();
+        assert!(cats.is_terminal());
+        assert!(gaddag.contains(&word("STAC")));
+    }
+
+    fn arb_words() -> impl Strategy<Value = Vec<Vec<TileMapIdx>>> {
+        prop::collection::vec(
+            prop::collection::vec((0u8..5).prop_map(TileMapIdx), 0..6),
+            0..40,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn matches_dawg(words in arb_words(), probes in arb_words()) {
+            let dawg: Dawg = words.iter().collect();
+            let packed = dawg.packed().unwrap();
+            for w in words.iter().chain(&probes) {
+                prop_assert_eq!(packed.contains(w), dawg.contains(w));
+                prop_assert_eq!(packed.walk(w).is_some(), dawg.walk(w).is_some());
+            }
+        }
+    }
+}
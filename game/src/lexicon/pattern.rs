@@ -0,0 +1,232 @@
+use crate::{
+    error::{Result, TileError},
+    TileMap, TileMapIdx,
+};
+
+use super::{Lexicon, NodeId, Word};
+
+/// One position of a [`Pattern`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternPart {
+    Tile(TileMapIdx),
+    /// Exactly one tile, whichever.
+    Any,
+    /// Any number of tiles, including none.
+    AnyRun,
+}
+
+/// A word shape to search the lexicon for, e.g. "A..LE" for five-letter words starting with A and
+/// ending in LE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    parts: Vec<PatternPart>,
+}
+
+impl Pattern {
+    pub fn new(parts: Vec<PatternPart>) -> Self {
+        Self { parts }
+    }
+
+    /// Words of length `len` with the given tiles at the given positions, e.g. what fits a slot on
+    /// the board. Positions past `len` are ignored.
+    pub fn with_fixed(len: usize, fixed: &[(usize, TileMapIdx)]) -> Self {
+        let mut parts = vec![PatternPart::Any; len];
+        for (pos, tile) in fixed {
+            if let Some(part) = parts.get_mut(*pos) {
+                *part = PatternPart::Tile(*tile);
+            }
+        }
+        Self { parts }
+    }
+
+    pub fn parts(&self) -> &[PatternPart] {
+        &self.parts
+    }
+}
+
+impl Lexicon {
+    /// Parses a pattern: `.` or `?` matches any one tile, `*` any number of tiles, anything else
+    /// is a letter. Tiles of more than one character can be wrapped in brackets, e.g. "[CH]".
+    /// Lowercase letters are read as uppercase if the tile map has no lowercase letters.
+    pub fn pattern(&self, pattern: &str) -> Result<Pattern> {
+        let mut parts = Vec::new();
+        let mut rest = pattern;
+        while let Some(c) = rest.chars().next() {
+            let (part, len) = match c {
+                '.' | '?' => (PatternPart::Any, 1),
+                '*' => (PatternPart::AnyRun, 1),
+                '[' => {
+                    let Some(end) = rest.find(']') else {
+                        return Err(TileError::UnclosedBracket(pattern.to_owned()).into());
+                    };
+                    let Some((tile, _)) = self.letter_prefix(&rest[1..end], true) else {
+                        return Err(TileError::UnknownTile {
+                            at: rest[..=end].to_owned(),
+                            input: pattern.to_owned(),
+                        }
+                        .into());
+                    };
+                    (PatternPart::Tile(tile), end + 1)
+                }
+                _ => {
+                    let Some((tile, len)) = self.letter_prefix(rest, false) else {
+                        return Err(TileError::UnknownTile {
+                            at: rest.to_owned(),
+                            input: pattern.to_owned(),
+                        }
+                        .into());
+                    };
+                    (PatternPart::Tile(tile), len)
+                }
+            };
+            parts.push(part);
+            rest = &rest[len..];
+        }
+        Ok(Pattern::new(parts))
+    }
+
+    /// The longest letter at the start of `s`, trying uppercase if nothing matches as written.
+    /// With `whole`, the letter has to be all of `s`.
+    fn letter_prefix(&self, s: &str, whole: bool) -> Option<(TileMapIdx, usize)> {
+        let find = |s: &str| {
+            self.letters
+                .iter()
+                .enumerate()
+                .filter(|(_, l)| !l.is_empty() && *l != TileMap::BLANK)
+                .filter(|(_, l)| if whole { s == *l } else { s.starts_with(*l) })
+                .max_by_key(|(_, l)| l.len())
+                .map(|(idx, l)| (TileMapIdx(idx as u8), l.len()))
+        };
+        find(s).or_else(|| {
+            let c = s.chars().next()?;
+            let upper = if whole {
+                s.to_uppercase()
+            } else {
+                c.to_uppercase().collect()
+            };
+            // Uppercasing can change the length, so only single characters are matched this way.
+            find(&upper)
+                .filter(|(_, len)| whole || *len == upper.len())
+                .map(|(idx, _)| (idx, if whole { s.len() } else { c.len_utf8() }))
+        })
+    }
+
+    /// Every word matching `pattern`, see [`Lexicon::pattern`], in tile order.
+    pub fn matches(&self, pattern: &str) -> Result<Vec<Word>> {
+        Ok(self.matching(&self.pattern(pattern)?))
+    }
+
+    /// Every word matching `pattern`, in tile order.
+    pub fn matching(&self, pattern: &Pattern) -> Vec<Word> {
+        let mut words = Vec::new();
+        let mut path = Vec::new();
+        self.collect_matches(self.dawg.root(), &pattern.parts, &mut path, &mut words);
+        // Several runs can match the same word in different ways.
+        words.sort();
+        words.dedup();
+        words
+    }
+
+    fn collect_matches(
+        &self,
+        node: NodeId,
+        parts: &[PatternPart],
+        path: &mut Word,
+        words: &mut Vec<Word>,
+    ) {
+        let Some((part, rest)) = parts.split_first() else {
+            if self.dawg.is_word(node) && !path.is_empty() {
+                words.push(path.clone());
+            }
+            return;
+        };
+        match part {
+            PatternPart::Tile(tile) => {
+                if let Some(child) = self.dawg.child(node, *tile) {
+                    path.push(*tile);
+                    self.collect_matches(child, rest, path, words);
+                    path.pop();
+                }
+            }
+            PatternPart::Any | PatternPart::AnyRun => {
+                if *part == PatternPart::AnyRun {
+                    self.collect_matches(node, rest, path, words);
+                }
+                let next = if *part == PatternPart::AnyRun {
+                    parts
+                } else {
+                    rest
+                };
+                for (tile, child) in self.dawg.children(node) {
+                    path.push(tile);
+                    self.collect_matches(child, next, path, words);
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn matches(lexicon: &Lexicon, pattern: &str) -> Vec<String> {
+        lexicon
+            .matches(pattern)
+            .unwrap()
+            .iter()
+            .map(|w| lexicon.spell(w))
+            .collect()
+    }
+
+    #[test]
+    fn wildcards() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "AISLE", "ANGLE", "AMPLE", "APPLE", "ABLE", "TABLE", "APPLES",
+            ],
+            &tiles,
+        );
+        assert_eq!(
+            matches(&lexicon, "A..LE"),
+            ["AISLE", "AMPLE", "ANGLE", "APPLE"]
+        );
+        assert_eq!(matches(&lexicon, "a?PLe"), ["AMPLE", "APPLE"]);
+        assert_eq!(matches(&lexicon, "*BLE"), ["ABLE", "TABLE"]);
+        assert_eq!(
+            matches(&lexicon, "A*LE*"),
+            lexicon
+                .matches("A*")
+                .unwrap()
+                .iter()
+                .map(|w| lexicon.spell(w))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(matches(&lexicon, "*P*P*"), ["APPLE", "APPLES"]);
+        assert_eq!(matches(&lexicon, "*"), matches(&lexicon, "**"));
+        assert!(matches(&lexicon, "").is_empty());
+        assert!(lexicon.matches("A1").is_err());
+        assert!(lexicon.matches("[A").is_err());
+    }
+
+    #[test]
+    fn fixed_positions() {
+        let tiles = TileMap::spanish();
+        let lexicon = Lexicon::from_words(["CHICO", "CHINO", "CHOCO", "COCHE", "CHINOS"], &tiles);
+        let idx = |l| tiles.find(l).unwrap();
+        let pattern = Pattern::with_fixed(4, &[(0, idx("CH")), (2, idx("N")), (9, idx("A"))]);
+        let found: Vec<_> = lexicon
+            .matching(&pattern)
+            .iter()
+            .map(|w| lexicon.spell(w))
+            .collect();
+        assert_eq!(found, ["CHINO"]);
+        assert_eq!(matches(&lexicon, "[CH]..O"), ["CHICO", "CHINO", "CHOCO"]);
+        assert_eq!(matches(&lexicon, "CH..O"), ["CHICO", "CHINO", "CHOCO"]);
+        assert_eq!(matches(&lexicon, "..[CH]."), ["COCHE"]);
+        assert!(lexicon.matches("[W]").is_err());
+    }
+}
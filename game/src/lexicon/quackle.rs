@@ -0,0 +1,249 @@
+//! Quackle's `.dawg` lexicon files, so lexicons built for Quackle can be reused.
+//!
+//! The graph is a list of 7-byte entries, one per edge:
+//!
+//! - bytes 0-2: the big-endian index of the entry starting the edge's children, 0 if there are none
+//! - byte 3: the letter in the low 5 bits, then flags: 32 if the path so far spells a word, 64 if
+//!   the entry is the last of its siblings, 128 if the word is also in the smaller lexicon Quackle
+//!   pairs with some word lists, which is ignored
+//! - bytes 4-6: a playability score, also ignored
+//!
+//! Entry 0 only points to the first letters of words. Version 0 files are just the entries, with
+//! letters counting from A. Version 1 files start with a `1` byte, a 16-byte hash of the word
+//! list, the word count as 3 big-endian bytes and the alphabet: a count byte, then each letter as
+//! a length byte and UTF-8. Letters count from the start of that alphabet.
+
+use std::{collections::BTreeSet, fs::File, io::Read, path::Path};
+
+use crate::{
+    error::{Context, LexiconError, Result},
+    TileMap, TileMapIdx,
+};
+
+use super::{Lexicon, Word};
+
+const ENTRY_LEN: usize = 7;
+const LETTER_MASK: u8 = 0b1_1111;
+const TERMINAL: u8 = 32;
+const LAST_CHILD: u8 = 64;
+/// Longer words mean the entries loop back on themselves.
+const MAX_WORD_LEN: usize = u8::MAX as usize;
+const TRUNCATED: LexiconError = LexiconError::Truncated("Quackle lexicon");
+
+impl Lexicon {
+    /// Reads a Quackle `.dawg` lexicon, mapping its letters to `tiles`. Fails, listing the
+    /// letters, if some word uses a letter `tiles` doesn't have.
+    pub fn from_quackle(mut reader: impl Read, tiles: &TileMap) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let (alphabet, entries) = match data.first() {
+            Some(0) => (('A'..='Z').map(String::from).collect(), data.as_slice()),
+            Some(1) => read_header(&data[1..])?,
+            Some(version) => return Err(LexiconError::QuackleVersion(*version).into()),
+            None => return Err(TRUNCATED.into()),
+        };
+        if entries.len() < ENTRY_LEN || !entries.len().is_multiple_of(ENTRY_LEN) {
+            return Err(TRUNCATED.into());
+        }
+
+        let mut walk = Walk {
+            entries,
+            letters: alphabet
+                .iter()
+                .map(|l| tiles.find(l).or_else(|| tiles.find(&l.to_uppercase())))
+                .collect(),
+            words: Vec::new(),
+            unknown: BTreeSet::new(),
+        };
+        let first = pointer(&entries[..ENTRY_LEN]);
+        if first != 0 {
+            walk.siblings(first, &mut Vec::new())?;
+        }
+        if !walk.unknown.is_empty() {
+            let letters = walk.unknown.iter().map(|c| alphabet[*c].clone()).collect();
+            return Err(LexiconError::MissingLetters(letters).into());
+        }
+        Ok(Self::from_tokenized(walk.words, Vec::new(), tiles))
+    }
+
+    pub fn from_quackle_path(path: impl AsRef<Path>, tiles: &TileMap) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        Self::from_quackle(file, tiles).with_context(|| format!("Reading {path:?}"))
+    }
+}
+
+/// Splits a version 1 file after the version byte into its alphabet and entries.
+fn read_header(data: &[u8]) -> Result<(Vec<String>, &[u8])> {
+    // The hash and word count aren't needed.
+    let rest = data.get(16 + 3..).ok_or(TRUNCATED)?;
+    let (&count, mut rest) = rest.split_first().ok_or(TRUNCATED)?;
+    let mut alphabet = Vec::with_capacity(count.into());
+    for _ in 0..count {
+        let (&len, r) = rest.split_first().ok_or(TRUNCATED)?;
+        let letter = r.get(..len.into()).ok_or(TRUNCATED)?;
+        let Ok(letter) = std::str::from_utf8(letter) else {
+            return Err(LexiconError::Corrupt("Quackle lexicon letter isn't UTF-8".into()).into());
+        };
+        alphabet.push(letter.to_owned());
+        rest = &r[len.into()..];
+    }
+    Ok((alphabet, rest))
+}
+
+fn pointer(entry: &[u8]) -> usize {
+    usize::from(entry[0]) << 16 | usize::from(entry[1]) << 8 | usize::from(entry[2])
+}
+
+struct Walk<'a> {
+    entries: &'a [u8],
+    /// The tile of each letter of the file's alphabet, if the tile map has it.
+    letters: Vec<Option<TileMapIdx>>,
+    words: Vec<Word>,
+    /// Alphabet positions of letters used by words but missing from the tile map.
+    unknown: BTreeSet<usize>,
+}
+
+impl Walk<'_> {
+    /// Collects the words below the sibling list starting at entry `first`, `path` holding the
+    /// alphabet positions of the letters so far.
+    fn siblings(&mut self, first: usize, path: &mut Vec<usize>) -> Result<()> {
+        let corrupt = |message: String| Err(LexiconError::Corrupt(message).into());
+        if path.len() >= MAX_WORD_LEN {
+            return corrupt(format!(
+                "Quackle lexicon has words longer than {MAX_WORD_LEN} letters"
+            ));
+        }
+        for i in first.. {
+            let Some(entry) = self.entries.get(i * ENTRY_LEN..(i + 1) * ENTRY_LEN) else {
+                return corrupt(format!(
+                    "Quackle lexicon entry {i} is past the end of the file"
+                ));
+            };
+            let code = usize::from(entry[3] & LETTER_MASK);
+            if code >= self.letters.len() {
+                return corrupt(format!(
+                    "Quackle lexicon letter {code} is outside its alphabet"
+                ));
+            }
+            path.push(code);
+            if entry[3] & TERMINAL != 0 {
+                match path.iter().map(|c| self.letters[*c]).collect() {
+                    Some(word) => self.words.push(word),
+                    None => self
+                        .unknown
+                        .extend(path.iter().filter(|c| self.letters[**c].is_none())),
+                }
+            }
+            let children = pointer(entry);
+            if children != 0 {
+                self.siblings(children, path)?;
+            }
+            path.pop();
+            if entry[3] & LAST_CHILD != 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::ScrubbleError,
+        lexicon::{NodeId, Trie},
+    };
+    use pretty_assertions::assert_eq;
+
+    /// Lays out `trie` as Quackle entries, with letters numbered by tile.
+    fn entries(trie: &Trie) -> Vec<u8> {
+        fn siblings(trie: &Trie, node: NodeId, out: &mut Vec<[u8; 7]>) -> usize {
+            let children: Vec<_> = trie.children(node).collect();
+            if children.is_empty() {
+                return 0;
+            }
+            let first = out.len();
+            out.extend(children.iter().map(|_| [0; 7]));
+            for (i, (tile, child)) in children.iter().enumerate() {
+                let below = siblings(trie, *child, out);
+                let mut flags = tile.as_u8();
+                if trie.is_word(*child) {
+                    flags |= TERMINAL;
+                }
+                if i == children.len() - 1 {
+                    flags |= LAST_CHILD;
+                }
+                let [_, a, b, c] = (below as u32).to_be_bytes();
+                out[first + i] = [a, b, c, flags, 0, 0, 1];
+            }
+            first
+        }
+        let mut out = vec![[0; 7]];
+        let first = siblings(trie, trie.root(), &mut out);
+        let [_, a, b, c] = (first as u32).to_be_bytes();
+        out[0] = [a, b, c, 0, 0, 0, 0];
+        out.concat()
+    }
+
+    fn words(lexicon: &Lexicon) -> Vec<String> {
+        lexicon.words().map(|w| lexicon.spell(&w)).collect()
+    }
+
+    #[test]
+    fn version_0() {
+        let tiles = TileMap::english();
+        let trie: Trie = ["CAT", "CATS", "DOG", "A"]
+            .into_iter()
+            .map(|w| tiles.tokenize(w).unwrap())
+            .collect();
+        let lexicon = Lexicon::from_quackle(entries(&trie).as_slice(), &tiles).unwrap();
+        assert_eq!(words(&lexicon), ["A", "CAT", "CATS", "DOG"]);
+        assert!(lexicon.gaddag().contains(&tiles.tokenize("CATS").unwrap()));
+    }
+
+    #[test]
+    fn version_1() {
+        let spanish = TileMap::spanish();
+        let alphabet = ["A", "ch", "O", "Ñ", "S"];
+        let code = |l: &str| TileMapIdx(alphabet.iter().position(|a| *a == l).unwrap() as u8);
+        let trie: Trie = [vec!["O", "ch", "O"], vec!["A", "Ñ", "O", "S"]]
+            .into_iter()
+            .map(|w| w.into_iter().map(code).collect::<Vec<_>>())
+            .collect();
+        let mut file = vec![1];
+        file.extend([0xab; 16]);
+        file.extend([0, 0, 2, alphabet.len() as u8]);
+        for letter in alphabet {
+            file.push(letter.len() as u8);
+            file.extend(letter.as_bytes());
+        }
+        file.extend(entries(&trie));
+        let lexicon = Lexicon::from_quackle(file.as_slice(), &spanish).unwrap();
+        assert_eq!(words(&lexicon), ["AÑOS", "OCHO"]);
+
+        let err = Lexicon::from_quackle(file.as_slice(), &TileMap::english()).unwrap_err();
+        assert!(matches!(
+            err,
+            ScrubbleError::Lexicon(LexiconError::MissingLetters(letters)) if letters == ["ch", "Ñ"]
+        ));
+        assert!(Lexicon::from_quackle(&file[..file.len() - 3], &spanish).is_err());
+        assert!(Lexicon::from_quackle(&file[..20], &spanish).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_entries() {
+        let tiles = TileMap::english();
+        let read = |entries: &[[u8; 7]]| Lexicon::from_quackle(entries.concat().as_slice(), &tiles);
+        assert!(read(&[]).is_err());
+        assert_eq!(read(&[[0; 7]]).unwrap().len(), 0);
+        // Points past the end.
+        assert!(read(&[[0, 0, 1, 0, 0, 0, 0], [0, 0, 2, 0, 0, 0, 0]]).is_err());
+        // Loops back on itself.
+        assert!(read(&[[0, 0, 1, 0, 0, 0, 0], [0, 0, 1, LAST_CHILD, 0, 0, 0]]).is_err());
+        // No letter 31 in A-Z.
+        assert!(read(&[[0, 0, 1, 0, 0, 0, 0], [0, 0, 0, 31 | LAST_CHILD, 0, 0, 0]]).is_err());
+        assert!(read(&[[2, 0, 1, 0, 0, 0, 0]]).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use crate::{
+    error::{Context, Result},
+    TileMap, TileMapIdx,
+};
+
+use super::Lexicon;
+
+/// The lexicons of a game: one to judge whether plays are valid, one for bots to find their own
+/// plays with, e.g. adjudicating with CSW while the bot plays NWL, or a bot that only knows
+/// common words.
+///
+/// Both are usually the same lexicon, which is then shared rather than copied. Cloning a set is
+/// cheap.
+#[derive(Debug, Clone)]
+pub struct LexiconSet {
+    judge: Arc<Lexicon>,
+    generation: Arc<Lexicon>,
+}
+
+impl LexiconSet {
+    /// Judges and generates with the same lexicon.
+    pub fn new(lexicon: Lexicon) -> Self {
+        let lexicon = Arc::new(lexicon);
+        Self {
+            judge: lexicon.clone(),
+            generation: lexicon,
+        }
+    }
+
+    /// Judges with `judge`, but generates from `generation`.
+    pub fn split(judge: Lexicon, generation: Lexicon) -> Self {
+        Self {
+            judge: Arc::new(judge),
+            generation: Arc::new(generation),
+        }
+    }
+
+    /// The lexicon plays are checked against.
+    pub fn judge(&self) -> &Lexicon {
+        &self.judge
+    }
+
+    /// The lexicon bots find their plays in.
+    pub fn generation(&self) -> &Lexicon {
+        &self.generation
+    }
+
+    /// Whether judging and generation use the same lexicon.
+    pub fn is_shared(&self) -> bool {
+        Arc::ptr_eq(&self.judge, &self.generation)
+    }
+
+    /// Whether the judge accepts `word`.
+    pub fn is_valid(&self, word: &[TileMapIdx]) -> bool {
+        self.judge.contains(word)
+    }
+
+    /// Checks both lexicons, see [`Lexicon::validate_against`].
+    pub fn validate_against(&self, tiles: &TileMap) -> Result<()> {
+        self.judge
+            .validate_against(tiles)
+            .context("Judging lexicon")?;
+        if !self.is_shared() {
+            self.generation
+                .validate_against(tiles)
+                .context("Generation lexicon")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Lexicon> for LexiconSet {
+    fn from(lexicon: Lexicon) -> Self {
+        Self::new(lexicon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, Game};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn judge_and_generate_separately() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let full = Lexicon::from_words(["QI", "ZA", "CAT", "XU"], &tiles);
+        let common = Lexicon::from_words(["CAT"], &tiles);
+
+        let set = LexiconSet::split(full.clone(), common.clone());
+        assert!(!set.is_shared());
+        assert!(set.is_valid(&word("QI")));
+        assert!(!set.generation().contains(&word("QI")));
+        assert_eq!(set.generation(), &common);
+        assert!(set.validate_against(&tiles).is_ok());
+
+        let shared = LexiconSet::from(full);
+        assert!(shared.is_shared());
+        assert_eq!(shared.judge(), shared.generation());
+
+        let game = Game::<BitboardImpl>::new(tiles.clone())
+            .with_lexicons(set)
+            .unwrap();
+        assert!(game.lexicons().unwrap().is_valid(&word("XU")));
+        assert_eq!(game.judge(&["QI", "QIS"]).unwrap().invalid, ["QIS"]);
+        let bare = Game::<BitboardImpl>::new(tiles);
+        assert!(bare.lexicons().is_none());
+        assert!(bare.judge(&["QI"]).is_err());
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let tiles = TileMap::english();
+        let set = LexiconSet::new(Lexicon::from_words(["QI", "ZA"], &tiles));
+        let qi = tiles.tokenize("QI").unwrap();
+        let found: Vec<_> = std::thread::scope(|s| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    let set = set.clone();
+                    let qi = &qi;
+                    s.spawn(move || set.generation().contains(qi))
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        assert_eq!(found, [true; 4]);
+    }
+
+    #[test]
+    fn rejects_other_tile_maps() {
+        let spanish = TileMap::spanish();
+        let english = TileMap::english();
+        let set = LexiconSet::split(
+            Lexicon::from_words(["CAT"], &english),
+            Lexicon::from_words(["CHURRO"], &spanish),
+        );
+        let err = set.validate_against(&english).unwrap_err();
+        assert_eq!(err.to_string(), "Generation lexicon");
+        assert!(Game::<BitboardImpl>::new(english)
+            .with_lexicons(set)
+            .is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use crate::{TileMap, TileMapIdx};
+
+use super::{Lexicon, Word};
+
+/// Statistics of a [`Lexicon`], see [`Lexicon::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LexiconStats {
+    pub words: usize,
+    /// The number of words of each length, in tiles.
+    pub by_length: BTreeMap<usize, usize>,
+    /// How often each tile occurs across all words, indexed by [`TileMapIdx`].
+    pub tile_frequencies: Vec<usize>,
+}
+
+impl LexiconStats {
+    /// How often `tile` occurs across all words.
+    pub fn frequency(&self, tile: TileMapIdx) -> usize {
+        self.tile_frequencies
+            .get(tile.as_usize())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Lexicon {
+    pub fn stats(&self) -> LexiconStats {
+        let mut stats = LexiconStats {
+            words: self.len(),
+            by_length: BTreeMap::new(),
+            tile_frequencies: vec![0; self.letters.len()],
+        };
+        for word in self.words() {
+            *stats.by_length.entry(word.len()).or_default() += 1;
+            for tile in word {
+                stats.tile_frequencies[tile.as_usize()] += 1;
+            }
+        }
+        stats
+    }
+
+    /// The words of `len` tiles, most likely to be drawn from a full bag of `tiles` first, as in
+    /// study lists. Words equally likely come in tile order. Blanks aren't counted.
+    pub fn ranked_words(&self, len: usize, tiles: &TileMap) -> Vec<Word> {
+        let mut ranked: Vec<_> = self
+            .words()
+            .filter(|w| w.len() == len)
+            .map(|w| (combinations(&w, tiles), w))
+            .collect();
+        // Stable, so ties stay in tile order.
+        ranked.sort_by(|(a, _), (b, _)| b.cmp(a));
+        ranked.into_iter().map(|(_, w)| w).collect()
+    }
+}
+
+/// The number of ways to draw the tiles of `word` from a full bag, the product of
+/// `count choose needed` over its tiles. Saturates instead of overflowing.
+pub fn combinations(word: &[TileMapIdx], tiles: &TileMap) -> u128 {
+    let mut needed = [0u8; 256];
+    for tile in word {
+        needed[tile.as_usize()] = needed[tile.as_usize()].saturating_add(1);
+    }
+    needed
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| **n > 0)
+        .map(|(idx, n)| {
+            let count = TileMapIdx::new(idx as u8, tiles).map_or(0, |t| tiles.count(t));
+            choose(count, *n)
+        })
+        .fold(1, u128::saturating_mul)
+}
+
+fn choose(n: u8, k: u8) -> u128 {
+    if k > n {
+        return 0;
+    }
+    let mut c: u128 = 1;
+    for i in 0..u128::from(k) {
+        // Each partial result is itself a binomial coefficient, so the division is exact.
+        let Some(product) = c.checked_mul(u128::from(n) - i) else {
+            return u128::MAX;
+        };
+        c = product / (i + 1);
+    }
+    c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn stats() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["AA", "AB", "CAB", "ABBA"], &tiles);
+        let stats = lexicon.stats();
+        assert_eq!(stats.words, 4);
+        assert_eq!(
+            stats.by_length.clone().into_iter().collect::<Vec<_>>(),
+            [(2, 2), (3, 1), (4, 1)]
+        );
+        let idx = |l| tiles.find(l).unwrap();
+        assert_eq!(stats.frequency(idx("A")), 6);
+        assert_eq!(stats.frequency(idx("B")), 4);
+        assert_eq!(stats.frequency(idx("Z")), 0);
+    }
+
+    #[test]
+    fn ranking() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        // E: 12, A: 9, T: 6, Z: 1, X: 1.
+        assert_eq!(combinations(&word("EAT"), &tiles), 12 * 9 * 6);
+        assert_eq!(combinations(&word("EEE"), &tiles), 220);
+        assert_eq!(combinations(&word("ZZ"), &tiles), 0);
+        assert_eq!(choose(4, 2), 6);
+        assert_eq!(choose(255, 128), u128::MAX);
+
+        let lexicon = Lexicon::from_words(["ZAX", "TEA", "ATE", "EEE", "ZZZ"], &tiles);
+        let ranked: Vec<_> = lexicon
+            .ranked_words(3, &tiles)
+            .iter()
+            .map(|w| lexicon.spell(w))
+            .collect();
+        assert_eq!(ranked, ["ATE", "TEA", "EEE", "ZAX", "ZZZ"]);
+        assert!(lexicon.ranked_words(4, &tiles).is_empty());
+    }
+}
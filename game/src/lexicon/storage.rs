@@ -0,0 +1,53 @@
+//! Backing memory for the lexicon arrays: owned, or borrowed from a memory-mapped file.
+
+#[cfg(feature = "mmap")]
+use std::sync::Arc;
+
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+
+/// An immutable array of `u32`s.
+#[derive(Debug, Clone)]
+pub(super) enum U32s {
+    Owned(Vec<u32>),
+    /// `len` little-endian `u32`s starting `start` bytes into the mapping. The mapping is page
+    /// aligned and `start` a multiple of 4, so it's used in place.
+    #[cfg(feature = "mmap")]
+    Mapped {
+        map: Arc<Mmap>,
+        start: usize,
+        len: usize,
+    },
+}
+
+impl U32s {
+    pub(super) fn as_slice(&self) -> &[u32] {
+        match self {
+            U32s::Owned(v) => v,
+            #[cfg(feature = "mmap")]
+            U32s::Mapped { map, start, len } => {
+                bytemuck::cast_slice(&map[*start..*start + *len * 4])
+            }
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub(super) fn iter(&self) -> std::slice::Iter<'_, u32> {
+        self.as_slice().iter()
+    }
+
+    pub(super) fn chunks_exact(&self, n: usize) -> std::slice::ChunksExact<'_, u32> {
+        self.as_slice().chunks_exact(n)
+    }
+}
+
+impl PartialEq for U32s {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for U32s {}
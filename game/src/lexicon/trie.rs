@@ -0,0 +1,164 @@
+use crate::TileMapIdx;
+
+use super::NodeId;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Node {
+    /// Sorted by tile.
+    children: Vec<(TileMapIdx, NodeId)>,
+    is_word: bool,
+}
+
+/// A prefix tree of tokenized words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trie {
+    nodes: Vec<Node>,
+    words: usize,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Trie {
+    /// A trie without any words.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+            words: 0,
+        }
+    }
+
+    /// Adds `word`, returning whether it was new.
+    pub fn insert(&mut self, word: &[TileMapIdx]) -> bool {
+        let mut node = self.root();
+        for &tile in word {
+            node = match self.child(node, tile) {
+                Some(child) => child,
+                None => {
+                    let child = NodeId(self.nodes.len() as u32);
+                    self.nodes.push(Node::default());
+                    let children = &mut self.nodes[node.0 as usize].children;
+                    let pos = children.partition_point(|(t, _)| *t < tile);
+                    children.insert(pos, (tile, child));
+                    child
+                }
+            };
+        }
+        let is_word = &mut self.nodes[node.0 as usize].is_word;
+        let new = !*is_word;
+        *is_word = true;
+        self.words += usize::from(new);
+        new
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn child(&self, node: NodeId, tile: TileMapIdx) -> Option<NodeId> {
+        let children = &self.nodes[node.0 as usize].children;
+        children
+            .binary_search_by_key(&tile, |(t, _)| *t)
+            .ok()
+            .map(|pos| children[pos].1)
+    }
+
+    /// The tiles that can follow `node`, in tile order.
+    pub fn children(&self, node: NodeId) -> impl Iterator<Item = (TileMapIdx, NodeId)> + '_ {
+        self.nodes[node.0 as usize].children.iter().copied()
+    }
+
+    /// Whether the path to `node` spells a word.
+    pub fn is_word(&self, node: NodeId) -> bool {
+        self.nodes[node.0 as usize].is_word
+    }
+
+    /// The node reached by following `tiles` from the root.
+    pub fn walk(&self, tiles: &[TileMapIdx]) -> Option<NodeId> {
+        tiles
+            .iter()
+            .try_fold(self.root(), |node, tile| self.child(node, *tile))
+    }
+
+    pub fn contains(&self, word: &[TileMapIdx]) -> bool {
+        self.walk(word).is_some_and(|node| self.is_word(node))
+    }
+
+    /// Whether some word starts with `prefix`, including `prefix` itself.
+    pub fn is_prefix(&self, prefix: &[TileMapIdx]) -> bool {
+        self.walk(prefix).is_some()
+    }
+
+    /// The number of words.
+    pub fn len(&self) -> usize {
+        self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<W: AsRef<[TileMapIdx]>> FromIterator<W> for Trie {
+    fn from_iter<T: IntoIterator<Item = W>>(iter: T) -> Self {
+        let mut trie = Self::new();
+        for word in iter {
+            trie.insert(word.as_ref());
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileMap;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn lookup() {
+        let tiles = TileMap::english();
+        let word = |w| tiles.tokenize(w).unwrap();
+        let trie: Trie = ["CAT", "CATS", "CAR", "DOG", "CAT"]
+            .into_iter()
+            .map(word)
+            .collect();
+        assert_eq!(trie.len(), 4);
+        assert_eq!(trie.node_count(), 9);
+        assert!(trie.contains(&word("CAT")));
+        assert!(trie.contains(&word("CATS")));
+        assert!(!trie.contains(&word("CA")));
+        assert!(!trie.contains(&word("COW")));
+        assert!(trie.is_prefix(&word("CA")));
+        assert!(trie.is_prefix(&word("CATS")));
+        assert!(trie.is_prefix(&[]));
+        assert!(!trie.is_prefix(&word("CATSS")));
+        assert!(!trie.contains(&[]));
+
+        let ca = trie.walk(&word("CA")).unwrap();
+        let next: Vec<_> = trie
+            .children(ca)
+            .map(|(t, node)| (tiles.get(t).unwrap().as_str(), trie.is_word(node)))
+            .collect();
+        assert_eq!(next, [("R", true), ("T", true)]);
+    }
+
+    #[test]
+    fn insert() {
+        let tiles = TileMap::english();
+        let mut trie = Trie::new();
+        assert!(trie.is_empty());
+        assert!(trie.insert(&tiles.tokenize("A").unwrap()));
+        assert!(!trie.insert(&tiles.tokenize("A").unwrap()));
+        assert!(trie.insert(&[]));
+        assert!(trie.contains(&[]));
+        assert_eq!(trie.len(), 2);
+    }
+}
@@ -1,56 +1,1226 @@
-use anyhow::Result;
 use bitboard::Bitboard;
-use board::Board;
+use board::{Board, Move, Play, Player, Scores, TilePlacement};
+use challenge::{ChallengeOutcome, LastPlay};
+use clock::Clocks;
+use error::{BoardError, GameError, Result, TileError};
+use event::GameEvent;
+use lexicon::{AlphagramIndex, JudgeResult, Lexicon, LexiconSet};
+use outcome::{EndReason, GameOutcome};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rules::{ChallengeRule, Rules, Variant};
+use score::{score_move_with, ScoreBreakdown};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use turn::{TurnRecord, TurnSummary};
 
+pub mod analysis;
 pub mod bitboard;
-mod board;
+pub mod board;
+pub mod bot;
+pub mod builder;
+pub mod challenge;
+pub mod clock;
+pub mod endgame;
+pub mod error;
+pub mod eval;
+pub mod event;
+pub mod gcg;
+pub mod inference;
+pub mod leaves;
+pub mod lexicon;
+pub mod movegen;
+pub mod outcome;
+pub mod preendgame;
+pub mod replay;
+pub mod rules;
+pub mod save;
+pub mod score;
+pub mod selfplay;
+pub mod sim;
+pub mod stats;
+pub mod tiles;
+pub mod tournament;
+pub mod turn;
+pub mod view;
 
-pub struct TileMap {
-    // TODO score
-    letters: Vec<String>,
+pub use builder::GameBuilder;
+pub use error::ScrubbleError;
+pub use tiles::{Rack, Tile, TileBag, TileMap, TileMapBuilder, TileMapIdx};
+pub use view::GameView;
+
+pub struct Game<BB: Bitboard> {
+    board: Board<BB>,
+    tiles: TileMap,
+    lexicons: Option<LexiconSet>,
+    /// Of the judging lexicon, only kept for [Clabbers](Variant::Clabbers).
+    alphagrams: Option<Arc<AlphagramIndex>>,
+    bag: TileBag,
+    racks: [Rack; 2],
+    rules: Rules,
+    /// See [`Game::seed`].
+    seed: u64,
+    /// Seeds the [turn RNG](Game::turn_rng), apart from the bag so bots can't replay its draws.
+    bot_seed: u64,
+    clocks: Clocks,
+    last_play: Option<LastPlay>,
+    /// Turns in a row that scored nothing.
+    scoreless_turns: usize,
+    /// The count from before the last play, in case it's challenged off.
+    scoreless_before_last: usize,
+    resigned: Option<Player>,
+    history: Vec<TurnRecord>,
+    /// Saved when the first turn is played, for [`Game::undo_to`] to replay from.
+    start: Option<Box<Start<BB>>>,
+    /// Queued until [drained](Game::drain_events).
+    events: Vec<GameEvent>,
+}
+
+/// The state of a game before its first turn.
+struct Start<BB: Bitboard> {
+    board: Board<BB>,
+    bag: TileBag,
+    racks: [Rack; 2],
 }
 
-impl TileMap {
-    pub fn new(letters: Vec<String>) -> Result<Self> {
-        if letters.len() > u8::MAX as usize {
-            anyhow::bail!("Max number of distinct tiles: {}", (u8::MAX as usize) + 1);
+impl<BB: Bitboard> Game<BB> {
+    /// The game ends after this many turns in a row that scored nothing.
+    pub const SCORELESS_TURNS: usize = 6;
+
+    /// A new game on the standard board, with both racks drawn from a randomly seeded bag.
+    pub fn new(tiles: TileMap) -> Self {
+        Self::builder()
+            .tiles(tiles)
+            .build()
+            .expect("The default setup is valid")
+    }
+
+    pub fn builder() -> GameBuilder<BB> {
+        GameBuilder::new()
+    }
+
+    /// Refills the bag, drawing reproducibly based on `seed`, and draws both racks again.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.bot_seed = bot_seed_for(seed);
+        self.racks = Default::default();
+        self.bag =
+            TileBag::seeded(&self.tiles, seed).with_min_for_exchange(self.rules.min_for_exchange);
+        self.draw_racks();
+        self
+    }
+
+    /// Plays by `rules`, drawing both racks again to fit their rack size and resetting the clocks.
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self.clocks = Clocks::new(rules.base_time);
+        self.bag = self.bag.with_min_for_exchange(rules.min_for_exchange);
+        self.draw_racks();
+        self.index_alphagrams();
+        self
+    }
+
+    /// Puts the racks back into the bag and draws them again.
+    fn draw_racks(&mut self) {
+        for rack in &mut self.racks {
+            self.bag.return_tiles(rack.tiles());
+            *rack = Rack::new(self.rules.rack_size);
+            self.bag.draw_to_fill(rack);
+        }
+    }
+
+    /// Sets the lexicons plays are judged and generated with, checking they fit the tile map.
+    pub fn with_lexicons(mut self, lexicons: impl Into<LexiconSet>) -> Result<Self> {
+        let lexicons = lexicons.into();
+        lexicons.validate_against(&self.tiles)?;
+        self.lexicons = Some(lexicons);
+        self.alphagrams = None;
+        self.index_alphagrams();
+        Ok(self)
+    }
+
+    /// Builds the alphagram index Clabbers is played with, unless it's already there.
+    fn index_alphagrams(&mut self) {
+        if self.rules.variant != Variant::Clabbers || self.alphagrams.is_some() {
+            return;
+        }
+        self.alphagrams = self
+            .lexicons
+            .as_ref()
+            .map(|l| Arc::new(l.judge().build_alphagram_index()));
+    }
+
+    pub fn board(&self) -> &Board<BB> {
+        &self.board
+    }
+
+    pub fn tiles(&self) -> &TileMap {
+        &self.tiles
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn clocks(&self) -> &Clocks {
+        &self.clocks
+    }
+
+    /// The seed the bag was shuffled with, random unless [chosen](GameBuilder::seed). Starting a
+    /// game with the same seed and making the same plays draws the same tiles.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// An RNG for random decisions about the turn to play, e.g. a bot's, derived from the
+    /// [bot seed](GameBuilder::bot_seed) and the number of turns played. It doesn't depend on the
+    /// bag's draws, so replaying or undoing to a turn makes the same decisions again, and it tells
+    /// nothing about them either.
+    pub fn turn_rng(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.bot_seed);
+        rng.set_stream(self.history.len() as u64);
+        rng
+    }
+
+    pub fn lexicons(&self) -> Option<&LexiconSet> {
+        self.lexicons.as_ref()
+    }
+
+    /// The alphagrams of the judging lexicon when playing [Clabbers](Variant::Clabbers), e.g. for
+    /// [`clabbers_moves`](movegen::clabbers_moves).
+    pub fn alphagrams(&self) -> Option<&AlphagramIndex> {
+        self.alphagrams.as_deref()
+    }
+
+    pub fn bag(&self) -> &TileBag {
+        &self.bag
+    }
+
+    pub fn rack(&self, player: Player) -> &Rack {
+        &self.racks[rack_idx(player)]
+    }
+
+    fn judge_lexicon(&self) -> Result<&Lexicon> {
+        Ok(self.lexicons.as_ref().ok_or(GameError::NoLexicon)?.judge())
+    }
+
+    /// Adjudicates a challenge of words as typed, all at once. Plays are judged by their tiles
+    /// instead, see [`LastPlay::phonies`].
+    pub fn judge(&self, words: &[&str]) -> Result<JudgeResult> {
+        let lexicon = self.judge_lexicon()?;
+        Ok(match self.clabbers_index()? {
+            Some(index) => lexicon.judge_clabbers(words, index),
+            None => lexicon.judge(words),
+        })
+    }
+
+    /// Whether the tiles of `word` form a word of the judging lexicon, or an anagram of one when
+    /// playing Clabbers.
+    fn is_word(&self, word: &[TileMapIdx]) -> Result<bool> {
+        let lexicon = self.judge_lexicon()?;
+        Ok(match self.clabbers_index()? {
+            Some(index) => index.contains_anagram(word),
+            None => lexicon.contains(word),
+        })
+    }
+
+    /// The alphagram index if playing Clabbers, which needs one.
+    fn clabbers_index(&self) -> Result<Option<&AlphagramIndex>> {
+        match self.rules.variant {
+            Variant::Standard => Ok(None),
+            Variant::Clabbers => self
+                .alphagrams
+                .as_deref()
+                .map(Some)
+                .ok_or_else(|| GameError::NoAlphagramIndex.into()),
+        }
+    }
+
+    /// The play that can be challenged, if any.
+    pub fn last_play(&self) -> Option<&LastPlay> {
+        self.last_play.as_ref()
+    }
+
+    /// `player` makes `play` on their turn: it's checked, scored and applied, their rack is
+    /// refilled from the bag, and the turn goes to the opponent.
+    ///
+    /// Placements only have to fit the board and the rack, see [`Move::validate_placement`]: the
+    /// words they form are left to a [challenge](Game::challenge), unless the rules make
+    /// challenges [void](ChallengeRule::Void).
+    pub fn play(&mut self, player: Player, play: Play) -> Result<TurnSummary> {
+        self.play_timed(player, play, Duration::ZERO)
+    }
+
+    /// Like [`Game::play`], charging `elapsed` to the player's clock. Overtime is only penalized
+    /// once the game is over, see [`GameOutcome::adjustments`].
+    pub fn play_timed(
+        &mut self,
+        player: Player,
+        play: Play,
+        elapsed: Duration,
+    ) -> Result<TurnSummary> {
+        if self.outcome().is_some() {
+            return Err(GameError::GameOver.into());
+        }
+        if self.board.current_turn() != player {
+            return Err(GameError::NotYourTurn(player).into());
+        }
+        if self.start.is_none() {
+            self.start = Some(Box::new(Start {
+                board: self.board.clone(),
+                bag: self.bag.clone(),
+                racks: self.racks.clone(),
+            }));
+        }
+        let lexicon = self.judge_lexicon()?;
+        let rack = self.rack(player);
+        let rack_before = rack.clone();
+        match &play {
+            Play::Place(m) if self.rules.challenge == ChallengeRule::Void => {
+                match self.clabbers_index()? {
+                    Some(index) => m.validate_clabbers(&self.board, rack, lexicon, index)?,
+                    None => m.validate(&self.board, rack, lexicon)?,
+                }
+            }
+            Play::Place(m) => m.validate_placement(&self.board, rack, lexicon)?,
+            Play::Exchange(_) | Play::Pass => {
+                play.validate(&self.board, rack, &self.bag, lexicon)?
+            }
+        }
+
+        let rack = &mut self.racks[rack_idx(player)];
+        let (score, drawn) = match &play {
+            Play::Place(m) => {
+                for (_, p) in m.placed() {
+                    rack.remove(rack_tile(&self.tiles, p)?)?;
+                }
+                let drawn = self.bag.draw_to_fill(rack);
+                (self.place(m, drawn.clone())?, drawn)
+            }
+            Play::Exchange(tiles) => {
+                let drawn = self.bag.exchange(rack, tiles)?;
+                self.pass_turn();
+                (ScoreBreakdown::default(), drawn)
+            }
+            Play::Pass => {
+                self.pass_turn();
+                (ScoreBreakdown::default(), Vec::new())
+            }
+        };
+        self.scoreless_before_last = self.scoreless_turns;
+        if score.total() == 0 {
+            self.scoreless_turns += 1;
+        } else {
+            self.scoreless_turns = 0;
+        }
+        self.clocks.spend(player, elapsed);
+        self.announce(player, false);
+        let summary = TurnSummary {
+            player,
+            play,
+            score,
+            drawn,
+            elapsed,
+        };
+        let phonies = match (&summary.play, &self.last_play) {
+            (Play::Place(_), Some(last)) => last.phonies.clone(),
+            _ => Vec::new(),
+        };
+        self.history.push(TurnRecord {
+            summary: summary.clone(),
+            phonies,
+            rack_before,
+            rack_after: self.rack(player).clone(),
+            totals: self.board.scores(),
+            played_at: SystemTime::now(),
+            challenge: None,
+            comment: None,
+        });
+        self.debug_check_scores();
+        Ok(summary)
+    }
+
+    /// Puts `m` on the board for the player to move, adds its score and hands the turn over,
+    /// remembering the play, the words it formed and which of them are phony for a challenge.
+    fn place(&mut self, m: &Move, drawn: Vec<TileMapIdx>) -> Result<ScoreBreakdown> {
+        let score = score_move_with(&self.board, m, &self.tiles, &self.rules);
+        let mut words = Vec::new();
+        let mut phonies = Vec::new();
+        for (_, squares) in m.words(&self.board) {
+            let tiles: Vec<_> = squares.iter().map(|(_, t)| t.tile()).collect();
+            let word: String = tiles
+                .iter()
+                .map(|&t| self.tiles.get(t).map_or("?", String::as_str))
+                .collect();
+            // Judged by its tiles, as spelling can't tell e.g. a CH tile from a C and an H.
+            if !self.is_word(&tiles)? {
+                phonies.push(word.clone());
+            }
+            words.push(word);
+        }
+        self.board.apply(m)?;
+        let player = self.board.current_turn();
+        for (location, tile) in m.placed() {
+            self.events.push(GameEvent::TilePlaced {
+                player,
+                location,
+                tile,
+            });
+        }
+        self.add_score(player, score.total() as i32);
+        self.board.end_turn();
+        self.last_play = Some(LastPlay {
+            player,
+            m: m.clone(),
+            score: score.total(),
+            words,
+            phonies,
+            drawn,
+        });
+        Ok(score)
+    }
+
+    /// Adds `points` to `player`'s score, queueing a [`GameEvent::ScoreChanged`].
+    fn add_score(&mut self, player: Player, points: i32) {
+        self.board.scores_mut().add(player, points);
+        self.events.push(GameEvent::ScoreChanged {
+            player,
+            change: points,
+            total: self.board.scores()[player],
+        });
+    }
+
+    /// Queues [`GameEvent::GameEnded`] if the game just ended, or [`GameEvent::TurnStarted`] if
+    /// the turn changed or the game went on after all.
+    fn announce(&mut self, turn_before: Player, ended_before: bool) {
+        let player = self.board.current_turn();
+        match self.outcome() {
+            Some(outcome) if !ended_before => self.events.push(GameEvent::GameEnded(outcome)),
+            Some(_) => {}
+            None if ended_before || player != turn_before => {
+                self.events.push(GameEvent::TurnStarted { player })
+            }
+            None => {}
+        }
+    }
+
+    /// Takes the events queued since the last call, oldest first. Events pile up until drained.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+
+    /// Hands the turn over without placing anything, which can't be challenged.
+    fn pass_turn(&mut self) {
+        self.board.end_turn();
+        self.last_play = None;
+    }
+
+    /// `challenger` challenges the last play. If any word it formed is phony, its tiles come off
+    /// the board and go back on the rack, the tiles drawn after it go back into the bag, and its
+    /// score is taken back; otherwise the [challenge rule](Rules::challenge) applies. Either way,
+    /// the play can't be challenged again.
+    pub fn challenge(&mut self, challenger: Player) -> Result<ChallengeOutcome> {
+        if self.rules.challenge == ChallengeRule::Void {
+            return Err(GameError::ChallengesVoid.into());
+        }
+        if self.resigned.is_some() {
+            return Err(GameError::GameOver.into());
+        }
+        let Some(last) = &self.last_play else {
+            return Err(GameError::NothingToChallenge.into());
+        };
+        if last.player == challenger || self.board.current_turn() != challenger {
+            return Err(GameError::NotChallenger.into());
+        }
+        self.settle_challenge(last.phonies.clone(), self.rules.challenge)
+    }
+
+    /// Settles a challenge of the last play: phony if `invalid` holds any words, otherwise `rule`
+    /// applies. The outcome is recorded in the history and queued as an event.
+    fn settle_challenge(
+        &mut self,
+        invalid: Vec<String>,
+        rule: ChallengeRule,
+    ) -> Result<ChallengeOutcome> {
+        let turn_before = self.board.current_turn();
+        let ended_before = self.outcome().is_some();
+        let outcome = self.resolve_challenge(invalid, rule)?;
+        let challenger = match &outcome {
+            ChallengeOutcome::Phony { play, .. } => play.player.other(),
+            ChallengeOutcome::Valid { .. } => turn_before,
+        };
+        self.events.push(GameEvent::ChallengeResolved {
+            challenger,
+            outcome: outcome.clone(),
+        });
+        self.announce(turn_before, ended_before);
+        let scores = self.board.scores();
+        let record = self
+            .history
+            .last_mut()
+            .expect("The challenged play is in the history");
+        record.totals = scores;
+        record.challenge = Some(outcome.clone());
+        self.debug_check_scores();
+        Ok(outcome)
+    }
+
+    fn resolve_challenge(
+        &mut self,
+        invalid: Vec<String>,
+        rule: ChallengeRule,
+    ) -> Result<ChallengeOutcome> {
+        let Some(last) = self.last_play.take() else {
+            return Err(GameError::NothingToChallenge.into());
+        };
+        if invalid.is_empty() {
+            match rule {
+                ChallengeRule::Double => {
+                    self.board.end_turn();
+                    self.scoreless_turns += 1;
+                }
+                ChallengeRule::Points(points) => self.add_score(last.player, points as i32),
+                ChallengeRule::Free | ChallengeRule::Void => {}
+            }
+            return Ok(ChallengeOutcome::Valid { rule });
+        }
+        self.board.retract(&last.m)?;
+        for (location, tile) in last.m.placed() {
+            self.events.push(GameEvent::TileRemoved {
+                player: last.player,
+                location,
+                tile,
+            });
+        }
+        self.add_score(last.player, -(last.score as i32));
+        let rack = &mut self.racks[rack_idx(last.player)];
+        rack.remove_all(&last.drawn)?;
+        self.bag.return_tiles(&last.drawn);
+        for (_, p) in last.m.placed() {
+            rack.add(rack_tile(&self.tiles, p)?)?;
+        }
+        self.scoreless_turns = self.scoreless_before_last + 1;
+        Ok(ChallengeOutcome::Phony {
+            invalid,
+            play: last,
+        })
+    }
+
+    /// Every turn played so far, in order. A turn's index is its ply.
+    pub fn history(&self) -> &[TurnRecord] {
+        &self.history
+    }
+
+    /// The turns `player` played.
+    pub fn turns_of(&self, player: Player) -> impl Iterator<Item = &TurnRecord> {
+        self.history.iter().filter(move |r| r.player() == player)
+    }
+
+    /// Checks that both scores add up to the history: the scores at the start, plus every play,
+    /// less the plays challenged off, plus points awarded for valid plays that were challenged.
+    ///
+    /// Runs automatically after every turn, challenge and undo in debug builds.
+    pub fn check_scores(&self) -> Result<()> {
+        let mut expected = self
+            .start
+            .as_ref()
+            .map_or_else(|| self.board.scores(), |start| start.board.scores());
+        for record in &self.history {
+            let player = record.player();
+            expected.add(player, record.summary.score.total() as i32);
+            match &record.challenge {
+                Some(ChallengeOutcome::Phony { play, .. }) => {
+                    expected.add(play.player, -(play.score as i32));
+                }
+                Some(ChallengeOutcome::Valid {
+                    rule: ChallengeRule::Points(points),
+                }) => expected.add(player, *points as i32),
+                Some(ChallengeOutcome::Valid { .. }) | None => {}
+            }
+        }
+        if expected != self.board.scores() {
+            return Err(BoardError::Invariant(format!(
+                "Scores {:?} don't match the history, which adds up to {expected:?}",
+                self.board.scores()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    fn debug_check_scores(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(e) = self.check_scores() {
+                panic!("Score invariant violated: {e}");
+            }
         }
-        Ok(Self { letters })
     }
 
-    pub fn get(&self, idx: TileMapIdx) -> Option<&String> {
-        self.letters.get(idx.0 as usize)
+    /// Attaches `comment` to the turn at `ply`, replacing any earlier one.
+    pub fn annotate(&mut self, ply: usize, comment: impl Into<String>) -> Result<()> {
+        let record = self
+            .history
+            .get_mut(ply)
+            .ok_or(GameError::NoSuchTurn(ply))?;
+        record.comment = Some(comment.into());
+        Ok(())
     }
 
-    pub fn find(&self, needle: &str) -> Option<TileMapIdx> {
-        self.letters
-            .iter()
-            .position(|s| s == needle)
-            .map(|idx| TileMapIdx(idx as u8))
+    /// Takes back every turn from `ply` on, along with any resignation, by replaying the turns
+    /// before it from the start. Draws come out the same, since the bag is restored with its RNG.
+    pub fn undo_to(&mut self, ply: usize) -> Result<()> {
+        if ply > self.history.len() {
+            return Err(GameError::NoSuchTurn(ply).into());
+        }
+        let Some(start) = self.start.take() else {
+            return Ok(());
+        };
+        let history = std::mem::take(&mut self.history);
+        let events = self.events.len();
+        self.board = start.board.clone();
+        self.bag = start.bag.clone();
+        self.racks = start.racks.clone();
+        self.start = Some(start);
+        self.clocks = Clocks::new(self.rules.base_time);
+        self.last_play = None;
+        self.scoreless_turns = 0;
+        self.scoreless_before_last = 0;
+        self.resigned = None;
+        for record in &history[..ply] {
+            self.replay_turn(record)?;
+        }
+        self.events.truncate(events);
+        self.events.push(GameEvent::Undone { ply });
+        self.debug_check_scores();
+        Ok(())
     }
 
-    fn len(&self) -> u8 {
-        self.letters.len() as u8
+    /// Plays the turn of `record` again, settling its challenge as it was.
+    fn replay_turn(&mut self, record: &TurnRecord) -> Result<()> {
+        // Imported games set racks rather than drawing them.
+        if self.rack(record.player()) != &record.rack_before {
+            self.set_rack(record.player(), record.rack_before.tiles())?;
+        }
+        let play = record.summary.play.clone();
+        self.play_timed(record.player(), play, record.summary.elapsed)?;
+        match &record.challenge {
+            Some(ChallengeOutcome::Phony { invalid, .. }) => {
+                self.settle_challenge(invalid.clone(), self.rules.challenge)?;
+            }
+            Some(ChallengeOutcome::Valid { rule }) => {
+                self.settle_challenge(Vec::new(), *rule)?;
+            }
+            None => {}
+        }
+        let replayed = self.history.last_mut().expect("Just played");
+        replayed.played_at = record.played_at;
+        replayed.comment.clone_from(&record.comment);
+        Ok(())
+    }
+
+    /// Gives `player` exactly `tiles`, taking them from the bag or, failing that, the opponent's
+    /// rack, which is then refilled.
+    fn set_rack(&mut self, player: Player, tiles: &[TileMapIdx]) -> Result<()> {
+        let rack = std::mem::replace(
+            &mut self.racks[rack_idx(player)],
+            Rack::new(self.rules.rack_size),
+        );
+        self.bag.return_tiles(rack.tiles());
+        for &tile in tiles {
+            if !self.bag.take(tile) {
+                self.racks[rack_idx(player.other())]
+                    .remove(tile)
+                    .map_err(|_| {
+                        let letter = self.tiles.get(tile).map_or("?", String::as_str);
+                        GameError::NoneLeft(letter.to_owned())
+                    })?;
+            }
+            self.racks[rack_idx(player)].add(tile)?;
+        }
+        self.bag
+            .draw_to_fill(&mut self.racks[rack_idx(player.other())]);
+        Ok(())
+    }
+
+    /// `player` gives up, ending the game.
+    pub fn resign(&mut self, player: Player) -> Result<GameOutcome> {
+        if self.outcome().is_some() {
+            return Err(GameError::GameOver.into());
+        }
+        self.resigned = Some(player);
+        let outcome = self.outcome().expect("Resigning ends the game");
+        self.events.push(GameEvent::GameEnded(outcome));
+        Ok(outcome)
+    }
+
+    /// How the game ended, or `None` while it goes on.
+    ///
+    /// A player going out ends the game at once, but their last play can still be challenged off,
+    /// which lets the game go on.
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        let players = [Player::First, Player::Second];
+        let went_out = players
+            .into_iter()
+            .find(|p| self.bag.is_empty() && self.rack(*p).is_empty());
+        let reason = match (self.resigned, went_out) {
+            (Some(player), _) => EndReason::Resigned(player),
+            (None, Some(player)) => EndReason::WentOut(player),
+            (None, None) if self.scoreless_turns >= Self::SCORELESS_TURNS => {
+                EndReason::ScorelessTurns
+            }
+            (None, None) => return None,
+        };
+
+        let mut adjustments = Scores::default();
+        if !matches!(reason, EndReason::Resigned(_)) {
+            for player in players {
+                let left: i32 = self
+                    .rack(player)
+                    .tiles()
+                    .iter()
+                    .map(|t| i32::from(self.tiles.score(*t)))
+                    .sum();
+                adjustments.add(player, -left);
+                if let EndReason::WentOut(out) = reason {
+                    adjustments.add(out, left);
+                }
+                let overtime = self
+                    .clocks
+                    .overtime_minutes(player)
+                    .saturating_mul(self.rules.overtime_penalty);
+                adjustments.add(player, -i32::try_from(overtime).unwrap_or(i32::MAX));
+            }
+        }
+        let mut scores = self.board.scores();
+        for player in players {
+            scores.add(player, adjustments[player]);
+        }
+        let winner = match reason {
+            EndReason::Resigned(player) => Some(player.other()),
+            _ => players.into_iter().find(|p| scores.spread(*p) > 0),
+        };
+        Some(GameOutcome {
+            reason,
+            scores,
+            adjustments,
+            winner,
+        })
     }
+}
 
-    pub fn english() -> Self {
-        Self::new(('A'..='Z').into_iter().map(|c| c.to_string()).collect())
-            .expect("The English alphabet has less than 256 letters")
+/// The tile `p` took from the rack.
+fn rack_tile(tiles: &TileMap, p: TilePlacement) -> Result<TileMapIdx> {
+    if p.is_blank {
+        tiles.blank_idx().ok_or_else(|| TileError::NoBlank.into())
+    } else {
+        Ok(p.tile)
     }
 }
 
-pub struct TileMapIdx(u8);
+/// The bot seed for a game whose bag is seeded with `seed`: random output of the bag's key, on a
+/// stream the bag doesn't draw from, so it can't be traced back to the key.
+fn bot_seed_for(seed: u64) -> u64 {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(u64::MAX);
+    rng.gen()
+}
 
-pub struct Game<BB: Bitboard> {
-    board: Board<BB>,
-    tiles: TileMap,
+fn rack_idx(player: Player) -> usize {
+    match player {
+        Player::First => 0,
+        Player::Second => 1,
+    }
 }
 
-impl<BB: Bitboard> Game<BB> {
-    pub fn new(tiles: TileMap) -> Self {
-        Self {
-            board: Board::new(tiles.len()),
-            tiles,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    /// A seeded game with the racks replaced by `racks`.
+    fn new_game(racks: [&str; 2]) -> Game<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "AT", "TA", "ACT", "QI"], &tiles);
+        let mut game = Game::new(tiles)
+            .with_seed(1)
+            .with_lexicons(lexicon)
+            .unwrap();
+        game.racks = racks.map(|r| Rack::parse(r, &game.tiles).unwrap());
+        game
+    }
+
+    fn place(game: &mut Game<BitboardImpl>, player: Player, notation: &str) -> Result<u32> {
+        let m = Move::parse(notation, game.board(), game.tiles())?;
+        Ok(game.play(player, Play::Place(m))?.score.total())
+    }
+
+    #[test]
+    fn turns() {
+        let mut game = new_game(["CATQ", "EEIIOOU"]);
+        let bag = game.bag().remaining();
+        assert!(place(&mut game, Player::Second, "8G CAT").is_err());
+        assert!(place(&mut game, Player::First, "8G CATS").is_err());
+        assert!(place(&mut game, Player::First, "8A CAT").is_err());
+        assert_eq!(game.board().occupied().count_ones(), 0);
+
+        assert_eq!(place(&mut game, Player::First, "8G CAT").unwrap(), 10);
+        assert_eq!(game.rack(Player::First).len(), 7);
+        assert_eq!(game.bag().remaining(), bag - 6);
+        assert_eq!(game.board().current_turn(), Player::Second);
+        assert_eq!(game.board().scores()[Player::First], 10);
+
+        let tiles = game.tiles().tokenize("EIO").unwrap();
+        let summary = game.play(Player::Second, Play::Exchange(tiles)).unwrap();
+        assert_eq!(summary.drawn.len(), 3);
+        assert_eq!(summary.score.total(), 0);
+        assert_eq!(game.bag().remaining(), bag - 6);
+        assert_eq!(game.rack(Player::Second).len(), 7);
+        assert!(game.challenge(Player::First).is_err());
+
+        game.play(Player::First, Play::Pass).unwrap();
+        assert_eq!(game.board().current_turn(), Player::Second);
+        let tiles = game.tiles().tokenize("QQ").unwrap();
+        assert!(game.play(Player::Second, Play::Exchange(tiles)).is_err());
+    }
+
+    #[test]
+    fn challenges() {
+        let mut game = new_game(["CATAS", "EEIIOOU"]);
+        assert!(game.challenge(Player::Second).is_err());
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        assert!(!game.last_play().unwrap().is_phony());
+        assert!(game.challenge(Player::First).is_err());
+        assert_eq!(
+            game.challenge(Player::Second).unwrap(),
+            ChallengeOutcome::Valid {
+                rule: ChallengeRule::Double
+            }
+        );
+        assert_eq!(game.board().current_turn(), Player::First);
+        assert!(game.challenge(Player::Second).is_err());
+
+        // AS, and AA and TS down, none in the lexicon.
+        game.racks[0] = Rack::parse("ASQ", game.tiles()).unwrap();
+        let bag = game.bag().remaining();
+        assert_eq!(place(&mut game, Player::First, "9H AS").unwrap(), 8);
+        assert_eq!(game.board().scores()[Player::First], 10 + 8);
+        assert_eq!(game.last_play().unwrap().phonies, ["AS", "AA", "TS"]);
+        let ChallengeOutcome::Phony { invalid, play } = game.challenge(Player::Second).unwrap()
+        else {
+            panic!("AS is phony");
+        };
+        assert_eq!(invalid, ["AS", "AA", "TS"]);
+        assert_eq!(play.words, ["AS", "AA", "TS"]);
+        assert_eq!(play.drawn.len(), 6);
+        assert_eq!(game.board().scores()[Player::First], 10);
+        assert_eq!(game.board().occupied().count_ones(), 3);
+        assert_eq!(game.board().current_turn(), Player::Second);
+        assert_eq!(game.rack(Player::First).to_letters(game.tiles()), "AQS");
+        assert_eq!(game.bag().remaining(), bag);
+        assert!(game.history()[1].is_retracted());
+
+        // Unchallenged, a phony stands.
+        game.play(Player::Second, Play::Pass).unwrap();
+        place(&mut game, Player::First, "9H AS").unwrap();
+        game.play(Player::Second, Play::Pass).unwrap();
+        assert!(game.challenge(Player::First).is_err());
+        assert!(game.history()[3].is_standing_phony());
+        assert!(!game.history()[1].is_standing_phony());
+    }
+
+    #[test]
+    fn digraph_phonies() {
+        use crate::board::{Direction, MoveTile};
+
+        let tiles = TileMap::spanish();
+        let lexicon = Lexicon::from_words(["CHA"], &tiles);
+        let [c, h, a] = ["C", "H", "A"].map(|l| tiles.find(l).unwrap());
+        let mut game: Game<BitboardImpl> = GameBuilder::new()
+            .tiles(tiles.clone())
+            .lexicons(lexicon)
+            .rack(Player::First, Rack::from_tiles(vec![c, h, a], 7).unwrap())
+            .seed(1)
+            .build()
+            .unwrap();
+        // C, H and A spell CHA, but the word is the CH tile and A.
+        let placed = [c, h, a]
+            .map(|tile| {
+                MoveTile::Placed(TilePlacement {
+                    tile,
+                    is_blank: false,
+                })
+            })
+            .to_vec();
+        let m = Move::new("G8".parse().unwrap(), Direction::Horizontal, placed).unwrap();
+        game.play(Player::First, Play::Place(m)).unwrap();
+        assert_eq!(game.last_play().unwrap().phonies, ["CHA"]);
+        let ChallengeOutcome::Phony { invalid, .. } = game.challenge(Player::Second).unwrap()
+        else {
+            panic!("C, H and A are phony");
+        };
+        assert_eq!(invalid, ["CHA"]);
+        assert!(game.board().occupied().is_empty());
+    }
+
+    #[test]
+    fn penalties() {
+        let mut game = new_game(["CAT", ""]);
+        game.rules = Rules::wespa();
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        game.challenge(Player::Second).unwrap();
+        assert_eq!(game.board().scores()[Player::First], 15);
+        assert_eq!(game.board().current_turn(), Player::Second);
+
+        let mut game = new_game(["CAT", ""]);
+        game.rules.challenge = ChallengeRule::Free;
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        game.challenge(Player::Second).unwrap();
+        assert_eq!(game.board().scores()[Player::First], 10);
+        assert_eq!(game.board().current_turn(), Player::Second);
+
+        // Nothing to judge against.
+        let mut game = Game::<BitboardImpl>::new(TileMap::english());
+        assert!(game.play(Player::First, Play::Pass).is_err());
+    }
+
+    #[test]
+    fn going_out() {
+        let mut game = new_game(["CAT", "QZ"]);
+        game.bag.draw(100);
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        let outcome = game.outcome().unwrap();
+        assert_eq!(outcome.reason, EndReason::WentOut(Player::First));
+        assert_eq!(outcome.adjustments[Player::First], 20);
+        assert_eq!(outcome.adjustments[Player::Second], -20);
+        assert_eq!(outcome.scores[Player::First], 30);
+        assert_eq!(outcome.scores[Player::Second], -20);
+        assert_eq!(outcome.winner, Some(Player::First));
+        assert_eq!(game.board().scores()[Player::First], 10);
+        assert!(game.play(Player::Second, Play::Pass).is_err());
+        assert!(game.resign(Player::Second).is_err());
+
+        // Unless the last play comes off.
+        let mut game = new_game(["AS", "QZ"]);
+        game.bag.draw(100);
+        place(&mut game, Player::First, "8H AS").unwrap();
+        assert!(game.outcome().is_some());
+        game.challenge(Player::Second).unwrap();
+        assert_eq!(game.outcome(), None);
+        game.play(Player::Second, Play::Pass).unwrap();
+    }
+
+    #[test]
+    fn history() {
+        let mut game = new_game(["CAT", "QZ"]);
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        game.play(Player::Second, Play::Pass).unwrap();
+
+        let history = game.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].rack_before,
+            Rack::parse("CAT", game.tiles()).unwrap()
+        );
+        assert_eq!(history[0].rack_after.len(), 7);
+        assert_eq!(history[0].totals[Player::First], 10);
+        assert_eq!(history[1].summary.play, Play::Pass);
+        assert_eq!(game.turns_of(Player::Second).count(), 1);
+
+        game.annotate(0, "Opening").unwrap();
+        assert_eq!(game.history()[0].comment.as_deref(), Some("Opening"));
+        assert!(game.annotate(2, "Nothing").is_err());
+    }
+
+    #[test]
+    fn challenged_history() {
+        let mut game = new_game(["AS", "QZ"]);
+        place(&mut game, Player::First, "8H AS").unwrap();
+        game.challenge(Player::Second).unwrap();
+        let record = &game.history()[0];
+        assert!(record.is_retracted());
+        assert_eq!(record.totals[Player::First], 0);
+        assert_eq!(record.summary.score.total(), 4);
+    }
+
+    #[test]
+    fn undo() {
+        let mut game = new_game(["CAT", "QZ"]);
+        let rack = game.rack(Player::First).clone();
+        let bag = game.bag().clone();
+        game.undo_to(0).unwrap();
+        assert_eq!(game.rack(Player::First), &rack);
+
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        game.annotate(0, "Opening").unwrap();
+        game.play(Player::Second, Play::Pass).unwrap();
+        let after_pass = (game.board().scores(), game.rack(Player::First).clone());
+        let tiles = game.rack(Player::First).tiles()[..2].to_vec();
+        game.play(Player::First, Play::Exchange(tiles)).unwrap();
+        game.resign(Player::Second).unwrap();
+        assert!(game.undo_to(4).is_err());
+
+        game.undo_to(2).unwrap();
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.outcome(), None);
+        assert_eq!(game.history()[0].comment.as_deref(), Some("Opening"));
+        assert_eq!(game.board().occupied().count_ones(), 3);
+        assert_eq!(game.board().current_turn(), Player::First);
+        assert_eq!(
+            (game.board().scores(), game.rack(Player::First).clone()),
+            after_pass
+        );
+
+        game.undo_to(0).unwrap();
+        assert_eq!(game.board().occupied().count_ones(), 0);
+        assert_eq!(game.board().scores(), Scores::default());
+        assert_eq!(game.rack(Player::First), &rack);
+        assert_eq!(game.bag().tiles(), bag.tiles());
+    }
+
+    #[test]
+    fn undoing_challenges() {
+        let mut game = new_game(["AS", "QZ"]);
+        place(&mut game, Player::First, "8H AS").unwrap();
+        game.challenge(Player::Second).unwrap();
+        game.play(Player::Second, Play::Pass).unwrap();
+        let rack = game.rack(Player::First).clone();
+        game.undo_to(1).unwrap();
+        assert!(game.history()[0].is_retracted());
+        assert_eq!(game.board().occupied().count_ones(), 0);
+        assert_eq!(game.rack(Player::First), &rack);
+        assert_eq!(game.board().current_turn(), Player::Second);
+    }
+
+    #[test]
+    fn scores_match_history() {
+        let mut game = new_game(["AS", "QZ"]);
+        game.rules.challenge = ChallengeRule::Points(5);
+        place(&mut game, Player::First, "8H AS").unwrap();
+        game.challenge(Player::Second).unwrap();
+        assert!(game.check_scores().is_ok());
+        game.undo_to(0).unwrap();
+        assert!(game.check_scores().is_ok());
+
+        place(&mut game, Player::First, "8H AS").unwrap();
+        game.board.scores_mut().add(Player::First, 1);
+        assert!(game.check_scores().is_err());
+    }
+
+    #[test]
+    fn events() {
+        let mut game = new_game(["AS", "QZ"]);
+        place(&mut game, Player::First, "8H AS").unwrap();
+        let events: Vec<_> = game.drain_events().collect();
+        let l = |s: &str| s.parse().unwrap();
+        let tile = |s| TilePlacement {
+            tile: game.tiles().find(s).unwrap(),
+            is_blank: false,
+        };
+        assert_eq!(
+            events,
+            [
+                GameEvent::TilePlaced {
+                    player: Player::First,
+                    location: l("H8"),
+                    tile: tile("A"),
+                },
+                GameEvent::TilePlaced {
+                    player: Player::First,
+                    location: l("I8"),
+                    tile: tile("S"),
+                },
+                GameEvent::ScoreChanged {
+                    player: Player::First,
+                    change: 4,
+                    total: 4,
+                },
+                GameEvent::TurnStarted {
+                    player: Player::Second,
+                },
+            ]
+        );
+
+        let outcome = game.challenge(Player::Second).unwrap();
+        let events: Vec<_> = game.drain_events().collect();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[0], GameEvent::TileRemoved { .. }));
+        assert_eq!(
+            events[2],
+            GameEvent::ScoreChanged {
+                player: Player::First,
+                change: -4,
+                total: 0,
+            }
+        );
+        assert_eq!(
+            events[3],
+            GameEvent::ChallengeResolved {
+                challenger: Player::Second,
+                outcome,
+            }
+        );
+
+        game.undo_to(0).unwrap();
+        game.resign(Player::First).unwrap();
+        let events: Vec<_> = game.drain_events().collect();
+        assert_eq!(events[0], GameEvent::Undone { ply: 0 });
+        assert!(matches!(events[1], GameEvent::GameEnded(_)));
+        assert_eq!(game.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn overtime() {
+        let mut game = new_game(["CAT", "QZ"]);
+        game.bag.draw(100);
+        game.play_timed(Player::First, Play::Pass, Duration::from_secs(26 * 60))
+            .unwrap();
+        let m = Move::parse("8G CAT", game.board(), game.tiles()).unwrap();
+        let summary = game
+            .play_timed(Player::Second, Play::Pass, Duration::from_secs(90))
+            .unwrap();
+        assert_eq!(summary.elapsed, Duration::from_secs(90));
+        game.play_timed(Player::First, Play::Place(m), Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(
+            game.clocks().remaining(Player::Second),
+            Some(Duration::from_secs(25 * 60 - 90))
+        );
+        let outcome = game.outcome().unwrap();
+        assert_eq!(outcome.adjustments[Player::First], 20 - 20);
+        assert_eq!(outcome.adjustments[Player::Second], -20);
+    }
+
+    #[test]
+    fn overtime_penalty_saturates() {
+        let mut game = new_game(["CAT", "QZ"]);
+        game.rules.overtime_penalty = u32::MAX;
+        game.bag.draw(100);
+        game.play_timed(Player::First, Play::Pass, Duration::from_secs(27 * 60))
+            .unwrap();
+        game.play_timed(Player::Second, Play::Pass, Duration::from_secs(1))
+            .unwrap();
+        let m = Move::parse("8G CAT", game.board(), game.tiles()).unwrap();
+        game.play_timed(Player::First, Play::Place(m), Duration::from_secs(1))
+            .unwrap();
+        let outcome = game.outcome().unwrap();
+        assert_eq!(outcome.adjustments[Player::First], 20 - i32::MAX);
+    }
+
+    #[test]
+    fn scoreless_turns() {
+        let mut game = new_game(["CAT", "QZ"]);
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        // A lost challenge counts as a scoreless turn.
+        game.challenge(Player::Second).unwrap();
+        for player in [Player::First, Player::Second, Player::First, Player::Second] {
+            game.play(player, Play::Pass).unwrap();
         }
+        assert_eq!(game.outcome(), None);
+        game.racks[0] = Rack::parse("DE", game.tiles()).unwrap();
+        game.play(Player::First, Play::Pass).unwrap();
+        let outcome = game.outcome().unwrap();
+        assert_eq!(outcome.reason, EndReason::ScorelessTurns);
+        assert_eq!(outcome.adjustments[Player::First], -3);
+        assert_eq!(outcome.adjustments[Player::Second], -20);
+        assert_eq!(outcome.winner, Some(Player::First));
+    }
+
+    #[test]
+    fn resigning() {
+        let mut game = new_game(["CAT", "QZ"]);
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        let outcome = game.resign(Player::First).unwrap();
+        assert_eq!(outcome.reason, EndReason::Resigned(Player::First));
+        assert_eq!(outcome.winner, Some(Player::Second));
+        assert_eq!(outcome.adjustments, Scores::default());
+        assert_eq!(outcome.scores[Player::First], 10);
+        assert!(game.challenge(Player::Second).is_err());
+        assert!(game.play(Player::Second, Play::Pass).is_err());
+    }
+
+    #[test]
+    fn rules() {
+        let rules = Rules {
+            bingo_bonus: 20,
+            rack_size: 3,
+            challenge: ChallengeRule::Void,
+            min_for_exchange: 100,
+            ..Rules::twl_club()
+        };
+        let mut game = Game::<BitboardImpl>::new(TileMap::english())
+            .with_seed(1)
+            .with_rules(rules);
+        assert_eq!(game.rack(Player::First).len(), 3);
+        assert_eq!(game.bag().remaining(), 94);
+        let tiles = game.rack(Player::First).tiles()[..1].to_vec();
+        assert!(game.play(Player::First, Play::Exchange(tiles)).is_err());
+
+        let mut game = new_game(["CAT", "EEIIOOU"]);
+        game.rules = rules;
+        assert!(place(&mut game, Player::First, "8G TAC").is_err());
+        assert_eq!(place(&mut game, Player::First, "8G CAT").unwrap(), 30);
+        assert!(game.challenge(Player::Second).is_err());
+    }
+
+    #[test]
+    fn seeds() {
+        use rand::Rng;
+
+        let random = Game::<BitboardImpl>::builder().build().unwrap();
+        let again = Game::<BitboardImpl>::builder()
+            .seed(random.seed())
+            .build()
+            .unwrap();
+        assert_eq!(again.rack(Player::First), random.rack(Player::First));
+        assert_eq!(again.bag().tiles(), random.bag().tiles());
+
+        let mut game = new_game(["CAT", "EEIIOOU"]);
+        assert_eq!(game.seed(), 1);
+        let before = game.turn_rng().gen::<u64>();
+        assert_eq!(game.turn_rng().gen::<u64>(), before);
+        place(&mut game, Player::First, "8G CAT").unwrap();
+        assert_ne!(game.turn_rng().gen::<u64>(), before);
+        game.undo_to(0).unwrap();
+        assert_eq!(game.turn_rng().gen::<u64>(), before);
+    }
+
+    #[test]
+    fn clabbers() {
+        let clabbers = Rules {
+            variant: Variant::Clabbers,
+            ..Rules::twl_club()
+        };
+        let new_game = |rules| {
+            let mut game = new_game(["ACT", "EEIIOOU"]);
+            game.rules = rules;
+            game.index_alphagrams();
+            game
+        };
+        let mut game = new_game(clabbers);
+        assert!(game.alphagrams().is_some());
+        assert!(game.judge(&["TCA", "IQ"]).unwrap().is_valid());
+        assert_eq!(game.judge(&["CT", "CAT"]).unwrap().invalid, ["CT"]);
+        place(&mut game, Player::First, "8G TAC").unwrap();
+        assert_eq!(
+            game.challenge(Player::Second).unwrap(),
+            ChallengeOutcome::Valid {
+                rule: ChallengeRule::Double
+            }
+        );
+
+        let mut game = new_game(Rules {
+            challenge: ChallengeRule::Void,
+            ..clabbers
+        });
+        assert!(place(&mut game, Player::First, "8G CT").is_err());
+        assert!(place(&mut game, Player::First, "8G ATC").is_ok());
+
+        // Without lexicons there's nothing to index or judge with.
+        let game = Game::<BitboardImpl>::new(TileMap::english()).with_rules(clabbers);
+        assert!(game.alphagrams().is_none());
+        assert!(game.judge(&["CAT"]).is_err());
     }
 }
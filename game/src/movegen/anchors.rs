@@ -0,0 +1,163 @@
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Location, Row},
+};
+
+/// A word has to place a tile on its anchor, so at most this many squares of a line go before it,
+/// whatever the [rack size](crate::rules::Rules::rack_size).
+pub const MAX_LIMIT: usize = Row::MAX as usize - 1;
+
+/// Where moves can be anchored, and how far each anchor's word can extend before it, see
+/// [`anchors_and_limits`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchors<BB: Bitboard> {
+    anchors: BB,
+    /// `horizontal[k - 1]`: the anchors whose horizontal limit is at least `k`.
+    horizontal: [BB; MAX_LIMIT],
+    vertical: [BB; MAX_LIMIT],
+}
+
+impl<BB: Bitboard> Anchors<BB> {
+    /// The empty squares a move has to cover one of.
+    pub fn anchors(&self) -> BB {
+        self.anchors
+    }
+
+    /// How many tiles from the rack a word in `direction` can place before the anchor at `l`: the
+    /// empty squares before it up to the previous anchor or the edge, at most
+    /// [`MAX_LIMIT`]. Zero if `l` isn't an anchor, or the square before it is occupied
+    /// and the word has to start with the tiles there instead.
+    pub fn limit(&self, l: Location, direction: Direction) -> usize {
+        self.planes(direction)
+            .iter()
+            .take_while(|plane| plane.is_set(l))
+            .count()
+    }
+
+    /// Every anchor with its limit in `direction`, row by row.
+    pub fn iter(&self, direction: Direction) -> impl Iterator<Item = (Location, usize)> + '_ {
+        self.anchors
+            .locations()
+            .map(move |l| (l, self.limit(l, direction)))
+    }
+
+    fn planes(&self, direction: Direction) -> &[BB; MAX_LIMIT] {
+        match direction {
+            Direction::Horizontal => &self.horizontal,
+            Direction::Vertical => &self.vertical,
+        }
+    }
+}
+
+/// Finds the anchors of `board`, the empty squares next to a tile, or the start square on an empty
+/// board, and their limits in both directions.
+///
+/// Works on whole bitboards: the squares `k` before every anchor are checked at once by shifting
+/// the free squares by `k`, so there are no per-square loops.
+pub fn anchors_and_limits<BB: Bitboard>(board: &Board<BB>) -> Anchors<BB> {
+    let occupied = board.occupied();
+    let anchors = if occupied.is_empty() {
+        BB::for_location(board.premiums().start())
+    } else {
+        (occupied.left(1) | occupied.right(1) | occupied.up(1) | occupied.down(1)) & !occupied
+    };
+    // Squares a prefix can be placed on.
+    let free = !(occupied | anchors) & BB::full();
+    let planes = |shift: fn(BB, usize) -> BB| {
+        let mut planes = [BB::empty(); MAX_LIMIT];
+        let mut reach = anchors;
+        for (k, plane) in planes.iter_mut().enumerate() {
+            reach &= shift(free, k + 1);
+            *plane = reach;
+        }
+        planes
+    };
+    Anchors {
+        anchors,
+        horizontal: planes(BB::right),
+        vertical: planes(BB::up),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, board::Move, TileMap};
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    /// The per-square version.
+    fn naive_limit(
+        board: &Board<BitboardImpl>,
+        anchors: BitboardImpl,
+        l: Location,
+        d: Direction,
+    ) -> usize {
+        if !anchors.is_set(l) {
+            return 0;
+        }
+        (1..=MAX_LIMIT as isize)
+            .map_while(|k| l.offset(d, -k))
+            .take_while(|s| board.tile_at(*s).is_none() && !anchors.is_set(*s))
+            .count()
+    }
+
+    #[test]
+    fn empty_board() {
+        let tiles = TileMap::english();
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let anchors = anchors_and_limits(&board);
+        assert_eq!(
+            anchors.iter(Direction::Horizontal).collect::<Vec<_>>(),
+            [(Location::CENTER, 7)]
+        );
+        // Up to the edge, more than a rack of 7 needs.
+        assert_eq!(anchors.limit(Location::CENTER, Direction::Vertical), 7);
+        assert_eq!(anchors.limit("A1".parse().unwrap(), Direction::Vertical), 0);
+    }
+
+    #[test]
+    fn after_first_move() {
+        let tiles = TileMap::english();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        board
+            .apply(&Move::parse("8D QUIXOTIC", &board, &tiles).unwrap())
+            .unwrap();
+        let anchors = anchors_and_limits(&board);
+        assert_eq!(anchors.anchors().count_ones(), 8 * 2 + 2);
+        let limit = |l: &str, d| anchors.limit(l.parse().unwrap(), d);
+        // Before the Q, from the edge.
+        assert_eq!(limit("C8", Direction::Horizontal), 2);
+        // Right after the C, the word has to start with QUIXOTIC.
+        assert_eq!(limit("L8", Direction::Horizontal), 0);
+        // Under the Q, anchors to the left stop it.
+        assert_eq!(limit("D9", Direction::Horizontal), 3);
+        assert_eq!(limit("E9", Direction::Horizontal), 0);
+        assert_eq!(limit("D9", Direction::Vertical), 0);
+        assert_eq!(limit("D7", Direction::Vertical), 6);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_per_square_loops(squares in prop::collection::vec((0..15usize, 0..15usize), 0..40)) {
+            let tiles = TileMap::english();
+            let mut board = Board::<BitboardImpl>::standard(&tiles);
+            let a = tiles.find("A").unwrap();
+            for (r, c) in squares {
+                let l = Location::new(
+                    crate::board::Row::from_idx(r).unwrap(),
+                    crate::board::Column::from_idx(c).unwrap(),
+                );
+                if board.tile_at(l).is_none() {
+                    board.place(l, a, false).unwrap();
+                }
+            }
+            let anchors = anchors_and_limits(&board);
+            for l in BitboardImpl::full().locations() {
+                for d in [Direction::Horizontal, Direction::Vertical] {
+                    prop_assert_eq!(anchors.limit(l, d), naive_limit(&board, anchors.anchors(), l, d));
+                }
+            }
+        }
+    }
+}
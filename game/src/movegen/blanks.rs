@@ -0,0 +1,149 @@
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Location, Move, MoveTile, TilePlacement},
+    score::score_move,
+    Rack, TileMap, TileMapIdx,
+};
+
+/// The best way to play a move's letters from a rack, see [`assign_blanks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlankAssignment {
+    /// The move with blanks where they cost the fewest points.
+    pub m: Move,
+    pub score: u32,
+    /// The squares the blanks went on.
+    pub blanks: Vec<Location>,
+}
+
+/// Decides which of the letters `m` places come from blanks, so the move scores the most. Only as
+/// many blanks are used as `rack` lacks real tiles for, and each goes where its letter would have
+/// been worth the least, e.g. off the premium squares. Whether `m` used blanks doesn't matter.
+///
+/// Returns `None` if `rack` can't supply the letters even with its blanks.
+pub fn assign_blanks<BB: Bitboard>(
+    board: &Board<BB>,
+    m: &Move,
+    rack: &Rack,
+    tiles: &TileMap,
+) -> Option<BlankAssignment> {
+    // The letters placed, each with the indices into the main word of its squares.
+    let mut letters: Vec<(TileMapIdx, Vec<usize>)> = Vec::new();
+    for (i, t) in m.tiles().iter().enumerate() {
+        if let MoveTile::Placed(p) = t {
+            match letters.iter_mut().find(|(l, _)| *l == p.tile) {
+                Some((_, squares)) => squares.push(i),
+                None => letters.push((p.tile, vec![i])),
+            }
+        }
+    }
+    let needed: usize = letters
+        .iter()
+        .map(|(l, squares)| squares.len().saturating_sub(rack.count(*l)))
+        .sum();
+    let available = tiles.blank_idx().map_or(0, |b| rack.count(b));
+    if needed > available {
+        return None;
+    }
+
+    let mut best: Option<BlankAssignment> = None;
+    let mut blanked = vec![false; m.tiles().len()];
+    for_each_assignment(&letters, rack, &mut blanked, &mut |blanked| {
+        let assigned = with_blanks(m, blanked);
+        let score = score_move(board, &assigned, tiles).total();
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            let blanks = assigned
+                .squares()
+                .zip(blanked)
+                .filter(|(_, b)| **b)
+                .map(|((l, _), _)| l)
+                .collect();
+            best = Some(BlankAssignment {
+                m: assigned,
+                score,
+                blanks,
+            });
+        }
+    });
+    best
+}
+
+/// Calls `f` with every choice of squares for the blanks, as flags per square of the main word.
+fn for_each_assignment(
+    letters: &[(TileMapIdx, Vec<usize>)],
+    rack: &Rack,
+    blanked: &mut Vec<bool>,
+    f: &mut impl FnMut(&[bool]),
+) {
+    let Some(((letter, squares), rest)) = letters.split_first() else {
+        f(blanked);
+        return;
+    };
+    let blanks = squares.len().saturating_sub(rack.count(*letter));
+    for_each_subset(squares, blanks, blanked, &mut |blanked| {
+        for_each_assignment(rest, rack, blanked, f)
+    });
+}
+
+/// Calls `f` with every way of flagging `k` of `squares`.
+fn for_each_subset(
+    squares: &[usize],
+    k: usize,
+    blanked: &mut Vec<bool>,
+    f: &mut impl FnMut(&mut Vec<bool>),
+) {
+    if k == 0 {
+        f(blanked);
+        return;
+    }
+    for (i, square) in squares.iter().enumerate().take(squares.len() + 1 - k) {
+        blanked[*square] = true;
+        for_each_subset(&squares[i + 1..], k - 1, blanked, f);
+        blanked[*square] = false;
+    }
+}
+
+fn with_blanks(m: &Move, blanked: &[bool]) -> Move {
+    let tiles = m
+        .tiles()
+        .iter()
+        .zip(blanked)
+        .map(|(t, is_blank)| match t {
+            MoveTile::Placed(p) => MoveTile::Placed(TilePlacement {
+                tile: p.tile,
+                is_blank: *is_blank,
+            }),
+            on_board => *on_board,
+        })
+        .collect();
+    Move::new(m.location(), m.direction(), tiles).expect("Same squares as a valid move")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn blanks_go_off_premiums() {
+        let tiles = TileMap::english();
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        // The first Z lands on the double letter at D8.
+        let m = Move::parse("8D zIZZ", &board, &tiles).unwrap();
+        let rack = Rack::parse("ZI??", &tiles).unwrap();
+        let best = assign_blanks(&board, &m, &rack, &tiles).unwrap();
+        assert_eq!(best.m.notation(&tiles), "8D ZIzz");
+        assert_eq!(best.blanks, ["F8".parse().unwrap(), "G8".parse().unwrap()]);
+        assert_eq!(best.score, 20 + 1);
+
+        // With enough real tiles, no blanks are spent.
+        let rack = Rack::parse("ZZZI?", &tiles).unwrap();
+        let best = assign_blanks(&board, &m, &rack, &tiles).unwrap();
+        assert!(best.blanks.is_empty());
+        assert_eq!(best.score, 20 + 1 + 10 + 10);
+        assert_eq!(
+            assign_blanks(&board, &m, &Rack::parse("ZI?", &tiles).unwrap(), &tiles),
+            None
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Location, Move, MoveTile, TilePlacement},
+    lexicon::Lexicon,
+    Rack,
+};
+
+use super::{
+    generator::{Generator, MoveSink},
+    CrossChecks,
+};
+
+/// Reusable storage for generated moves, see [`generate_into`]. The tiles of all moves share one
+/// vector, so once the buffer has grown to fit a position, generating moves again doesn't
+/// allocate per move.
+#[derive(Debug, Clone, Default)]
+pub struct MoveBuffer {
+    tiles: Vec<MoveTile>,
+    moves: Vec<(Location, Direction, Range<usize>)>,
+}
+
+/// A move stored in a [`MoveBuffer`], borrowing its tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveView<'a> {
+    location: Location,
+    direction: Direction,
+    tiles: &'a [MoveTile],
+}
+
+impl MoveBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Removes all moves, keeping the storage.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.moves.clear();
+    }
+
+    pub fn get(&self, i: usize) -> Option<MoveView<'_>> {
+        let (location, direction, range) = self.moves.get(i)?;
+        Some(MoveView {
+            location: *location,
+            direction: *direction,
+            tiles: &self.tiles[range.clone()],
+        })
+    }
+
+    /// The moves in the order they were generated in.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = MoveView<'_>> + '_ {
+        self.moves
+            .iter()
+            .map(|(location, direction, range)| MoveView {
+                location: *location,
+                direction: *direction,
+                tiles: &self.tiles[range.clone()],
+            })
+    }
+}
+
+impl MoveSink for MoveBuffer {
+    fn push(&mut self, location: Location, direction: Direction, tiles: &[MoveTile]) {
+        let start = self.tiles.len();
+        self.tiles.extend_from_slice(tiles);
+        self.moves
+            .push((location, direction, start..self.tiles.len()));
+    }
+}
+
+impl<'a> MoveView<'a> {
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Every square of the main word, see [`Move::tiles`].
+    pub fn tiles(&self) -> &'a [MoveTile] {
+        self.tiles
+    }
+
+    /// The squares that receive a tile from the rack.
+    pub fn placed(&self) -> impl Iterator<Item = (Location, TilePlacement)> + 'a {
+        self.location
+            .ray(self.direction)
+            .zip(self.tiles)
+            .filter_map(|(l, t)| match t {
+                MoveTile::Placed(p) => Some((l, *p)),
+                MoveTile::OnBoard(_) => None,
+            })
+    }
+
+    /// Copies the move out of the buffer.
+    pub fn to_move(&self) -> Move {
+        Move::new(self.location, self.direction, self.tiles.to_vec())
+            .expect("Generated moves are valid")
+    }
+}
+
+/// Replaces the contents of `buffer` with every legal move for `rack` on `board`, in the same order
+/// as [`iter_moves`](super::iter_moves).
+///
+/// Only setting up the generator allocates, for the cross checks of the position; the moves
+/// themselves go into `buffer`'s storage.
+pub fn generate_into<BB: Bitboard>(
+    buffer: &mut MoveBuffer,
+    board: &Board<BB>,
+    rack: &Rack,
+    lexicon: &Lexicon,
+) {
+    generate_with(buffer, Generator::new(board, rack, lexicon));
+}
+
+/// Like [`generate_into`], but with the `cross_checks` of `board` already at hand instead of computing
+/// them. Searches that play moves one after another can keep them current with
+/// [`CrossChecks::update`] instead of recomputing them for every position.
+pub fn generate_into_with<BB: Bitboard>(
+    buffer: &mut MoveBuffer,
+    board: &Board<BB>,
+    rack: &Rack,
+    lexicon: &Lexicon,
+    cross_checks: Arc<CrossChecks<BB>>,
+) {
+    generate_with(
+        buffer,
+        Generator::with_cross_checks(board, rack, lexicon, cross_checks),
+    );
+}
+
+pub(super) fn generate_with<BB: Bitboard>(
+    buffer: &mut MoveBuffer,
+    mut generator: Generator<'_, BB>,
+) {
+    buffer.clear();
+    for (anchor, direction, limit) in generator.anchors() {
+        generator.generate(anchor, direction, limit, buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, movegen::iter_moves, TileMap};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn same_moves_as_iterator() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "ACT", "AT", "TA", "SAT", "AS"], &tiles);
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        let rack = Rack::parse("AST?", &tiles).unwrap();
+
+        let mut buffer = MoveBuffer::new();
+        generate_into(&mut buffer, &board, &rack, &lexicon);
+        let expected: Vec<_> = iter_moves(&board, &rack, &lexicon).collect();
+        assert_eq!(buffer.len(), expected.len());
+        assert_eq!(
+            buffer.iter().map(|m| m.to_move()).collect::<Vec<_>>(),
+            expected
+        );
+        let first = buffer.get(0).unwrap();
+        assert_eq!(first.placed().count(), expected[0].num_placed());
+        assert_eq!(buffer.get(buffer.len()), None);
+
+        // Generating again reuses the storage.
+        let capacity = (buffer.tiles.capacity(), buffer.moves.capacity());
+        generate_into(&mut buffer, &board, &rack, &lexicon);
+        assert_eq!(buffer.len(), expected.len());
+        assert_eq!((buffer.tiles.capacity(), buffer.moves.capacity()), capacity);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Location, Move, Row},
+    lexicon::{AlphagramIndex, Lexicon, WordCursor},
+    TileMapIdx,
+};
+
+/// Which tiles can go on which empty squares without forming an invalid cross word, for words in
+/// both directions. The move generator computes these once per position, or keeps them up to date
+/// move by move, see [`CrossChecks::update`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossChecks<BB: Bitboard> {
+    horizontal: DirectionChecks<BB>,
+    vertical: DirectionChecks<BB>,
+    /// Whether these are [Clabbers checks](CrossChecks::clabbers), to be updated as such.
+    clabbers: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DirectionChecks<BB: Bitboard> {
+    /// Empty squares without tiles across them, where any tile goes.
+    unconstrained: BB,
+    /// Indexed by tile: the other empty squares where the cross word it forms is valid.
+    valid: Vec<BB>,
+}
+
+impl<BB: Bitboard> CrossChecks<BB> {
+    pub fn new(board: &Board<BB>, lexicon: &Lexicon) -> Self {
+        Self::from_root(board, lexicon.cursor(), lexicon.num_tiles(), false)
+    }
+
+    /// The checks for [Clabbers](crate::rules::Variant::Clabbers), where a cross word only has to
+    /// be an anagram of a word in `index`.
+    pub fn clabbers(board: &Board<BB>, index: &AlphagramIndex) -> Self {
+        Self::from_root(board, index.cursor(), index.num_tiles(), true)
+    }
+
+    fn from_root<'a>(
+        board: &Board<BB>,
+        root: impl WordCursor<'a>,
+        num_tiles: usize,
+        clabbers: bool,
+    ) -> Self {
+        Self {
+            horizontal: DirectionChecks::new(board, root, num_tiles, Direction::Horizontal),
+            vertical: DirectionChecks::new(board, root, num_tiles, Direction::Vertical),
+            clabbers,
+        }
+    }
+
+    /// Brings the checks up to date after `m` was applied to `board`, recomputing only the squares
+    /// whose cross words it can have changed: the columns it placed tiles in for horizontal words,
+    /// and the rows for vertical ones. Much cheaper than [`CrossChecks::new`] on a full board.
+    ///
+    /// Panics if these are [Clabbers checks](CrossChecks::clabbers), which
+    /// [`CrossChecks::update_clabbers`] updates instead.
+    pub fn update(&mut self, board: &Board<BB>, lexicon: &Lexicon, m: &Move) {
+        assert!(
+            !self.clabbers,
+            "Clabbers checks are updated with update_clabbers"
+        );
+        self.update_from(board, lexicon.cursor(), m);
+    }
+
+    /// Like [`CrossChecks::update`], for [Clabbers checks](CrossChecks::clabbers). `index` has to
+    /// be the one they were built with.
+    ///
+    /// Panics if these aren't Clabbers checks.
+    pub fn update_clabbers(&mut self, board: &Board<BB>, index: &AlphagramIndex, m: &Move) {
+        assert!(
+            self.clabbers,
+            "Only Clabbers checks are updated with update_clabbers"
+        );
+        self.update_from(board, index.cursor(), m);
+    }
+
+    fn update_from<'a>(&mut self, board: &Board<BB>, root: impl WordCursor<'a>, m: &Move) {
+        let mut rows = [0; 16];
+        let mut columns = 0;
+        for (l, _) in m.placed() {
+            rows[l.row().as_idx()] = (1 << Row::MAX) - 1;
+            columns |= 1 << l.column().as_idx();
+        }
+        let mut column_rows = [columns; 16];
+        column_rows[usize::from(Row::MAX)] = 0;
+        self.horizontal
+            .recompute(board, root, Direction::Horizontal, BB::new_raw(column_rows));
+        self.vertical
+            .recompute(board, root, Direction::Vertical, BB::new_raw(rows));
+    }
+
+    fn checks(&self, direction: Direction) -> &DirectionChecks<BB> {
+        match direction {
+            Direction::Horizontal => &self.horizontal,
+            Direction::Vertical => &self.vertical,
+        }
+    }
+
+    /// Whether `tile` can go on the empty square `l` as part of a word in `direction`.
+    pub fn allows(&self, direction: Direction, tile: TileMapIdx, l: Location) -> bool {
+        self.allowed(direction, tile).is_set(l)
+    }
+
+    /// The empty squares `tile` can go on as part of a word in `direction`.
+    pub fn allowed(&self, direction: Direction, tile: TileMapIdx) -> BB {
+        let checks = self.checks(direction);
+        checks.unconstrained
+            | checks
+                .valid
+                .get(tile.as_usize())
+                .copied()
+                .unwrap_or(BB::empty())
+    }
+
+    /// The empty squares at least one tile can go on as part of a word in `direction`.
+    pub fn playable(&self, direction: Direction) -> BB {
+        let checks = self.checks(direction);
+        checks
+            .valid
+            .iter()
+            .fold(checks.unconstrained, |playable, valid| playable | *valid)
+    }
+
+    /// For each tile, the squares where placing it on its own forms only valid words, at least one.
+    pub fn hook_map(&self) -> Vec<BB> {
+        let isolated = self.horizontal.unconstrained & self.vertical.unconstrained;
+        (0..self.horizontal.valid.len())
+            .map(|t| {
+                let t = TileMapIdx(t as u8);
+                self.allowed(Direction::Horizontal, t)
+                    & self.allowed(Direction::Vertical, t)
+                    & !isolated
+            })
+            .collect()
+    }
+}
+
+/// For each tile, indexed by [`TileMapIdx`], the squares of `board` where placing it forms valid
+/// cross words, e.g. to show hooks as overlays. See [`CrossChecks::hook_map`].
+pub fn hook_map<BB: Bitboard>(board: &Board<BB>, lexicon: &Lexicon) -> Vec<BB> {
+    CrossChecks::new(board, lexicon).hook_map()
+}
+
+impl<BB: Bitboard> DirectionChecks<BB> {
+    fn new<'a>(
+        board: &Board<BB>,
+        root: impl WordCursor<'a>,
+        num_tiles: usize,
+        direction: Direction,
+    ) -> Self {
+        let mut checks = Self {
+            unconstrained: BB::empty(),
+            valid: vec![BB::empty(); num_tiles],
+        };
+        checks.recompute(board, root, direction, BB::full());
+        checks
+    }
+
+    /// Recomputes the checks of the squares in `dirty`, leaving the others as they are.
+    fn recompute<'a>(
+        &mut self,
+        board: &Board<BB>,
+        root: impl WordCursor<'a>,
+        direction: Direction,
+        dirty: BB,
+    ) {
+        let occupied = board.occupied();
+        let neighbours = match direction {
+            Direction::Horizontal => occupied.up(1) | occupied.down(1),
+            Direction::Vertical => occupied.left(1) | occupied.right(1),
+        };
+        let empty = !occupied & dirty & BB::full();
+        self.unconstrained = (self.unconstrained & !dirty) | (empty & !neighbours);
+        for valid in &mut self.valid {
+            *valid &= !dirty;
+        }
+        let across = direction.perpendicular();
+        for l in (empty & neighbours).locations() {
+            let before = cross_run(board, l, across, -1);
+            let after = cross_run(board, l, across, 1);
+            let Some(prefix) = root.walk(&before.iter().rev().copied().collect::<Vec<_>>()) else {
+                continue;
+            };
+            for (tile, cursor) in prefix.children() {
+                if cursor.walk(&after).is_some_and(|c| c.is_terminal()) {
+                    self.valid[tile.as_usize()] |= BB::for_location(l);
+                }
+            }
+        }
+    }
+}
+
+/// The tiles on the board going away from `l` in steps of `step` along `direction`, nearest first.
+pub(crate) fn cross_run<BB: Bitboard>(
+    board: &Board<BB>,
+    l: Location,
+    direction: Direction,
+    step: isize,
+) -> Vec<TileMapIdx> {
+    std::iter::successors(l.offset(direction, step), |s| s.offset(direction, step))
+        .map_while(|s| board.tile_at(s))
+        .collect()
+}
+
+/// Whether the square next to `l` along `direction` holds a tile, on either side.
+pub(crate) fn has_neighbour<BB: Bitboard>(
+    board: &Board<BB>,
+    l: Location,
+    direction: Direction,
+) -> bool {
+    [-1, 1]
+        .into_iter()
+        .filter_map(|step| l.offset(direction, step))
+        .any(|s| board.tile_at(s).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        movegen::{clabbers_moves, iter_moves},
+        Rack, TileMap,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hooks() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "CATS", "SCAT", "AT", "TA", "AS"], &tiles);
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let map = hook_map(&board, &lexicon);
+        assert!(map.iter().all(|b| b.is_empty()));
+
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        let map = hook_map(&board, &lexicon);
+        let squares = |letter: &str| -> Vec<String> {
+            map[tiles.find(letter).unwrap().as_usize()]
+                .locations()
+                .map(|l| l.to_string())
+                .collect()
+        };
+        // SCAT, CATS, and AS down from the A.
+        assert_eq!(squares("S"), ["F8", "J8", "H9"]);
+        // TA and AT with the A, AT and TA with the T.
+        assert_eq!(squares("T"), ["H7", "H9"]);
+        assert_eq!(squares("A"), ["I7", "I9"]);
+        assert!(squares("Q").is_empty());
+    }
+
+    #[test]
+    fn updates_match_recomputing() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "AT", "TA", "AS", "CAT", "CATS", "ACT", "ACTS", "SCAT", "TAS", "SAT", "EAT", "TEA",
+                "ETA", "SEAT", "EATS", "TEAS", "ATE", "SEA", "TEE",
+            ],
+            &tiles,
+        );
+        let rack = Rack::parse("ACEST?", &tiles).unwrap();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let mut checks = CrossChecks::new(&board, &lexicon);
+        for turn in 0..8 {
+            // Vary the moves so they go both ways and through earlier ones.
+            let moves: Vec<_> = iter_moves(&board, &rack, &lexicon).collect();
+            let Some(m) = moves.get(turn * 7 % moves.len().max(1)) else {
+                break;
+            };
+            board.apply(m).unwrap();
+            checks.update(&board, &lexicon, m);
+            assert_eq!(checks, CrossChecks::new(&board, &lexicon), "After {m:?}");
+        }
+        assert!(board.occupied().count_ones() > 12);
+    }
+
+    #[test]
+    fn clabbers_updates_match_recomputing() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            ["AT", "CAT", "ACTS", "SEAT", "TEAS", "ATE", "TEE", "EAST"],
+            &tiles,
+        );
+        let index = lexicon.build_alphagram_index();
+        let rack = Rack::parse("ACEST?", &tiles).unwrap();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let mut checks = CrossChecks::clabbers(&board, &index);
+        let mut updated = 0;
+        for turn in 0..8 {
+            let moves: Vec<_> = clabbers_moves(&board, &rack, &lexicon, &index).collect();
+            let Some(m) = moves.get(turn * 7 % moves.len().max(1)) else {
+                break;
+            };
+            board.apply(m).unwrap();
+            checks.update_clabbers(&board, &index, m);
+            assert_eq!(checks, CrossChecks::clabbers(&board, &index), "After {m:?}");
+            updated += 1;
+        }
+        assert!(updated > 3);
+        // Only anagrams of the words are in the lexicon, so ordered checks would allow less.
+        assert_ne!(
+            checks.hook_map(),
+            CrossChecks::new(&board, &lexicon).hook_map()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Location, MoveTile},
+    score::BINGO_TILES,
+    tiles::TileCounts,
+    TileMapIdx,
+};
+
+/// Restricts generation to moves of interest, see [`filtered_moves`](super::filtered_moves).
+/// Anchors that can't lead to a matching move are skipped rather than generated and dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveFilter {
+    covering: Option<Location>,
+    using: Option<TileMapIdx>,
+    bingos: bool,
+    literal: bool,
+}
+
+impl MoveFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only moves placing a tile on `l`, e.g. to see what reaches a triple word square.
+    pub fn covering(mut self, l: Location) -> Self {
+        self.covering = Some(l);
+        self
+    }
+
+    /// Only moves placing `tile` from the rack, not a blank standing in for it.
+    pub fn using(mut self, tile: TileMapIdx) -> Self {
+        self.using = Some(tile);
+        self
+    }
+
+    /// Only moves placing [`BINGO_TILES`] tiles or more.
+    pub fn bingos(mut self) -> Self {
+        self.bingos = true;
+        self
+    }
+
+    /// Generates every literal placement. Otherwise, on an empty board whose premiums are
+    /// symmetric along the diagonal, only horizontal moves are generated, as each vertical one is
+    /// the mirror image of a horizontal one and scores the same.
+    pub fn literal(mut self) -> Self {
+        self.literal = true;
+        self
+    }
+
+    /// Whether moves in `direction` on `board` can be left out as mirror images of moves in the
+    /// other direction, see [`MoveFilter::literal`].
+    pub(crate) fn skips_mirrored<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        direction: Direction,
+    ) -> bool {
+        let premiums = board.premiums();
+        direction == Direction::Vertical
+            && !self.literal
+            // Covering a square off the diagonal breaks the symmetry.
+            && self
+                .covering
+                .is_none_or(|l| l.row().as_idx() == l.column().as_idx())
+            && board.occupied().is_empty()
+            && premiums.transposed() == *premiums
+    }
+
+    /// Whether any move on `board` from `rack` can match.
+    pub(crate) fn allows_position<BB: Bitboard>(
+        &self,
+        board: &Board<BB>,
+        rack: &TileCounts,
+    ) -> bool {
+        self.covering.is_none_or(|l| board.tile_at(l).is_none())
+            && self.using.is_none_or(|tile| rack.get(tile) > 0)
+            && (!self.bingos || rack.total() >= BINGO_TILES)
+    }
+
+    /// Whether a move in `direction` anchored at `anchor` can match.
+    pub(crate) fn allows_anchor(&self, anchor: Location, direction: Direction) -> bool {
+        self.covering.is_none_or(|l| match direction {
+            Direction::Horizontal => l.row() == anchor.row(),
+            Direction::Vertical => l.column() == anchor.column(),
+        })
+    }
+
+    /// Whether a word starting at `start` that has reached `square` without matching can't match
+    /// anymore, because it went past the square it has to cover.
+    pub(crate) fn passed(
+        &self,
+        start: Location,
+        direction: Direction,
+        square: Location,
+        word: &[MoveTile],
+    ) -> bool {
+        let Some(target) = self.covering else {
+            return false;
+        };
+        let index = |l: Location| match direction {
+            Direction::Horizontal => (l.column().as_idx(), l.row() == start.row()),
+            Direction::Vertical => (l.row().as_idx(), l.column() == start.column()),
+        };
+        let (target_idx, on_line) = index(target);
+        let (start_idx, square_idx) = (index(start).0, index(square).0);
+        on_line
+            && target_idx < square_idx
+            && target_idx
+                .checked_sub(start_idx)
+                .and_then(|i| word.get(i))
+                .is_none_or(|t| matches!(t, MoveTile::OnBoard(_)))
+    }
+
+    /// Whether the move in `direction` starting at `start` with `tiles` matches.
+    pub(crate) fn accepts(
+        &self,
+        start: Location,
+        direction: Direction,
+        tiles: &[MoveTile],
+    ) -> bool {
+        let placed = || {
+            start
+                .ray(direction)
+                .zip(tiles)
+                .filter_map(|(l, t)| match t {
+                    MoveTile::Placed(p) => Some((l, *p)),
+                    MoveTile::OnBoard(_) => None,
+                })
+        };
+        self.covering
+            .is_none_or(|target| placed().any(|(l, _)| l == target))
+            && self
+                .using
+                .is_none_or(|tile| placed().any(|(_, p)| p.tile == tile && !p.is_blank))
+            && (!self.bingos || placed().count() >= BINGO_TILES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::Move,
+        lexicon::Lexicon,
+        movegen::{filtered_moves, iter_moves},
+        Rack, TileMap,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn same_as_filtering_everything() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "CAT", "CATS", "SCAT", "ACT", "ACTS", "AT", "AS", "TA", "SAT", "QAT", "QATS",
+                "STAT",
+            ],
+            &tiles,
+        );
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        let rack = Rack::parse("QAST?", &tiles).unwrap();
+        let q = tiles.find("Q").unwrap();
+        let all: Vec<_> = iter_moves(&board, &rack, &lexicon).collect();
+
+        for target in ["J8", "G9", "H7", "I10"] {
+            let target: Location = target.parse().unwrap();
+            let filter = MoveFilter::new().covering(target);
+            let expected: Vec<_> = all
+                .iter()
+                .filter(|m| m.placed().any(|(l, _)| l == target))
+                .cloned()
+                .collect();
+            assert!(!expected.is_empty(), "{target}");
+            assert_eq!(
+                filtered_moves(&board, &rack, &lexicon, filter).collect::<Vec<_>>(),
+                expected
+            );
+        }
+        let filter = MoveFilter::new().covering("H8".parse().unwrap());
+        assert_eq!(filtered_moves(&board, &rack, &lexicon, filter).count(), 0);
+
+        let with_q: Vec<_> =
+            filtered_moves(&board, &rack, &lexicon, MoveFilter::new().using(q)).collect();
+        assert!(!with_q.is_empty());
+        assert_eq!(
+            with_q,
+            all.iter()
+                .filter(|m| m.placed().any(|(_, p)| p.tile == q && !p.is_blank))
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        let no_q = Rack::parse("AST?", &tiles).unwrap();
+        assert_eq!(
+            filtered_moves(&board, &no_q, &lexicon, MoveFilter::new().using(q)).count(),
+            0
+        );
+        assert_eq!(
+            filtered_moves(&board, &rack, &lexicon, MoveFilter::new().bingos()).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn bingos() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["RETAINS", "NASTIER", "RETINA", "AT"], &tiles);
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let rack = Rack::parse("AEINRST", &tiles).unwrap();
+        let bingos = filtered_moves(&board, &rack, &lexicon, MoveFilter::new().bingos());
+        // Each of the two words seven ways across the center.
+        assert_eq!(bingos.count(), 2 * 7);
+        let filter = MoveFilter::new().bingos().literal();
+        // And down through it.
+        assert_eq!(
+            filtered_moves(&board, &rack, &lexicon, filter).count(),
+            2 * 7 * 2
+        );
+    }
+}
@@ -0,0 +1,578 @@
+use std::sync::Arc;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Location, Move, MoveTile, Row, TilePlacement},
+    lexicon::{AlphagramCursor, AlphagramIndex, Lexicon, LexiconCursor, PackedDawg, WordCursor},
+    tiles::TileCounts,
+    Rack, TileMapIdx,
+};
+
+use super::{
+    anchors_and_limits,
+    cross_checks::{has_neighbour, CrossChecks},
+    MoveFilter,
+};
+
+/// Where the generator puts the moves it finds.
+pub(crate) trait MoveSink {
+    fn push(&mut self, location: Location, direction: Direction, tiles: &[MoveTile]);
+}
+
+impl MoveSink for Vec<Move> {
+    fn push(&mut self, location: Location, direction: Direction, tiles: &[MoveTile]) {
+        self.push(
+            Move::new(location, direction, tiles.to_vec())
+                .expect("Generated moves are on the board"),
+        );
+    }
+}
+
+/// Generates the moves anchored at one anchor at a time, after Appel and Jacobson: the part of the
+/// word before the anchor is placed on empty squares that aren't anchors themselves, so every move
+/// is found exactly once, from the first anchor it covers.
+///
+/// Words are followed with `C`, in order from a [`LexiconCursor`], or in any order from an
+/// [`AlphagramCursor`] for Clabbers, where the rack and board tiles only have to add up to a word.
+///
+/// Clones share the cross checks, so each thread can have its own.
+#[derive(Clone)]
+pub(crate) struct Generator<'a, BB: Bitboard, C = LexiconCursor<'a>> {
+    board: &'a Board<BB>,
+    root: C,
+    cross_checks: Arc<CrossChecks<BB>>,
+    rack: TileCounts,
+    blank: Option<TileMapIdx>,
+    filter: MoveFilter,
+    /// The main word so far, reused across moves.
+    word: Vec<MoveTile>,
+}
+
+impl<'a, BB: Bitboard> Generator<'a, BB> {
+    pub fn new(board: &'a Board<BB>, rack: &Rack, lexicon: &'a Lexicon) -> Self {
+        Self::with_cross_checks(
+            board,
+            rack,
+            lexicon,
+            Arc::new(CrossChecks::new(board, lexicon)),
+        )
+    }
+
+    /// Uses `cross_checks` of `board` instead of computing them, e.g. kept up to date with
+    /// [`CrossChecks::update`].
+    pub fn with_cross_checks(
+        board: &'a Board<BB>,
+        rack: &Rack,
+        lexicon: &'a Lexicon,
+        cross_checks: Arc<CrossChecks<BB>>,
+    ) -> Self {
+        Self::from_root(board, rack, lexicon.cursor(), lexicon.blank(), cross_checks)
+    }
+
+    /// Follows words in `packed`, the [packed](crate::lexicon::Dawg::packed) DAWG of `lexicon`.
+    pub fn packed(
+        board: &'a Board<BB>,
+        rack: &Rack,
+        lexicon: &'a Lexicon,
+        packed: &'a PackedDawg,
+    ) -> Self {
+        Self::from_root(
+            board,
+            rack,
+            packed.cursor(),
+            lexicon.blank(),
+            Arc::new(CrossChecks::new(board, lexicon)),
+        )
+    }
+}
+
+impl<'a, BB: Bitboard> Generator<'a, BB, AlphagramCursor<'a>> {
+    /// Generates Clabbers moves from `index`, built from `lexicon`.
+    pub fn clabbers(
+        board: &'a Board<BB>,
+        rack: &Rack,
+        lexicon: &'a Lexicon,
+        index: &'a AlphagramIndex,
+    ) -> Self {
+        Self::from_root(
+            board,
+            rack,
+            index.cursor(),
+            lexicon.blank(),
+            Arc::new(CrossChecks::clabbers(board, index)),
+        )
+    }
+}
+
+impl<'a, BB: Bitboard, C: WordCursor<'a>> Generator<'a, BB, C> {
+    fn from_root(
+        board: &'a Board<BB>,
+        rack: &Rack,
+        root: C,
+        blank: Option<TileMapIdx>,
+        cross_checks: Arc<CrossChecks<BB>>,
+    ) -> Self {
+        Self {
+            board,
+            root,
+            cross_checks,
+            rack: TileCounts::from(rack),
+            blank,
+            filter: MoveFilter::default(),
+            word: Vec::with_capacity(usize::from(Row::MAX)),
+        }
+    }
+
+    /// Only generates moves matching `filter`.
+    pub fn with_filter(mut self, filter: MoveFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Every anchor of the board in both directions with its limit, leaving out those the filter
+    /// rules out.
+    pub fn anchors(&self) -> Vec<(Location, Direction, usize)> {
+        if !self.filter.allows_position(self.board, &self.rack) {
+            return Vec::new();
+        }
+        let anchors = anchors_and_limits(self.board);
+        [Direction::Horizontal, Direction::Vertical]
+            .into_iter()
+            .flat_map(|d| anchors.iter(d).map(move |(l, limit)| (l, d, limit)))
+            .filter(|(l, d, _)| {
+                self.filter.allows_anchor(*l, *d) && !self.filter.skips_mirrored(self.board, *d)
+            })
+            .collect()
+    }
+
+    /// Adds the moves in `direction` whose first anchor is `anchor` to `out`.
+    pub fn generate(
+        &mut self,
+        anchor: Location,
+        direction: Direction,
+        limit: usize,
+        out: &mut impl MoveSink,
+    ) {
+        self.word.clear();
+        let occupied = |l: &Location| self.board.tile_at(*l).is_some();
+        if anchor.offset(direction, -1).is_some_and(|l| occupied(&l)) {
+            // The word has to start with the tiles right before the anchor.
+            let mut start = anchor;
+            while let Some(prev) = start.offset(direction, -1).filter(occupied) {
+                start = prev;
+            }
+            let mut cursor = self.root;
+            for l in start.ray(direction).take_while(|l| *l != anchor) {
+                let tile = self.board.tile_at(l).expect("Occupied before the anchor");
+                let Some(child) = cursor.child(tile) else {
+                    return;
+                };
+                cursor = child;
+                self.word.push(MoveTile::OnBoard(tile));
+            }
+            self.extend(cursor, start, direction, Some(anchor), false, out);
+        } else {
+            self.before_anchor(self.root, anchor, direction, limit, out);
+        }
+    }
+
+    /// Places the tiles of `self.word` right before `anchor`, then tries to extend it with up to
+    /// `limit` more before continuing from the anchor.
+    fn before_anchor(
+        &mut self,
+        cursor: C,
+        anchor: Location,
+        direction: Direction,
+        limit: usize,
+        out: &mut impl MoveSink,
+    ) {
+        let start = anchor
+            .offset(direction, -(self.word.len() as isize))
+            .expect("The limit keeps the word on the board");
+        self.extend(cursor, start, direction, Some(anchor), false, out);
+        if limit == 0 {
+            return;
+        }
+        for (tile, child) in cursor.children() {
+            // Squares before the anchor have no neighbours, so no cross checks apply.
+            self.with_placed(tile, |g| {
+                g.before_anchor(child, anchor, direction, limit - 1, out)
+            });
+        }
+    }
+
+    /// Continues the word starting at `start` on `square`, `None` past the edge of the board.
+    fn extend(
+        &mut self,
+        cursor: C,
+        start: Location,
+        direction: Direction,
+        square: Option<Location>,
+        covers_anchor: bool,
+        out: &mut impl MoveSink,
+    ) {
+        let next = |l: Location| l.offset(direction, 1);
+        if let Some(l) = square {
+            if self.filter.passed(start, direction, l, &self.word) {
+                return;
+            }
+            if let Some(tile) = self.board.tile_at(l) {
+                if let Some(child) = cursor.child(tile) {
+                    self.word.push(MoveTile::OnBoard(tile));
+                    self.extend(child, start, direction, next(l), covers_anchor, out);
+                    self.word.pop();
+                }
+                return;
+            }
+        }
+        if covers_anchor && cursor.is_terminal() {
+            self.record(start, direction, out);
+        }
+        let Some(l) = square else {
+            return;
+        };
+        for (tile, child) in cursor.children() {
+            if !self.cross_checks.allows(direction, tile, l) {
+                continue;
+            }
+            self.with_placed(tile, |g| {
+                g.extend(child, start, direction, next(l), true, out)
+            });
+        }
+    }
+
+    /// Calls `f` with `tile` from the rack added to the word, once as the tile itself and once as
+    /// a blank, as far as the rack has them.
+    fn with_placed(&mut self, tile: TileMapIdx, mut f: impl FnMut(&mut Self)) {
+        for (from_rack, is_blank) in [(Some(tile), false), (self.blank, true)] {
+            let Some(from_rack) = from_rack else {
+                continue;
+            };
+            if self.rack.remove(from_rack, 1) == 0 {
+                continue;
+            }
+            self.word
+                .push(MoveTile::Placed(TilePlacement { tile, is_blank }));
+            f(self);
+            self.word.pop();
+            self.rack.add(from_rack, 1);
+        }
+    }
+
+    fn record(&self, start: Location, direction: Direction, out: &mut impl MoveSink) {
+        if self.word.len() < 2 || !self.filter.accepts(start, direction, &self.word) {
+            return;
+        }
+        let mut placed = self
+            .word
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| matches!(t, MoveTile::Placed(_)));
+        let (first, _) = placed.next().expect("Records only after placing a tile");
+        if placed.next().is_none() && direction == Direction::Vertical {
+            // A single tile forming words both ways is found horizontally too.
+            let l = start
+                .offset(direction, first as isize)
+                .expect("On the board");
+            if has_neighbour(self.board, l, Direction::Horizontal) {
+                return;
+            }
+        }
+        out.push(start, direction, &self.word);
+    }
+}
+
+/// Every legal move for `rack` on `board`, generated one anchor at a time as the iterator is
+/// advanced, so taking only some of them doesn't pay for the rest.
+///
+/// Moves forming words in both directions with a single tile are generated once, horizontally.
+/// Opening moves usually are too, see [`MoveFilter::literal`] for every placement.
+pub fn iter_moves<'a, BB: Bitboard>(
+    board: &'a Board<BB>,
+    rack: &Rack,
+    lexicon: &'a Lexicon,
+) -> impl Iterator<Item = Move> + 'a {
+    moves_from(Generator::new(board, rack, lexicon))
+}
+
+/// Like [`iter_moves`], but only the moves matching `filter`.
+pub fn filtered_moves<'a, BB: Bitboard>(
+    board: &'a Board<BB>,
+    rack: &Rack,
+    lexicon: &'a Lexicon,
+    filter: MoveFilter,
+) -> impl Iterator<Item = Move> + 'a {
+    moves_from(Generator::new(board, rack, lexicon).with_filter(filter))
+}
+
+/// Like [`iter_moves`], following words in `packed`, the [packed](crate::lexicon::Dawg::packed)
+/// DAWG of `lexicon`, which is smaller and faster to walk for large lexicons.
+pub fn packed_moves<'a, BB: Bitboard>(
+    board: &'a Board<BB>,
+    rack: &Rack,
+    lexicon: &'a Lexicon,
+    packed: &'a PackedDawg,
+) -> impl Iterator<Item = Move> + 'a {
+    moves_from(Generator::packed(board, rack, lexicon, packed))
+}
+
+/// Every legal move for `rack` on `board` in [Clabbers](crate::rules::Variant::Clabbers), where
+/// the tiles of each word formed only have to be an anagram of a word of `lexicon`. `index` has to
+/// be built from `lexicon`, see [`Lexicon::build_alphagram_index`].
+pub fn clabbers_moves<'a, BB: Bitboard>(
+    board: &'a Board<BB>,
+    rack: &Rack,
+    lexicon: &'a Lexicon,
+    index: &'a AlphagramIndex,
+) -> impl Iterator<Item = Move> + 'a {
+    moves_from(Generator::clabbers(board, rack, lexicon, index))
+}
+
+fn moves_from<'a, BB: Bitboard, C: WordCursor<'a> + 'a>(
+    mut generator: Generator<'a, BB, C>,
+) -> impl Iterator<Item = Move> + 'a {
+    let mut anchors = generator.anchors().into_iter();
+    // Holds the moves of one anchor at a time, keeping its capacity between anchors.
+    let mut buffer = Vec::new();
+    std::iter::from_fn(move || loop {
+        if let Some(m) = buffer.pop() {
+            return Some(m);
+        }
+        let (anchor, direction, limit) = anchors.next()?;
+        generator.generate(anchor, direction, limit, &mut buffer);
+        // Popping reverses them, so keep the order they were found in.
+        buffer.reverse();
+    })
+}
+
+/// Like [`iter_moves`], but with the anchors split across rayon's threads. Moves come in the same
+/// order.
+#[cfg(feature = "rayon")]
+pub fn par_moves<BB: Bitboard + Send + Sync>(
+    board: &Board<BB>,
+    rack: &Rack,
+    lexicon: &Lexicon,
+) -> Vec<Move> {
+    use rayon::prelude::*;
+
+    let generator = Generator::new(board, rack, lexicon);
+    generator
+        .anchors()
+        .into_par_iter()
+        .map_with(generator, |generator, (anchor, direction, limit)| {
+            let mut moves = Vec::new();
+            generator.generate(anchor, direction, limit, &mut moves);
+            moves
+        })
+        .flatten_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{bitboard::BitboardImpl, board::Premiums, TileMap};
+    use pretty_assertions::assert_eq;
+
+    const WORDS: [&str; 14] = [
+        "AT", "TA", "AS", "CAT", "CATS", "ACT", "ACTS", "SCAT", "TAS", "SAT", "EAT", "TEA", "ETA",
+        "SEAT",
+    ];
+
+    /// Every move as a word of the lexicon placed anywhere, with each placed tile tried as a
+    /// blank, kept if it validates.
+    fn brute_force(board: &Board<BitboardImpl>, rack: &Rack, lexicon: &Lexicon) -> HashSet<Move> {
+        let mut moves = HashSet::new();
+        for start in BitboardImpl::full().locations() {
+            for direction in [Direction::Horizontal, Direction::Vertical] {
+                for word in lexicon.words() {
+                    let Some(squares) = (0..word.len())
+                        .map(|i| start.offset(direction, i as isize))
+                        .collect::<Option<Vec<_>>>()
+                    else {
+                        continue;
+                    };
+                    for blank in std::iter::once(None).chain((0..word.len()).map(Some)) {
+                        let tiles: Vec<_> = squares
+                            .iter()
+                            .zip(&word)
+                            .enumerate()
+                            .map(|(i, (l, t))| match board.tile_at(*l) {
+                                Some(existing) => MoveTile::OnBoard(existing),
+                                None => MoveTile::Placed(TilePlacement {
+                                    tile: *t,
+                                    is_blank: blank == Some(i),
+                                }),
+                            })
+                            .collect();
+                        if tiles.iter().zip(&word).any(|(m, t)| m.tile() != *t) {
+                            continue;
+                        }
+                        let Ok(m) = Move::new(start, direction, tiles) else {
+                            continue;
+                        };
+                        let single = m
+                            .placed()
+                            .map(|(l, _)| l)
+                            .next()
+                            .filter(|_| m.num_placed() == 1);
+                        if direction == Direction::Vertical
+                            && single
+                                .is_some_and(|l| has_neighbour(board, l, Direction::Horizontal))
+                        {
+                            continue;
+                        }
+                        if m.validate(board, rack, lexicon).is_ok() {
+                            moves.insert(m);
+                        }
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    fn check(board: &Board<BitboardImpl>, rack: &str, lexicon: &Lexicon, tiles: &TileMap) -> usize {
+        let rack = Rack::parse(rack, tiles).unwrap();
+        let generated: Vec<_> =
+            filtered_moves(board, &rack, lexicon, MoveFilter::new().literal()).collect();
+        let unique: HashSet<_> = generated.iter().cloned().collect();
+        assert_eq!(unique.len(), generated.len(), "Duplicate moves");
+        assert_eq!(unique, brute_force(board, &rack, lexicon));
+        generated.len()
+    }
+
+    #[test]
+    fn first_move() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "ACT", "AT", "TA"], &tiles);
+        let board = Board::standard(&tiles);
+        // Each three letter word three ways, each two letter word two ways, in both directions.
+        assert_eq!(check(&board, "CAT", &lexicon, &tiles), 2 * (3 + 3 + 2 + 2));
+        assert_eq!(check(&board, "QZ", &lexicon, &tiles), 0);
+        // Only across unless asked for every placement.
+        let rack = Rack::parse("CAT", &tiles).unwrap();
+        let moves: Vec<_> = iter_moves(&board, &rack, &lexicon).collect();
+        assert_eq!(moves.len(), 3 + 3 + 2 + 2);
+        assert!(moves.iter().all(|m| m.direction() == Direction::Horizontal));
+
+        // Not if the board isn't symmetric, here because the start is off the diagonal.
+        let empty = ".".repeat(usize::from(Row::MAX));
+        let rows = vec![empty.as_str(); usize::from(Row::MAX)];
+        let premiums = Premiums::from_rows(&rows, "G8".parse().unwrap()).unwrap();
+        let board = Board::<BitboardImpl>::new(&tiles, premiums);
+        assert_eq!(iter_moves(&board, &rack, &lexicon).count(), 2 * 10);
+
+        // A rack of 8 can reach 7 squares before the start.
+        let lexicon = Lexicon::from_words(["RETAINED"], &tiles);
+        let rack = Rack::from_tiles(tiles.tokenize("RETAINED").unwrap(), 8).unwrap();
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let starts: Vec<_> = iter_moves(&board, &rack, &lexicon)
+            .map(|m| m.location().to_string())
+            .collect();
+        assert_eq!(starts, ["H8", "G8", "F8", "E8", "D8", "C8", "B8", "A8"]);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(WORDS, &tiles);
+        let mut board = Board::standard(&tiles);
+        for m in ["8G CAT", "I7 E.A", "9E ETA"] {
+            let m = Move::parse(m, &board, &tiles).unwrap();
+            board.apply(&m).unwrap();
+        }
+        assert!(check(&board, "ACST", &lexicon, &tiles) > 0);
+        assert!(check(&board, "AES?", &lexicon, &tiles) > 0);
+        check(&board, "T", &lexicon, &tiles);
+    }
+
+    #[test]
+    fn lazy() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(WORDS, &tiles);
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let rack = Rack::parse("ACEST??", &tiles).unwrap();
+        let first: Vec<_> = iter_moves(&board, &rack, &lexicon).take(3).collect();
+        let all: Vec<_> = iter_moves(&board, &rack, &lexicon).collect();
+        assert_eq!(first, all[..3]);
+    }
+
+    #[test]
+    fn packed() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(WORDS, &tiles);
+        let packed = lexicon.dawg().packed().unwrap();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        for m in ["8G CAT", "I7 E.A"] {
+            let m = Move::parse(m, &board, &tiles).unwrap();
+            board.apply(&m).unwrap();
+        }
+        let rack = Rack::parse("AES?", &tiles).unwrap();
+        let moves: Vec<_> = packed_moves(&board, &rack, &lexicon, &packed).collect();
+        assert!(!moves.is_empty());
+        assert_eq!(
+            moves,
+            iter_moves(&board, &rack, &lexicon).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clabbers() {
+        fn orders(word: &str) -> Vec<String> {
+            if word.len() <= 1 {
+                return vec![word.to_owned()];
+            }
+            (0..word.len())
+                .flat_map(|i| {
+                    let rest = format!("{}{}", &word[..i], &word[i + 1..]);
+                    orders(&rest)
+                        .into_iter()
+                        .map(move |o| format!("{}{o}", &word[i..=i]))
+                })
+                .collect()
+        }
+
+        let tiles = TileMap::english();
+        let words = ["AT", "CAT", "SEAT", "EAST", "TEAS"];
+        let lexicon = Lexicon::from_words(words, &tiles);
+        let index = lexicon.build_alphagram_index();
+        // The same as standard play with every order of every word.
+        let every_order = Lexicon::from_words(words.iter().flat_map(|w| orders(w)), &tiles);
+        assert_eq!(every_order.len(), 2 + 6 + 24);
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        for m in ["8G TCA", "I7 E.S"] {
+            let m = Move::parse(m, &board, &tiles).unwrap();
+            board.apply(&m).unwrap();
+        }
+        for rack in ["ACST", "AES?"] {
+            let rack = Rack::parse(rack, &tiles).unwrap();
+            let moves: HashSet<_> = clabbers_moves(&board, &rack, &lexicon, &index).collect();
+            let expected: HashSet<_> = iter_moves(&board, &rack, &every_order).collect();
+            assert!(!moves.is_empty());
+            assert_eq!(moves, expected);
+            assert!(moves
+                .iter()
+                .all(|m| m.validate_clabbers(&board, &rack, &lexicon, &index).is_ok()));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(WORDS, &tiles);
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        for m in ["8G CAT", "I7 E.A"] {
+            let m = Move::parse(m, &board, &tiles).unwrap();
+            board.apply(&m).unwrap();
+        }
+        let rack = Rack::parse("ACEST?", &tiles).unwrap();
+        assert_eq!(
+            par_moves(&board, &rack, &lexicon),
+            iter_moves(&board, &rack, &lexicon).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! Move generation.
+
+mod anchors;
+mod blanks;
+mod buffer;
+mod cross_checks;
+mod filter;
+mod generator;
+mod perft;
+mod placements;
+mod top;
+
+pub use anchors::{anchors_and_limits, Anchors, MAX_LIMIT};
+pub use blanks::{assign_blanks, BlankAssignment};
+pub use buffer::{generate_into, generate_into_with, MoveBuffer, MoveView};
+pub use cross_checks::{hook_map, CrossChecks};
+pub use filter::MoveFilter;
+#[cfg(feature = "rayon")]
+pub use generator::par_moves;
+pub use generator::{clabbers_moves, filtered_moves, iter_moves, packed_moves};
+pub use perft::{perft, perft_positions, Position};
+pub use placements::placements_of;
+pub use top::{leave, top_moves, Equity, RankedMove, Ranker, RawScore};
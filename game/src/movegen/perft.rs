@@ -0,0 +1,171 @@
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Move, Player},
+    lexicon::Lexicon,
+    Rack, TileMapIdx,
+};
+
+use super::{buffer::generate_with, generator::Generator, CrossChecks, MoveBuffer, MoveFilter};
+
+/// Each square's tile and whether it's a blank, then the racks.
+type PositionKey = (Vec<Option<(TileMapIdx, bool)>>, [Vec<TileMapIdx>; 2]);
+
+/// A board with both players' racks, see [`perft`]. Racks aren't refilled, so perft counts plays
+/// without depending on draws from the bag.
+#[derive(Debug, Clone)]
+pub struct Position<BB: Bitboard> {
+    pub board: Board<BB>,
+    pub racks: [Rack; 2],
+    pub to_move: Player,
+}
+
+impl<BB: Bitboard> Position<BB> {
+    pub fn rack(&self, player: Player) -> &Rack {
+        match player {
+            Player::First => &self.racks[0],
+            Player::Second => &self.racks[1],
+        }
+    }
+
+    /// The position after the player to move plays `m`, taking its tiles from their rack.
+    fn play(&self, m: &Move, blank: Option<TileMapIdx>) -> Self {
+        let mut next = self.clone();
+        next.board.apply(m).expect("Generated moves fit the board");
+        let rack = match self.to_move {
+            Player::First => &mut next.racks[0],
+            Player::Second => &mut next.racks[1],
+        };
+        for (_, p) in m.placed() {
+            let t = if p.is_blank { blank } else { Some(p.tile) };
+            rack.remove(t.expect("Blanks come from the rack"))
+                .expect("Generated moves use tiles from the rack");
+        }
+        next.to_move = self.to_move.other();
+        next
+    }
+
+    /// Everything that tells positions apart.
+    fn key(&self) -> PositionKey {
+        let board = BB::full()
+            .locations()
+            .map(|l| Some((self.board.tile_at(l)?, self.board.is_blank(l))))
+            .collect();
+        (board, self.racks.clone().map(|r| r.tiles().to_vec()))
+    }
+}
+
+/// Counts the sequences of `depth` tile placements from `position`, the players taking turns.
+/// Exchanges and passes aren't counted, as they're always available, but mirrored opening moves
+/// are, see [`MoveFilter::literal`].
+///
+/// Like perft in chess engines, this is for catching move generator regressions: the counts for
+/// fixed positions only change if the generator does.
+pub fn perft<BB: Bitboard>(position: &Position<BB>, lexicon: &Lexicon, depth: usize) -> u64 {
+    let mut buffers = vec![MoveBuffer::new(); depth];
+    let cross_checks = Arc::new(CrossChecks::new(&position.board, lexicon));
+    count(position, lexicon, cross_checks, &mut buffers)
+}
+
+/// Counts with the cross checks of `position` kept up to date move by move.
+fn count<BB: Bitboard>(
+    position: &Position<BB>,
+    lexicon: &Lexicon,
+    cross_checks: Arc<CrossChecks<BB>>,
+    buffers: &mut [MoveBuffer],
+) -> u64 {
+    let Some((buffer, deeper)) = buffers.split_first_mut() else {
+        return 1;
+    };
+    let generator = Generator::with_cross_checks(
+        &position.board,
+        position.rack(position.to_move),
+        lexicon,
+        cross_checks.clone(),
+    );
+    generate_with(buffer, generator.with_filter(MoveFilter::new().literal()));
+    if deeper.is_empty() {
+        return buffer.len() as u64;
+    }
+    buffer
+        .iter()
+        .map(|m| {
+            let m = m.to_move();
+            let next = position.play(&m, lexicon.blank());
+            let mut checks = (*cross_checks).clone();
+            checks.update(&next.board, lexicon, &m);
+            count(&next, lexicon, Arc::new(checks), deeper)
+        })
+        .sum()
+}
+
+/// The number of distinct positions after `depth` tile placements from `position`. Lower than
+/// [`perft`] when different orders of plays lead to the same board and racks.
+pub fn perft_positions<BB: Bitboard>(
+    position: &Position<BB>,
+    lexicon: &Lexicon,
+    depth: usize,
+) -> usize {
+    let mut frontier = vec![position.clone()];
+    for _ in 0..depth {
+        let mut seen = HashSet::new();
+        let mut next = Vec::new();
+        let mut buffer = MoveBuffer::new();
+        for p in &frontier {
+            let generator = Generator::new(&p.board, p.rack(p.to_move), lexicon);
+            generate_with(
+                &mut buffer,
+                generator.with_filter(MoveFilter::new().literal()),
+            );
+            for m in buffer.iter() {
+                let after = p.play(&m.to_move(), lexicon.blank());
+                if seen.insert(after.key()) {
+                    next.push(after);
+                }
+            }
+        }
+        frontier = next;
+    }
+    frontier.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, TileMap};
+    use pretty_assertions::assert_eq;
+
+    fn position(first: &str, second: &str, tiles: &TileMap) -> Position<BitboardImpl> {
+        Position {
+            board: Board::standard(tiles),
+            racks: [
+                Rack::parse(first, tiles).unwrap(),
+                Rack::parse(second, tiles).unwrap(),
+            ],
+            to_move: Player::First,
+        }
+    }
+
+    #[test]
+    fn reference_counts() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "AT", "TA", "AS", "CAT", "CATS", "ACT", "SCAT", "TAS", "SAT", "EAT", "TEA", "ETA",
+            ],
+            &tiles,
+        );
+        let start = position("CAT", "SEA?", &tiles);
+        let counts: Vec<_> = (0..=3).map(|d| perft(&start, &lexicon, d)).collect();
+        // Checked against validating every placement of every word.
+        assert_eq!(counts, [1, 20, 1258, 320]);
+        let positions: Vec<_> = (0..=3)
+            .map(|d| perft_positions(&start, &lexicon, d))
+            .collect();
+        // Fewer than the perft counts, as different sequences of plays can put the same tiles on the
+        // same squares: 8G AT then an A from the second rack on H7 leaves the same board and racks
+        // as H7 AT then an A on G8.
+        assert_eq!(positions, [1, 20, 1248, 308]);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Move, MoveTile, TilePlacement},
+    lexicon::Lexicon,
+    tiles::TileCounts,
+    Rack, TileMap,
+};
+
+use super::assign_blanks;
+
+/// Every legal move for `rack` on `board` whose main word is `word`, e.g. to answer "can I play
+/// QUIXOTIC anywhere?". Words not in `lexicon` have none.
+///
+/// Each position and direction is listed once, using tiles from the rack before blanks, and the
+/// blanks where they cost the fewest points, see [`assign_blanks`]. Moves come row by row from
+/// where they start, horizontal before vertical.
+pub fn placements_of<BB: Bitboard>(
+    word: &str,
+    board: &Board<BB>,
+    rack: &Rack,
+    lexicon: &Lexicon,
+    tiles: &TileMap,
+) -> Vec<Move> {
+    let Some(word) = lexicon.tokenize(word).filter(|w| lexicon.contains(w)) else {
+        return Vec::new();
+    };
+    let mut moves = Vec::new();
+    for start in BB::full().locations() {
+        for direction in [Direction::Horizontal, Direction::Vertical] {
+            if start.offset(direction, word.len() as isize - 1).is_none() {
+                continue;
+            }
+            let mut available = TileCounts::from(rack);
+            let move_tiles: Option<Vec<_>> = start
+                .ray(direction)
+                .zip(&word)
+                .map(|(l, tile)| match board.tile_at(l) {
+                    Some(existing) => (existing == *tile).then_some(MoveTile::OnBoard(existing)),
+                    None if available.remove(*tile, 1) == 1 => {
+                        Some(MoveTile::Placed(TilePlacement {
+                            tile: *tile,
+                            is_blank: false,
+                        }))
+                    }
+                    None => {
+                        let blank = lexicon.blank()?;
+                        (available.remove(blank, 1) == 1).then_some(MoveTile::Placed(
+                            TilePlacement {
+                                tile: *tile,
+                                is_blank: true,
+                            },
+                        ))
+                    }
+                })
+                .collect();
+            let Some(m) = move_tiles.and_then(|t| Move::new(start, direction, t).ok()) else {
+                continue;
+            };
+            if m.validate(board, rack, lexicon).is_ok() {
+                moves.push(assign_blanks(board, &m, rack, tiles).map_or(m, |best| best.m));
+            }
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, TileMap};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn everywhere_a_word_fits() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "QUIXOTIC", "QI", "CAT", "AT", "TA", "XI", "AX", "TI", "ZIZIZ",
+            ],
+            &tiles,
+        );
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let notation =
+            |moves: Vec<Move>| -> Vec<_> { moves.iter().map(|m| m.notation(&tiles)).collect() };
+
+        let rack = Rack::parse("Q?", &tiles).unwrap();
+        let first = placements_of("qi", &board, &rack, &lexicon, &tiles);
+        // Two ways across the center in each direction, the I always a blank.
+        assert_eq!(
+            notation(first),
+            ["H7 Qi", "8G Qi", "8H Qi", "H8 Qi"].map(String::from)
+        );
+        assert!(placements_of("QIS", &board, &rack, &lexicon, &tiles).is_empty());
+        assert!(placements_of("CAT", &board, &rack, &lexicon, &tiles).is_empty());
+
+        // The real Z goes on the double letter at L8, not the first square it could.
+        let rack = Rack::parse("ZII??", &tiles).unwrap();
+        let ziziz = placements_of("ZIZIZ", &board, &rack, &lexicon, &tiles);
+        let across = ziziz.iter().find(|m| m.notation(&tiles).starts_with("8H"));
+        assert_eq!(across.unwrap().notation(&tiles), "8H zIzIZ");
+
+        board
+            .apply(&Move::parse("8G CAT", &board, &tiles).unwrap())
+            .unwrap();
+        let rack = Rack::parse("QUIXOTI", &tiles).unwrap();
+        assert_eq!(
+            notation(placements_of("QUIXOTIC", &board, &rack, &lexicon, &tiles)),
+            ["G1 QUIXOTIC"]
+        );
+        // Everywhere else, the X or the I forms an invalid cross word.
+        let xi = placements_of("XI", &board, &rack, &lexicon, &tiles);
+        assert_eq!(notation(xi.clone()), ["9H XI"]);
+        let xi_word = tiles.tokenize("XI").unwrap();
+        let generated: Vec<_> = crate::movegen::iter_moves(&board, &rack, &lexicon)
+            .filter(|m| {
+                m.tiles()
+                    .iter()
+                    .map(|t| t.tile())
+                    .eq(xi_word.iter().copied())
+            })
+            .collect();
+        assert_eq!(xi, generated);
+    }
+}
@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+use crate::{
+    bitboard::Bitboard, board::Board, board::Move, leaves::LeaveTable, lexicon::Lexicon,
+    score::score_move, Rack, TileMap, TileMapIdx,
+};
+
+use super::iter_moves;
+
+/// How [`top_moves`] ranks moves, higher first.
+pub trait Ranker {
+    /// The value of a move scoring `score` and keeping `leave` on the rack.
+    fn rank(&self, m: &Move, score: u32, leave: &[TileMapIdx]) -> f32;
+}
+
+/// Ranks moves by their score alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawScore;
+
+impl Ranker for RawScore {
+    fn rank(&self, _: &Move, score: u32, _: &[TileMapIdx]) -> f32 {
+        score as f32
+    }
+}
+
+/// Ranks moves by equity: their score plus the value of the leave.
+#[derive(Debug, Clone, Copy)]
+pub struct Equity<'a>(pub &'a LeaveTable);
+
+impl Ranker for Equity<'_> {
+    fn rank(&self, _: &Move, score: u32, leave: &[TileMapIdx]) -> f32 {
+        score as f32 + self.0.value_of(leave)
+    }
+}
+
+/// A move found by [`top_moves`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedMove {
+    pub m: Move,
+    pub score: u32,
+    /// What the [`Ranker`] made of it.
+    pub rank: f32,
+}
+
+/// The tiles left on `rack` after playing `m`.
+pub fn leave(rack: &Rack, m: &Move, tiles: &TileMap) -> Vec<TileMapIdx> {
+    let mut left = rack.clone();
+    for (_, p) in m.placed() {
+        let from_rack = if p.is_blank {
+            tiles.blank_idx()
+        } else {
+            Some(p.tile)
+        };
+        if let Some(t) = from_rack {
+            // Generated moves only use tiles from the rack.
+            let _ = left.remove(t);
+        }
+    }
+    left.tiles().to_vec()
+}
+
+/// The `n` best moves for `rack` on `board` according to `ranker`, best first. Moves ranked
+/// equally stay in the order they were generated in.
+pub fn top_moves<BB: Bitboard>(
+    board: &Board<BB>,
+    rack: &Rack,
+    lexicon: &Lexicon,
+    tiles: &TileMap,
+    n: usize,
+    ranker: &impl Ranker,
+) -> Vec<RankedMove> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let by_rank = |a: &(usize, RankedMove), b: &(usize, RankedMove)| {
+        b.1.rank
+            .partial_cmp(&a.1.rank)
+            .unwrap_or(Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    };
+    let mut best = Vec::with_capacity(2 * n);
+    for (i, m) in iter_moves(board, rack, lexicon).enumerate() {
+        let score = score_move(board, &m, tiles).total();
+        let rank = ranker.rank(&m, score, &leave(rack, &m, tiles));
+        best.push((i, RankedMove { m, score, rank }));
+        // Keep at most twice as many as needed, so trimming is amortized.
+        if best.len() == 2 * n {
+            best.select_nth_unstable_by(n - 1, by_rank);
+            best.truncate(n);
+        }
+    }
+    best.sort_unstable_by(by_rank);
+    best.truncate(n);
+    best.into_iter().map(|(_, m)| m).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn by_score_and_equity() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "AT", "TA"], &tiles);
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let rack = Rack::parse("CAT", &tiles).unwrap();
+
+        let top = top_moves(&board, &rack, &lexicon, &tiles, 3, &RawScore);
+        assert_eq!(top.len(), 3);
+        assert!(top.iter().all(|r| r.score == 10 && r.rank == 10.0));
+        assert_eq!(top[0].m.notation(&tiles), "8H CAT");
+        let all = top_moves(&board, &rack, &lexicon, &tiles, 100, &RawScore);
+        assert_eq!(all.len(), iter_moves(&board, &rack, &lexicon).count());
+        assert!(all.windows(2).all(|w| w[0].rank >= w[1].rank));
+
+        // Keeping the C is worth more than playing it.
+        let mut leaves = LeaveTable::new();
+        leaves.insert(&tiles.tokenize("C").unwrap(), 20.0).unwrap();
+        let top = top_moves(&board, &rack, &lexicon, &tiles, 1, &Equity(&leaves));
+        assert_eq!(top[0].score, 4);
+        assert_eq!(top[0].rank, 24.0);
+        assert_eq!(
+            leave(&rack, &top[0].m, &tiles),
+            tiles.tokenize("C").unwrap()
+        );
+        assert!(top_moves(&board, &rack, &lexicon, &tiles, 0, &RawScore).is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+//! How a game ended, see [`Game::outcome`](crate::Game::outcome).
+
+use crate::board::{Player, Scores};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndReason {
+    /// The player used up their rack with the bag empty.
+    WentOut(Player),
+    /// [`Game::SCORELESS_TURNS`](crate::Game::SCORELESS_TURNS) turns in a row scored nothing.
+    ScorelessTurns,
+    Resigned(Player),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameOutcome {
+    pub reason: EndReason,
+    /// The final scores, adjustments included.
+    pub scores: Scores,
+    /// How much each score changed at the end: players lose the value of the tiles left on their
+    /// rack, and a player who went out gains the value of their opponent's rack. Overtime costs the
+    /// [overtime penalty](crate::rules::Rules::overtime_penalty) per started minute.
+    pub adjustments: Scores,
+    /// `None` for a tie. A player who resigned loses regardless of the score.
+    pub winner: Option<Player>,
+}
@@ -0,0 +1,440 @@
+//! Solving pre-endgames: with a few tiles left in the bag, every way the unseen tiles could be
+//! split between the bag and the opponent's rack is played out to the endgame.
+
+use std::{cmp::Ordering, iter::repeat_n};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    board::Play,
+    bot::placements,
+    endgame::{rack_value, score, EndgameSolver, Position},
+    error::{GameError, Result},
+    rack_idx,
+    view::GameView,
+    Game, Rack, TileMapIdx,
+};
+
+/// Chooses plays with 1 to [`MAX_BAG`](Self::MAX_BAG) tiles in the bag, where the next draws
+/// decide the endgame and sims alone guess badly.
+///
+/// Every rack the opponent could hold is weighed by its probability, and so is every draw after a
+/// play. Once the bag is empty, the endgame is solved with an [`EndgameSolver`]. While it isn't,
+/// both players are assumed to know the bag's contents, just not their order, and play for the
+/// [`Objective`]. Exchanges aren't considered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreEndgameSolver {
+    endgame: EndgameSolver,
+    candidates: Option<usize>,
+    objective: Objective,
+}
+
+/// What a [`PreEndgameSolver`] maximizes, the other breaking ties.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Objective {
+    /// The chance of winning: a sure 1 point win beats a likely 50 point one.
+    #[default]
+    WinProbability,
+    /// The expected final spread.
+    Spread,
+}
+
+/// The result of [`PreEndgameSolver::solve`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreEndgameSolution {
+    /// Every play considered, the best first.
+    pub plays: Vec<PreEndgamePlay>,
+    /// How many racks the opponent could hold.
+    pub racks: usize,
+}
+
+/// A play with how it works out on average.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreEndgamePlay {
+    pub play: Play,
+    /// The expected final spread of the player making it, rack adjustments included.
+    pub spread: f64,
+    /// The chance of winning the game with it, ties counting half.
+    pub win_probability: f64,
+}
+
+impl PreEndgameSolution {
+    pub fn best(&self) -> Option<&PreEndgamePlay> {
+        self.plays.first()
+    }
+}
+
+impl PreEndgameSolver {
+    /// The most tiles in the bag handled: the draws to try grow quickly with it.
+    pub const MAX_BAG: usize = 6;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only tries the `n` highest scoring placements on every turn, besides passing.
+    pub fn candidates(mut self, n: usize) -> Self {
+        self.candidates = Some(n);
+        self
+    }
+
+    /// What to maximize, [`Objective::WinProbability`] by default.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Solves the endgames with `endgame`, e.g. one with a [depth limit](EndgameSolver::max_depth).
+    pub fn endgame(mut self, endgame: EndgameSolver) -> Self {
+        self.endgame = endgame;
+        self
+    }
+
+    /// Solves the pre-endgame for the viewing player, on their turn.
+    pub fn solve<BB: Bitboard>(&self, view: &GameView<BB>) -> Result<PreEndgameSolution> {
+        if view.game.outcome().is_some() {
+            return Err(GameError::GameOver.into());
+        }
+        if !view.is_my_turn() {
+            return Err(GameError::NotYourTurn(view.player()).into());
+        }
+        let in_bag = view.bag_len();
+        if !(1..=Self::MAX_BAG).contains(&in_bag) {
+            return Err(GameError::NotPreEndgame {
+                len: in_bag,
+                max: Self::MAX_BAG,
+            }
+            .into());
+        }
+        if view.lexicons().is_none() {
+            return Err(GameError::NoLexicon.into());
+        }
+        let game = view.game;
+        let unseen: Vec<TileMapIdx> = view
+            .unseen()
+            .iter()
+            .flat_map(|(t, n)| repeat_n(t, n.into()))
+            .collect();
+        let opponent_racks = draws(&unseen, view.opponent_rack_len());
+        let spread = view.scores().spread(view.player());
+
+        let capacity = view.rack().capacity();
+        let positions = opponent_racks
+            .iter()
+            .map(|(rack, _)| {
+                Ok(Position::new(
+                    view,
+                    Rack::from_tiles(rack.clone(), capacity)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut plays = Vec::new();
+        for (play, points) in self.candidate_plays(game, &positions[0]) {
+            let mut total = Outcome::default();
+            for (position, (rack, probability)) in positions.iter().zip(&opponent_racks) {
+                let bag = remove(&unseen, rack);
+                let outcome = self.play_out(game, position, &bag, &play, points, spread)?;
+                total.add(&outcome, *probability);
+            }
+            plays.push(PreEndgamePlay {
+                play,
+                spread: total.spread,
+                win_probability: total.win,
+            });
+        }
+        // Stable, so ties go to the higher scoring play.
+        plays.sort_by(|a, b| {
+            let key = |p: &PreEndgamePlay| Outcome {
+                spread: p.spread,
+                win: p.win_probability,
+            };
+            self.compare(&key(b), &key(a))
+        });
+        Ok(PreEndgameSolution {
+            plays,
+            racks: opponent_racks.len(),
+        })
+    }
+
+    /// The best outcome for the player to move in `position` with `bag` left, their spread so far
+    /// being `spread`.
+    fn best<BB: Bitboard>(
+        &self,
+        game: &Game<BB>,
+        position: &Position<BB>,
+        bag: &[TileMapIdx],
+        spread: i32,
+    ) -> Result<Outcome> {
+        let mut best: Option<Outcome> = None;
+        for (play, points) in self.candidate_plays(game, position) {
+            let outcome = self.play_out(game, position, bag, &play, points, spread)?;
+            if best
+                .as_ref()
+                .is_none_or(|b| self.compare(&outcome, b) == Ordering::Greater)
+            {
+                best = Some(outcome);
+            }
+        }
+        Ok(best.unwrap_or_default())
+    }
+
+    /// The expected outcome of making `play`, scoring `points`, for the player to move, over
+    /// every draw from `bag` that could follow.
+    fn play_out<BB: Bitboard>(
+        &self,
+        game: &Game<BB>,
+        position: &Position<BB>,
+        bag: &[TileMapIdx],
+        play: &Play,
+        points: i32,
+        spread: i32,
+    ) -> Result<Outcome> {
+        let player = position.to_move;
+        let mut after = position.clone();
+        if let Play::Place(m) = play {
+            after.board.apply(m)?;
+            let rack = &mut after.racks[rack_idx(player)];
+            for (_, p) in m.placed() {
+                rack.remove(crate::rack_tile(game.tiles(), p)?)?;
+            }
+        }
+        after.scoreless = if points > 0 { 0 } else { after.scoreless + 1 };
+        after.to_move = player.other();
+        let spread = spread + points;
+        let placed = match play {
+            Play::Place(m) => m.num_placed().min(bag.len()),
+            _ => 0,
+        };
+
+        let mut total = Outcome::default();
+        for (drawn, probability) in draws(bag, placed) {
+            let mut position = after.clone();
+            for &t in &drawn {
+                position.racks[rack_idx(player)].add(t)?;
+            }
+            let bag = remove(bag, &drawn);
+            let tiles = game.tiles();
+            let outcome = if position.scoreless >= Game::<BB>::SCORELESS_TURNS {
+                let own = rack_value(tiles, position.rack(player));
+                let other = rack_value(tiles, position.rack(player.other()));
+                Outcome::of(spread + other - own)
+            } else if bag.is_empty() {
+                let endgame = self.endgame.search(game, position)?;
+                Outcome::of(spread - endgame.spread)
+            } else {
+                self.best(game, &position, &bag, -spread)?.flip()
+            };
+            total.add(&outcome, probability);
+        }
+        Ok(total)
+    }
+
+    /// Orders outcomes by the objective.
+    fn compare(&self, a: &Outcome, b: &Outcome) -> Ordering {
+        let (a, b) = match self.objective {
+            Objective::WinProbability => ((a.win, a.spread), (b.win, b.spread)),
+            Objective::Spread => ((a.spread, a.win), (b.spread, b.win)),
+        };
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    }
+
+    /// The placements to try for the player to move with their scores, highest first, and passing.
+    fn candidate_plays<BB: Bitboard>(
+        &self,
+        game: &Game<BB>,
+        position: &Position<BB>,
+    ) -> Vec<(Play, i32)> {
+        let rack = position.rack(position.to_move);
+        let mut plays: Vec<(Play, i32)> = placements(game, &position.board, rack)
+            .into_iter()
+            .map(|m| {
+                let points = score(game, &position.board, &m);
+                (Play::Place(m), points)
+            })
+            .collect();
+        plays.sort_by_key(|(_, points)| -points);
+        if let Some(n) = self.candidates {
+            plays.truncate(n);
+        }
+        plays.push((Play::Pass, 0));
+        plays
+    }
+}
+
+/// An expected final spread and chance of winning, for one player.
+#[derive(Debug, Clone, Copy, Default)]
+struct Outcome {
+    spread: f64,
+    win: f64,
+}
+
+impl Outcome {
+    fn of(spread: i32) -> Self {
+        let win = match spread.cmp(&0) {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0,
+        };
+        Self {
+            spread: f64::from(spread),
+            win,
+        }
+    }
+
+    /// The same outcome for the opponent.
+    fn flip(self) -> Self {
+        Self {
+            spread: -self.spread,
+            win: 1.0 - self.win,
+        }
+    }
+
+    fn add(&mut self, other: &Outcome, probability: f64) {
+        self.spread += other.spread * probability;
+        self.win += other.win * probability;
+    }
+}
+
+/// `pool` without one copy of each of `tiles`.
+fn remove(pool: &[TileMapIdx], tiles: &[TileMapIdx]) -> Vec<TileMapIdx> {
+    let mut rest = pool.to_vec();
+    for t in tiles {
+        if let Some(i) = rest.iter().position(|r| r == t) {
+            rest.remove(i);
+        }
+    }
+    rest
+}
+
+/// Every distinct set of `n` tiles that can be drawn from `pool`, sorted, with its probability.
+fn draws(pool: &[TileMapIdx], n: usize) -> Vec<(Vec<TileMapIdx>, f64)> {
+    fn go(
+        counts: &[(TileMapIdx, usize)],
+        n: usize,
+        ways: f64,
+        drawn: &mut Vec<TileMapIdx>,
+        out: &mut Vec<(Vec<TileMapIdx>, f64)>,
+    ) {
+        let Some((&(tile, count), rest)) = counts.split_first() else {
+            if n == 0 {
+                out.push((drawn.clone(), ways));
+            }
+            return;
+        };
+        for take in 0..=count.min(n) {
+            drawn.extend(repeat_n(tile, take));
+            go(rest, n - take, ways * binomial(count, take), drawn, out);
+            drawn.truncate(drawn.len() - take);
+        }
+    }
+
+    let mut counts: Vec<(TileMapIdx, usize)> = Vec::new();
+    for &t in pool {
+        match counts.iter_mut().find(|(c, _)| *c == t) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((t, 1)),
+        }
+    }
+    counts.sort();
+    let mut out = Vec::new();
+    go(&counts, n, 1.0, &mut Vec::with_capacity(n), &mut out);
+    let total = binomial(pool.len(), n);
+    for (_, p) in &mut out {
+        *p /= total;
+    }
+    out
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl, board::Player, lexicon::Lexicon, tiles::TileMapBuilder, GameBuilder,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// A game with the first player holding `rack` on an empty board, and a tile set of `rack`
+    /// and `unseen`, of which the second player holds `opponent`.
+    fn setup(rack: &str, opponent: &str, unseen: &str, words: &[&str]) -> Game<BitboardImpl> {
+        let scores = [("A", 1), ("C", 3), ("S", 1), ("T", 1), ("Q", 10), ("X", 8)];
+        let all = format!("{rack}{unseen}");
+        let tiles = scores
+            .iter()
+            .fold(TileMapBuilder::new(), |builder, &(letter, score)| {
+                let count = all.matches(letter).count();
+                builder.add_tile(letter, u8::try_from(count).unwrap(), score)
+            })
+            .build()
+            .unwrap();
+        let lexicon = Lexicon::from_words(words.iter().copied(), &tiles);
+        GameBuilder::new()
+            .tiles(tiles.clone())
+            .lexicons(lexicon)
+            .rack(Player::First, Rack::parse(rack, &tiles).unwrap())
+            .rack(Player::Second, Rack::parse(opponent, &tiles).unwrap())
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn draw_probabilities() {
+        let tiles = crate::TileMap::english();
+        let pool = tiles.tokenize("AAB").unwrap();
+        let draws: Vec<(String, f64)> = draws(&pool, 2)
+            .into_iter()
+            .map(|(d, p)| {
+                (
+                    d.iter().map(|t| tiles.get(*t).unwrap().as_str()).collect(),
+                    p,
+                )
+            })
+            .collect();
+        let third = 1.0 / 3.0;
+        assert_eq!(
+            draws,
+            vec![("AB".to_string(), 2.0 * third), ("AA".to_string(), third)]
+        );
+        assert_eq!(super::draws(&pool, 0), vec![(Vec::new(), 1.0)]);
+    }
+
+    #[test]
+    fn weighs_the_draws() {
+        // One of Q and X is in the bag, the other on the opponent's rack. Neither can be played,
+        // so whoever is stuck with the Q loses 2 more points.
+        let game = setup("CAT", "Q", "QX", &["AT", "CAT"]);
+        assert_eq!(game.bag().tiles().len(), 1);
+        let solution = PreEndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap();
+        assert_eq!(solution.racks, 2);
+        // CAT scores 10, then draws the last tile: 10 - 10 + 8 or 10 - 8 + 10.
+        let best = solution.best().unwrap();
+        assert!(matches!(&best.play, Play::Place(m) if m.num_placed() == 3));
+        assert_eq!((best.spread, best.win_probability), (10.0, 1.0));
+        // AT scores 4 and draws, leaving C for CAT later, 5 more.
+        let at = solution
+            .plays
+            .iter()
+            .find(|p| matches!(&p.play, Play::Place(m) if m.num_placed() == 2))
+            .unwrap();
+        assert_eq!(at.spread, 9.0);
+    }
+
+    #[test]
+    fn needs_a_short_bag() {
+        let game = setup("CAT", "Q", "Q", &["CAT"]);
+        let err = PreEndgameSolver::new()
+            .solve(&game.view(Player::First))
+            .unwrap_err();
+        assert!(matches!(
+            err.cause(),
+            crate::ScrubbleError::Game(GameError::NotPreEndgame { len: 0, max: 6 })
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+//! Stepping through a game turn by turn, see [`Game::replay`].
+
+use crate::{
+    bitboard::Bitboard,
+    board::Board,
+    clock::Clocks,
+    error::{GameError, Result},
+    turn::TurnRecord,
+    Game,
+};
+
+/// A copy of a game that steps through its turns, see [`Game::replay`].
+pub struct Replay<'a, BB: Bitboard> {
+    turns: &'a [TurnRecord],
+    /// The game as of the turns replayed so far.
+    game: Game<BB>,
+}
+
+impl<BB: Bitboard> Game<BB> {
+    /// Replays the game from the start, yielding the board after each turn, challenge included,
+    /// with the turn's record. The game itself doesn't change.
+    ///
+    /// Turns are played again the way [`Game::undo_to`] does, so draws and challenges come out as
+    /// they did, and [`Replay::seek`] can step back as well as forward.
+    pub fn replay(&self) -> Replay<'_, BB> {
+        // Without a start, no turn was played and the game is still as it started.
+        let (board, bag, racks) = match &self.start {
+            Some(start) => (&start.board, &start.bag, &start.racks),
+            None => (&self.board, &self.bag, &self.racks),
+        };
+        let game = Game {
+            board: board.clone(),
+            tiles: self.tiles.clone(),
+            lexicons: self.lexicons.clone(),
+            alphagrams: self.alphagrams.clone(),
+            bag: bag.clone(),
+            racks: racks.clone(),
+            rules: self.rules,
+            seed: self.seed,
+            bot_seed: self.bot_seed,
+            clocks: Clocks::new(self.rules.base_time),
+            last_play: None,
+            scoreless_turns: 0,
+            scoreless_before_last: 0,
+            resigned: None,
+            history: Vec::new(),
+            start: None,
+            events: Vec::new(),
+        };
+        Replay {
+            turns: &self.history,
+            game,
+        }
+    }
+}
+
+impl<'a, BB: Bitboard> Replay<'a, BB> {
+    /// How many turns have been replayed.
+    pub fn ply(&self) -> usize {
+        self.game.history.len()
+    }
+
+    /// The game as of the turns replayed so far, e.g. for the racks and scores at that point.
+    pub fn game(&self) -> &Game<BB> {
+        &self.game
+    }
+
+    pub fn board(&self) -> &Board<BB> {
+        &self.game.board
+    }
+
+    /// Goes to the position after `ply` turns, forward by replaying turns, back by
+    /// [undoing](Game::undo_to) them.
+    pub fn seek(&mut self, ply: usize) -> Result<&Board<BB>> {
+        if ply > self.turns.len() {
+            return Err(GameError::NoSuchTurn(ply).into());
+        }
+        if ply < self.ply() {
+            self.game.undo_to(ply)?;
+        }
+        while self.ply() < ply {
+            self.game.replay_turn(&self.turns[self.ply()])?;
+        }
+        Ok(self.board())
+    }
+}
+
+impl<'a, BB: Bitboard> Iterator for Replay<'a, BB> {
+    type Item = Result<(Board<BB>, &'a TurnRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.turns.get(self.ply())?;
+        Some(
+            self.game
+                .replay_turn(record)
+                .map(|()| (self.game.board.clone(), record)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::{Move, Play, Player},
+        Lexicon, TileMap,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn replay() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "AT", "TA"], &tiles);
+        let mut game = Game::<BitboardImpl>::new(tiles)
+            .with_seed(4)
+            .with_lexicons(lexicon)
+            .unwrap();
+        assert_eq!(game.replay().count(), 0);
+
+        game.set_rack(Player::First, &game.tiles.tokenize("CAT").unwrap())
+            .unwrap();
+        let m = Move::parse("8G CAT", game.board(), game.tiles()).unwrap();
+        game.play(Player::First, Play::Place(m)).unwrap();
+        game.play(Player::Second, Play::Pass).unwrap();
+        game.set_rack(Player::First, &game.tiles.tokenize("X").unwrap())
+            .unwrap();
+        let m = Move::parse("H7 X.", game.board(), game.tiles()).unwrap();
+        game.play(Player::First, Play::Place(m)).unwrap();
+        game.challenge(Player::Second).unwrap();
+
+        let steps: Vec<_> = game.replay().map(Result::unwrap).collect();
+        let occupied: Vec<_> = steps
+            .iter()
+            .map(|(board, _)| board.occupied().count_ones())
+            .collect();
+        // The phony XA was challenged off.
+        assert_eq!(occupied, [3, 3, 3]);
+        let records: Vec<_> = steps.iter().map(|(_, record)| *record).collect();
+        assert_eq!(records, game.history().iter().collect::<Vec<_>>());
+        assert_eq!(steps[2].0.scores(), game.board().scores());
+
+        let mut replay = game.replay();
+        assert_eq!(replay.seek(1).unwrap().occupied().count_ones(), 3);
+        assert_eq!(
+            replay.game().rack(Player::Second),
+            &game.history()[1].rack_after
+        );
+        assert_eq!(replay.seek(0).unwrap().occupied().count_ones(), 0);
+        assert_eq!(
+            replay.game().rack(Player::First).tiles(),
+            game.tiles().tokenize("ACT").unwrap()
+        );
+        replay.seek(3).unwrap();
+        assert_eq!(replay.game().rack(Player::First), game.rack(Player::First));
+        assert!(replay.next().is_none());
+        assert!(replay.seek(4).is_err());
+        // The game itself is left as it was.
+        assert_eq!(game.history().len(), 3);
+    }
+}
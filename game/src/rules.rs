@@ -0,0 +1,100 @@
+//! Rule sets, see [`Game::with_rules`](crate::Game::with_rules).
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    score::{BINGO_BONUS, BINGO_TILES},
+    TileBag,
+};
+
+/// What a challenge of a valid play costs, or whether plays can be challenged at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeRule {
+    /// Double challenge: the challenger loses their turn.
+    #[default]
+    Double,
+    /// The player who made the challenged play gets this many points.
+    Points(u32),
+    /// Nothing happens.
+    Free,
+    /// No challenges: plays forming words not in the lexicon are rejected when made, as in
+    /// [casual play](Rules::casual).
+    Void,
+}
+
+/// Which words plays can form.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Variant {
+    /// Words have to be in the lexicon.
+    #[default]
+    Standard,
+    /// Clabbers: any order of the tiles of a word in the lexicon is a word too, e.g. TAC or ATC
+    /// for CAT. Plays are judged with an [`AlphagramIndex`](crate::lexicon::AlphagramIndex), see
+    /// [`Game::alphagrams`](crate::Game::alphagrams).
+    Clabbers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    pub bingo_bonus: u32,
+    /// How many tiles a rack holds. Placing all of them earns the bingo bonus.
+    pub rack_size: usize,
+    pub challenge: ChallengeRule,
+    /// How many tiles need to be in the bag to exchange.
+    pub min_for_exchange: usize,
+    /// How much time each player has for the game, `None` for untimed games.
+    pub base_time: Option<Duration>,
+    /// Points lost per started minute over time.
+    pub overtime_penalty: u32,
+    #[serde(default)]
+    pub variant: Variant,
+}
+
+impl Rules {
+    /// North American club and tournament play: double challenge.
+    pub fn twl_club() -> Self {
+        Self {
+            bingo_bonus: BINGO_BONUS,
+            rack_size: BINGO_TILES,
+            challenge: ChallengeRule::Double,
+            min_for_exchange: TileBag::<rand_chacha::ChaCha8Rng>::MIN_FOR_EXCHANGE,
+            base_time: Some(Duration::from_secs(25 * 60)),
+            overtime_penalty: 10,
+            variant: Variant::Standard,
+        }
+    }
+
+    /// WESPA tournament play: an unsuccessful challenge gives the challenged player 5 points.
+    pub fn wespa() -> Self {
+        Self {
+            challenge: ChallengeRule::Points(5),
+            ..Self::twl_club()
+        }
+    }
+
+    /// Casual play: phonies are rejected outright and there are no clocks.
+    pub fn casual() -> Self {
+        Self {
+            challenge: ChallengeRule::Void,
+            base_time: None,
+            ..Self::twl_club()
+        }
+    }
+
+    /// The bonus for a move placing `placed` tiles.
+    pub fn bingo_bonus_for(&self, placed: usize) -> u32 {
+        if placed >= self.rack_size {
+            self.bingo_bonus
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::twl_club()
+    }
+}
@@ -0,0 +1,403 @@
+//! Saving and loading games in progress, see [`Game::save`].
+//!
+//! A save holds everything but the tile map and the lexicons: the board with its layout, both
+//! racks, the bag with its RNG state and seed, the bot seed, the rules, the clocks and the
+//! history. The tile map is identified by its [fingerprint](TileMap::fingerprint), so loading
+//! with a different one fails.
+//!
+//! The binary format is [`Game::MAGIC`] and the format version as a little-endian `u32`, followed
+//! by the same data as the JSON format, encoded with bincode.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Location, Move, Play, Player, Premiums, Scores, TilePlacement},
+    challenge::{ChallengeOutcome, LastPlay},
+    clock::Clocks,
+    error::{FormatError, Result, TileError},
+    rules::Rules,
+    turn::TurnRecord,
+    Game, Rack, Start, TileBag, TileMap, TileMapIdx,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    Json,
+    /// Smaller and faster, but not human-readable.
+    Binary,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    version: u32,
+    tiles: u64,
+    board: SavedBoard,
+    bag: TileBag,
+    racks: [Rack; 2],
+    rules: Rules,
+    seed: u64,
+    bot_seed: u64,
+    clocks: Clocks,
+    last_play: Option<LastPlay>,
+    scoreless_turns: usize,
+    scoreless_before_last: usize,
+    resigned: Option<Player>,
+    history: Vec<TurnRecord>,
+    start: Option<SavedStart>,
+}
+
+/// The board's layout and its tiles, in no particular order.
+#[derive(Serialize, Deserialize)]
+struct SavedBoard {
+    /// See [`Premiums::to_rows`].
+    premiums: Vec<String>,
+    start: Location,
+    tiles: Vec<(Location, TilePlacement)>,
+    current_turn: Player,
+    scores: Scores,
+}
+
+/// The state before the first turn. Its board is the game's board as it was then.
+#[derive(Serialize, Deserialize)]
+struct SavedStart {
+    board: SavedBoard,
+    bag: TileBag,
+    racks: [Rack; 2],
+}
+
+impl SavedBoard {
+    fn new<BB: Bitboard>(board: &Board<BB>) -> Self {
+        Self {
+            premiums: board.premiums().to_rows(),
+            start: board.premiums().start(),
+            tiles: board
+                .occupied()
+                .locations()
+                .filter_map(|l| {
+                    let tile = board.tile_at(l)?;
+                    let is_blank = board.is_blank(l);
+                    Some((l, TilePlacement { tile, is_blank }))
+                })
+                .collect(),
+            current_turn: board.current_turn(),
+            scores: board.scores(),
+        }
+    }
+
+    fn into_board<BB: Bitboard>(self, tiles: &TileMap) -> Result<Board<BB>> {
+        let rows: Vec<_> = self.premiums.iter().map(String::as_str).collect();
+        let mut board = Board::new(tiles, Premiums::from_rows(&rows, self.start)?);
+        for (l, p) in self.tiles {
+            board.place(l, p.tile, p.is_blank)?;
+        }
+        if self.current_turn == Player::Second {
+            board.end_turn();
+        }
+        *board.scores_mut() = self.scores;
+        Ok(board)
+    }
+}
+
+impl<BB: Bitboard> Game<BB> {
+    pub const MAGIC: &'static [u8; 4] = b"SCGM";
+    pub const SAVE_VERSION: u32 = 6;
+
+    /// Writes the game so it can be [loaded](Game::load) and continued, undo included.
+    pub fn save(&self, mut writer: impl Write, format: SaveFormat) -> Result<()> {
+        let saved = SavedGame {
+            version: Self::SAVE_VERSION,
+            tiles: self.tiles.fingerprint(),
+            board: SavedBoard::new(&self.board),
+            bag: self.bag.clone(),
+            racks: self.racks.clone(),
+            rules: self.rules,
+            seed: self.seed,
+            bot_seed: self.bot_seed,
+            clocks: self.clocks,
+            last_play: self.last_play.clone(),
+            scoreless_turns: self.scoreless_turns,
+            scoreless_before_last: self.scoreless_before_last,
+            resigned: self.resigned,
+            history: self.history.clone(),
+            start: self.start.as_ref().map(|start| SavedStart {
+                board: SavedBoard::new(&start.board),
+                bag: start.bag.clone(),
+                racks: start.racks.clone(),
+            }),
+        };
+        match format {
+            SaveFormat::Json => serde_json::to_writer(writer, &saved)?,
+            SaveFormat::Binary => {
+                writer.write_all(Self::MAGIC)?;
+                writer.write_all(&Self::SAVE_VERSION.to_le_bytes())?;
+                bincode::serialize_into(writer, &saved)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a game written by [`Game::save`] that was played with `tiles`. Lexicons aren't
+    /// saved, so they have to be set again with [`Game::with_lexicons`].
+    pub fn load(mut reader: impl Read, format: SaveFormat, tiles: TileMap) -> Result<Self> {
+        let saved: SavedGame = match format {
+            SaveFormat::Json => {
+                let value: serde_json::Value = serde_json::from_reader(reader)?;
+                Self::check_version(value.get("version").and_then(serde_json::Value::as_u64))?;
+                serde_json::from_value(value)?
+            }
+            SaveFormat::Binary => {
+                let mut header = [0; 8];
+                reader.read_exact(&mut header)?;
+                if &header[..4] != Self::MAGIC {
+                    return Err(FormatError::NotASave.into());
+                }
+                let version = u32::from_le_bytes(header[4..].try_into().expect("4 bytes"));
+                Self::check_version(Some(version.into()))?;
+                bincode::deserialize_from(reader)?
+            }
+        };
+        tiles.check_fingerprint(saved.tiles)?;
+
+        let board = saved.board.into_board(&tiles)?;
+        let start = match saved.start {
+            Some(start) => Some(Start {
+                board: start.board.into_board(&tiles)?,
+                bag: start.bag,
+                racks: start.racks,
+            }),
+            None => None,
+        };
+        let drawable = saved
+            .bag
+            .tiles()
+            .iter()
+            .chain(saved.racks.iter().flat_map(Rack::tiles));
+        let drawable_at_start = start.iter().flat_map(|s| {
+            s.bag
+                .tiles()
+                .iter()
+                .chain(s.racks.iter().flat_map(Rack::tiles))
+        });
+        for tile in drawable.chain(drawable_at_start) {
+            check_tile(&tiles, *tile)?;
+        }
+        for turn in &saved.history {
+            check_turn(&tiles, turn)?;
+        }
+        if let Some(last) = &saved.last_play {
+            check_last_play(&tiles, last)?;
+        }
+
+        Ok(Self {
+            board,
+            tiles,
+            lexicons: None,
+            alphagrams: None,
+            bag: saved.bag,
+            racks: saved.racks,
+            rules: saved.rules,
+            seed: saved.seed,
+            bot_seed: saved.bot_seed,
+            clocks: saved.clocks,
+            last_play: saved.last_play,
+            scoreless_turns: saved.scoreless_turns,
+            scoreless_before_last: saved.scoreless_before_last,
+            resigned: saved.resigned,
+            history: saved.history,
+            start: start.map(Box::new),
+            events: Vec::new(),
+        })
+    }
+
+    fn check_version(version: Option<u64>) -> Result<()> {
+        let version = version.ok_or(FormatError::NoSaveVersion)?;
+        if version != u64::from(Self::SAVE_VERSION) {
+            return Err(FormatError::SaveVersion {
+                found: version,
+                expected: Self::SAVE_VERSION,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn check_tile(tiles: &TileMap, tile: TileMapIdx) -> Result<()> {
+    if tile.as_u8() >= tiles.len() {
+        return Err(TileError::NotInTileMap(tile.as_u8()).into());
+    }
+    Ok(())
+}
+
+fn check_move(tiles: &TileMap, m: &Move) -> Result<()> {
+    m.tiles()
+        .iter()
+        .try_for_each(|t| check_tile(tiles, t.tile()))
+}
+
+fn check_last_play(tiles: &TileMap, last: &LastPlay) -> Result<()> {
+    check_move(tiles, &last.m)?;
+    last.drawn.iter().try_for_each(|t| check_tile(tiles, *t))
+}
+
+/// Checks every tile a turn of the history holds: the play, the racks and what was drawn.
+fn check_turn(tiles: &TileMap, turn: &TurnRecord) -> Result<()> {
+    match &turn.summary.play {
+        Play::Place(m) => check_move(tiles, m)?,
+        Play::Exchange(exchanged) => exchanged.iter().try_for_each(|t| check_tile(tiles, *t))?,
+        Play::Pass => {}
+    }
+    let held = turn
+        .summary
+        .drawn
+        .iter()
+        .chain(turn.rack_before.tiles())
+        .chain(turn.rack_after.tiles());
+    for tile in held {
+        check_tile(tiles, *tile)?;
+    }
+    if let Some(ChallengeOutcome::Phony { play, .. }) = &turn.challenge {
+        check_last_play(tiles, play)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::{Direction, MoveTile},
+        Lexicon,
+    };
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn played() -> Game<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "AT", "TA"], &tiles);
+        let mut game = Game::new(tiles)
+            .with_seed(3)
+            .with_lexicons(lexicon)
+            .unwrap();
+        game.set_rack(Player::First, &game.tiles.tokenize("CAT").unwrap())
+            .unwrap();
+        let m = Move::parse("8G CAT", game.board(), game.tiles()).unwrap();
+        game.play_timed(Player::First, Play::Place(m), Duration::from_secs(42))
+            .unwrap();
+        game.challenge(Player::Second).unwrap();
+        game.annotate(0, "Opening").unwrap();
+        game
+    }
+
+    fn roundtrip(game: &Game<BitboardImpl>, format: SaveFormat) -> Game<BitboardImpl> {
+        let mut buf = Vec::new();
+        game.save(&mut buf, format).unwrap();
+        let lexicons = game.lexicons().unwrap().clone();
+        Game::load(buf.as_slice(), format, TileMap::english())
+            .unwrap()
+            .with_lexicons(lexicons)
+            .unwrap()
+    }
+
+    #[test]
+    fn save_and_load() {
+        for format in [SaveFormat::Json, SaveFormat::Binary] {
+            let mut game = played();
+            let mut loaded = roundtrip(&game, format);
+            assert_eq!(loaded.history(), game.history());
+            assert_eq!(loaded.board().scores(), game.board().scores());
+            assert_eq!(loaded.board().current_turn(), Player::First);
+            assert_eq!(loaded.board().occupied(), game.board().occupied());
+            assert_eq!(loaded.clocks(), game.clocks());
+            assert_eq!(loaded.bag().tiles(), game.bag().tiles());
+            assert_eq!(loaded.seed(), 3);
+            assert_eq!(loaded.turn_rng(), game.turn_rng());
+
+            // The bag draws the same tiles after loading.
+            let tiles = game.rack(Player::First).tiles()[..3].to_vec();
+            let drawn = game.play(Player::First, Play::Exchange(tiles.clone()));
+            let loaded_drawn = loaded.play(Player::First, Play::Exchange(tiles));
+            assert_eq!(loaded_drawn.unwrap().drawn, drawn.unwrap().drawn);
+
+            loaded.undo_to(0).unwrap();
+            assert_eq!(loaded.board().occupied().count_ones(), 0);
+            assert_eq!(
+                loaded.rack(Player::First).tiles(),
+                loaded.tiles().tokenize("ACT").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_mismatches() {
+        let game = played();
+        let mut buf = Vec::new();
+        game.save(&mut buf, SaveFormat::Binary).unwrap();
+        let other_tiles = TileMap::from_tiles(Vec::new()).unwrap();
+        assert!(
+            Game::<BitboardImpl>::load(buf.as_slice(), SaveFormat::Binary, other_tiles).is_err()
+        );
+        assert!(
+            Game::<BitboardImpl>::load(&buf[..20], SaveFormat::Binary, TileMap::english()).is_err()
+        );
+        buf[4] = 9;
+        assert!(
+            Game::<BitboardImpl>::load(buf.as_slice(), SaveFormat::Binary, TileMap::english())
+                .is_err()
+        );
+
+        let mut json = Vec::new();
+        game.save(&mut json, SaveFormat::Json).unwrap();
+        let json = String::from_utf8(json)
+            .unwrap()
+            .replacen("\"version\":6", "\"version\":7", 1);
+        let loaded =
+            Game::<BitboardImpl>::load(json.as_bytes(), SaveFormat::Json, TileMap::english());
+        let Err(err) = loaded else {
+            panic!("Loaded a save of an unknown version");
+        };
+        assert!(err.to_string().contains("version 7"));
+    }
+
+    #[test]
+    fn rejects_history_tiles_not_in_the_tile_map() {
+        let placed = MoveTile::Placed(TilePlacement {
+            tile: TileMapIdx(200),
+            is_blank: false,
+        });
+        let bad_move = Move::new(Location::CENTER, Direction::Horizontal, vec![placed]).unwrap();
+        let corruptions: [fn(&mut Game<BitboardImpl>, Move); 3] = [
+            |game, m| game.history[0].summary.play = Play::Place(m),
+            |game, _| {
+                game.history[0].rack_after = Rack::from_tiles(vec![TileMapIdx(200)], 7).unwrap()
+            },
+            |game, m| {
+                game.last_play = Some(LastPlay {
+                    player: Player::First,
+                    m,
+                    score: 0,
+                    words: Vec::new(),
+                    phonies: Vec::new(),
+                    drawn: Vec::new(),
+                })
+            },
+        ];
+        for corrupt in corruptions {
+            let mut game = played();
+            corrupt(&mut game, bad_move.clone());
+            for format in [SaveFormat::Json, SaveFormat::Binary] {
+                let mut buf = Vec::new();
+                game.save(&mut buf, format).unwrap();
+                let loaded = Game::<BitboardImpl>::load(buf.as_slice(), format, TileMap::english());
+                let Err(err) = loaded else {
+                    panic!("Loaded a tile not in the tile map");
+                };
+                assert!(err.to_string().contains("200"), "{err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Scoring moves.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Direction, Location, Move, MoveTile, Play},
+    rules::Rules,
+    TileMap, TileMapIdx,
+};
+
+/// Bonus for using a whole rack in one move.
+pub const BINGO_BONUS: u32 = 50;
+/// How many tiles a move has to place to earn [`BINGO_BONUS`].
+pub const BINGO_TILES: usize = 7;
+
+/// The score of one word formed by a move.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordScore {
+    /// The first square of the word.
+    pub location: Location,
+    pub direction: Direction,
+    /// The letters of the word, designated letters for blanks.
+    pub tiles: Vec<TileMapIdx>,
+    /// The sum of the letter scores, with letter premiums applied.
+    pub letters: u32,
+    /// The product of the word premiums covered by the move.
+    pub multiplier: u32,
+}
+
+impl WordScore {
+    pub fn score(&self) -> u32 {
+        self.letters * self.multiplier
+    }
+}
+
+/// How a move scores: the main word first, then each cross word, plus any bingo bonus.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub words: Vec<WordScore>,
+    pub bingo: u32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> u32 {
+        self.words.iter().map(WordScore::score).sum::<u32>() + self.bingo
+    }
+}
+
+/// Scores `m` played on `board`, which it has to fit, see [`Move::validate_against`].
+///
+/// Premiums only count on squares the move covers, blanks score zero, and every word the move
+/// forms scores, see [`Move::words`].
+pub fn score_move<BB: Bitboard>(board: &Board<BB>, m: &Move, tiles: &TileMap) -> ScoreBreakdown {
+    let mut breakdown = ScoreBreakdown::default();
+    for (direction, squares) in m.words(board) {
+        breakdown
+            .words
+            .push(score_word(board, direction, &squares, tiles));
+    }
+    if m.num_placed() >= BINGO_TILES {
+        breakdown.bingo = BINGO_BONUS;
+    }
+    breakdown
+}
+
+/// Like [`score_move`], but with the bingo bonus of `rules`, which may differ from
+/// [`BINGO_BONUS`] and take a rack of another size to earn.
+pub fn score_move_with<BB: Bitboard>(
+    board: &Board<BB>,
+    m: &Move,
+    tiles: &TileMap,
+    rules: &Rules,
+) -> ScoreBreakdown {
+    let mut breakdown = score_move(board, m, tiles);
+    breakdown.bingo = rules.bingo_bonus_for(m.num_placed());
+    breakdown
+}
+
+/// Scores `play`: placements as in [`score_move`], exchanges and passes score nothing.
+pub fn score_play<BB: Bitboard>(board: &Board<BB>, play: &Play, tiles: &TileMap) -> ScoreBreakdown {
+    play.as_move()
+        .map_or_else(ScoreBreakdown::default, |m| score_move(board, m, tiles))
+}
+
+fn score_word<BB: Bitboard>(
+    board: &Board<BB>,
+    direction: Direction,
+    squares: &[(Location, MoveTile)],
+    tiles: &TileMap,
+) -> WordScore {
+    let mut letters = 0;
+    let mut multiplier = 1;
+    for (l, t) in squares {
+        match t {
+            MoveTile::Placed(p) => {
+                let premium = board.premiums().get(*l);
+                let letter = if p.is_blank {
+                    0
+                } else {
+                    u32::from(tiles.score(p.tile))
+                };
+                letters += letter * premium.map_or(1, |p| u32::from(p.letter_multiplier()));
+                multiplier *= premium.map_or(1, |p| u32::from(p.word_multiplier()));
+            }
+            MoveTile::OnBoard(tile) if !board.is_blank(*l) => {
+                letters += u32::from(tiles.score(*tile));
+            }
+            MoveTile::OnBoard(_) => {}
+        }
+    }
+    WordScore {
+        location: squares[0].0,
+        direction,
+        tiles: squares.iter().map(|(_, t)| t.tile()).collect(),
+        letters,
+        multiplier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitboardImpl;
+    use pretty_assertions::assert_eq;
+
+    fn play(board: &mut Board<BitboardImpl>, notation: &str, tiles: &TileMap) -> ScoreBreakdown {
+        let m = Move::parse(notation, board, tiles).unwrap();
+        let score = score_move(board, &m, tiles);
+        board.apply(&m).unwrap();
+        score
+    }
+
+    #[test]
+    fn premiums_only_count_once() {
+        let tiles = TileMap::english();
+        let mut board = Board::standard(&tiles);
+        // Center double word.
+        assert_eq!(play(&mut board, "8F CAT", &tiles).total(), 10);
+        // Through the T on the center, which doesn't double again.
+        let through = play(&mut board, "H7 AT", &tiles);
+        assert_eq!(through.words.len(), 1);
+        assert_eq!(through.total(), 2);
+    }
+
+    #[test]
+    fn cross_words() {
+        let tiles = TileMap::english();
+        let mut board = Board::standard(&tiles);
+        play(&mut board, "8F CAT", &tiles);
+        // AT, with T on a double letter, plus CA and AT downwards.
+        let score = play(&mut board, "9F AT", &tiles);
+        let words: Vec<_> = score
+            .words
+            .iter()
+            .map(|w| (w.location.to_string(), w.direction, w.score()))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                ("F9".to_string(), Direction::Horizontal, 3),
+                ("F8".to_string(), Direction::Vertical, 4),
+                ("G8".to_string(), Direction::Vertical, 3),
+            ]
+        );
+        assert_eq!(score.total(), 10);
+
+        // A single tile only scores the word it forms.
+        let single = play(&mut board, "E9 S", &tiles);
+        assert_eq!(single.words.len(), 1);
+        assert_eq!(single.words[0].tiles, tiles.tokenize("SAT").unwrap());
+        assert_eq!(single.total(), 3);
+    }
+
+    #[test]
+    fn blanks_and_bingos() {
+        let tiles = TileMap::english();
+        let mut board = Board::standard(&tiles);
+        assert_eq!(play(&mut board, "8F CaT", &tiles).total(), 8);
+
+        let mut board = Board::standard(&tiles);
+        let bingo = play(&mut board, "8H RETAINS", &tiles);
+        assert_eq!(bingo.bingo, BINGO_BONUS);
+        assert_eq!(bingo.total(), 16 + 50);
+        // The blank on the board scores zero in later words too.
+        let mut board = Board::standard(&tiles);
+        play(&mut board, "8F CaT", &tiles);
+        assert_eq!(play(&mut board, "G7 xA", &tiles).total(), 0);
+    }
+
+    #[test]
+    fn non_placements_score_nothing() {
+        let tiles = TileMap::english();
+        let board = Board::<BitboardImpl>::standard(&tiles);
+        let m = Move::parse("8F CAT", &board, &tiles).unwrap();
+        assert_eq!(score_play(&board, &Play::from(m), &tiles).total(), 10);
+        let exchange = Play::Exchange(tiles.tokenize("CAT").unwrap());
+        assert_eq!(
+            score_play(&board, &exchange, &tiles),
+            ScoreBreakdown::default()
+        );
+        assert_eq!(score_play(&board, &Play::Pass, &tiles).total(), 0);
+    }
+}
@@ -0,0 +1,265 @@
+//! Bot against bot games, for comparing strategies, tuning them and generating test games.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bitboard::Bitboard,
+    board::Player,
+    bot::Strategy,
+    error::{Context, Result},
+    outcome::GameOutcome,
+    stats::GameStats,
+    turn::TurnRecord,
+    GameBuilder,
+};
+
+/// One game of a [self-play run](run).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfPlayGame {
+    /// The game was set up with this seed, see [`GameBuilder::seed`].
+    pub seed: u64,
+    /// The side bot A played.
+    pub a_played: Player,
+    pub outcome: GameOutcome,
+    pub stats: GameStats,
+    pub history: Vec<TurnRecord>,
+}
+
+impl SelfPlayGame {
+    /// Bot A's final score minus bot B's.
+    pub fn spread(&self) -> i32 {
+        self.outcome.scores.spread(self.a_played)
+    }
+
+    pub fn a_won(&self) -> bool {
+        self.outcome.winner == Some(self.a_played)
+    }
+
+    pub fn b_won(&self) -> bool {
+        self.outcome.winner == Some(self.a_played.other())
+    }
+}
+
+/// The games of a [self-play run](run), in the order they were set up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelfPlayReport {
+    pub games: Vec<SelfPlayGame>,
+}
+
+impl SelfPlayReport {
+    pub fn wins_a(&self) -> usize {
+        self.games.iter().filter(|g| g.a_won()).count()
+    }
+
+    pub fn wins_b(&self) -> usize {
+        self.games.iter().filter(|g| g.b_won()).count()
+    }
+
+    pub fn ties(&self) -> usize {
+        self.games.len() - self.wins_a() - self.wins_b()
+    }
+
+    /// Bot A's share of the games, ties counting half.
+    pub fn score_a(&self) -> f64 {
+        if self.games.is_empty() {
+            return 0.0;
+        }
+        (self.wins_a() as f64 + self.ties() as f64 / 2.0) / self.games.len() as f64
+    }
+
+    /// Bot A's average [spread](SelfPlayGame::spread).
+    pub fn average_spread(&self) -> f64 {
+        if self.games.is_empty() {
+            return 0.0;
+        }
+        self.games
+            .iter()
+            .map(|g| f64::from(g.spread()))
+            .sum::<f64>()
+            / self.games.len() as f64
+    }
+}
+
+/// Plays `n_games` games of `bot_a` against `bot_b`, each set up by `setup` with its tiles,
+/// lexicons and rules, and a seed of its own drawn from `seed`. The bots take turns going first,
+/// and each game gets fresh clones of them.
+///
+/// The same seed gives the same games, as long as the bots make their random choices with the
+/// [turn RNG](crate::GameView::turn_rng).
+pub fn run<BB, A, B>(
+    setup: &GameBuilder<BB>,
+    n_games: usize,
+    bot_a: &A,
+    bot_b: &B,
+    seed: u64,
+) -> Result<SelfPlayReport>
+where
+    BB: Bitboard,
+    A: Strategy<BB> + Clone,
+    B: Strategy<BB> + Clone,
+{
+    let games = game_seeds(seed, n_games)
+        .enumerate()
+        .map(|(i, seed)| play(setup, seed, first_for_a(i), bot_a.clone(), bot_b.clone()))
+        .collect::<Result<_>>()?;
+    Ok(SelfPlayReport { games })
+}
+
+/// Like [`run`], but with the games spread across rayon's threads. The report is the same.
+#[cfg(feature = "rayon")]
+pub fn par_run<BB, A, B>(
+    setup: &GameBuilder<BB>,
+    n_games: usize,
+    bot_a: &A,
+    bot_b: &B,
+    seed: u64,
+) -> Result<SelfPlayReport>
+where
+    BB: Bitboard + Send + Sync,
+    A: Strategy<BB> + Clone + Send + Sync,
+    B: Strategy<BB> + Clone + Send + Sync,
+{
+    use rayon::prelude::*;
+
+    let seeds: Vec<_> = game_seeds(seed, n_games).collect();
+    let games = seeds
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, seed)| play(setup, seed, first_for_a(i), bot_a.clone(), bot_b.clone()))
+        .collect::<Result<_>>()?;
+    Ok(SelfPlayReport { games })
+}
+
+fn game_seeds(seed: u64, n_games: usize) -> impl Iterator<Item = u64> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    (0..n_games).map(move |_| rng.gen())
+}
+
+/// Bot A goes first in even games.
+fn first_for_a(game: usize) -> Player {
+    if game.is_multiple_of(2) {
+        Player::First
+    } else {
+        Player::Second
+    }
+}
+
+fn play<BB: Bitboard>(
+    setup: &GameBuilder<BB>,
+    seed: u64,
+    a_played: Player,
+    mut bot_a: impl Strategy<BB>,
+    mut bot_b: impl Strategy<BB>,
+) -> Result<SelfPlayGame> {
+    let mut game = setup.clone().seed(seed).build()?;
+    loop {
+        if let Some(outcome) = game.outcome() {
+            return Ok(SelfPlayGame {
+                seed,
+                a_played,
+                outcome,
+                stats: game.stats(),
+                history: game.history().to_vec(),
+            });
+        }
+        let player = game.board().current_turn();
+        let play = if player == a_played {
+            bot_a.choose(&game.view(player))
+        } else {
+            bot_b.choose(&game.view(player))
+        };
+        game.play(player, play).with_context(|| {
+            format!(
+                "Turn {} of the game with seed {seed}",
+                game.history().len() + 1
+            )
+        })?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl, board::Play, movegen::iter_moves, Game, GameView, Lexicon, TileMap,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// Plays the first move it finds, if any.
+    #[derive(Clone)]
+    struct FirstMove;
+
+    impl Strategy<BitboardImpl> for FirstMove {
+        fn choose(&mut self, view: &GameView<BitboardImpl>) -> Play {
+            let lexicon = view.lexicons().expect("Set up with a lexicon").generation();
+            iter_moves(view.board(), view.rack(), lexicon)
+                .next()
+                .map_or(Play::Pass, Play::Place)
+        }
+    }
+
+    /// Never plays.
+    #[derive(Clone)]
+    struct Passer;
+
+    impl Strategy<BitboardImpl> for Passer {
+        fn choose(&mut self, _: &GameView<BitboardImpl>) -> Play {
+            Play::Pass
+        }
+    }
+
+    fn setup() -> GameBuilder<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "AT", "TA", "AN", "NA", "IT", "TI", "IN", "NO", "ON", "TO", "EAT", "TEA", "NOT",
+            ],
+            &tiles,
+        );
+        Game::builder().tiles(tiles).lexicons(lexicon)
+    }
+
+    #[test]
+    fn run_games() {
+        let report = run(&setup(), 4, &FirstMove, &Passer, 7).unwrap();
+        let sides: Vec<_> = report.games.iter().map(|g| g.a_played).collect();
+        assert_eq!(
+            sides,
+            [Player::First, Player::Second, Player::First, Player::Second]
+        );
+        let game = &report.games[0];
+        assert_eq!(game.stats.turns(), game.history.len());
+        assert_eq!(game.stats.second.passes, game.stats.second.turns);
+        assert_eq!(
+            report.wins_a() + report.wins_b() + report.ties(),
+            report.games.len()
+        );
+        assert!(report.wins_a() > 0);
+        assert!(report.average_spread() > 0.0);
+
+        // The same seed plays the same games, though not at the same time.
+        let again = run(&setup(), 4, &FirstMove, &Passer, 7).unwrap();
+        let summary = |r: &SelfPlayReport| -> Vec<_> {
+            r.games
+                .iter()
+                .map(|g| (g.seed, g.outcome, g.stats.clone()))
+                .collect()
+        };
+        assert_eq!(summary(&again), summary(&report));
+        assert_ne!(
+            run(&setup(), 1, &FirstMove, &Passer, 8).unwrap().games[0].seed,
+            report.games[0].seed
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let report = run(&setup(), 3, &FirstMove, &FirstMove, 1).unwrap();
+        let parallel = par_run(&setup(), 3, &FirstMove, &FirstMove, 1).unwrap();
+        let outcomes =
+            |r: &SelfPlayReport| -> Vec<_> { r.games.iter().map(|g| g.outcome).collect() };
+        assert_eq!(outcomes(&parallel), outcomes(&report));
+    }
+}
@@ -0,0 +1,552 @@
+//! Simulation: playing candidate plays out a few turns against sampled opponent racks and draws,
+//! to judge them by where they lead rather than by their equity alone.
+
+use std::{ops::ControlFlow, sync::Arc};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Play, Player},
+    bot::{placements, Equity},
+    endgame::{rack_value, score, Position},
+    error::Result,
+    eval::{Standing, WinModel},
+    inference::RackInference,
+    leaves::LeaveTable,
+    movegen::leave,
+    rack_idx, rack_tile,
+    tiles::TileCounts,
+    view::GameView,
+    Game, Rack, TileMapIdx,
+};
+
+/// Simulates the top plays by equity for the viewing player.
+///
+/// Every iteration samples a rack for the opponent and an order for the bag, makes the play, and
+/// lets both sides continue with the highest equity placement for a few turns. A play is rated by
+/// the spread it gains in that time plus the value of the leave it ends with, less the
+/// opponent's, and by the chance of winning from there. Iteration `i` draws the same tiles for
+/// every candidate, so differences come from the plays rather than luck.
+///
+/// Iterations run in batches. In between, candidates statistically behind the leader stop being
+/// simulated, and the simulation stops once a single one is left.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    leaves: Arc<LeaveTable>,
+    win_model: WinModel,
+    inference: Option<RackInference>,
+    candidates: usize,
+    plies: usize,
+    iterations: usize,
+    batch: usize,
+    min_iterations: usize,
+    early_stop: Option<f64>,
+}
+
+/// The state of a simulation, when done or as [progress](Simulator::simulate).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimResult {
+    /// In the order they were picked while simulating; the best first when done, pruned ones
+    /// last.
+    pub candidates: Vec<SimCandidate>,
+    /// How many iterations the candidates still simulated have had.
+    pub iterations: usize,
+    /// Whether the simulation stopped before all iterations, because one candidate dominated or
+    /// the progress callback said so.
+    pub stopped_early: bool,
+}
+
+/// A simulated play.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimCandidate {
+    pub play: Play,
+    /// Its equity before simulating.
+    pub static_equity: f32,
+    pub iterations: usize,
+    /// The average rating over the iterations, see [`Simulator`].
+    pub equity: f64,
+    pub win_probability: f64,
+    /// Whether it fell so far behind the leader that it stopped being simulated.
+    pub pruned: bool,
+    /// The sum of squared deviations from `equity`, for the standard error.
+    m2: f64,
+}
+
+impl SimCandidate {
+    fn new(play: Play, static_equity: f32) -> Self {
+        Self {
+            play,
+            static_equity,
+            iterations: 0,
+            equity: 0.0,
+            win_probability: 0.0,
+            pruned: false,
+            m2: 0.0,
+        }
+    }
+
+    /// The standard error of [`SimCandidate::equity`], infinite before two iterations.
+    pub fn std_error(&self) -> f64 {
+        if self.iterations < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.iterations as f64;
+        (self.m2 / (n - 1.0) / n).sqrt()
+    }
+
+    fn add(&mut self, sample: Sample) {
+        self.iterations += 1;
+        let n = self.iterations as f64;
+        let delta = sample.equity - self.equity;
+        self.equity += delta / n;
+        self.m2 += delta * (sample.equity - self.equity);
+        self.win_probability += (sample.win - self.win_probability) / n;
+    }
+}
+
+impl SimResult {
+    /// The best candidate, once done.
+    pub fn best(&self) -> Option<&SimCandidate> {
+        self.candidates.first()
+    }
+
+    /// Marks the candidates more than `z` standard errors behind the leader as pruned.
+    fn prune(&mut self, z: f64) {
+        let Some(leader) = self
+            .candidates
+            .iter()
+            .filter(|c| !c.pruned)
+            .max_by(|a, b| a.equity.total_cmp(&b.equity))
+            .cloned()
+        else {
+            return;
+        };
+        for c in self.candidates.iter_mut().filter(|c| !c.pruned) {
+            let error = c.std_error().hypot(leader.std_error());
+            c.pruned = leader.equity - c.equity > z * error;
+        }
+    }
+}
+
+/// The outcome of one iteration, for the player simulating.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    equity: f64,
+    win: f64,
+}
+
+impl Simulator {
+    pub fn new(leaves: impl Into<Arc<LeaveTable>>) -> Self {
+        Self {
+            leaves: leaves.into(),
+            win_model: WinModel::default(),
+            inference: None,
+            candidates: 10,
+            plies: 2,
+            iterations: 1000,
+            batch: 50,
+            min_iterations: 100,
+            early_stop: Some(2.58),
+        }
+    }
+
+    /// How many of the top plays by equity to simulate, 10 by default.
+    pub fn candidates(mut self, n: usize) -> Self {
+        self.candidates = n;
+        self
+    }
+
+    /// How many turns to play after the candidate, 2 by default.
+    pub fn plies(mut self, plies: usize) -> Self {
+        self.plies = plies;
+        self
+    }
+
+    /// The most iterations per candidate, 1000 by default.
+    pub fn iterations(mut self, n: usize) -> Self {
+        self.iterations = n;
+        self
+    }
+
+    /// How many iterations to run between progress reports and pruning, 50 by default.
+    pub fn batch(mut self, n: usize) -> Self {
+        self.batch = n.max(1);
+        self
+    }
+
+    /// Prunes candidates more than `z` standard errors behind the leader, once they've had
+    /// `min_iterations`. By default 2.58, about 99% confidence, after 100 iterations. `None` runs
+    /// every candidate to the end.
+    pub fn early_stop(mut self, z: Option<f64>, min_iterations: usize) -> Self {
+        self.early_stop = z;
+        self.min_iterations = min_iterations;
+        self
+    }
+
+    /// Samples the opponent's rack from `inference` instead of uniformly from the unseen tiles.
+    pub fn inference(mut self, inference: RackInference) -> Self {
+        self.inference = Some(inference);
+        self
+    }
+
+    pub fn win_model(mut self, model: WinModel) -> Self {
+        self.win_model = model;
+        self
+    }
+
+    /// Simulates for the viewing player, on their turn. `progress` gets the partial results after
+    /// every batch and can stop the simulation early.
+    pub fn simulate<BB: Bitboard>(
+        &self,
+        view: &GameView<BB>,
+        progress: impl FnMut(&SimResult) -> ControlFlow<()>,
+    ) -> Result<SimResult> {
+        let rollouts = Rollouts::new(self, view);
+        self.run(&rollouts, progress, |work| {
+            work.iter().map(|&(c, i)| rollouts.rollout(c, i)).collect()
+        })
+    }
+
+    /// Like [`Simulator::simulate`], but with every batch spread across rayon's threads. The
+    /// results are the same.
+    #[cfg(feature = "rayon")]
+    pub fn par_simulate<BB: Bitboard + Send + Sync>(
+        &self,
+        view: &GameView<BB>,
+        progress: impl FnMut(&SimResult) -> ControlFlow<()>,
+    ) -> Result<SimResult> {
+        use rayon::prelude::*;
+
+        let rollouts = Rollouts::new(self, view);
+        self.run(&rollouts, progress, |work| {
+            work.par_iter()
+                .map(|&(c, i)| rollouts.rollout(c, i))
+                .collect()
+        })
+    }
+
+    /// Runs the batches, with `batch` running the iterations it's given, each a candidate and an
+    /// iteration number.
+    fn run<BB: Bitboard>(
+        &self,
+        rollouts: &Rollouts<BB>,
+        mut progress: impl FnMut(&SimResult) -> ControlFlow<()>,
+        batch: impl Fn(&[(usize, usize)]) -> Result<Vec<Sample>>,
+    ) -> Result<SimResult> {
+        let mut result = SimResult {
+            candidates: rollouts
+                .plays
+                .iter()
+                .map(|(play, equity)| SimCandidate::new(play.clone(), *equity))
+                .collect(),
+            iterations: 0,
+            stopped_early: false,
+        };
+        while result.iterations < self.iterations {
+            let n = self.batch.min(self.iterations - result.iterations);
+            let iterations = result.iterations..result.iterations + n;
+            let work: Vec<(usize, usize)> = (0..result.candidates.len())
+                .filter(|c| !result.candidates[*c].pruned)
+                .flat_map(|c| iterations.clone().map(move |i| (c, i)))
+                .collect();
+            for (&(c, _), sample) in work.iter().zip(batch(&work)?) {
+                result.candidates[c].add(sample);
+            }
+            result.iterations += n;
+            if let Some(z) = self.early_stop {
+                if result.iterations >= self.min_iterations {
+                    result.prune(z);
+                }
+            }
+            let left = result.candidates.iter().filter(|c| !c.pruned).count();
+            let done = result.iterations >= self.iterations;
+            if progress(&result).is_break() || left <= 1 {
+                result.stopped_early = !done;
+                break;
+            }
+        }
+        result
+            .candidates
+            .sort_by(|a, b| a.pruned.cmp(&b.pruned).then(b.equity.total_cmp(&a.equity)));
+        Ok(result)
+    }
+}
+
+/// What every iteration starts from.
+struct Rollouts<'a, BB: Bitboard> {
+    sim: &'a Simulator,
+    view: GameView<'a, BB>,
+    plays: Vec<(Play, f32)>,
+    unseen: TileCounts,
+    seed: u64,
+}
+
+impl<'a, BB: Bitboard> Rollouts<'a, BB> {
+    fn new(sim: &'a Simulator, view: &GameView<'a, BB>) -> Self {
+        let mut plays = Equity::new(sim.leaves.clone()).rated(view);
+        // Stable, so equal plays stay in the order they were generated.
+        plays.sort_by(|a, b| b.1.total_cmp(&a.1));
+        plays.truncate(sim.candidates.max(1));
+        if plays.is_empty() {
+            plays.push((Play::Pass, 0.0));
+        }
+        Self {
+            sim,
+            view: *view,
+            plays,
+            unseen: view.unseen().into_counts(),
+            seed: view.turn_rng().gen(),
+        }
+    }
+
+    fn game(&self) -> &'a Game<BB> {
+        self.view.game
+    }
+
+    /// Iteration `iteration` of candidate `candidate`.
+    fn rollout(&self, candidate: usize, iteration: usize) -> Result<Sample> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(iteration as u64);
+        let opponent = match &self.sim.inference {
+            Some(inference) => inference.sample(&mut rng),
+            None => self
+                .unseen
+                .sample_rack(self.view.opponent_rack_len(), &mut rng),
+        };
+        let mut bag: Vec<TileMapIdx> = self
+            .unseen
+            .saturating_sub(&TileCounts::from(&opponent))
+            .iter()
+            .flat_map(|(t, n)| std::iter::repeat_n(t, n.into()))
+            .collect();
+        let mut position = Position::new(&self.view, opponent);
+        let mut points = [0; 2];
+
+        let me = self.view.player();
+        let play = &self.plays[candidate].0;
+        points[rack_idx(me)] += self.apply(&mut position, &mut bag, play, &mut rng)?;
+        for _ in 0..self.sim.plies {
+            if is_over(&position, &bag) {
+                break;
+            }
+            let player = position.to_move;
+            let reply = self.reply(&position, bag.is_empty());
+            points[rack_idx(player)] += self.apply(&mut position, &mut bag, &reply, &mut rng)?;
+        }
+        Ok(self.evaluate(&position, &bag, points))
+    }
+
+    /// The highest equity placement for the player to move, or a pass.
+    fn reply(&self, position: &Position<BB>, endgame: bool) -> Play {
+        let (game, tiles) = (self.game(), self.game().tiles());
+        let rack = position.rack(position.to_move);
+        let mut best: Option<(f32, Play)> = None;
+        for m in placements(game, &position.board, rack) {
+            let mut equity = score(game, &position.board, &m) as f32;
+            if !endgame {
+                equity += self.sim.leaves.value_of(&leave(rack, &m, tiles));
+            }
+            if best.as_ref().is_none_or(|(e, _)| equity > *e) {
+                best = Some((equity, Play::Place(m)));
+            }
+        }
+        best.map_or(Play::Pass, |(_, play)| play)
+    }
+
+    /// Makes `play` for the player to move and draws, returning its score.
+    fn apply(
+        &self,
+        position: &mut Position<BB>,
+        bag: &mut Vec<TileMapIdx>,
+        play: &Play,
+        rng: &mut impl Rng,
+    ) -> Result<i32> {
+        let (game, player) = (self.game(), position.to_move);
+        let points = match play {
+            Play::Place(m) => {
+                let points = score(game, &position.board, m);
+                position.board.apply(m)?;
+                let rack = &mut position.racks[rack_idx(player)];
+                for (_, p) in m.placed() {
+                    rack.remove(rack_tile(game.tiles(), p)?)?;
+                }
+                draw(bag, rack, rng)?;
+                points
+            }
+            Play::Exchange(tiles) => {
+                let rack = &mut position.racks[rack_idx(player)];
+                rack.remove_all(tiles)?;
+                draw(bag, rack, rng)?;
+                bag.extend_from_slice(tiles);
+                0
+            }
+            Play::Pass => 0,
+        };
+        position.scoreless = if points > 0 {
+            0
+        } else {
+            position.scoreless + 1
+        };
+        position.to_move = player.other();
+        Ok(points)
+    }
+
+    /// Rates where an iteration ended up for the simulating player, having scored `points`.
+    fn evaluate(&self, position: &Position<BB>, bag: &[TileMapIdx], points: [i32; 2]) -> Sample {
+        let tiles = self.game().tiles();
+        let me = self.view.player();
+        let mut scores = self.view.scores();
+        for player in [Player::First, Player::Second] {
+            scores.add(player, points[rack_idx(player)]);
+        }
+        if is_over(position, bag) {
+            for player in [Player::First, Player::Second] {
+                let left = rack_value(tiles, position.rack(player));
+                scores.add(player, -left);
+                if bag.is_empty() && position.rack(player.other()).is_empty() {
+                    scores.add(player.other(), left);
+                }
+            }
+            let spread = scores.spread(me);
+            let win = match spread.signum() {
+                1 => 1.0,
+                0 => 0.5,
+                _ => 0.0,
+            };
+            let gained = spread - self.view.scores().spread(me);
+            return Sample {
+                equity: f64::from(gained),
+                win,
+            };
+        }
+        let mut equity = f64::from(scores.spread(me) - self.view.scores().spread(me));
+        if !bag.is_empty() {
+            let value =
+                |player: Player| f64::from(self.sim.leaves.value_of(position.rack(player).tiles()));
+            equity += value(me) - value(me.other());
+        }
+        let standing = Standing {
+            player: me,
+            scores,
+            unseen: bag.len() + position.rack(me.other()).len(),
+        };
+        Sample {
+            equity,
+            win: self
+                .sim
+                .win_model
+                .win_probability(&standing, position.to_move),
+        }
+    }
+}
+
+fn is_over<BB: Bitboard>(position: &Position<BB>, bag: &[TileMapIdx]) -> bool {
+    (bag.is_empty() && position.racks.iter().any(Rack::is_empty))
+        || position.scoreless >= Game::<BB>::SCORELESS_TURNS
+}
+
+/// Fills `rack` from `bag` at random, as far as the bag goes.
+fn draw(bag: &mut Vec<TileMapIdx>, rack: &mut Rack, rng: &mut impl Rng) -> Result<()> {
+    while rack.missing() > 0 && !bag.is_empty() {
+        let i = rng.gen_range(0..bag.len());
+        rack.add(bag.swap_remove(i))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, lexicon::Lexicon, GameBuilder, TileMap};
+    use pretty_assertions::assert_eq;
+
+    fn setup() -> Game<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "CAT", "AT", "TA", "ZA", "ZAS", "CATS", "SAT", "EAT", "TEA", "ETA",
+            ],
+            &tiles,
+        );
+        GameBuilder::new()
+            .tiles(tiles.clone())
+            .lexicons(lexicon)
+            .rack(Player::First, Rack::parse("CATZSEI", &tiles).unwrap())
+            .seed(3)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn simulate() {
+        let game = setup();
+        let view = game.view(Player::First);
+        let sim = Simulator::new(LeaveTable::new())
+            .candidates(4)
+            .iterations(40)
+            .batch(10)
+            .early_stop(None, 0);
+        let mut reports = 0;
+        let result = sim
+            .simulate(&view, |partial| {
+                reports += 1;
+                assert!(partial
+                    .candidates
+                    .iter()
+                    .all(|c| c.iterations == partial.iterations));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!((reports, result.iterations), (4, 40));
+        assert!(!result.stopped_early);
+        assert_eq!(result.candidates.len(), 4);
+        let equities: Vec<f64> = result.candidates.iter().map(|c| c.equity).collect();
+        assert!(equities.windows(2).all(|w| w[0] >= w[1]));
+        let best = result.best().unwrap();
+        assert!((0.0..=1.0).contains(&best.win_probability));
+        assert!(best.std_error().is_finite());
+
+        // The same game and seed simulate the same.
+        assert_eq!(
+            sim.simulate(&view, |_| ControlFlow::Continue(())).unwrap(),
+            result
+        );
+
+        let stopped = sim.simulate(&view, |_| ControlFlow::Break(())).unwrap();
+        assert_eq!(stopped.iterations, 10);
+        assert!(stopped.stopped_early);
+    }
+
+    #[test]
+    fn prunes() {
+        let game = setup();
+        let view = game.view(Player::First);
+        let result = Simulator::new(LeaveTable::new())
+            .candidates(6)
+            .iterations(200)
+            .batch(20)
+            .early_stop(Some(0.0), 20)
+            .simulate(&view, |_| ControlFlow::Continue(()))
+            .unwrap();
+        // With no margin for error, only the leader is left after the first batch.
+        assert!(result.stopped_early);
+        assert_eq!(result.iterations, 20);
+        assert!(result.candidates[1..].iter().all(|c| c.pruned));
+        assert!(!result.candidates[0].pruned);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let game = setup();
+        let view = game.view(Player::First);
+        let sim = Simulator::new(LeaveTable::new()).iterations(30).batch(10);
+        let keep_going = |_: &SimResult| ControlFlow::Continue(());
+        assert_eq!(
+            sim.par_simulate(&view, keep_going).unwrap(),
+            sim.simulate(&view, keep_going).unwrap()
+        );
+    }
+}
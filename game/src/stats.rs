@@ -0,0 +1,175 @@
+//! Statistics over a game's history, see [`Game::stats`].
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Play, Player},
+    rack_idx, Game, TileMapIdx,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub turns: usize,
+    /// Points scored by plays that stayed on the board.
+    pub points: u32,
+    pub bingos: usize,
+    pub tiles_played: usize,
+    pub exchanges: usize,
+    pub passes: usize,
+    /// Plays that were challenged off.
+    pub phonies_retracted: usize,
+    /// The ply of the highest-scoring play, if any play stayed on the board.
+    pub best_play: Option<usize>,
+    pub best_score: u32,
+    /// The tiles drawn, including the opening rack. Along with the two fields below, this says
+    /// how lucky the draws were, independent of how well they were played.
+    pub tiles_drawn: usize,
+    pub blanks_drawn: usize,
+    /// The sum of the face values of the tiles drawn.
+    pub value_drawn: u32,
+}
+
+impl PlayerStats {
+    pub fn average_per_turn(&self) -> f64 {
+        ratio(self.points, self.turns)
+    }
+
+    /// Points per tile played, which rewards playing tiles well rather than drawing good ones.
+    pub fn points_per_tile(&self) -> f64 {
+        ratio(self.points, self.tiles_played)
+    }
+
+    pub fn average_draw_value(&self) -> f64 {
+        ratio(self.value_drawn, self.tiles_drawn)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameStats {
+    pub first: PlayerStats,
+    pub second: PlayerStats,
+}
+
+impl GameStats {
+    pub fn player(&self, player: Player) -> &PlayerStats {
+        match player {
+            Player::First => &self.first,
+            Player::Second => &self.second,
+        }
+    }
+
+    fn player_mut(&mut self, player: Player) -> &mut PlayerStats {
+        match player {
+            Player::First => &mut self.first,
+            Player::Second => &mut self.second,
+        }
+    }
+
+    pub fn turns(&self) -> usize {
+        self.first.turns + self.second.turns
+    }
+
+    pub fn average_per_turn(&self) -> f64 {
+        ratio(self.first.points + self.second.points, self.turns())
+    }
+}
+
+fn ratio(n: u32, d: usize) -> f64 {
+    if d == 0 {
+        0.0
+    } else {
+        f64::from(n) / d as f64
+    }
+}
+
+impl<BB: Bitboard> Game<BB> {
+    /// Statistics for the turns played so far.
+    pub fn stats(&self) -> GameStats {
+        let mut stats = GameStats::default();
+        let count_drawn = |stats: &mut GameStats, player, drawn: &[TileMapIdx]| {
+            let s = stats.player_mut(player);
+            s.tiles_drawn += drawn.len();
+            s.blanks_drawn += drawn.iter().filter(|t| self.tiles.is_blank(**t)).count();
+            s.value_drawn += drawn
+                .iter()
+                .map(|t| u32::from(self.tiles.score(*t)))
+                .sum::<u32>();
+        };
+        if let Some(start) = &self.start {
+            for player in [Player::First, Player::Second] {
+                count_drawn(&mut stats, player, start.racks[rack_idx(player)].tiles());
+            }
+        }
+        for (ply, record) in self.history.iter().enumerate() {
+            let summary = &record.summary;
+            let s = stats.player_mut(summary.player);
+            s.turns += 1;
+            match &summary.play {
+                Play::Place(_) if record.is_retracted() => {
+                    s.phonies_retracted += 1;
+                    continue;
+                }
+                Play::Place(m) => {
+                    let score = summary.score.total();
+                    s.points += score;
+                    s.tiles_played += m.num_placed();
+                    s.bingos += usize::from(summary.score.bingo > 0);
+                    if s.best_play.is_none() || score > s.best_score {
+                        s.best_play = Some(ply);
+                        s.best_score = score;
+                    }
+                }
+                Play::Exchange(_) => s.exchanges += 1,
+                Play::Pass => s.passes += 1,
+            }
+            count_drawn(&mut stats, summary.player, &summary.drawn);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, board::Move, Lexicon, TileMap};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn stats() {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(["CAT", "AT", "TA"], &tiles);
+        let mut game = Game::<BitboardImpl>::builder()
+            .seed(2)
+            .lexicons(lexicon)
+            .build()
+            .unwrap();
+        let place = |game: &mut Game<BitboardImpl>, player, rack: &str, notation| {
+            let rack = game.tiles().tokenize(rack).unwrap();
+            game.set_rack(player, &rack).unwrap();
+            let m = Move::parse(notation, game.board(), game.tiles()).unwrap();
+            game.play(player, Play::Place(m)).unwrap();
+        };
+        place(&mut game, Player::First, "CAT", "8G CAT");
+        let tiles = game.rack(Player::Second).tiles()[..2].to_vec();
+        game.play(Player::Second, Play::Exchange(tiles)).unwrap();
+        place(&mut game, Player::First, "AS", "9H AS");
+        game.challenge(Player::Second).unwrap();
+        place(&mut game, Player::Second, "A", "I7 A.");
+
+        let stats = game.stats();
+        let first = stats.player(Player::First);
+        assert_eq!((first.turns, first.points, first.tiles_played), (2, 10, 3));
+        assert_eq!((first.phonies_retracted, first.best_play), (1, Some(0)));
+        assert_eq!(first.average_per_turn(), 5.0);
+        // The opening rack was set to CAT, which was played out and refilled.
+        assert_eq!(first.tiles_drawn, 3 + 7);
+        let second = stats.player(Player::Second);
+        assert_eq!(
+            (second.exchanges, second.best_play, second.best_score),
+            (1, Some(3), 3)
+        );
+        // The rack was set to A before its play, so it drew a full rack afterwards.
+        assert_eq!(second.tiles_drawn, 7 + 2 + 7);
+        assert_eq!(stats.turns(), 4);
+        assert_eq!(stats.average_per_turn(), 13.0 / 4.0);
+    }
+}
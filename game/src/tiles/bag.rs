@@ -0,0 +1,231 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{board::PlayError, error::Result};
+
+use super::{Rack, TileMap, TileMapIdx};
+
+/// The tiles that haven't been drawn yet.
+///
+/// Every draw picks uniformly at random using the bag's own RNG, so the order tiles are returned in
+/// doesn't matter and there is no separate shuffle step. Two bags built from the same tile map and
+/// seed produce the same draws.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileBag<R: Rng = ChaCha8Rng> {
+    tiles: Vec<TileMapIdx>,
+    rng: R,
+    min_for_exchange: usize,
+}
+
+impl TileBag<ChaCha8Rng> {
+    /// A full bag for `tiles`, drawing reproducibly based on `seed`.
+    pub fn seeded(tiles: &TileMap, seed: u64) -> Self {
+        Self::new(tiles, ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> TileBag<R> {
+    /// How many tiles need to be in the bag to allow an exchange, unless set otherwise with
+    /// [`TileBag::with_min_for_exchange`].
+    pub const MIN_FOR_EXCHANGE: usize = 7;
+
+    /// A full bag for `tiles`.
+    pub fn new(tiles: &TileMap, rng: R) -> Self {
+        let tiles = tiles
+            .iter()
+            .flat_map(|(idx, _)| std::iter::repeat_n(idx, tiles.count(idx) as usize))
+            .collect();
+        Self {
+            tiles,
+            rng,
+            min_for_exchange: Self::MIN_FOR_EXCHANGE,
+        }
+    }
+
+    pub fn with_min_for_exchange(mut self, min: usize) -> Self {
+        self.min_for_exchange = min;
+        self
+    }
+
+    /// How many tiles need to be in the bag to allow an exchange.
+    pub fn min_for_exchange(&self) -> usize {
+        self.min_for_exchange
+    }
+
+    /// Draws up to `n` tiles, fewer if the bag runs out.
+    pub fn draw(&mut self, n: usize) -> Vec<TileMapIdx> {
+        (0..n.min(self.tiles.len()))
+            .map(|_| {
+                let idx = self.rng.gen_range(0..self.tiles.len());
+                self.tiles.swap_remove(idx)
+            })
+            .collect()
+    }
+
+    /// Draws until `rack` is full or the bag is empty, returning the drawn tiles.
+    pub fn draw_to_fill(&mut self, rack: &mut Rack) -> Vec<TileMapIdx> {
+        let drawn = self.draw(rack.missing());
+        for t in &drawn {
+            rack.add(*t)
+                .expect("Drew only as many tiles as the rack misses");
+        }
+        drawn
+    }
+
+    pub fn can_exchange(&self) -> bool {
+        self.remaining() >= self.min_for_exchange
+    }
+
+    /// Swaps `tiles` from `rack` for new ones, returning the new tiles.
+    ///
+    /// Replacements are drawn before the exchanged tiles go back into the bag, so a player can't
+    /// draw their own tiles back.
+    pub fn exchange(&mut self, rack: &mut Rack, tiles: &[TileMapIdx]) -> Result<Vec<TileMapIdx>> {
+        if tiles.is_empty() {
+            return Err(PlayError::EmptyExchange.into());
+        }
+        if !self.can_exchange() {
+            return Err(PlayError::BagTooSmall {
+                remaining: self.remaining(),
+                needed: self.min_for_exchange,
+            }
+            .into());
+        }
+        rack.remove_all(tiles)?;
+        let drawn = self.draw_to_fill(rack);
+        self.return_tiles(tiles);
+        Ok(drawn)
+    }
+
+    /// Takes out one `tile` in particular, returning whether the bag held one.
+    pub fn take(&mut self, tile: TileMapIdx) -> bool {
+        match self.tiles.iter().position(|t| *t == tile) {
+            Some(pos) => {
+                self.tiles.swap_remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Puts `tiles` back into the bag.
+    pub fn return_tiles(&mut self, tiles: &[TileMapIdx]) {
+        self.tiles.extend_from_slice(tiles);
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The tiles in the bag, in no particular order.
+    pub fn tiles(&self) -> &[TileMapIdx] {
+        &self.tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn draw_and_return() {
+        let tiles = TileMap::english();
+        let mut bag = TileBag::seeded(&tiles, 1);
+        assert_eq!(bag.remaining(), 100);
+        let drawn = bag.draw(7);
+        assert_eq!(drawn.len(), 7);
+        assert_eq!(bag.remaining(), 93);
+        bag.return_tiles(&drawn[..3]);
+        assert_eq!(bag.remaining(), 96);
+        assert!(bag.take(drawn[0]));
+        assert_eq!(bag.remaining(), 95);
+        bag.return_tiles(&drawn[..1]);
+        assert_eq!(bag.draw(200).len(), 96);
+        assert!(bag.is_empty());
+        assert!(bag.draw(1).is_empty());
+    }
+
+    #[test]
+    fn seeded_draws_are_reproducible() {
+        let tiles = TileMap::english();
+        let draw = |seed| TileBag::seeded(&tiles, seed).draw(20);
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+    }
+
+    #[test]
+    fn draw_to_fill() {
+        let tiles = TileMap::english();
+        let mut bag = TileBag::seeded(&tiles, 3);
+        let mut rack = Rack::parse("QI", &tiles).unwrap();
+        assert_eq!(bag.draw_to_fill(&mut rack).len(), 5);
+        assert!(rack.is_full());
+        assert!(bag.draw_to_fill(&mut rack).is_empty());
+
+        let mut bag = TileBag::seeded(&tiles, 3);
+        bag.draw(97);
+        let mut rack = Rack::default();
+        assert_eq!(bag.draw_to_fill(&mut rack).len(), 3);
+        assert_eq!(rack.len(), 3);
+    }
+
+    #[test]
+    fn exchange() {
+        let tiles = TileMap::english();
+        let idx = |l| tiles.find(l).unwrap();
+        let mut bag = TileBag::seeded(&tiles, 5);
+        let mut rack = Rack::parse("QQUVVWW", &tiles).unwrap();
+        bag.draw(86);
+        assert_eq!(bag.remaining(), 14);
+        assert!(bag
+            .exchange(&mut rack, &[idx("Q"), idx("Q"), idx("Q")])
+            .is_err());
+        assert!(bag.exchange(&mut rack, &[]).is_err());
+        let drawn = bag.exchange(&mut rack, &[idx("Q"), idx("V")]).unwrap();
+        assert_eq!(drawn.len(), 2);
+        assert!(rack.is_full());
+        assert_eq!(bag.remaining(), 14);
+        assert_eq!(
+            rack.count(idx("Q")) + drawn.iter().filter(|t| **t == idx("Q")).count(),
+            1
+        );
+
+        bag.draw(8);
+        assert!(!bag.can_exchange());
+        assert!(bag.exchange(&mut rack, &[idx("W")]).is_err());
+        assert!(rack.is_full());
+
+        let mut bag = bag.with_min_for_exchange(1);
+        assert!(bag.can_exchange());
+        assert_eq!(bag.exchange(&mut rack, &[idx("W")]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn caller_provided_rng() {
+        let tiles = TileMap::english();
+        let draw = || {
+            let mut bag = TileBag::new(&tiles, rand::rngs::StdRng::seed_from_u64(9));
+            let mut rack = Rack::default();
+            bag.draw_to_fill(&mut rack);
+            let exchanged = rack.tiles()[..3].to_vec();
+            bag.exchange(&mut rack, &exchanged).unwrap();
+            rack
+        };
+        assert_eq!(draw(), draw());
+    }
+
+    #[test]
+    fn drawing_everything_yields_the_distribution() {
+        let tiles = TileMap::english();
+        let mut drawn = TileBag::seeded(&tiles, 7).draw(100);
+        drawn.sort();
+        let expected: Vec<_> = TileBag::seeded(&tiles, 0).tiles().to_vec();
+        assert_eq!(drawn, expected);
+    }
+}
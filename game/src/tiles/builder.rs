@@ -0,0 +1,172 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{Result, TileError};
+
+use super::{Tile, TileMap};
+
+/// Builds a custom [`TileMap`] tile by tile.
+///
+/// Problems are collected rather than reported one at a time, so [`TileMapBuilder::build`] lists
+/// every duplicate or invalid tile at once.
+#[derive(Debug, Clone, Default)]
+pub struct TileMapBuilder {
+    tiles: Vec<Tile>,
+    blanks: u8,
+    case_insensitive: bool,
+    name: Option<String>,
+}
+
+impl TileMapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_tile(self, letter: impl Into<String>, count: u8, score: u16) -> Self {
+        self.add_custom(Tile::new(letter, score, count))
+    }
+
+    /// Adds a fully specified tile, e.g. one marked as a vowel.
+    pub fn add_custom(mut self, tile: Tile) -> Self {
+        self.tiles.push(tile);
+        self
+    }
+
+    /// How many blanks the bag holds. No blank tile is added if this is zero.
+    pub fn blanks(mut self, count: u8) -> Self {
+        self.blanks = count;
+        self
+    }
+
+    /// See [`TileMap::with_name`].
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// See [`TileMap::case_insensitive`]. Letters differing only in case count as duplicates.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn build(self) -> Result<TileMap> {
+        let key = |letter: &str| -> String {
+            let nfc: String = letter.nfc().collect();
+            if self.case_insensitive {
+                nfc.to_uppercase().nfc().collect()
+            } else {
+                nfc
+            }
+        };
+        let mut problems = Vec::new();
+        let distinct = self.tiles.len() + usize::from(self.blanks > 0);
+        if distinct > u8::MAX as usize {
+            problems.push(format!(
+                "{distinct} distinct tiles, at most {} are supported",
+                u8::MAX
+            ));
+        }
+        let mut seen: Vec<(String, usize)> = Vec::with_capacity(self.tiles.len());
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let k = key(&tile.letter);
+            if k.is_empty() {
+                problems.push(format!("Tile #{} has an empty letter", i + 1));
+            } else if k == TileMap::BLANK {
+                problems.push(format!(
+                    "Tile #{} uses {:?}, which is reserved for the blank",
+                    i + 1,
+                    TileMap::BLANK
+                ));
+            } else if let Some((_, first)) = seen.iter().find(|(s, _)| *s == k) {
+                problems.push(format!(
+                    "Tile #{} {:?} duplicates tile #{} {:?}",
+                    i + 1,
+                    tile.letter,
+                    first + 1,
+                    self.tiles[*first].letter
+                ));
+            } else {
+                seen.push((k, i));
+            }
+        }
+        if !problems.is_empty() {
+            return Err(TileError::InvalidTileMap(problems).into());
+        }
+
+        let mut tiles = TileMap::from_tiles(self.tiles)?.case_insensitive(self.case_insensitive);
+        if let Some(name) = self.name {
+            tiles = tiles.with_name(name);
+        }
+        if self.blanks > 0 {
+            tiles.with_blank(self.blanks)
+        } else {
+            Ok(tiles)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn build() {
+        let tiles = TileMapBuilder::new()
+            .add_tile("A", 5, 1)
+            .add_custom(Tile::new("Q", 10, 1).tagged("power"))
+            .blanks(2)
+            .name("tiny")
+            .build()
+            .unwrap();
+        let a = tiles.find("A").unwrap();
+        assert_eq!((tiles.count(a), tiles.score(a)), (5, 1));
+        assert_eq!(tiles.total_count(), 8);
+        assert!(tiles.blank_idx().is_some());
+        assert_eq!(tiles.name(), Some("tiny"));
+
+        let no_blanks = TileMapBuilder::new().add_tile("A", 1, 1).build().unwrap();
+        assert_eq!(no_blanks.blank_idx(), None);
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let err = TileMapBuilder::new()
+            .add_tile("E", 1, 1)
+            .add_tile("E\u{301}", 1, 1)
+            .add_tile("É", 1, 1)
+            .add_tile("", 1, 1)
+            .add_tile("?", 1, 1)
+            .add_tile("E", 1, 1)
+            .build()
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Invalid tile map:\n  \
+             Tile #3 \"É\" duplicates tile #2 \"E\\u{301}\"\n  \
+             Tile #4 has an empty letter\n  \
+             Tile #5 uses \"?\", which is reserved for the blank\n  \
+             Tile #6 \"E\" duplicates tile #1 \"E\""
+        );
+
+        assert!(TileMapBuilder::new()
+            .add_tile("a", 1, 1)
+            .add_tile("A", 1, 1)
+            .case_insensitive(true)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn tile_limit() {
+        let letters = |n: u32| {
+            (0..n).fold(TileMapBuilder::new(), |b, i| {
+                b.add_tile(char::from_u32(0x4e00 + i).unwrap(), 1, 1)
+            })
+        };
+        assert!(letters(255).build().is_ok());
+        assert!(letters(254).blanks(2).build().is_ok());
+        assert!(letters(255).blanks(2).build().is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Context, FormatError, Result};
+
+use super::{Tile, TileMap};
+
+/// File formats for tile map definitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Guesses the format from a `.toml` or `.json` extension.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("toml") => Ok(Self::Toml),
+            Some(e) if e.eq_ignore_ascii_case("json") => Ok(Self::Json),
+            _ => Err(FormatError::UnknownExtension(path.to_owned()).into()),
+        }
+    }
+}
+
+/// The on-disk form of a [`TileMap`]. In TOML:
+///
+/// ```toml
+/// name = "english"
+/// blanks = 2
+/// case_insensitive = false
+///
+/// [[tiles]]
+/// letter = "A"
+/// score = 1
+/// count = 9
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TileMapConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    blanks: u8,
+    /// See [`TileMap::case_insensitive`].
+    #[serde(default)]
+    case_insensitive: bool,
+    tiles: Vec<Tile>,
+}
+
+impl TileMap {
+    pub fn from_reader(mut reader: impl Read, format: ConfigFormat) -> Result<Self> {
+        let config: TileMapConfig = match format {
+            ConfigFormat::Toml => {
+                let mut s = String::new();
+                reader.read_to_string(&mut s)?;
+                toml::from_str(&s)?
+            }
+            ConfigFormat::Json => serde_json::from_reader(reader)?,
+        };
+        let mut map = Self::from_tiles(config.tiles)?.case_insensitive(config.case_insensitive);
+        if let Some(name) = config.name {
+            map = map.with_name(name);
+        }
+        if config.blanks > 0 {
+            map.with_blank(config.blanks)
+        } else {
+            Ok(map)
+        }
+    }
+
+    /// Reads a tile map, picking the format from the file extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+        let file = File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        Self::from_reader(BufReader::new(file), format).with_context(|| format!("Reading {path:?}"))
+    }
+
+    pub fn to_writer(&self, mut writer: impl Write, format: ConfigFormat) -> Result<()> {
+        let config = TileMapConfig {
+            name: self.name().map(str::to_owned),
+            blanks: self.blank_idx().map_or(0, |b| self.count(b)),
+            case_insensitive: self.is_case_insensitive(),
+            tiles: self
+                .letters()
+                .map(|(idx, _)| self.tile(idx).expect("Iterating our own tiles").clone())
+                .collect(),
+        };
+        match format {
+            ConfigFormat::Toml => writer.write_all(toml::to_string(&config)?.as_bytes())?,
+            ConfigFormat::Json => serde_json::to_writer_pretty(writer, &config)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn read_toml() {
+        let config = r#"
+            blanks = 1
+
+            [[tiles]]
+            letter = "TLH"
+            score = 3
+            count = 2
+
+            [[tiles]]
+            letter = "A"
+            score = 1
+            count = 8
+            is_vowel = true
+            tags = ["common"]
+        "#;
+        let tiles = TileMap::from_reader(config.as_bytes(), ConfigFormat::Toml).unwrap();
+        assert_eq!(tiles.total_count(), 11);
+        assert_eq!(tiles.score(tiles.find("TLH").unwrap()), 3);
+        assert!(tiles.blank_idx().is_some());
+        assert!(tiles.is_vowel(tiles.find("A").unwrap()));
+        assert!(!tiles.is_vowel(tiles.find("TLH").unwrap()));
+        assert!(tiles.has_tag(tiles.find("A").unwrap(), "common"));
+    }
+
+    #[test]
+    fn roundtrip() {
+        for format in [ConfigFormat::Toml, ConfigFormat::Json] {
+            let spanish = TileMap::spanish();
+            let mut buf = Vec::new();
+            spanish.to_writer(&mut buf, format).unwrap();
+            let read = TileMap::from_reader(buf.as_slice(), format).unwrap();
+            let all = |t: &TileMap| -> Vec<_> {
+                t.iter()
+                    .map(|(idx, _)| t.tile(idx).unwrap().clone())
+                    .collect()
+            };
+            assert_eq!(all(&read), all(&spanish));
+            assert_eq!(read.blank_idx(), spanish.blank_idx());
+            assert_eq!(read.fingerprint(), spanish.fingerprint());
+            assert_eq!(read.name(), Some("spanish"));
+
+            let mut buf = Vec::new();
+            spanish
+                .case_insensitive(true)
+                .to_writer(&mut buf, format)
+                .unwrap();
+            let read = TileMap::from_reader(buf.as_slice(), format).unwrap();
+            assert!(read.is_case_insensitive());
+            assert_eq!(read.find("ch"), read.find("CH"));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_configs() {
+        let unknown_field = r#"{"tiles": [], "extra": 1}"#;
+        assert!(TileMap::from_reader(unknown_field.as_bytes(), ConfigFormat::Json).is_err());
+        let missing_score = r#"{"tiles": [{"letter": "A", "count": 1}]}"#;
+        assert!(TileMap::from_reader(missing_score.as_bytes(), ConfigFormat::Json).is_err());
+        assert!(ConfigFormat::from_path(Path::new("tiles.yaml")).is_err());
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("tiles.TOML")).unwrap(),
+            ConfigFormat::Toml
+        );
+    }
+}
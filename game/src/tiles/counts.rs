@@ -0,0 +1,327 @@
+use std::{
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use rand::Rng;
+
+use crate::{bitboard::Bitboard, board::Board};
+
+use super::{Rack, TileMap, TileMapIdx};
+
+/// A multiset of tiles, stored as a count per [`TileMapIdx`].
+///
+/// Counts saturate at `u8::MAX` instead of overflowing.
+#[derive(Debug, Clone, Default)]
+pub struct TileCounts {
+    /// May have trailing zeros, which equality and hashing ignore.
+    counts: Vec<u8>,
+}
+
+impl TileCounts {
+    /// No tiles at all.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A full bag of `tiles`.
+    pub fn full(tiles: &TileMap) -> Self {
+        Self {
+            counts: tiles.iter().map(|(idx, _)| tiles.count(idx)).collect(),
+        }
+    }
+
+    pub fn get(&self, tile: TileMapIdx) -> u8 {
+        self.counts.get(tile.as_usize()).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, tile: TileMapIdx, n: u8) {
+        if self.counts.len() <= tile.as_usize() {
+            self.counts.resize(tile.as_usize() + 1, 0);
+        }
+        let count = &mut self.counts[tile.as_usize()];
+        *count = count.saturating_add(n);
+    }
+
+    /// Removes up to `n` copies of `tile`, returning how many were actually removed.
+    pub fn remove(&mut self, tile: TileMapIdx, n: u8) -> u8 {
+        let Some(count) = self.counts.get_mut(tile.as_usize()) else {
+            return 0;
+        };
+        let removed = n.min(*count);
+        *count -= removed;
+        removed
+    }
+
+    /// The total number of tiles.
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|c| *c as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|c| *c == 0)
+    }
+
+    /// Every tile kind with a non-zero count.
+    pub fn iter(&self) -> impl Iterator<Item = (TileMapIdx, u8)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| **c > 0)
+            .map(|(idx, c)| (TileMapIdx(idx as u8), *c))
+    }
+
+    /// Combines the counts of both sides tile by tile, over the longer of the two.
+    fn zip_with(&self, other: &Self, f: impl Fn(u8, u8) -> u8) -> Self {
+        let len = self.counts.len().max(other.counts.len());
+        Self {
+            counts: (0..len)
+                .map(|idx| {
+                    let idx = TileMapIdx(idx as u8);
+                    f(self.get(idx), other.get(idx))
+                })
+                .collect(),
+        }
+    }
+
+    fn trimmed(&self) -> &[u8] {
+        let len = self
+            .counts
+            .iter()
+            .rposition(|c| *c > 0)
+            .map_or(0, |i| i + 1);
+        &self.counts[..len]
+    }
+
+    /// Each tile as often as in either side, i.e. the maximum of the counts.
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_with(other, u8::max)
+    }
+
+    /// Each tile as often as in both sides, i.e. the minimum of the counts.
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip_with(other, u8::min)
+    }
+
+    /// All tiles of both sides together.
+    pub fn sum(&self, other: &Self) -> Self {
+        self.zip_with(other, u8::saturating_add)
+    }
+
+    /// The tiles left after taking away `other`, with counts clamped at zero.
+    pub fn saturating_sub(&self, other: &Self) -> Self {
+        self.zip_with(other, u8::saturating_sub)
+    }
+
+    /// Draws `n` random tiles without replacement, e.g. a plausible opponent rack from the
+    /// [`UnseenPool`]. Draws fewer if there aren't `n` tiles.
+    pub fn sample_rack(&self, n: usize, rng: &mut impl Rng) -> Rack {
+        let mut rack = Rack::new(n);
+        self.sample_into(n, rng, &mut rack);
+        rack
+    }
+
+    /// Like [`TileCounts::sample_rack`], but reuses `rack`, replacing its tiles. Doesn't allocate
+    /// if `rack` already has room for `n` tiles, so simulations can call it in a tight loop.
+    ///
+    /// Panics if `n` exceeds the capacity of `rack`.
+    pub fn sample_into(&self, n: usize, rng: &mut impl Rng, rack: &mut Rack) {
+        assert!(
+            n <= rack.capacity(),
+            "Can't sample {n} tiles into a smaller rack"
+        );
+        rack.clear();
+        let mut remaining = [0u8; u8::MAX as usize];
+        let counts = self.trimmed();
+        remaining[..counts.len()].copy_from_slice(counts);
+        let mut total = self.total();
+        for _ in 0..n.min(total) {
+            let mut pick = rng.gen_range(0..total);
+            let idx = remaining
+                .iter()
+                .position(|c| {
+                    let hit = pick < *c as usize;
+                    pick = pick.wrapping_sub(*c as usize);
+                    hit
+                })
+                .expect("`pick` is below the total");
+            remaining[idx] -= 1;
+            total -= 1;
+            rack.add(TileMapIdx(idx as u8))
+                .expect("The rack has room for `n` tiles");
+        }
+    }
+
+    /// Whether this holds every tile of `other`, counting duplicates.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.iter().all(|(t, n)| self.get(t) >= n)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        other.is_superset(self)
+    }
+}
+
+impl PartialEq for TileCounts {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl Eq for TileCounts {}
+
+impl Hash for TileCounts {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed().hash(state);
+    }
+}
+
+impl FromIterator<TileMapIdx> for TileCounts {
+    fn from_iter<T: IntoIterator<Item = TileMapIdx>>(iter: T) -> Self {
+        let mut counts = Self::new();
+        for t in iter {
+            counts.add(t, 1);
+        }
+        counts
+    }
+}
+
+impl From<&Rack> for TileCounts {
+    fn from(rack: &Rack) -> Self {
+        rack.tiles().iter().copied().collect()
+    }
+}
+
+/// The tiles a player hasn't seen: the full distribution minus what's on the board and on their own
+/// rack. These are the tiles that could be on the opponent's rack or in the bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnseenPool(TileCounts);
+
+impl UnseenPool {
+    pub fn new<BB: Bitboard>(tiles: &TileMap, board: &Board<BB>, rack: &Rack) -> Self {
+        let mut played = TileCounts::new();
+        for l in board.occupied().locations() {
+            let tile = match (board.is_blank(l), tiles.blank_idx()) {
+                (true, Some(blank)) => blank,
+                _ => board.tile_at(l).expect("Occupied squares hold a tile"),
+            };
+            played.add(tile, 1);
+        }
+        let unseen = TileCounts::full(tiles)
+            .saturating_sub(&played)
+            .saturating_sub(&rack.into());
+        Self(unseen)
+    }
+
+    pub fn into_counts(self) -> TileCounts {
+        self.0
+    }
+}
+
+impl Deref for UnseenPool {
+    type Target = TileCounts;
+
+    fn deref(&self) -> &TileCounts {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        board::{Board, Move},
+    };
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    fn arb_counts() -> impl Strategy<Value = TileCounts> {
+        prop::collection::vec(0u8..5, 0..8).prop_map(|counts| TileCounts { counts })
+    }
+
+    proptest! {
+        #[test]
+        fn union_and_intersection_bound(a in arb_counts(), b in arb_counts()) {
+            let union = a.union(&b);
+            let intersection = a.intersection(&b);
+            prop_assert!(union.is_superset(&a) && union.is_superset(&b));
+            prop_assert!(intersection.is_subset(&a) && intersection.is_subset(&b));
+            prop_assert_eq!(union.total() + intersection.total(), a.total() + b.total());
+            prop_assert_eq!(a.union(&b), b.union(&a));
+            prop_assert_eq!(a.intersection(&b), b.intersection(&a));
+        }
+
+        #[test]
+        fn sub_undoes_sum(a in arb_counts(), b in arb_counts()) {
+            let sum = a.sum(&b);
+            prop_assert!(sum.is_superset(&a));
+            prop_assert_eq!(sum.saturating_sub(&b), a.clone());
+            prop_assert_eq!(sum.total(), a.total() + b.total());
+            let diff = a.saturating_sub(&b);
+            prop_assert!(diff.is_subset(&a));
+            prop_assert_eq!(diff.sum(&a.intersection(&b)), a.clone());
+            prop_assert_eq!(a.is_superset(&b), b.saturating_sub(&a).is_empty());
+        }
+    }
+
+    #[test]
+    fn unseen() {
+        let tiles = TileMap::english();
+        let idx = |l| tiles.find(l).unwrap();
+        let mut board = Board::<BitboardImpl>::standard(&tiles);
+        let m = Move::parse("8H ZeE", &board, &tiles).unwrap();
+        board.apply(&m).unwrap();
+        let rack = Rack::parse("EEQ?", &tiles).unwrap();
+        let unseen = UnseenPool::new(&tiles, &board, &rack);
+        assert_eq!(unseen.total(), 100 - 3 - 4);
+        assert_eq!(unseen.get(idx("Z")), 0);
+        assert_eq!(unseen.get(idx("E")), 12 - 1 - 2);
+        assert_eq!(unseen.get(idx("Q")), 0);
+        assert_eq!(unseen.get(tiles.blank_idx().unwrap()), 0);
+        assert_eq!(unseen.get(idx("A")), 9);
+    }
+
+    #[test]
+    fn sampling() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let tiles = TileMap::english();
+        let idx = |l| tiles.find(l).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let pool: TileCounts = [idx("Q"), idx("U"), idx("U")].into_iter().collect();
+        let mut rack = Rack::default();
+        for _ in 0..20 {
+            pool.sample_into(2, &mut rng, &mut rack);
+            assert_eq!(rack.len(), 2);
+            assert!(pool.is_superset(&(&rack).into()));
+        }
+        assert_eq!(pool.sample_rack(7, &mut rng).to_letters(&tiles), "QUU");
+
+        let full = TileCounts::full(&tiles);
+        let mut zs = 0;
+        for _ in 0..1000 {
+            let rack = full.sample_rack(7, &mut rng);
+            assert_eq!(rack.len(), 7);
+            zs += rack.count(idx("Z"));
+        }
+        // One Z in 100 tiles: expect 70 Zs in 1000 racks of 7.
+        assert!((40..100).contains(&zs), "{zs}");
+    }
+
+    #[test]
+    fn counts() {
+        let tiles = TileMap::english();
+        let idx = |l| tiles.find(l).unwrap();
+        let mut counts: TileCounts = [idx("A"), idx("B"), idx("A")].into_iter().collect();
+        assert_eq!(counts.get(idx("A")), 2);
+        assert_eq!(counts.remove(idx("A"), 5), 2);
+        assert_eq!(counts.remove(idx("Z"), 1), 0);
+        assert_eq!(counts.iter().collect::<Vec<_>>(), [(idx("B"), 1)]);
+        assert!(!counts.is_empty());
+        counts.remove(idx("B"), 1);
+        assert!(counts.is_empty());
+        assert_eq!(counts, TileCounts::new());
+    }
+}
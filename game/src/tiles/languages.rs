@@ -0,0 +1,411 @@
+//! Tile sets for the official Scrabble editions, as `(letter, score, count)` plus the vowels.
+
+use super::{Tile, TileMap};
+
+const ENGLISH: &[(&str, u16, u8)] = &[
+    ("A", 1, 9),
+    ("B", 3, 2),
+    ("C", 3, 2),
+    ("D", 2, 4),
+    ("E", 1, 12),
+    ("F", 4, 2),
+    ("G", 2, 3),
+    ("H", 4, 2),
+    ("I", 1, 9),
+    ("J", 8, 1),
+    ("K", 5, 1),
+    ("L", 1, 4),
+    ("M", 3, 2),
+    ("N", 1, 6),
+    ("O", 1, 8),
+    ("P", 3, 2),
+    ("Q", 10, 1),
+    ("R", 1, 6),
+    ("S", 1, 4),
+    ("T", 1, 6),
+    ("U", 1, 4),
+    ("V", 4, 2),
+    ("W", 4, 2),
+    ("X", 8, 1),
+    ("Y", 4, 2),
+    ("Z", 10, 1),
+];
+
+/// Super Scrabble: the English scores with roughly doubled counts.
+const ENGLISH_SUPER: &[(&str, u16, u8)] = &[
+    ("A", 1, 16),
+    ("B", 3, 4),
+    ("C", 3, 6),
+    ("D", 2, 8),
+    ("E", 1, 24),
+    ("F", 4, 4),
+    ("G", 2, 5),
+    ("H", 4, 5),
+    ("I", 1, 13),
+    ("J", 8, 2),
+    ("K", 5, 2),
+    ("L", 1, 7),
+    ("M", 3, 6),
+    ("N", 1, 13),
+    ("O", 1, 15),
+    ("P", 3, 4),
+    ("Q", 10, 2),
+    ("R", 1, 13),
+    ("S", 1, 10),
+    ("T", 1, 15),
+    ("U", 1, 7),
+    ("V", 4, 3),
+    ("W", 4, 4),
+    ("X", 8, 2),
+    ("Y", 4, 4),
+    ("Z", 10, 2),
+];
+
+const FRENCH: &[(&str, u16, u8)] = &[
+    ("A", 1, 9),
+    ("B", 3, 2),
+    ("C", 3, 2),
+    ("D", 2, 3),
+    ("E", 1, 15),
+    ("F", 4, 2),
+    ("G", 2, 2),
+    ("H", 4, 2),
+    ("I", 1, 8),
+    ("J", 8, 1),
+    ("K", 10, 1),
+    ("L", 1, 5),
+    ("M", 2, 3),
+    ("N", 1, 6),
+    ("O", 1, 6),
+    ("P", 3, 2),
+    ("Q", 8, 1),
+    ("R", 1, 6),
+    ("S", 1, 6),
+    ("T", 1, 6),
+    ("U", 1, 6),
+    ("V", 4, 2),
+    ("W", 10, 1),
+    ("X", 10, 1),
+    ("Y", 10, 1),
+    ("Z", 10, 1),
+];
+
+const GERMAN: &[(&str, u16, u8)] = &[
+    ("A", 1, 5),
+    ("Ä", 6, 1),
+    ("B", 3, 2),
+    ("C", 4, 2),
+    ("D", 1, 4),
+    ("E", 1, 15),
+    ("F", 4, 2),
+    ("G", 2, 3),
+    ("H", 2, 4),
+    ("I", 1, 6),
+    ("J", 6, 1),
+    ("K", 4, 2),
+    ("L", 2, 3),
+    ("M", 3, 4),
+    ("N", 1, 9),
+    ("O", 2, 3),
+    ("Ö", 8, 1),
+    ("P", 4, 1),
+    ("Q", 10, 1),
+    ("R", 1, 6),
+    ("S", 1, 7),
+    ("T", 1, 6),
+    ("U", 1, 6),
+    ("Ü", 6, 1),
+    ("V", 6, 1),
+    ("W", 3, 1),
+    ("X", 8, 1),
+    ("Y", 10, 1),
+    ("Z", 3, 1),
+];
+
+const SPANISH: &[(&str, u16, u8)] = &[
+    ("A", 1, 12),
+    ("B", 3, 2),
+    ("C", 3, 4),
+    ("CH", 5, 1),
+    ("D", 2, 5),
+    ("E", 1, 12),
+    ("F", 4, 1),
+    ("G", 2, 2),
+    ("H", 4, 2),
+    ("I", 1, 6),
+    ("J", 8, 1),
+    ("L", 1, 4),
+    ("LL", 8, 1),
+    ("M", 3, 2),
+    ("N", 1, 5),
+    ("Ñ", 8, 1),
+    ("O", 1, 9),
+    ("P", 3, 2),
+    ("Q", 5, 1),
+    ("R", 1, 5),
+    ("RR", 8, 1),
+    ("S", 1, 6),
+    ("T", 1, 4),
+    ("U", 1, 5),
+    ("V", 4, 1),
+    ("X", 8, 1),
+    ("Y", 4, 1),
+    ("Z", 10, 1),
+];
+
+const POLISH: &[(&str, u16, u8)] = &[
+    ("A", 1, 9),
+    ("Ą", 5, 1),
+    ("B", 3, 2),
+    ("C", 2, 3),
+    ("Ć", 6, 1),
+    ("D", 2, 3),
+    ("E", 1, 7),
+    ("Ę", 5, 1),
+    ("F", 5, 1),
+    ("G", 3, 2),
+    ("H", 3, 2),
+    ("I", 1, 8),
+    ("J", 3, 2),
+    ("K", 2, 3),
+    ("L", 2, 3),
+    ("Ł", 3, 2),
+    ("M", 2, 3),
+    ("N", 1, 5),
+    ("Ń", 7, 1),
+    ("O", 1, 6),
+    ("Ó", 5, 1),
+    ("P", 2, 3),
+    ("R", 1, 4),
+    ("S", 1, 4),
+    ("Ś", 5, 1),
+    ("T", 2, 3),
+    ("U", 3, 2),
+    ("W", 1, 4),
+    ("Y", 2, 4),
+    ("Z", 1, 5),
+    ("Ź", 9, 1),
+    ("Ż", 5, 1),
+];
+
+const ITALIAN: &[(&str, u16, u8)] = &[
+    ("A", 1, 14),
+    ("B", 5, 3),
+    ("C", 2, 6),
+    ("D", 5, 3),
+    ("E", 1, 11),
+    ("F", 5, 3),
+    ("G", 8, 2),
+    ("H", 8, 2),
+    ("I", 1, 12),
+    ("L", 3, 5),
+    ("M", 3, 5),
+    ("N", 3, 5),
+    ("O", 1, 15),
+    ("P", 5, 3),
+    ("Q", 10, 1),
+    ("R", 2, 6),
+    ("S", 2, 6),
+    ("T", 2, 6),
+    ("U", 3, 5),
+    ("V", 5, 3),
+    ("Z", 8, 2),
+];
+
+const DUTCH: &[(&str, u16, u8)] = &[
+    ("A", 1, 6),
+    ("B", 3, 2),
+    ("C", 5, 2),
+    ("D", 2, 5),
+    ("E", 1, 18),
+    ("F", 4, 2),
+    ("G", 3, 3),
+    ("H", 4, 2),
+    ("I", 1, 4),
+    ("J", 4, 2),
+    ("K", 3, 3),
+    ("L", 3, 3),
+    ("M", 3, 3),
+    ("N", 1, 10),
+    ("O", 1, 6),
+    ("P", 3, 2),
+    ("Q", 10, 1),
+    ("R", 2, 5),
+    ("S", 2, 5),
+    ("T", 2, 5),
+    ("U", 4, 3),
+    ("V", 4, 2),
+    ("W", 5, 2),
+    ("X", 8, 1),
+    ("Y", 8, 1),
+    ("Z", 4, 2),
+];
+
+impl TileMap {
+    fn from_table(name: &str, table: &[(&str, u16, u8)], vowels: &[&str], blanks: u8) -> Self {
+        Self::from_tiles(
+            table
+                .iter()
+                .map(|(letter, score, count)| {
+                    let tile = Tile::new(*letter, *score, *count);
+                    if vowels.contains(letter) {
+                        tile.vowel()
+                    } else {
+                        tile
+                    }
+                })
+                .collect(),
+        )
+        .and_then(|tiles| tiles.with_blank(blanks))
+        .expect("Built-in tile sets are valid")
+        .with_name(name)
+    }
+
+    /// Names of the built-in tile maps, see [`TileMap::preset`].
+    pub const PRESETS: &'static [&'static str] = &[
+        "english",
+        "english_super",
+        "french",
+        "german",
+        "spanish",
+        "polish",
+        "italian",
+        "dutch",
+    ];
+
+    /// The built-in tile map called `name`, e.g. "english".
+    pub fn preset(name: &str) -> Option<Self> {
+        Some(match name {
+            "english" => Self::english(),
+            "english_super" => Self::english_super(),
+            "french" => Self::french(),
+            "german" => Self::german(),
+            "spanish" => Self::spanish(),
+            "polish" => Self::polish(),
+            "italian" => Self::italian(),
+            "dutch" => Self::dutch(),
+            _ => return None,
+        })
+    }
+
+    /// 100 tiles.
+    pub fn english() -> Self {
+        Self::from_table("english", ENGLISH, &["A", "E", "I", "O", "U"], 2)
+    }
+
+    /// 200 tiles for Super Scrabble, including 4 blanks.
+    ///
+    /// Meant for the 21×21 board, which
+    /// [`Board::super_scrabble`](crate::board::Board::super_scrabble) can't build yet; on a
+    /// standard board the bag simply lasts longer.
+    pub fn english_super() -> Self {
+        Self::from_table(
+            "english_super",
+            ENGLISH_SUPER,
+            &["A", "E", "I", "O", "U"],
+            4,
+        )
+    }
+
+    /// 102 tiles.
+    pub fn french() -> Self {
+        Self::from_table("french", FRENCH, &["A", "E", "I", "O", "U", "Y"], 2)
+    }
+
+    /// 102 tiles.
+    pub fn german() -> Self {
+        Self::from_table(
+            "german",
+            GERMAN,
+            &["A", "Ä", "E", "I", "O", "Ö", "U", "Ü"],
+            2,
+        )
+    }
+
+    /// 100 tiles, with CH, LL and RR as single tiles.
+    pub fn spanish() -> Self {
+        Self::from_table("spanish", SPANISH, &["A", "E", "I", "O", "U"], 2)
+    }
+
+    /// 100 tiles.
+    pub fn polish() -> Self {
+        Self::from_table(
+            "polish",
+            POLISH,
+            &["A", "Ą", "E", "Ę", "I", "O", "Ó", "U", "Y"],
+            2,
+        )
+    }
+
+    /// 120 tiles.
+    pub fn italian() -> Self {
+        Self::from_table("italian", ITALIAN, &["A", "E", "I", "O", "U"], 2)
+    }
+
+    /// 102 tiles.
+    pub fn dutch() -> Self {
+        Self::from_table("dutch", DUTCH, &["A", "E", "I", "O", "U"], 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn totals() {
+        let totals = [
+            TileMap::english(),
+            TileMap::english_super(),
+            TileMap::french(),
+            TileMap::german(),
+            TileMap::spanish(),
+            TileMap::polish(),
+            TileMap::italian(),
+            TileMap::dutch(),
+        ]
+        .map(|t| t.total_count());
+        assert_eq!(totals, [100, 200, 102, 102, 100, 100, 120, 102]);
+        for name in TileMap::PRESETS {
+            assert_eq!(TileMap::preset(name).unwrap().name(), Some(*name));
+        }
+        assert_eq!(TileMap::preset("klingon"), None);
+    }
+
+    #[test]
+    fn spot_checks() {
+        let check = |tiles: TileMap, letter: &str, score: u16, count: u8| {
+            let idx = tiles.find(letter).unwrap();
+            assert_eq!(
+                (tiles.score(idx), tiles.count(idx)),
+                (score, count),
+                "{letter}"
+            );
+        };
+        check(TileMap::english_super(), "E", 1, 24);
+        check(TileMap::english_super(), "V", 4, 3);
+        check(TileMap::english_super(), "?", 0, 4);
+        check(TileMap::french(), "K", 10, 1);
+        check(TileMap::german(), "Ö", 8, 1);
+        check(TileMap::spanish(), "CH", 5, 1);
+        check(TileMap::spanish(), "RR", 8, 1);
+        check(TileMap::polish(), "Ź", 9, 1);
+        check(TileMap::italian(), "O", 1, 15);
+        check(TileMap::dutch(), "E", 1, 18);
+    }
+
+    #[test]
+    fn vowels() {
+        let vowels = |tiles: TileMap| -> String {
+            tiles
+                .iter()
+                .filter(|(idx, _)| tiles.is_vowel(*idx))
+                .map(|(_, l)| l)
+                .collect()
+        };
+        assert_eq!(vowels(TileMap::english()), "AEIOU");
+        assert_eq!(vowels(TileMap::german()), "AÄEIOÖUÜ");
+        assert_eq!(vowels(TileMap::polish()), "AĄEĘIOÓUY");
+    }
+}
@@ -0,0 +1,469 @@
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{Result, TileError};
+
+mod bag;
+mod builder;
+mod config;
+mod counts;
+mod languages;
+mod rack;
+
+pub use bag::TileBag;
+pub use builder::TileMapBuilder;
+pub use config::ConfigFormat;
+pub use counts::{TileCounts, UnseenPool};
+pub use rack::{Rack, RackDisplay};
+
+/// A kind of tile in a [`TileMap`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tile {
+    /// What's printed on the tile. Can be more than one character, e.g. Spanish "CH".
+    pub letter: String,
+    pub score: u16,
+    /// How many of this tile are in a full bag.
+    pub count: u8,
+    #[serde(default)]
+    pub is_vowel: bool,
+    /// Free-form labels for strategy code, e.g. "power" for high-value tiles.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Tile {
+    pub fn new(letter: impl Into<String>, score: u16, count: u8) -> Self {
+        Self {
+            letter: letter.into(),
+            score,
+            count,
+            is_vowel: false,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn vowel(mut self) -> Self {
+        self.is_vowel = true;
+        self
+    }
+
+    pub fn tagged(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileMap {
+    tiles: Vec<Tile>,
+    blank: Option<TileMapIdx>,
+    case_insensitive: bool,
+    name: Option<String>,
+    /// Each tile's letter in the form [`TileMap::find`] compares it in, see [`TileMap::lookup_key`].
+    keys: Vec<String>,
+}
+
+impl TileMap {
+    pub const BLANK: &'static str = "?";
+    pub(crate) const BLANK_CHAR: char = '?';
+
+    /// A tile map where every tile is worth zero points and none are in the bag. Use
+    /// [`TileMapBuilder`] for anything more.
+    pub fn new(letters: Vec<String>) -> Result<Self> {
+        Self::from_tiles(letters.into_iter().map(|l| Tile::new(l, 0, 0)).collect())
+    }
+
+    /// Letters are stored in Unicode normalization form C.
+    pub fn from_tiles(mut tiles: Vec<Tile>) -> Result<Self> {
+        if tiles.len() > u8::MAX as usize {
+            return Err(TileError::TooManyTiles.into());
+        }
+        for t in &mut tiles {
+            t.letter = t.letter.nfc().collect();
+        }
+        let mut map = Self {
+            tiles,
+            blank: None,
+            case_insensitive: false,
+            name: None,
+            keys: Vec::new(),
+        };
+        map.update_keys();
+        Ok(map)
+    }
+
+    /// A human-readable name, e.g. "english", shown when tile maps don't match.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// A hash of everything tile indices depend on: the letters in order, their scores and counts,
+    /// and which one is the blank. The name, vowels and tags aren't included.
+    ///
+    /// Stable across platforms and releases, so it's stored with anything serialized that holds
+    /// [`TileMapIdx`]s and checked with [`TileMap::check_fingerprint`] on load.
+    pub fn fingerprint(&self) -> u64 {
+        // 64-bit FNV-1a.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut write = |bytes: &[u8]| {
+            for b in bytes {
+                hash ^= u64::from(*b);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        write(&[self.len(), self.blank.map_or(u8::MAX, |b| b.0)]);
+        for t in &self.tiles {
+            write(&(t.letter.len() as u32).to_le_bytes());
+            write(t.letter.as_bytes());
+            write(&t.score.to_le_bytes());
+            write(&[t.count]);
+        }
+        hash
+    }
+
+    /// Fails if data stored with `fingerprint` was made for a different tile map.
+    pub fn check_fingerprint(&self, fingerprint: u64) -> Result<()> {
+        if fingerprint != self.fingerprint() {
+            return Err(TileError::Mismatch {
+                expected: fingerprint,
+                actual: self.fingerprint(),
+                name: self.name().map(str::to_owned),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Makes [`TileMap::find`] and [`TileMap::tokenize`] ignore case, so "é" finds "É".
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self.update_keys();
+        self
+    }
+
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    fn update_keys(&mut self) {
+        self.keys = self
+            .tiles
+            .iter()
+            .map(|t| self.lookup_key(&t.letter))
+            .collect();
+    }
+
+    /// The form letters are compared in: NFC, and uppercased if the map is case-insensitive.
+    fn lookup_key(&self, s: &str) -> String {
+        if self.case_insensitive {
+            s.nfc().collect::<String>().to_uppercase().nfc().collect()
+        } else {
+            s.nfc().collect()
+        }
+    }
+
+    /// Adds `count` blanks, written as [`TileMap::BLANK`].
+    pub fn with_blank(mut self, count: u8) -> Result<Self> {
+        if let Some(blank) = self.blank {
+            self.tiles[blank.as_usize()].count = count;
+            return Ok(self);
+        }
+        if self.find(Self::BLANK).is_some() {
+            return Err(TileError::ReservedBlank.into());
+        }
+        if self.tiles.len() >= u8::MAX as usize {
+            return Err(TileError::TooManyTiles.into());
+        }
+        self.tiles.push(Tile::new(Self::BLANK, 0, count));
+        self.keys.push(self.lookup_key(Self::BLANK));
+        self.blank = Some(TileMapIdx(self.len() - 1));
+        Ok(self)
+    }
+
+    /// The blank tile, if this tile map has one.
+    pub fn blank_idx(&self) -> Option<TileMapIdx> {
+        self.blank
+    }
+
+    pub fn is_blank(&self, idx: TileMapIdx) -> bool {
+        self.blank == Some(idx)
+    }
+
+    pub fn get(&self, idx: TileMapIdx) -> Option<&String> {
+        self.tiles.get(idx.as_usize()).map(|t| &t.letter)
+    }
+
+    pub fn tile(&self, idx: TileMapIdx) -> Option<&Tile> {
+        self.tiles.get(idx.as_usize())
+    }
+
+    /// The point value of `idx`.
+    ///
+    /// Panics if `idx` is not from this tile map.
+    pub fn score(&self, idx: TileMapIdx) -> u16 {
+        self.tiles[idx.as_usize()].score
+    }
+
+    /// Panics if `idx` is not from this tile map.
+    pub fn is_vowel(&self, idx: TileMapIdx) -> bool {
+        self.tiles[idx.as_usize()].is_vowel
+    }
+
+    /// Panics if `idx` is not from this tile map.
+    pub fn has_tag(&self, idx: TileMapIdx, tag: &str) -> bool {
+        self.tiles[idx.as_usize()].tags.iter().any(|t| t == tag)
+    }
+
+    /// How many of `idx` are in a full bag.
+    ///
+    /// Panics if `idx` is not from this tile map.
+    pub fn count(&self, idx: TileMapIdx) -> u8 {
+        self.tiles[idx.as_usize()].count
+    }
+
+    /// The number of tiles in a full bag.
+    pub fn total_count(&self) -> usize {
+        self.tiles.iter().map(|t| t.count as usize).sum()
+    }
+
+    /// Looks up a tile by its letter. Composed and decomposed forms of accented letters are treated
+    /// the same, and so is case if the map is [case-insensitive](TileMap::case_insensitive).
+    pub fn find(&self, needle: &str) -> Option<TileMapIdx> {
+        let needle = self.lookup_key(needle);
+        self.keys
+            .iter()
+            .position(|k| *k == needle)
+            .map(|idx| TileMapIdx(idx as u8))
+    }
+
+    /// Splits `word` into tiles, preferring the longest tile at each position.
+    ///
+    /// Falls back to shorter tiles where the longest match leaves a remainder that can't be
+    /// tokenized, so any word made of this map's letters is accepted. The blank never matches.
+    ///
+    /// Letters are compared like in [`TileMap::find`].
+    pub fn tokenize(&self, word: &str) -> Result<Vec<TileMapIdx>> {
+        let original = word;
+        let word = &self.lookup_key(word);
+        let mut candidates: Vec<_> = self
+            .letters()
+            .filter(|(_, l)| !l.is_empty())
+            .map(|(idx, _)| (idx, self.keys[idx.as_usize()].as_str()))
+            .collect();
+        candidates.sort_by_key(|(_, l)| std::cmp::Reverse(l.len()));
+
+        // `reachable[i]`: whether `word[i..]` can be tokenized. Filled back to front.
+        let mut reachable = vec![false; word.len() + 1];
+        reachable[word.len()] = true;
+        for i in (0..word.len()).rev().filter(|i| word.is_char_boundary(*i)) {
+            reachable[i] = candidates
+                .iter()
+                .any(|(_, l)| word[i..].starts_with(l) && reachable[i + l.len()]);
+        }
+        if !reachable[0] {
+            let stuck = (0..word.len())
+                .rev()
+                .find(|i| word.is_char_boundary(*i) && !reachable[*i])
+                .unwrap_or(0);
+            return Err(TileError::Unsplittable {
+                word: original.to_owned(),
+                at: word[stuck..].to_owned(),
+            }
+            .into());
+        }
+
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < word.len() {
+            let (idx, l) = candidates
+                .iter()
+                .find(|(_, l)| word[i..].starts_with(l) && reachable[i + l.len()])
+                .expect("A reachable position has a matching tile");
+            tokens.push(*idx);
+            i += l.len();
+        }
+        Ok(tokens)
+    }
+
+    /// All tiles, including the blank.
+    pub fn iter(&self) -> impl Iterator<Item = (TileMapIdx, &str)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(idx, t)| (TileMapIdx(idx as u8), t.letter.as_str()))
+    }
+
+    /// All tiles a blank can be designated as, i.e. everything but the blank.
+    pub fn letters(&self) -> impl Iterator<Item = (TileMapIdx, &str)> {
+        self.iter().filter(|(idx, _)| !self.is_blank(*idx))
+    }
+
+    pub(crate) fn len(&self) -> u8 {
+        self.tiles.len() as u8
+    }
+}
+
+/// A tile kind, as an index into a [`TileMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TileMapIdx(pub(crate) u8);
+
+impl TileMapIdx {
+    /// `idx` if it refers to a tile in `tiles`.
+    pub fn new(idx: u8, tiles: &TileMap) -> Option<Self> {
+        (idx < tiles.len()).then_some(Self(idx))
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self.0
+    }
+
+    pub fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn english_scores() {
+        let tiles = TileMap::english();
+        let score = |l| tiles.score(tiles.find(l).unwrap());
+        assert_eq!(score("A"), 1);
+        assert_eq!(score("K"), 5);
+        assert_eq!(score("Q"), 10);
+        assert_eq!(score("Z"), 10);
+        let total: u16 = tiles.iter().map(|(idx, _)| tiles.score(idx)).sum();
+        assert_eq!(total, 87);
+        assert_eq!(tiles.score(tiles.blank_idx().unwrap()), 0);
+    }
+
+    #[test]
+    fn english_distribution() {
+        let tiles = TileMap::english();
+        assert_eq!(tiles.total_count(), 100);
+        assert_eq!(tiles.count(tiles.blank_idx().unwrap()), 2);
+        assert_eq!(tiles.count(tiles.find("E").unwrap()), 12);
+        assert_eq!(tiles.count(tiles.find("Z").unwrap()), 1);
+    }
+
+    #[test]
+    fn fingerprint() {
+        let english = TileMap::english();
+        assert_eq!(english.name(), Some("english"));
+        assert_eq!(english.fingerprint(), 0x27fc_ce6a_cdf8_197a);
+        assert_eq!(
+            english.clone().with_name("other").fingerprint(),
+            english.fingerprint()
+        );
+        assert_ne!(
+            TileMap::english_super().fingerprint(),
+            english.fingerprint()
+        );
+        assert!(english.check_fingerprint(english.fingerprint()).is_ok());
+        let err = english
+            .check_fingerprint(TileMap::french().fingerprint())
+            .unwrap_err();
+        assert!(err.to_string().contains("english"));
+    }
+
+    #[test]
+    fn blank() {
+        let tiles = TileMap::english();
+        let blank = tiles.blank_idx().unwrap();
+        assert_eq!(tiles.find("?"), Some(blank));
+        assert!(tiles.is_blank(blank));
+        assert!(!tiles.is_blank(tiles.find("A").unwrap()));
+        assert_eq!(tiles.iter().count(), 27);
+        assert_eq!(tiles.letters().count(), 26);
+        assert!(TileMap::new(vec!["?".into()])
+            .unwrap()
+            .with_blank(1)
+            .is_err());
+        let no_blank = TileMap::new(vec!["A".into()]).unwrap();
+        assert_eq!(no_blank.blank_idx(), None);
+    }
+
+    #[test]
+    fn tokenize() {
+        let tiles = TileMap::spanish();
+        let letters = |word| -> Vec<_> {
+            tiles
+                .tokenize(word)
+                .unwrap()
+                .into_iter()
+                .map(|idx| tiles.get(idx).unwrap().as_str())
+                .collect()
+        };
+        assert_eq!(letters("CHURRO"), ["CH", "U", "RR", "O"]);
+        assert_eq!(letters("LLAMA"), ["LL", "A", "M", "A"]);
+        assert_eq!(letters("AÑO"), ["A", "Ñ", "O"]);
+        assert_eq!(letters(""), Vec::<&str>::new());
+        assert!(tiles.tokenize("KIWI").is_err());
+        assert!(tiles.tokenize("CASA?").is_err());
+        assert!(tiles.tokenize("casa").is_err());
+    }
+
+    #[test]
+    fn normalization() {
+        let tiles = TileMap::new(vec!["E\u{301}".into(), "ẞ".into()]).unwrap();
+        let acute = tiles.find("\u{c9}").unwrap();
+        assert_eq!(tiles.get(acute).unwrap(), "\u{c9}");
+        assert_eq!(tiles.find("E\u{301}"), Some(acute));
+        assert_eq!(tiles.find("é"), None);
+        assert!(tiles.tokenize("ÉÉ").is_ok());
+
+        let tiles = tiles.case_insensitive(true);
+        assert_eq!(tiles.find("é"), Some(acute));
+        assert_eq!(tiles.find("e\u{301}"), Some(acute));
+        assert_eq!(tiles.tokenize("éẞÉ").unwrap().len(), 3);
+        assert_eq!(
+            TileMap::spanish()
+                .case_insensitive(true)
+                .tokenize("chico")
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn tokenize_backtracks() {
+        let tiles = TileMap::new(vec!["A".into(), "AB".into(), "BC".into()]).unwrap();
+        let tokens = tiles.tokenize("ABC").unwrap();
+        assert_eq!(
+            tokens,
+            [tiles.find("A").unwrap(), tiles.find("BC").unwrap()]
+        );
+        let tokens = tiles.tokenize("ABA").unwrap();
+        assert_eq!(
+            tokens,
+            [tiles.find("AB").unwrap(), tiles.find("A").unwrap()]
+        );
+    }
+
+    #[test]
+    fn checked_idx() {
+        let tiles = TileMap::english();
+        assert_eq!(TileMapIdx::new(0, &tiles), tiles.find("A"));
+        assert_eq!(TileMapIdx::new(26, &tiles), tiles.blank_idx());
+        assert_eq!(TileMapIdx::new(27, &tiles), None);
+        assert_eq!(tiles.find("C").map(TileMapIdx::as_u8), Some(2));
+    }
+
+    #[test]
+    fn too_many_tiles() {
+        let letters: Vec<_> = (0..256).map(|i| i.to_string()).collect();
+        assert!(TileMap::new(letters[..255].to_vec()).is_ok());
+        assert!(TileMap::new(letters).is_err());
+    }
+}
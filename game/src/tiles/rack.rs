@@ -0,0 +1,366 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::TilePlacement,
+    error::{Result, ScrubbleError, TileError},
+};
+
+use super::{TileCounts, TileMap, TileMapIdx};
+
+/// The tiles a player holds, as a multiset: order doesn't matter, duplicates do.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RackData")]
+pub struct Rack {
+    /// Always sorted.
+    tiles: Vec<TileMapIdx>,
+    capacity: usize,
+}
+
+/// A [`Rack`] as deserialized, before checking it fits and sorting it.
+#[derive(Deserialize)]
+struct RackData {
+    tiles: Vec<TileMapIdx>,
+    capacity: usize,
+}
+
+impl TryFrom<RackData> for Rack {
+    type Error = ScrubbleError;
+
+    fn try_from(data: RackData) -> Result<Self> {
+        Self::from_tiles(data.tiles, data.capacity)
+    }
+}
+
+impl Default for Rack {
+    fn default() -> Self {
+        Self::new(Self::STANDARD_SIZE)
+    }
+}
+
+impl Rack {
+    pub const STANDARD_SIZE: usize = 7;
+
+    /// An empty rack holding at most `capacity` tiles.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            tiles: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn from_tiles(mut tiles: Vec<TileMapIdx>, capacity: usize) -> Result<Self> {
+        if tiles.len() > capacity {
+            return Err(TileError::RackOverflow {
+                len: tiles.len(),
+                capacity,
+            }
+            .into());
+        }
+        tiles.sort();
+        Ok(Self { tiles, capacity })
+    }
+
+    /// Parses a rack of standard size written as its letters, with `?` for blanks, e.g. "AEINST?".
+    ///
+    /// Tiles of more than one character can be wrapped in brackets, e.g. "[CH]AEIR?", which
+    /// [`Rack::display`] always does. Without brackets, they are split like in [`TileMap::tokenize`].
+    pub fn parse(s: &str, tiles: &TileMap) -> Result<Self> {
+        Self::from_tiles(Self::parse_tiles(s, tiles)?, Self::STANDARD_SIZE)
+    }
+
+    /// Parses tiles written like in [`Rack::parse`], however many there are.
+    pub(crate) fn parse_tiles(s: &str, tiles: &TileMap) -> Result<Vec<TileMapIdx>> {
+        let mut parsed = Vec::new();
+        let mut rest = s;
+        while !rest.is_empty() {
+            let (plain, bracketed) = match rest.find(['[', TileMap::BLANK_CHAR]) {
+                Some(pos) => rest.split_at(pos),
+                None => (rest, ""),
+            };
+            parsed.extend(tiles.tokenize(plain)?);
+            rest = if let Some(r) = bracketed.strip_prefix(TileMap::BLANK_CHAR) {
+                let Some(blank) = tiles.blank_idx() else {
+                    return Err(TileError::NoBlank.into());
+                };
+                parsed.push(blank);
+                r
+            } else if let Some(r) = bracketed.strip_prefix('[') {
+                let Some((letter, r)) = r.split_once(']') else {
+                    return Err(TileError::UnclosedBracket(s.to_owned()).into());
+                };
+                match tiles.find(letter) {
+                    Some(idx) if !tiles.is_blank(idx) => parsed.push(idx),
+                    _ => {
+                        return Err(TileError::UnknownTile {
+                            at: format!("[{letter}]"),
+                            input: s.to_owned(),
+                        }
+                        .into())
+                    }
+                }
+                r
+            } else {
+                ""
+            };
+        }
+        Ok(parsed)
+    }
+
+    /// The letters on the rack in tile map order, the format [`Rack::parse`] accepts.
+    pub fn to_letters(&self, tiles: &TileMap) -> String {
+        self.display(tiles).to_string()
+    }
+
+    /// Displays the rack like [`Rack::to_letters`]. A rack only makes sense together with its tile
+    /// map, so `Rack` itself implements neither `Display` nor `FromStr`.
+    pub fn display<'a>(&'a self, tiles: &'a TileMap) -> RackDisplay<'a> {
+        RackDisplay { rack: self, tiles }
+    }
+
+    pub fn add(&mut self, tile: TileMapIdx) -> Result<()> {
+        if self.is_full() {
+            return Err(TileError::RackFull.into());
+        }
+        let pos = self.tiles.partition_point(|t| *t <= tile);
+        self.tiles.insert(pos, tile);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, tile: TileMapIdx) -> Result<()> {
+        match self.tiles.binary_search(&tile) {
+            Ok(pos) => {
+                self.tiles.remove(pos);
+                Ok(())
+            }
+            Err(_) => Err(TileError::NotOnRack(tile).into()),
+        }
+    }
+
+    /// Removes all of `tiles`, or nothing if the rack doesn't hold all of them.
+    pub fn remove_all(&mut self, tiles: &[TileMapIdx]) -> Result<()> {
+        if !self.contains_all(tiles) {
+            return Err(TileError::NotAllOnRack.into());
+        }
+        for t in tiles {
+            self.remove(*t)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all tiles, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    pub fn contains(&self, tile: TileMapIdx) -> bool {
+        self.tiles.binary_search(&tile).is_ok()
+    }
+
+    /// Whether every tile in `tiles` is on the rack, counting duplicates.
+    pub fn contains_all(&self, tiles: &[TileMapIdx]) -> bool {
+        TileCounts::from(self).is_superset(&tiles.iter().copied().collect())
+    }
+
+    /// How many copies of `tile` are on the rack.
+    pub fn count(&self, tile: TileMapIdx) -> usize {
+        let start = self.tiles.partition_point(|t| *t < tile);
+        let end = self.tiles.partition_point(|t| *t <= tile);
+        end - start
+    }
+
+    /// The tiles, sorted by index.
+    pub fn tiles(&self) -> &[TileMapIdx] {
+        &self.tiles
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.tiles.len() >= self.capacity
+    }
+
+    /// Every way to spell `word` with tiles from the rack, as one placement per letter.
+    ///
+    /// Blanks can stand in for any letter, including ones the rack also holds, and which letters
+    /// they cover changes the score, so each distinct choice is listed. Spellings using fewer blanks
+    /// come first. Returns no spellings if the rack can't make the word.
+    pub fn spellings(&self, word: &str, tiles: &TileMap) -> Result<Vec<Vec<TilePlacement>>> {
+        let word = tiles.tokenize(word)?;
+        let blanks = tiles.blank_idx().map_or(0, |b| self.count(b));
+        let mut available: Vec<_> = self
+            .tiles
+            .iter()
+            .filter(|t| !tiles.is_blank(**t))
+            .copied()
+            .collect();
+        let mut spellings = Vec::new();
+        let mut current = Vec::with_capacity(word.len());
+        spell(&word, &mut available, blanks, &mut current, &mut spellings);
+        spellings.sort_by_key(|s| s.iter().filter(|p| p.is_blank).count());
+        Ok(spellings)
+    }
+
+    /// How many tiles are needed to fill the rack.
+    pub fn missing(&self) -> usize {
+        self.capacity.saturating_sub(self.tiles.len())
+    }
+}
+
+/// See [`Rack::display`].
+pub struct RackDisplay<'a> {
+    rack: &'a Rack,
+    tiles: &'a TileMap,
+}
+
+impl fmt::Display for RackDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for t in &self.rack.tiles {
+            let letter = self.tiles.get(*t).map_or("", String::as_str);
+            if letter.chars().count() > 1 {
+                write!(f, "[{letter}]")?;
+            } else {
+                f.write_str(letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn spell(
+    word: &[TileMapIdx],
+    available: &mut Vec<TileMapIdx>,
+    blanks: usize,
+    current: &mut Vec<TilePlacement>,
+    out: &mut Vec<Vec<TilePlacement>>,
+) {
+    let Some((&tile, rest)) = word.split_first() else {
+        out.push(current.clone());
+        return;
+    };
+    if let Some(pos) = available.iter().position(|t| *t == tile) {
+        available.swap_remove(pos);
+        current.push(TilePlacement {
+            tile,
+            is_blank: false,
+        });
+        spell(rest, available, blanks, current, out);
+        current.pop();
+        available.push(tile);
+    }
+    if blanks > 0 {
+        current.push(TilePlacement {
+            tile,
+            is_blank: true,
+        });
+        spell(rest, available, blanks - 1, current, out);
+        current.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_and_print() {
+        let tiles = TileMap::english();
+        let rack = Rack::parse("TSENIA?", &tiles).unwrap();
+        assert_eq!(rack.len(), 7);
+        assert!(rack.is_full());
+        assert_eq!(rack.to_letters(&tiles), "AEINST?");
+        assert!(Rack::parse("AEINSTRS", &tiles).is_err());
+        assert!(Rack::parse("A1", &tiles).is_err());
+    }
+
+    #[test]
+    fn brackets() {
+        let tiles = TileMap::spanish();
+        let rack = Rack::parse("R?[CH]AEI[RR]", &tiles).unwrap();
+        assert_eq!(rack.len(), 7);
+        assert_eq!(rack.display(&tiles).to_string(), "A[CH]EIR[RR]?");
+        assert_eq!(Rack::parse("ACHEIRRR?", &tiles).unwrap(), rack);
+        assert_eq!(Rack::parse(&rack.to_letters(&tiles), &tiles).unwrap(), rack);
+        assert_eq!(
+            Rack::parse("[C]H", &tiles).unwrap().to_letters(&tiles),
+            "CH"
+        );
+        assert!(Rack::parse("[CH", &tiles).is_err());
+        assert!(Rack::parse("[W]", &tiles).is_err());
+        assert!(Rack::parse("[?]", &tiles).is_err());
+        assert!(Rack::parse("?", &TileMap::new(vec!["A".into()]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn multiset() {
+        let tiles = TileMap::english();
+        let idx = |l| tiles.find(l).unwrap();
+        let mut rack = Rack::parse("EEL", &tiles).unwrap();
+        assert_eq!(rack.count(idx("E")), 2);
+        assert!(rack.contains_all(&[idx("E"), idx("L"), idx("E")]));
+        assert!(!rack.contains_all(&[idx("E"), idx("E"), idx("E")]));
+        assert!(rack.remove_all(&[idx("E"), idx("X")]).is_err());
+        assert_eq!(rack.len(), 3);
+        rack.remove_all(&[idx("E"), idx("L")]).unwrap();
+        assert_eq!(rack.to_letters(&tiles), "E");
+        assert!(rack.remove(idx("L")).is_err());
+        rack.add(idx("A")).unwrap();
+        assert_eq!(rack.to_letters(&tiles), "AE");
+        assert_eq!(rack.missing(), 5);
+    }
+
+    #[test]
+    fn spellings() {
+        let tiles = TileMap::english();
+        let blanks = |spelling: &Vec<TilePlacement>| -> String {
+            spelling
+                .iter()
+                .map(|p| if p.is_blank { '?' } else { '.' })
+                .collect()
+        };
+        let rack = Rack::parse("EELR?", &tiles).unwrap();
+        let spellings: Vec<_> = rack
+            .spellings("LEER", &tiles)
+            .unwrap()
+            .iter()
+            .map(blanks)
+            .collect();
+        assert_eq!(spellings, ["....", "...?", "..?.", ".?..", "?..."]);
+
+        let spellings = rack.spellings("REEL", &tiles).unwrap();
+        assert_eq!(spellings[0][0].tile, tiles.find("R").unwrap());
+
+        let spellings: Vec<_> = rack
+            .spellings("LEVER", &tiles)
+            .unwrap()
+            .iter()
+            .map(blanks)
+            .collect();
+        assert_eq!(spellings, ["..?.."]);
+
+        assert!(rack.spellings("EVEN", &tiles).unwrap().is_empty());
+        assert!(rack.spellings("E1", &tiles).is_err());
+    }
+
+    #[test]
+    fn capacity() {
+        let tiles = TileMap::english();
+        let mut rack = Rack::new(2);
+        rack.add(tiles.find("A").unwrap()).unwrap();
+        rack.add(tiles.find("B").unwrap()).unwrap();
+        assert!(rack.add(tiles.find("C").unwrap()).is_err());
+        assert!(Rack::from_tiles(vec![tiles.find("A").unwrap(); 3], 2).is_err());
+    }
+}
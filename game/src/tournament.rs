@@ -0,0 +1,355 @@
+//! Round robin tournaments between bots, with Elo-like ratings, for telling whether a change to a
+//! strategy made it stronger.
+
+use std::fmt;
+
+use crate::{
+    bitboard::Bitboard,
+    board::Play,
+    bot::Strategy,
+    error::Result,
+    selfplay::{self, SelfPlayReport},
+    view::GameView,
+    GameBuilder,
+};
+
+/// A bot in a [tournament](round_robin), under a name for the tables.
+pub struct Entrant<BB: Bitboard> {
+    pub name: String,
+    make: Box<dyn Fn() -> Box<dyn Strategy<BB>>>,
+}
+
+impl<BB: Bitboard> Entrant<BB> {
+    /// Every game gets a fresh clone of `bot`.
+    pub fn new<S>(name: impl Into<String>, bot: S) -> Self
+    where
+        S: Strategy<BB> + Clone + 'static,
+    {
+        Self {
+            name: name.into(),
+            make: Box::new(move || Box::new(bot.clone())),
+        }
+    }
+}
+
+/// An entrant's bot for one game, so [`selfplay::run`] can clone it.
+struct Fresh<'a, BB: Bitboard> {
+    entrant: &'a Entrant<BB>,
+    bot: Box<dyn Strategy<BB>>,
+}
+
+impl<'a, BB: Bitboard> Fresh<'a, BB> {
+    fn new(entrant: &'a Entrant<BB>) -> Self {
+        Self {
+            entrant,
+            bot: (entrant.make)(),
+        }
+    }
+}
+
+impl<BB: Bitboard> Clone for Fresh<'_, BB> {
+    fn clone(&self) -> Self {
+        Self::new(self.entrant)
+    }
+}
+
+impl<BB: Bitboard> Strategy<BB> for Fresh<'_, BB> {
+    fn choose(&mut self, view: &GameView<BB>) -> Play {
+        self.bot.choose(view)
+    }
+}
+
+/// Wins, losses and ties, and the spread over them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+    pub spread: i64,
+}
+
+impl Record {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.ties
+    }
+
+    /// The share of the games won, ties counting half.
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        (self.wins as f64 + self.ties as f64 / 2.0) / self.games() as f64
+    }
+
+    fn add(&mut self, other: &Record) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.ties += other.ties;
+        self.spread += other.spread;
+    }
+
+    /// The same games, from the other side.
+    fn flipped(&self) -> Self {
+        Self {
+            wins: self.losses,
+            losses: self.wins,
+            ties: self.ties,
+            spread: -self.spread,
+        }
+    }
+
+    fn of(report: &SelfPlayReport) -> Self {
+        Self {
+            wins: report.wins_a(),
+            losses: report.wins_b(),
+            ties: report.ties(),
+            spread: report.games.iter().map(|g| i64::from(g.spread())).sum(),
+        }
+    }
+}
+
+/// An Elo-like rating, relative to the other entrants: they average 0, and a bot rated 400
+/// higher is expected to score 10 to 1 against another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub elo: f64,
+    /// The standard error of `elo`, about a third of the 95% confidence interval either way.
+    pub error: f64,
+}
+
+/// The results of a [round robin](round_robin).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tournament {
+    /// The entrants' names, in the order they were given.
+    pub names: Vec<String>,
+    /// `results[i][j]` is how entrant `i` did against `j`.
+    pub results: Vec<Vec<Record>>,
+    pub ratings: Vec<Rating>,
+}
+
+impl Tournament {
+    /// How entrant `i` did over all their games.
+    pub fn record(&self, i: usize) -> Record {
+        let mut total = Record::default();
+        for record in &self.results[i] {
+            total.add(record);
+        }
+        total
+    }
+
+    /// The entrants' indices by rating, the highest first.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|&a, &b| self.ratings[b].elo.total_cmp(&self.ratings[a].elo));
+        ranking
+    }
+}
+
+/// A cross table by rating, with each entrant's record, spread and rating with a 95% interval,
+/// then their wins and losses against everyone else.
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranking = self.ranking();
+        let width = self.names.iter().map(String::len).max().unwrap_or(0);
+        write!(
+            f,
+            "{:width$}  {:>10}  {:>7}  {:>12}",
+            "", "W-L-T", "Spread", "Elo"
+        )?;
+        for &j in &ranking {
+            write!(f, "  {:>9}", self.names[j])?;
+        }
+        writeln!(f)?;
+        for &i in &ranking {
+            let record = self.record(i);
+            let rating = self.ratings[i];
+            let wlt = format!("{}-{}-{}", record.wins, record.losses, record.ties);
+            let elo = format!("{:.0} ± {:.0}", rating.elo, 1.96 * rating.error);
+            write!(
+                f,
+                "{:width$}  {wlt:>10}  {:>+7}  {elo:>12}",
+                self.names[i], record.spread
+            )?;
+            for &j in &ranking {
+                let cell = if i == j {
+                    String::from("-")
+                } else {
+                    let r = self.results[i][j];
+                    format!("{}-{}", r.wins, r.losses)
+                };
+                write!(f, "  {cell:>9}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `n_games` games between every two of `bots`, each a [self-play run](selfplay::run) set
+/// up by `setup` with its tiles, lexicons and rules, and rates them from the results.
+///
+/// The ratings are the maximum likelihood estimate of a Bradley-Terry model, with every pairing
+/// counted as having one more tie so a bot that won or lost all its games still gets a finite
+/// rating. Ties count as half a win each.
+pub fn round_robin<BB: Bitboard>(
+    bots: &[Entrant<BB>],
+    n_games: usize,
+    setup: &GameBuilder<BB>,
+    seed: u64,
+) -> Result<Tournament> {
+    let n = bots.len();
+    let mut results = vec![vec![Record::default(); n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            // Every pairing gets its own seed, and games of its own.
+            let pairing_seed = seed ^ ((i * n + j) as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            let (a, b) = (Fresh::new(&bots[i]), Fresh::new(&bots[j]));
+            let report = selfplay::run(setup, n_games, &a, &b, pairing_seed)?;
+            results[i][j] = Record::of(&report);
+            results[j][i] = results[i][j].flipped();
+        }
+    }
+    Ok(Tournament {
+        names: bots.iter().map(|b| b.name.clone()).collect(),
+        ratings: ratings(&results),
+        results,
+    })
+}
+
+/// Fits the ratings to `results` by minorization-maximization, see [`round_robin`].
+fn ratings(results: &[Vec<Record>]) -> Vec<Rating> {
+    let n = results.len();
+    // Games and points of every pairing, with the extra tie.
+    let games = |i: usize, j: usize| results[i][j].games() as f64 + 1.0;
+    let points = |i: usize| -> f64 {
+        (0..n)
+            .filter(|&j| j != i)
+            .map(|j| results[i][j].score() * (games(i, j) - 1.0) + 0.5)
+            .sum()
+    };
+
+    let mut strengths = vec![1.0; n];
+    for _ in 0..1000 {
+        let next: Vec<f64> = (0..n)
+            .map(|i| {
+                let expected: f64 = (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| games(i, j) / (strengths[i] + strengths[j]))
+                    .sum();
+                if expected > 0.0 {
+                    points(i) / expected
+                } else {
+                    1.0
+                }
+            })
+            .collect();
+        // Normalized to a geometric mean of 1, so the ratings average 0.
+        let mean = next.iter().map(|s| s.ln()).sum::<f64>() / n as f64;
+        let next: Vec<f64> = next.iter().map(|s| s / mean.exp()).collect();
+        let change = next
+            .iter()
+            .zip(&strengths)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        strengths = next;
+        if change < 1e-10 {
+            break;
+        }
+    }
+
+    // The error is the inverse square root of the Fisher information, holding the others fixed.
+    let scale = 400.0 / std::f64::consts::LN_10;
+    (0..n)
+        .map(|i| {
+            let information: f64 = (0..n)
+                .filter(|&j| j != i)
+                .map(|j| {
+                    let p = strengths[i] / (strengths[i] + strengths[j]);
+                    games(i, j) * p * (1.0 - p)
+                })
+                .sum();
+            Rating {
+                elo: scale * strengths[i].ln(),
+                error: scale / information.sqrt(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bitboard::BitboardImpl,
+        bot::{Equity, Greedy},
+        leaves::LeaveTable,
+        Game, Lexicon, TileMap,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// Never plays.
+    #[derive(Clone)]
+    struct Passer;
+
+    impl Strategy<BitboardImpl> for Passer {
+        fn choose(&mut self, _: &GameView<BitboardImpl>) -> Play {
+            Play::Pass
+        }
+    }
+
+    fn setup() -> GameBuilder<BitboardImpl> {
+        let tiles = TileMap::english();
+        let lexicon = Lexicon::from_words(
+            [
+                "AT", "TA", "AN", "NA", "IT", "TI", "IN", "NO", "ON", "TO", "EAT", "TEA", "NOT",
+            ],
+            &tiles,
+        );
+        Game::builder().tiles(tiles).lexicons(lexicon)
+    }
+
+    #[test]
+    fn round_robin_ratings() {
+        let bots = [
+            Entrant::new("passer", Passer),
+            Entrant::new("greedy", Greedy),
+            Entrant::new("equity", Equity::new(LeaveTable::new())),
+        ];
+        let tournament = round_robin(&bots, 4, &setup(), 1).unwrap();
+        assert_eq!(tournament.names, ["passer", "greedy", "equity"]);
+        for i in 0..3 {
+            assert_eq!(tournament.results[i][i], Record::default());
+            assert_eq!(tournament.record(i).games(), 8);
+        }
+        assert_eq!(tournament.results[0][1], tournament.results[1][0].flipped());
+        assert_eq!(tournament.record(0).wins, 0);
+        assert_eq!(tournament.ranking()[2], 0);
+
+        let mean = tournament.ratings.iter().map(|r| r.elo).sum::<f64>() / 3.0;
+        assert!(mean.abs() < 1e-6);
+        let passer = tournament.ratings[0];
+        assert!(passer.elo < -100.0 && passer.error.is_finite());
+
+        let table = tournament.to_string();
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.lines().last().unwrap().starts_with("passer"));
+
+        assert_eq!(round_robin(&bots, 4, &setup(), 1).unwrap(), tournament);
+    }
+
+    #[test]
+    fn even_ratings() {
+        let even = Record {
+            wins: 5,
+            losses: 5,
+            ties: 0,
+            spread: 0,
+        };
+        let results = vec![vec![Record::default(), even], vec![even, Record::default()]];
+        let ratings = ratings(&results);
+        assert!(ratings[0].elo.abs() < 1e-6);
+        // 11 games at even odds: sqrt(11 / 4) in natural units.
+        let expected = 400.0 / std::f64::consts::LN_10 / (11.0_f64 / 4.0).sqrt();
+        assert!((ratings[0].error - expected).abs() < 1e-6);
+    }
+}
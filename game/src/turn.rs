@@ -0,0 +1,58 @@
+//! What happened on a turn, see [`Game::play`](crate::Game::play) and
+//! [`Game::history`](crate::Game::history).
+
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Play, Player, Scores},
+    challenge::ChallengeOutcome,
+    score::ScoreBreakdown,
+    Rack, TileMapIdx,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnSummary {
+    pub player: Player,
+    pub play: Play,
+    /// Empty for exchanges and passes.
+    pub score: ScoreBreakdown,
+    /// The tiles drawn from the bag afterwards.
+    pub drawn: Vec<TileMapIdx>,
+    /// The time the player took, zero unless played with [`Game::play_timed`](crate::Game::play_timed).
+    pub elapsed: Duration,
+}
+
+/// A turn as kept in a game's history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnRecord {
+    pub summary: TurnSummary,
+    /// The phony words the play formed, see [`LastPlay::phonies`](crate::challenge::LastPlay::phonies).
+    pub phonies: Vec<String>,
+    pub rack_before: Rack,
+    /// The rack after drawing, as it was before any challenge.
+    pub rack_after: Rack,
+    /// Both scores after the turn, including the effect of a challenge.
+    pub totals: Scores,
+    pub played_at: SystemTime,
+    /// How a challenge of the play went, if there was one.
+    pub challenge: Option<ChallengeOutcome>,
+    pub comment: Option<String>,
+}
+
+impl TurnRecord {
+    pub fn player(&self) -> Player {
+        self.summary.player
+    }
+
+    /// Whether the play was challenged off the board.
+    pub fn is_retracted(&self) -> bool {
+        matches!(self.challenge, Some(ChallengeOutcome::Phony { .. }))
+    }
+
+    /// Whether the play formed a phony that stayed on the board.
+    pub fn is_standing_phony(&self) -> bool {
+        !self.phonies.is_empty() && !self.is_retracted()
+    }
+}
@@ -0,0 +1,175 @@
+//! What one player of a game may see.
+
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Move, Play, Player, Scores},
+    lexicon::{AlphagramIndex, LexiconSet},
+    rules::Rules,
+    tiles::UnseenPool,
+    Game, Rack, TileMap,
+};
+
+/// A game as one player sees it: their own rack, the board and the scores, and how many tiles are
+/// in the bag and on the opponent's rack, but not which. [Strategies](crate::bot::Strategy)
+/// choose their plays from this, so they can't peek.
+#[derive(Clone, Copy)]
+pub struct GameView<'a, BB: Bitboard> {
+    pub(crate) game: &'a Game<BB>,
+    player: Player,
+}
+
+impl<BB: Bitboard> Game<BB> {
+    /// The game as `player` sees it.
+    pub fn view(&self, player: Player) -> GameView<'_, BB> {
+        GameView { game: self, player }
+    }
+}
+
+impl<'a, BB: Bitboard> GameView<'a, BB> {
+    /// Whose view this is.
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Whether it's the viewing player's turn.
+    pub fn is_my_turn(&self) -> bool {
+        self.game.board().current_turn() == self.player
+    }
+
+    pub fn rack(&self) -> &'a Rack {
+        self.game.rack(self.player)
+    }
+
+    pub fn board(&self) -> &'a Board<BB> {
+        self.game.board()
+    }
+
+    pub fn scores(&self) -> Scores {
+        self.game.board().scores()
+    }
+
+    /// The tiles in the bag or on the opponent's rack.
+    pub fn unseen(&self) -> UnseenPool {
+        UnseenPool::new(self.game.tiles(), self.game.board(), self.rack())
+    }
+
+    pub fn bag_len(&self) -> usize {
+        self.game.bag().remaining()
+    }
+
+    pub fn opponent_rack_len(&self) -> usize {
+        self.game.rack(self.player.other()).len()
+    }
+
+    /// Whether the bag holds enough tiles to exchange.
+    pub fn can_exchange(&self) -> bool {
+        self.game.bag().can_exchange()
+    }
+
+    /// How many scoreless turns in a row there have been, see [`Game::SCORELESS_TURNS`].
+    pub fn scoreless_turns(&self) -> usize {
+        self.game.scoreless_turns
+    }
+
+    pub fn tiles(&self) -> &'a TileMap {
+        self.game.tiles()
+    }
+
+    pub fn rules(&self) -> &'a Rules {
+        self.game.rules()
+    }
+
+    pub fn lexicons(&self) -> Option<&'a LexiconSet> {
+        self.game.lexicons()
+    }
+
+    pub fn alphagrams(&self) -> Option<&'a AlphagramIndex> {
+        self.game.alphagrams()
+    }
+
+    /// See [`Game::turn_rng`].
+    pub fn turn_rng(&self) -> ChaCha8Rng {
+        self.game.turn_rng()
+    }
+
+    /// The turns played so far, oldest first, as both players saw them.
+    pub fn turns(&self) -> impl DoubleEndedIterator<Item = SeenTurn<'a>> + 'a {
+        self.game.history().iter().map(|turn| SeenTurn {
+            player: turn.player(),
+            play: match &turn.summary.play {
+                Play::Place(m) => SeenPlay::Place(m),
+                Play::Exchange(tiles) => SeenPlay::Exchange(tiles.len()),
+                Play::Pass => SeenPlay::Pass,
+            },
+            retracted: turn.is_retracted(),
+        })
+    }
+}
+
+/// A turn as both players saw it, see [`GameView::turns`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeenTurn<'a> {
+    pub player: Player,
+    pub play: SeenPlay<'a>,
+    /// Whether the play was challenged off the board.
+    pub retracted: bool,
+}
+
+/// A [`Play`], without the tiles of an exchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeenPlay<'a> {
+    Place(&'a Move),
+    /// How many tiles were exchanged.
+    Exchange(usize),
+    Pass,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitboardImpl, GameBuilder};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sees_own_tiles() {
+        let tiles = TileMap::english();
+        let rack = Rack::parse("QUIZ", &tiles).unwrap();
+        let game: Game<BitboardImpl> = GameBuilder::new()
+            .tiles(tiles.clone())
+            .rack(Player::First, rack.clone())
+            .seed(1)
+            .build()
+            .unwrap();
+        let view = game.view(Player::First);
+        assert!(view.is_my_turn());
+        assert_eq!(view.rack(), &rack);
+        assert_eq!(view.opponent_rack_len(), 7);
+        let unseen = view.unseen();
+        assert_eq!(unseen.total(), view.bag_len() + 7);
+        assert_eq!(unseen.get(tiles.find("Q").unwrap()), 0);
+        assert!(!game.view(Player::Second).is_my_turn());
+    }
+
+    #[test]
+    fn cannot_replay_the_bag() {
+        use crate::TileBag;
+        use rand::SeedableRng;
+
+        let game: Game<BitboardImpl> = GameBuilder::new().seed(1).build().unwrap();
+        let view = game.view(Player::First);
+        let mut rng = view.turn_rng();
+        assert_ne!(rng.get_seed(), ChaCha8Rng::seed_from_u64(1).get_seed());
+
+        // Drawing both racks the way the game did, from the start of the bag's stream.
+        rng.set_stream(0);
+        rng.set_word_pos(0);
+        let mut bag = TileBag::new(view.tiles(), rng);
+        let mut racks = [(); 2].map(|()| Rack::new(7));
+        for rack in &mut racks {
+            bag.draw_to_fill(rack);
+        }
+        assert_ne!(&racks[1], game.rack(Player::Second));
+    }
+}
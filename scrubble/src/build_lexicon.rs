@@ -0,0 +1,100 @@
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use game::{
+    lexicon::{DawgStats, Lexicon, Trie},
+    TileMap,
+};
+
+/// Builds a binary lexicon from a word list, to be loaded without rebuilding it.
+#[derive(clap::Args)]
+pub struct Args {
+    /// A plain-text word list, a Quackle `.dawg` or a `.kwg`.
+    input: PathBuf,
+    /// Where to write the lexicon.
+    #[arg(short, long)]
+    output: PathBuf,
+    /// A built-in tile set, e.g. "english", or a `.toml`/`.json` tile map.
+    #[arg(short, long, default_value = "english")]
+    tiles: String,
+    /// The structures to report sizes of. The lexicon always holds both.
+    #[arg(short, long, value_enum, default_value_t = Structure::Both)]
+    structure: Structure,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Structure {
+    Dawg,
+    Gaddag,
+    Both,
+}
+
+fn tile_map(tiles: &str) -> Result<TileMap> {
+    match TileMap::preset(tiles) {
+        Some(map) => Ok(map),
+        None if Path::new(tiles).extension().is_some() => Ok(TileMap::from_path(tiles)?),
+        None => anyhow::bail!(
+            "Unknown tile set {tiles:?}, expected one of {} or a tile map file",
+            TileMap::PRESETS.join(", ")
+        ),
+    }
+}
+
+fn read(input: &Path, tiles: &TileMap) -> Result<Lexicon> {
+    match input.extension().and_then(|e| e.to_str()) {
+        Some("dawg") => Ok(Lexicon::from_quackle_path(input, tiles)?),
+        Some("kwg") => Ok(Lexicon::from_kwg_path(input, tiles)?),
+        _ => {
+            let file = File::open(input).with_context(|| format!("Opening {input:?}"))?;
+            Lexicon::from_word_list(BufReader::new(file), tiles)
+                .with_context(|| format!("Reading {input:?}"))
+        }
+    }
+}
+
+fn print_stats(name: &str, stats: DawgStats) {
+    println!(
+        "{name}: {} nodes, {} edges, against {} nodes for a plain trie",
+        stats.nodes, stats.edges, stats.trie_nodes
+    );
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let tiles = tile_map(&args.tiles)?;
+
+    let start = Instant::now();
+    let lexicon = read(&args.input, &tiles)?;
+    println!("Built {} words in {:.2?}", lexicon.len(), start.elapsed());
+    if !lexicon.skipped().is_empty() {
+        println!(
+            "Skipped {} words the tile set can't spell, e.g. {:?}",
+            lexicon.skipped().len(),
+            lexicon.skipped()[0]
+        );
+    }
+
+    let trie: Trie = lexicon.words().collect();
+    if args.structure != Structure::Gaddag {
+        print_stats("DAWG", lexicon.dawg().stats(&trie));
+    }
+    if args.structure != Structure::Dawg {
+        print_stats("GADDAG", lexicon.gaddag().stats(&trie));
+    }
+
+    let start = Instant::now();
+    lexicon.save_to_path(&args.output)?;
+    let size = fs::metadata(&args.output)?.len();
+    println!(
+        "Wrote {} bytes to {:?} in {:.2?}",
+        size,
+        args.output,
+        start.elapsed()
+    );
+    Ok(())
+}